@@ -0,0 +1,63 @@
+//! Compiles the GLSL shaders of the built-in pipelines to SPIR-V, with the
+//! `glsl` feature.
+//!
+//! Every `.vert` and `.frag` file in `src/primitive/shaders` is compiled to
+//! `$OUT_DIR/shaders/<file>.spv`, which the pipelines embed with
+//! `include_spirv!` instead of the SPIR-V checked in next to the sources.
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+
+    #[cfg(feature = "glsl")]
+    glsl::compile_all();
+}
+
+#[cfg(feature = "glsl")]
+mod glsl {
+    use std::env;
+    use std::fs;
+    use std::io::Read;
+    use std::path::{Path, PathBuf};
+
+    const SHADERS: &str = "src/primitive/shaders";
+
+    pub fn compile_all() {
+        let output = PathBuf::from(env::var("OUT_DIR").expect("OUT_DIR is set")).join("shaders");
+
+        fs::create_dir_all(&output).expect("Create shader output directory");
+
+        println!("cargo:rerun-if-changed={}", SHADERS);
+
+        for entry in fs::read_dir(SHADERS).expect("Read shader directory") {
+            let path = entry.expect("Read shader directory entry").path();
+
+            let ty = match path.extension().and_then(|extension| extension.to_str()) {
+                Some("vert") => glsl_to_spirv::ShaderType::Vertex,
+                Some("frag") => glsl_to_spirv::ShaderType::Fragment,
+                _ => continue,
+            };
+
+            println!("cargo:rerun-if-changed={}", path.display());
+
+            compile(&path, ty, &output);
+        }
+    }
+
+    fn compile(path: &Path, ty: glsl_to_spirv::ShaderType, output: &Path) {
+        let source = fs::read_to_string(path)
+            .unwrap_or_else(|error| panic!("Read {}: {}", path.display(), error));
+
+        let mut compiled = glsl_to_spirv::compile(&source, ty)
+            .unwrap_or_else(|error| panic!("Compile {}:\n{}", path.display(), error));
+
+        let mut spirv = Vec::new();
+        compiled
+            .read_to_end(&mut spirv)
+            .expect("Read compiled shader");
+
+        let name = path.file_name().expect("Shader has a file name");
+        let destination = output.join(format!("{}.spv", name.to_string_lossy()));
+
+        fs::write(&destination, spirv)
+            .unwrap_or_else(|error| panic!("Write {}: {}", destination.display(), error));
+    }
+}
@@ -0,0 +1,107 @@
+use std::sync::Arc;
+use visper_graphics::core::rectangle::Rectangle;
+use visper_graphics::primitive::custom::{Attribute, Format, Layout, Program};
+use visper_graphics::primitive::Primitive;
+use visper_graphics::renderer::target::Target;
+use visper_graphics::renderer::Renderer;
+use visper_graphics::shader::{Shader, Stage};
+use winit::{
+    event::Event::{RedrawRequested, WindowEvent},
+    event_loop::{ControlFlow, EventLoop},
+};
+
+const VERTEX: &str = r#"
+#version 450
+
+layout(location = 0) in vec2 v_Pos;
+layout(location = 1) in vec4 i_Color;
+
+layout(set = 0, binding = 0) uniform Globals {
+    mat4 u_Transform;
+    float u_Scale;
+};
+
+layout(set = 0, binding = 1) uniform Bounds {
+    vec4 u_Bounds;
+};
+
+layout(location = 0) out vec4 o_Color;
+layout(location = 1) out float o_Ratio;
+
+void main() {
+    vec2 position = (u_Bounds.xy + v_Pos * u_Bounds.zw) * u_Scale;
+
+    o_Color = i_Color;
+    o_Ratio = v_Pos.x;
+
+    gl_Position = u_Transform * vec4(position, 0.0, 1.0);
+}
+"#;
+
+const FRAGMENT: &str = r#"
+#version 450
+
+layout(location = 0) in vec4 v_Color;
+layout(location = 1) in float v_Ratio;
+
+layout(location = 0) out vec4 o_Color;
+
+void main() {
+    o_Color = vec4(v_Color.rgb * v_Ratio, v_Color.a);
+}
+"#;
+
+fn main() {
+    let event_loop = EventLoop::new();
+    let window = winit::window::Window::new(&event_loop).unwrap();
+    let size = window.inner_size();
+
+    let mut renderer = Renderer::new();
+    let mut target = Target::new(renderer.device(), &window, size.width, size.height, window.scale_factor());
+
+    let program = Arc::new(
+        Program::new(
+            Shader::from_glsl(VERTEX, Stage::Vertex).expect("Compile vertex shader"),
+            Shader::from_glsl(FRAGMENT, Stage::Fragment).expect("Compile fragment shader"),
+        )
+        .instances(Layout {
+            stride: 16,
+            attributes: vec![Attribute {
+                location: 1,
+                format: Format::Float4,
+                offset: 0,
+            }],
+        }),
+    );
+
+    event_loop.run(move |event, _, control_flow| match event {
+        WindowEvent {
+            event: winit::event::WindowEvent::CloseRequested,
+            ..
+        } => *control_flow = ControlFlow::Exit,
+
+        WindowEvent {
+            event: winit::event::WindowEvent::Resized(size),
+            ..
+        } => {
+            target.resize(size.width, size.height, window.scale_factor());
+            window.request_redraw();
+        }
+
+        RedrawRequested(_) => {
+            let color: Vec<u8> = [0.2f32, 0.4, 1.0, 1.0]
+                .iter()
+                .flat_map(|component| component.to_le_bytes().to_vec())
+                .collect();
+
+            renderer.draw(&mut target, &[Primitive::Custom {
+                bounds: Rectangle { x: 30.0, y: 30.0, width: 200.0, height: 100.0 },
+                program: program.clone(),
+                uniforms: Vec::new(),
+                instances: color,
+            }]);
+        }
+
+        _ => *control_flow = ControlFlow::Wait,
+    })
+}
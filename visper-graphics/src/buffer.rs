@@ -0,0 +1,679 @@
+//! Upload data to the GPU without creating buffers every frame.
+//!
+//! The pipelines write everything they upload during a frame, like quads or
+//! the uniforms of a custom primitive, to the [`Belt`] of the renderer instead
+//! of creating a mapped buffer per upload. The writes are allocated from ring
+//! buffers that the [`Belt`] reuses from one frame to the next, and grows
+//! when a frame needs more room. When the frame is done, the [`Belt`] stages
+//! all the writes in a single buffer and copies them to their destinations,
+//! before the frame is drawn.
+//!
+//! The staging buffers are reused too: once the GPU is done copying from one,
+//! it goes back to the [`Belt`], which only creates a new one when none of
+//! the free ones fits the writes of a frame.
+//!
+//! [`Belt`]: struct.Belt.html
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// The alignment of copies between buffers.
+const COPY_ALIGNMENT: u64 = 4;
+
+/// The alignment of the offsets of uniform bindings.
+const UNIFORM_ALIGNMENT: u64 = 256;
+
+/// The initial size of the ring buffers, in bytes.
+const RING_SIZE: u64 = 64 * 1024;
+
+/// What a [`Belt`] uploaded during a frame.
+///
+/// [`Belt`]: struct.Belt.html
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Stats {
+    /// The number of bytes uploaded.
+    pub bytes: u64,
+
+    /// The number of writes to buffers.
+    pub writes: usize,
+
+    /// The number of buffers created or grown, including staging buffers.
+    pub allocations: usize,
+}
+
+/// A GPU buffer.
+#[derive(Debug)]
+struct Buffer {
+    raw: Arc<wgpu::Buffer>,
+    size: u64,
+    usage: wgpu::BufferUsage,
+}
+
+impl Buffer {
+    /// Creates a [`Buffer`] of the given size, in bytes.
+    ///
+    /// [`Buffer`]: struct.Buffer.html
+    fn new(device: &wgpu::Device, usage: wgpu::BufferUsage, size: u64) -> Buffer {
+        let size = align(size.max(COPY_ALIGNMENT), COPY_ALIGNMENT);
+
+        Buffer {
+            raw: Arc::new(device.create_buffer(&wgpu::BufferDescriptor {
+                size,
+                usage: usage | wgpu::BufferUsage::COPY_DST,
+            })),
+            size,
+            usage,
+        }
+    }
+}
+
+/// A part of a buffer written during the current frame.
+#[derive(Debug, Clone)]
+pub(crate) struct Slice {
+    buffer: Arc<wgpu::Buffer>,
+    offset: u64,
+    size: u64,
+}
+
+impl Slice {
+    pub(crate) fn buffer(&self) -> &wgpu::Buffer {
+        &self.buffer
+    }
+
+    pub(crate) fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    /// Returns the range of the [`Slice`] in its buffer, for bindings.
+    ///
+    /// [`Slice`]: struct.Slice.html
+    pub(crate) fn range(&self) -> std::ops::Range<u64> {
+        self.offset..self.offset + self.size
+    }
+}
+
+/// The offsets allocated in a [`Ring`] during a frame.
+///
+/// [`Ring`]: struct.Ring.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Cursor {
+    position: u64,
+    capacity: u64,
+    alignment: u64,
+}
+
+impl Cursor {
+    fn new(capacity: u64, alignment: u64) -> Cursor {
+        Cursor {
+            position: 0,
+            capacity,
+            alignment,
+        }
+    }
+
+    /// Returns the offset of the given number of bytes.
+    ///
+    /// If they do not fit, the capacity grows to a power of two at least
+    /// twice as big, which is returned too.
+    fn allocate(&mut self, size: u64) -> (u64, Option<u64>) {
+        let offset = align(self.position, self.alignment);
+        let end = offset + align(size, COPY_ALIGNMENT);
+
+        let grown = if end > self.capacity {
+            self.capacity = end.max(self.capacity * 2).next_power_of_two();
+
+            Some(self.capacity)
+        } else {
+            None
+        };
+
+        self.position = end;
+
+        (offset, grown)
+    }
+
+    /// Allocates from the start again.
+    fn rewind(&mut self) {
+        self.position = 0;
+    }
+}
+
+/// A buffer that is allocated from the start again every frame.
+#[derive(Debug)]
+struct Ring {
+    buffer: Buffer,
+    cursor: Cursor,
+}
+
+impl Ring {
+    fn new(device: &wgpu::Device, usage: wgpu::BufferUsage, alignment: u64) -> Ring {
+        let buffer = Buffer::new(device, usage, RING_SIZE);
+
+        Ring {
+            cursor: Cursor::new(buffer.size, alignment),
+            buffer,
+        }
+    }
+
+    /// Returns the offset of the given number of bytes, replacing the buffer
+    /// by a bigger one if they do not fit.
+    ///
+    /// The slices allocated before the buffer grew keep the buffer they were
+    /// written to alive until the frame is submitted.
+    fn allocate(&mut self, device: &wgpu::Device, size: u64, stats: &mut Stats) -> u64 {
+        let (offset, grown) = self.cursor.allocate(size);
+
+        if let Some(capacity) = grown {
+            self.buffer = Buffer::new(device, self.buffer.usage, capacity);
+            stats.allocations += 1;
+        }
+
+        offset
+    }
+}
+
+/// A buffer that is reused once the GPU is done with it.
+trait Chunk {
+    /// Returns the size of the [`Chunk`], in bytes.
+    ///
+    /// [`Chunk`]: trait.Chunk.html
+    fn size(&self) -> u64;
+
+    /// Returns whether the GPU is done with the [`Chunk`].
+    ///
+    /// [`Chunk`]: trait.Chunk.html
+    fn is_free(&self) -> bool;
+
+    /// Frees the [`Chunk`] once the GPU is done with it.
+    ///
+    /// [`Chunk`]: trait.Chunk.html
+    fn recall(&self);
+}
+
+/// The chunks a [`Belt`] stages its writes in: the ones that went back to
+/// it, free or not yet, and the ones of the frame being submitted.
+///
+/// [`Belt`]: struct.Belt.html
+#[derive(Debug)]
+struct Pool<T> {
+    chunks: Vec<T>,
+    in_flight: Vec<T>,
+}
+
+impl<T: Chunk> Pool<T> {
+    fn new() -> Pool<T> {
+        Pool {
+            chunks: Vec::new(),
+            in_flight: Vec::new(),
+        }
+    }
+
+    /// Takes the smallest free chunk that fits the given number of bytes.
+    fn take(&mut self, size: u64) -> Option<T> {
+        let index = self
+            .chunks
+            .iter()
+            .enumerate()
+            .filter(|(_, chunk)| chunk.size() >= size && chunk.is_free())
+            .min_by_key(|(_, chunk)| chunk.size())
+            .map(|(index, _)| index)?;
+
+        Some(self.chunks.swap_remove(index))
+    }
+
+    /// Drops the free chunks that do not fit the given number of bytes,
+    /// since a bigger chunk replaces them.
+    fn trim(&mut self, size: u64) {
+        self.chunks
+            .retain(|chunk| chunk.size() >= size || !chunk.is_free());
+    }
+
+    /// Keeps a chunk the frame being submitted copies from.
+    fn submit(&mut self, chunk: T) {
+        self.in_flight.push(chunk);
+    }
+
+    /// Takes back the chunks of the submitted frame, to reuse them once the
+    /// GPU is done with them.
+    fn recall(&mut self) {
+        for chunk in self.in_flight.drain(..) {
+            chunk.recall();
+            self.chunks.push(chunk);
+        }
+    }
+}
+
+/// A buffer the writes of a frame are staged in, before being copied to
+/// their destinations.
+#[derive(Debug)]
+struct Staging {
+    raw: wgpu::Buffer,
+    size: u64,
+    is_free: Arc<AtomicBool>,
+}
+
+impl Staging {
+    /// Creates a [`Staging`] buffer holding the given bytes, with room for
+    /// at least as many.
+    ///
+    /// [`Staging`]: struct.Staging.html
+    fn new(device: &wgpu::Device, bytes: &[u8]) -> Staging {
+        let size = align(bytes.len() as u64, COPY_ALIGNMENT).next_power_of_two();
+        let mapped = device.create_buffer_mapped(
+            size as usize,
+            wgpu::BufferUsage::MAP_WRITE | wgpu::BufferUsage::COPY_SRC,
+        );
+
+        mapped.data[..bytes.len()].copy_from_slice(bytes);
+
+        Staging {
+            raw: mapped.finish(),
+            size,
+            is_free: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Writes the given bytes at the start of a free [`Staging`] buffer.
+    ///
+    /// Returns whether they were written: a buffer the GPU is done with is
+    /// mapped as soon as the device is polled.
+    ///
+    /// [`Staging`]: struct.Staging.html
+    fn write(&self, device: &wgpu::Device, bytes: &Arc<Mutex<Vec<u8>>>) -> bool {
+        let size = bytes.lock().expect("Lock staged bytes").len();
+        let bytes = Arc::clone(bytes);
+        let written = Arc::new(AtomicBool::new(false));
+        let done = Arc::clone(&written);
+
+        self.is_free.store(false, Ordering::Release);
+        self.raw.map_write_async(
+            0,
+            size as u64,
+            move |result: wgpu::BufferMapAsyncResult<&mut [u8]>| {
+                if let Ok(mapping) = result {
+                    mapping
+                        .data
+                        .copy_from_slice(&bytes.lock().expect("Lock staged bytes")[..size]);
+                    done.store(true, Ordering::Release);
+                }
+            },
+        );
+
+        device.poll(false);
+
+        written.load(Ordering::Acquire)
+    }
+}
+
+impl Chunk for Staging {
+    fn size(&self) -> u64 {
+        self.size
+    }
+
+    fn is_free(&self) -> bool {
+        self.is_free.load(Ordering::Acquire)
+    }
+
+    fn recall(&self) {
+        let is_free = Arc::clone(&self.is_free);
+
+        self.raw.map_write_async(
+            0,
+            self.size,
+            move |_: wgpu::BufferMapAsyncResult<&mut [u8]>| is_free.store(true, Ordering::Release),
+        );
+    }
+}
+
+/// A copy from the staged bytes of a frame to a buffer.
+#[derive(Debug)]
+struct Transfer<B> {
+    source: u64,
+    destination: B,
+    offset: u64,
+    size: u64,
+}
+
+/// The writes of a frame, staged in memory until they are copied to their
+/// buffers.
+#[derive(Debug)]
+struct Writes<B> {
+    bytes: Arc<Mutex<Vec<u8>>>,
+    transfers: Vec<Transfer<B>>,
+}
+
+impl<B> Writes<B> {
+    fn new() -> Writes<B> {
+        Writes {
+            bytes: Arc::new(Mutex::new(Vec::new())),
+            transfers: Vec::new(),
+        }
+    }
+
+    /// Stages the given bytes, to be copied at the given offset of the
+    /// destination.
+    fn push(&mut self, destination: B, offset: u64, bytes: &[u8], stats: &mut Stats) {
+        if bytes.is_empty() {
+            return;
+        }
+
+        let mut staged = self.bytes.lock().expect("Lock staged bytes");
+        let source = staged.len() as u64;
+        let size = align(bytes.len() as u64, COPY_ALIGNMENT);
+
+        staged.extend_from_slice(bytes);
+        staged.resize((source + size) as usize, 0);
+        drop(staged);
+
+        self.transfers.push(Transfer {
+            source,
+            destination,
+            offset,
+            size,
+        });
+
+        stats.bytes += bytes.len() as u64;
+        stats.writes += 1;
+    }
+
+    /// Returns the number of staged bytes, padding included.
+    fn size(&self) -> u64 {
+        self.bytes.lock().expect("Lock staged bytes").len() as u64
+    }
+
+    fn clear(&mut self) {
+        self.bytes.lock().expect("Lock staged bytes").clear();
+        self.transfers.clear();
+    }
+}
+
+/// Stages the writes of a frame to GPU buffers, shared by all the pipelines.
+#[derive(Debug)]
+pub(crate) struct Belt {
+    writes: Writes<Arc<wgpu::Buffer>>,
+    staging: Pool<Staging>,
+    uniforms: Ring,
+    vertices: Ring,
+    current: Stats,
+    last: Stats,
+}
+
+impl Belt {
+    pub(crate) fn new(device: &wgpu::Device) -> Belt {
+        Belt {
+            writes: Writes::new(),
+            staging: Pool::new(),
+            uniforms: Ring::new(device, wgpu::BufferUsage::UNIFORM, UNIFORM_ALIGNMENT),
+            vertices: Ring::new(device, wgpu::BufferUsage::VERTEX, COPY_ALIGNMENT),
+            current: Stats::default(),
+            last: Stats::default(),
+        }
+    }
+
+    /// Writes uniforms that only live for the current frame.
+    pub(crate) fn uniforms(&mut self, device: &wgpu::Device, bytes: &[u8]) -> Slice {
+        let offset = self
+            .uniforms
+            .allocate(device, bytes.len() as u64, &mut self.current);
+        let buffer = Arc::clone(&self.uniforms.buffer.raw);
+
+        self.writes
+            .push(Arc::clone(&buffer), offset, bytes, &mut self.current);
+
+        Slice {
+            buffer,
+            offset,
+            size: bytes.len() as u64,
+        }
+    }
+
+    /// Writes vertices that only live for the current frame.
+    pub(crate) fn vertices(&mut self, device: &wgpu::Device, bytes: &[u8]) -> Slice {
+        let offset = self
+            .vertices
+            .allocate(device, bytes.len() as u64, &mut self.current);
+        let buffer = Arc::clone(&self.vertices.buffer.raw);
+
+        self.writes
+            .push(Arc::clone(&buffer), offset, bytes, &mut self.current);
+
+        Slice {
+            buffer,
+            offset,
+            size: bytes.len() as u64,
+        }
+    }
+
+    /// Copies all the writes of the frame to their buffers, through a single
+    /// staging buffer, and starts the next frame.
+    ///
+    /// The returned commands have to be submitted before the ones drawing the
+    /// frame, and [`recall`] called after.
+    ///
+    /// [`recall`]: #method.recall
+    pub(crate) fn finish(&mut self, device: &wgpu::Device) -> Option<wgpu::CommandBuffer> {
+        let commands = if self.writes.transfers.is_empty() {
+            None
+        } else {
+            let staging = self.stage_frame(device);
+
+            let mut encoder = device
+                .create_command_encoder(&wgpu::CommandEncoderDescriptor { todo: 0 });
+
+            for transfer in &self.writes.transfers {
+                encoder.copy_buffer_to_buffer(
+                    &staging.raw,
+                    transfer.source,
+                    &transfer.destination,
+                    transfer.offset,
+                    transfer.size,
+                );
+            }
+
+            self.staging.submit(staging);
+
+            Some(encoder.finish())
+        };
+
+        self.writes.clear();
+        self.uniforms.cursor.rewind();
+        self.vertices.cursor.rewind();
+        self.last = std::mem::take(&mut self.current);
+
+        commands
+    }
+
+    /// Takes back the staging buffers of the submitted frame, to reuse them
+    /// once the GPU is done copying from them.
+    pub(crate) fn recall(&mut self) {
+        self.staging.recall();
+    }
+
+    /// Returns the smallest free staging buffer that fits the writes of the
+    /// frame, holding them, or a new one if none does.
+    fn stage_frame(&mut self, device: &wgpu::Device) -> Staging {
+        let size = self.writes.size();
+
+        // Polling frees the buffers the GPU is done copying from
+        device.poll(false);
+
+        if let Some(staging) = self.staging.take(size) {
+            // A free buffer is mapped right away, or dropped if it is not
+            if staging.write(device, &self.writes.bytes) {
+                return staging;
+            }
+        }
+
+        self.staging.trim(size);
+        self.current.allocations += 1;
+
+        Staging::new(device, &self.writes.bytes.lock().expect("Lock staged bytes"))
+    }
+
+    /// Returns what was uploaded during the last frame.
+    pub fn stats(&self) -> Stats {
+        self.last
+    }
+}
+
+/// Returns the bytes of plain data, like vertices or uniforms.
+///
+/// The type must not have any padding, like the `#[repr(C)]` structs of
+/// floats the pipelines upload.
+pub(crate) fn bytes<T: Copy>(data: &[T]) -> &[u8] {
+    // Safe because the data is plain, fully initialized memory
+    unsafe { std::slice::from_raw_parts(data.as_ptr() as *const u8, std::mem::size_of_val(data)) }
+}
+
+fn align(value: u64, alignment: u64) -> u64 {
+    value.div_ceil(alignment) * alignment
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    #[derive(Debug)]
+    struct Fake {
+        size: u64,
+        is_free: Rc<Cell<bool>>,
+    }
+
+    impl Fake {
+        fn new(size: u64, is_free: bool) -> Fake {
+            Fake {
+                size,
+                is_free: Rc::new(Cell::new(is_free)),
+            }
+        }
+    }
+
+    impl Chunk for Fake {
+        fn size(&self) -> u64 {
+            self.size
+        }
+
+        fn is_free(&self) -> bool {
+            self.is_free.get()
+        }
+
+        // The GPU frees the chunk later
+        fn recall(&self) {}
+    }
+
+    #[test]
+    fn values_are_aligned_up() {
+        assert_eq!(align(0, 4), 0);
+        assert_eq!(align(1, 4), 4);
+        assert_eq!(align(4, 4), 4);
+        assert_eq!(align(257, UNIFORM_ALIGNMENT), 512);
+    }
+
+    #[test]
+    fn bytes_of_floats() {
+        assert_eq!(bytes(&[1.0f32, 2.0]).len(), 8);
+        assert_eq!(bytes(&[1.0f32]), &1.0f32.to_ne_bytes());
+    }
+
+    #[test]
+    fn rings_grow_to_fit_their_writes() {
+        let mut cursor = Cursor::new(64, 16);
+
+        assert_eq!(cursor.allocate(10), (0, None));
+        assert_eq!(cursor.allocate(10), (16, None));
+        assert_eq!(cursor.allocate(30), (32, None));
+        assert_eq!(cursor.allocate(100), (64, Some(256)));
+        assert_eq!(cursor.allocate(1), (176, None));
+    }
+
+    #[test]
+    fn rings_are_allocated_from_the_start_every_frame() {
+        let mut cursor = Cursor::new(64, 16);
+
+        assert_eq!(cursor.allocate(100), (0, Some(128)));
+
+        cursor.rewind();
+
+        assert_eq!(cursor.allocate(100), (0, None));
+        assert_eq!(cursor.allocate(10), (112, None));
+    }
+
+    #[test]
+    fn staging_chunks_are_reused_once_the_gpu_is_done() {
+        let mut pool = Pool::new();
+        let chunk = Fake::new(128, false);
+        let is_free = Rc::clone(&chunk.is_free);
+
+        pool.submit(chunk);
+
+        assert!(pool.take(64).is_none());
+
+        pool.recall();
+
+        assert!(pool.take(64).is_none());
+
+        is_free.set(true);
+
+        assert_eq!(pool.take(64).map(|chunk| chunk.size), Some(128));
+        assert!(pool.take(64).is_none());
+    }
+
+    #[test]
+    fn staging_chunks_are_taken_smallest_first() {
+        let mut pool = Pool::new();
+
+        for chunk in [
+            Fake::new(512, true),
+            Fake::new(64, true),
+            Fake::new(128, true),
+            Fake::new(32, false),
+        ] {
+            pool.submit(chunk);
+        }
+
+        pool.recall();
+
+        assert_eq!(pool.take(100).map(|chunk| chunk.size), Some(128));
+        assert_eq!(pool.take(100).map(|chunk| chunk.size), Some(512));
+        assert!(pool.take(100).is_none());
+
+        pool.trim(100);
+
+        let sizes: Vec<u64> = pool.chunks.iter().map(|chunk| chunk.size).collect();
+
+        assert_eq!(sizes, vec![32]);
+    }
+
+    #[test]
+    fn writes_are_staged_aligned() {
+        let mut writes = Writes::new();
+        let mut stats = Stats::default();
+
+        writes.push("quads", 0, &[1, 2, 3], &mut stats);
+        writes.push("uniforms", 256, &[], &mut stats);
+        writes.push("uniforms", 256, &[4; 8], &mut stats);
+
+        let transfers: Vec<_> = writes
+            .transfers
+            .iter()
+            .map(|transfer| (transfer.destination, transfer.source, transfer.offset, transfer.size))
+            .collect();
+
+        assert_eq!(transfers, vec![("quads", 0, 0, 4), ("uniforms", 4, 256, 8)]);
+        assert_eq!(writes.size(), 12);
+        assert_eq!(
+            stats,
+            Stats {
+                bytes: 11,
+                writes: 2,
+                allocations: 0,
+            }
+        );
+
+        writes.clear();
+
+        assert_eq!(writes.size(), 0);
+        assert!(writes.transfers.is_empty());
+    }
+}
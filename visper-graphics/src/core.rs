@@ -1,3 +1,5 @@
 pub mod point;
 pub mod vector;
-pub mod rectangle;
\ No newline at end of file
+pub mod rectangle;
+pub mod size;
+pub mod color;
@@ -0,0 +1,94 @@
+/// A color in the sRGB color space.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Color {
+    /// Red component, 0.0 - 1.0
+    pub r: f32,
+
+    /// Green component, 0.0 - 1.0
+    pub g: f32,
+
+    /// Blue component, 0.0 - 1.0
+    pub b: f32,
+
+    /// Alpha component, 0.0 - 1.0
+    pub a: f32,
+}
+
+impl Color {
+    /// The black color.
+    pub const BLACK: Color = Color::from_rgba(0.0, 0.0, 0.0, 1.0);
+
+    /// The white color.
+    pub const WHITE: Color = Color::from_rgba(1.0, 1.0, 1.0, 1.0);
+
+    /// A color with no opacity.
+    pub const TRANSPARENT: Color = Color::from_rgba(0.0, 0.0, 0.0, 0.0);
+
+    /// Creates a [`Color`] from its RGB components.
+    ///
+    /// [`Color`]: struct.Color.html
+    pub const fn from_rgb(r: f32, g: f32, b: f32) -> Color {
+        Color { r, g, b, a: 1.0 }
+    }
+
+    /// Creates a [`Color`] from its RGBA components.
+    ///
+    /// [`Color`]: struct.Color.html
+    pub const fn from_rgba(r: f32, g: f32, b: f32, a: f32) -> Color {
+        Color { r, g, b, a }
+    }
+
+    /// Creates a [`Color`] from its RGB8 components.
+    ///
+    /// [`Color`]: struct.Color.html
+    pub fn from_rgb8(r: u8, g: u8, b: u8) -> Color {
+        Color::from_rgba8(r, g, b, 1.0)
+    }
+
+    /// Creates a [`Color`] from its RGB8 components and an alpha value.
+    ///
+    /// [`Color`]: struct.Color.html
+    pub fn from_rgba8(r: u8, g: u8, b: u8, a: f32) -> Color {
+        Color {
+            r: f32::from(r) / 255.0,
+            g: f32::from(g) / 255.0,
+            b: f32::from(b) / 255.0,
+            a,
+        }
+    }
+
+    /// Converts the [`Color`] into its linear components.
+    ///
+    /// [`Color`]: struct.Color.html
+    pub fn into_linear(self) -> [f32; 4] {
+        // As described in:
+        // https://en.wikipedia.org/wiki/SRGB#The_reverse_transformation
+        fn linear_component(u: f32) -> f32 {
+            if u < 0.04045 {
+                u / 12.92
+            } else {
+                ((u + 0.055) / 1.055).powf(2.4)
+            }
+        }
+
+        [
+            linear_component(self.r),
+            linear_component(self.g),
+            linear_component(self.b),
+            self.a,
+        ]
+    }
+}
+
+impl From<[f32; 3]> for Color {
+    fn from([r, g, b]: [f32; 3]) -> Self {
+        Color::from_rgb(r, g, b)
+    }
+}
+
+impl From<[f32; 4]> for Color {
+    fn from([r, g, b, a]: [f32; 4]) -> Self {
+        Color::from_rgba(r, g, b, a)
+    }
+}
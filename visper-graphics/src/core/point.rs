@@ -2,6 +2,7 @@ use crate::core::vector::Vector;
 
 /// A 2D point.
 #[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Point {
     /// The X coordinate.
     pub x: f32,
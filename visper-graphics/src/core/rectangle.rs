@@ -2,6 +2,7 @@ use crate::core::point::Point;
 
 /// A rectangle.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Rectangle<T = f32> {
     /// X coordinate of the top-left corner.
     pub x: T,
@@ -27,6 +28,40 @@ impl Rectangle<f32> {
             && self.y <= point.y
             && point.y <= self.y + self.height
     }
+
+    /// Returns the area shared by two rectangles, if any.
+    pub fn intersection(&self, other: &Rectangle<f32>) -> Option<Rectangle<f32>> {
+        let x = self.x.max(other.x);
+        let y = self.y.max(other.y);
+        let right = (self.x + self.width).min(other.x + other.width);
+        let bottom = (self.y + self.height).min(other.y + other.height);
+
+        if right > x && bottom > y {
+            Some(Rectangle {
+                x,
+                y,
+                width: right - x,
+                height: bottom - y,
+            })
+        } else {
+            None
+        }
+    }
+
+    /// Returns the smallest rectangle containing both rectangles.
+    pub fn union(&self, other: &Rectangle<f32>) -> Rectangle<f32> {
+        let x = self.x.min(other.x);
+        let y = self.y.min(other.y);
+        let right = (self.x + self.width).max(other.x + other.width);
+        let bottom = (self.y + self.height).max(other.y + other.height);
+
+        Rectangle {
+            x,
+            y,
+            width: right - x,
+            height: bottom - y,
+        }
+    }
 }
 
 impl std::ops::Mul<f32> for Rectangle<u32> {
@@ -1,7 +1,11 @@
+pub mod buffer;
 pub mod core;
+#[macro_use]
+pub mod shader;
 pub mod primitive;
 pub mod transformation;
 pub mod renderer;
+pub mod text;
 
 #[cfg(test)]
 mod tests {
@@ -1 +1,166 @@
-pub mod quad;
\ No newline at end of file
+pub mod custom;
+pub mod layer;
+pub mod quad;
+pub mod svg;
+pub mod text;
+
+use crate::core::color::Color;
+use crate::core::rectangle::Rectangle;
+use crate::core::vector::Vector;
+use crate::text::{rich, HorizontalAlignment, VerticalAlignment};
+use crate::transformation::Transformation;
+use std::sync::Arc;
+
+/// A rendering primitive.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub enum Primitive {
+    /// An empty primitive.
+    #[default]
+    None,
+
+    /// A group of primitives, drawn in order.
+    Group {
+        /// The primitives of the group.
+        primitives: Vec<Primitive>,
+    },
+
+    /// A quad with rounded corners and an optional border.
+    Quad {
+        /// The bounds of the quad.
+        bounds: Rectangle,
+
+        /// The background color of the quad.
+        background: Color,
+
+        /// The border radius of the quad.
+        border_radius: f32,
+
+        /// The border width of the quad.
+        border_width: f32,
+
+        /// The border color of the quad.
+        border_color: Color,
+    },
+
+    /// A run of text.
+    Text {
+        /// The contents of the text.
+        content: String,
+
+        /// The bounds of the text.
+        bounds: Rectangle,
+
+        /// The color of the text.
+        color: Color,
+
+        /// The size of the text.
+        size: f32,
+
+        /// The horizontal alignment of the text.
+        horizontal_alignment: HorizontalAlignment,
+
+        /// The vertical alignment of the text.
+        vertical_alignment: VerticalAlignment,
+    },
+
+    /// A paragraph of styled text, laid out ahead of time.
+    ///
+    /// See the [`rich`] module.
+    ///
+    /// [`rich`]: ../text/rich/index.html
+    RichText {
+        /// The layout of the paragraph.
+        layout: Arc<rich::Layout>,
+
+        /// The bounds of the paragraph, which its layout starts at the
+        /// top-left corner of.
+        bounds: Rectangle,
+    },
+
+    /// A vector image, scaled to fit its bounds and centered in them.
+    ///
+    /// See the [`svg`] module.
+    ///
+    /// [`svg`]: svg/index.html
+    Svg {
+        /// The image to draw.
+        handle: svg::Handle,
+
+        /// The bounds of the image.
+        bounds: Rectangle,
+    },
+
+    /// A primitive drawn by a [`Program`] of your own, clipped to its bounds.
+    ///
+    /// [`Program`]: custom/struct.Program.html
+    Custom {
+        /// The bounds of the primitive.
+        bounds: Rectangle,
+
+        /// The program that draws the primitive.
+        program: Arc<custom::Program>,
+
+        /// The uniforms of the program, as laid out by its shaders.
+        uniforms: Vec<u8>,
+
+        /// The instances to draw, as laid out by the program.
+        instances: Vec<u8>,
+    },
+
+    /// A primitive drawn on a texture of its own, clipped to its bounds,
+    /// then drawn as a whole over what is below it.
+    ///
+    /// See the [`layer`] module.
+    ///
+    /// [`layer`]: layer/index.html
+    Layer {
+        /// The bounds of the layer.
+        bounds: Rectangle,
+
+        /// The opacity of the whole layer, from 0 to 1.
+        opacity: f32,
+
+        /// The transformation of the layer, relative to the top-left corner
+        /// of its bounds.
+        transformation: Transformation,
+
+        /// How the layer is blended with what is below it.
+        blend: layer::Blend,
+
+        /// The content of the layer.
+        content: Box<Primitive>,
+    },
+
+    /// A primitive drawn at a z-index of its own, above every primitive of a
+    /// lower z-index and below every primitive of a higher one, wherever
+    /// they are in the tree.
+    ///
+    /// Primitives are at the z-index of the closest [`Primitive::ZIndex`]
+    /// they are in, or 0. Primitives at the same z-index are drawn in order.
+    /// The content is still clipped by the [`Primitive::Clip`]s it is in, and
+    /// only sorted against the content of the [`Primitive::Layer`] it is in.
+    ///
+    /// [`Primitive::ZIndex`]: #variant.ZIndex
+    /// [`Primitive::Clip`]: #variant.Clip
+    /// [`Primitive::Layer`]: #variant.Layer
+    ZIndex {
+        /// The z-index of the content.
+        index: i32,
+
+        /// The content drawn at the z-index.
+        content: Box<Primitive>,
+    },
+
+    /// A primitive clipped to some bounds and scrolled by an offset.
+    Clip {
+        /// The bounds outside of which the content is not drawn.
+        bounds: Rectangle,
+
+        /// The offset the content is moved back by, like the scroll position
+        /// of a scrollable area.
+        offset: Vector,
+
+        /// The clipped primitive.
+        content: Box<Primitive>,
+    },
+}
@@ -0,0 +1,453 @@
+//! Draw primitives with shaders of your own.
+//!
+//! A [`Program`] pairs a vertex and a fragment [`Shader`] with the layout of
+//! the data they read. A [`Primitive::Custom`] draws a [`Program`] inside
+//! some bounds, with its own uniforms and instances.
+//!
+//! The shaders get the same inputs as the quad pipeline:
+//!
+//! ```glsl
+//! // The corners of the unit square, from (0, 0) to (1, 1)
+//! layout(location = 0) in vec2 v_Pos;
+//!
+//! // The projection of the target, in physical pixels, and its scale factor
+//! layout(set = 0, binding = 0) uniform Globals {
+//!     mat4 u_Transform;
+//!     float u_Scale;
+//! };
+//!
+//! // The bounds of the primitive, in logical pixels: x, y, width, height
+//! layout(set = 0, binding = 1) uniform Bounds {
+//!     vec4 u_Bounds;
+//! };
+//!
+//! // The uniforms of the primitive, if the program has any
+//! layout(set = 1, binding = 0) uniform Uniforms { ... };
+//! ```
+//!
+//! The attributes of the instances start at location 1. Drawing is clipped
+//! to the bounds of the primitive.
+//!
+//! [`Program`]: struct.Program.html
+//! [`Shader`]: ../../shader/struct.Shader.html
+//! [`Primitive::Custom`]: ../enum.Primitive.html#variant.Custom
+use crate::buffer::{self, Belt};
+use crate::core::rectangle::Rectangle;
+use crate::primitive::quad;
+use crate::shader::Shader;
+use std::mem::size_of;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+
+/// The identifier of a [`Program`].
+///
+/// The renderer builds a single pipeline per [`Id`].
+///
+/// [`Program`]: struct.Program.html
+/// [`Id`]: struct.Id.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Id(u64);
+
+/// A pair of shaders and the layout of the data they read.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Program {
+    id: Id,
+    vertex: Shader,
+    fragment: Shader,
+    instance: Layout,
+    uniforms_size: u64,
+}
+
+impl Program {
+    /// Creates a [`Program`] with the given shaders, no instance attributes
+    /// and no uniforms.
+    ///
+    /// [`Program`]: struct.Program.html
+    pub fn new(vertex: Shader, fragment: Shader) -> Self {
+        Program {
+            id: Id(NEXT_ID.fetch_add(1, Ordering::Relaxed)),
+            vertex,
+            fragment,
+            instance: Layout::default(),
+            uniforms_size: 0,
+        }
+    }
+
+    /// Sets the [`Layout`] of the instances of the [`Program`].
+    ///
+    /// Without one, every primitive is drawn as a single instance.
+    ///
+    /// [`Layout`]: struct.Layout.html
+    /// [`Program`]: struct.Program.html
+    pub fn instances(mut self, layout: Layout) -> Self {
+        self.instance = layout;
+        self
+    }
+
+    /// Sets the size, in bytes, of the uniforms of the [`Program`].
+    ///
+    /// [`Program`]: struct.Program.html
+    pub fn uniforms(mut self, size: u64) -> Self {
+        self.uniforms_size = size;
+        self
+    }
+
+    /// Returns the [`Id`] of the [`Program`].
+    ///
+    /// [`Id`]: struct.Id.html
+    /// [`Program`]: struct.Program.html
+    pub fn id(&self) -> Id {
+        self.id
+    }
+}
+
+/// The layout of the instances of a [`Program`].
+///
+/// [`Program`]: struct.Program.html
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Layout {
+    /// The size of an instance, in bytes.
+    pub stride: u64,
+
+    /// The attributes of an instance.
+    pub attributes: Vec<Attribute>,
+}
+
+/// An attribute of the instances of a [`Program`].
+///
+/// [`Program`]: struct.Program.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Attribute {
+    /// The location of the attribute in the vertex shader, starting at 1.
+    pub location: u32,
+
+    /// The format of the attribute.
+    pub format: Format,
+
+    /// The offset of the attribute in an instance, in bytes.
+    pub offset: u64,
+}
+
+/// The format of an [`Attribute`].
+///
+/// [`Attribute`]: struct.Attribute.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// A `float`.
+    Float,
+
+    /// A `vec2`.
+    Float2,
+
+    /// A `vec3`.
+    Float3,
+
+    /// A `vec4`.
+    Float4,
+
+    /// A `uint`.
+    Uint,
+
+    /// An `int`.
+    Int,
+}
+
+impl From<Format> for wgpu::VertexFormat {
+    fn from(format: Format) -> wgpu::VertexFormat {
+        match format {
+            Format::Float => wgpu::VertexFormat::Float,
+            Format::Float2 => wgpu::VertexFormat::Float2,
+            Format::Float3 => wgpu::VertexFormat::Float3,
+            Format::Float4 => wgpu::VertexFormat::Float4,
+            Format::Uint => wgpu::VertexFormat::Uint,
+            Format::Int => wgpu::VertexFormat::Int,
+        }
+    }
+}
+
+/// A [`Primitive::Custom`] ready to be drawn by its [`Pipeline`].
+///
+/// [`Primitive::Custom`]: ../enum.Primitive.html#variant.Custom
+/// [`Pipeline`]: struct.Pipeline.html
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Draw<'a> {
+    /// The bounds of the primitive, in logical pixels.
+    pub bounds: Rectangle,
+
+    /// The bounds to clip the primitive to, in physical pixels.
+    pub clip: Rectangle<u32>,
+
+    /// The uniforms of the primitive.
+    pub uniforms: &'a [u8],
+
+    /// The instances of the primitive.
+    pub instances: &'a [u8],
+}
+
+/// The pipeline of a [`Program`].
+///
+/// [`Program`]: struct.Program.html
+#[derive(Debug)]
+pub(crate) struct Pipeline {
+    pipeline: wgpu::RenderPipeline,
+    globals_layout: wgpu::BindGroupLayout,
+    uniforms_layout: Option<wgpu::BindGroupLayout>,
+    vertices: wgpu::Buffer,
+    indices: wgpu::Buffer,
+    stride: u64,
+    uniforms_size: u64,
+}
+
+impl Pipeline {
+    pub(crate) fn new(device: &mut wgpu::Device, program: &Program) -> Pipeline {
+        let vs_module = program.vertex.module(device);
+        let fs_module = program.fragment.module(device);
+
+        let globals_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            bindings: &[
+                wgpu::BindGroupLayoutBinding {
+                    binding: 0,
+                    visibility: wgpu::ShaderStage::VERTEX | wgpu::ShaderStage::FRAGMENT,
+                    ty: wgpu::BindingType::UniformBuffer { dynamic: false },
+                },
+                wgpu::BindGroupLayoutBinding {
+                    binding: 1,
+                    visibility: wgpu::ShaderStage::VERTEX | wgpu::ShaderStage::FRAGMENT,
+                    ty: wgpu::BindingType::UniformBuffer { dynamic: false },
+                },
+            ],
+        });
+
+        let uniforms_layout = if program.uniforms_size > 0 {
+            Some(device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                bindings: &[wgpu::BindGroupLayoutBinding {
+                    binding: 0,
+                    visibility: wgpu::ShaderStage::VERTEX | wgpu::ShaderStage::FRAGMENT,
+                    ty: wgpu::BindingType::UniformBuffer { dynamic: false },
+                }],
+            }))
+        } else {
+            None
+        };
+
+        let bind_group_layouts: Vec<&wgpu::BindGroupLayout> = std::iter::once(&globals_layout)
+            .chain(uniforms_layout.as_ref())
+            .collect();
+
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            bind_group_layouts: &bind_group_layouts,
+        });
+
+        let attributes: Vec<wgpu::VertexAttributeDescriptor> = program
+            .instance
+            .attributes
+            .iter()
+            .map(|attribute| wgpu::VertexAttributeDescriptor {
+                shader_location: attribute.location,
+                format: attribute.format.into(),
+                offset: attribute.offset,
+            })
+            .collect();
+
+        let unit = wgpu::VertexBufferDescriptor {
+            stride: size_of::<quad::Vertex>() as u64,
+            step_mode: wgpu::InputStepMode::Vertex,
+            attributes: &[wgpu::VertexAttributeDescriptor {
+                shader_location: 0,
+                format: wgpu::VertexFormat::Float2,
+                offset: 0,
+            }],
+        };
+
+        let instances = wgpu::VertexBufferDescriptor {
+            stride: program.instance.stride,
+            step_mode: wgpu::InputStepMode::Instance,
+            attributes: &attributes,
+        };
+
+        let vertex_buffers = if program.instance.stride > 0 {
+            vec![unit, instances]
+        } else {
+            vec![unit]
+        };
+
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            layout: &layout,
+            vertex_stage: wgpu::ProgrammableStageDescriptor {
+                module: &vs_module,
+                entry_point: "main",
+            },
+            fragment_stage: Some(wgpu::ProgrammableStageDescriptor {
+                module: &fs_module,
+                entry_point: "main",
+            }),
+            rasterization_state: Some(wgpu::RasterizationStateDescriptor {
+                front_face: wgpu::FrontFace::Cw,
+                cull_mode: wgpu::CullMode::None,
+                depth_bias: 0,
+                depth_bias_slope_scale: 0.0,
+                depth_bias_clamp: 0.0,
+            }),
+            primitive_topology: wgpu::PrimitiveTopology::TriangleList,
+            color_states: &[wgpu::ColorStateDescriptor {
+                format: wgpu::TextureFormat::Bgra8UnormSrgb,
+                color_blend: wgpu::BlendDescriptor {
+                    src_factor: wgpu::BlendFactor::SrcAlpha,
+                    dst_factor: wgpu::BlendFactor::OneMinusSrcAlpha,
+                    operation: wgpu::BlendOperation::Add,
+                },
+                alpha_blend: wgpu::BlendDescriptor {
+                    src_factor: wgpu::BlendFactor::One,
+                    dst_factor: wgpu::BlendFactor::OneMinusSrcAlpha,
+                    operation: wgpu::BlendOperation::Add,
+                },
+                write_mask: wgpu::ColorWrite::ALL,
+            }],
+            depth_stencil_state: None,
+            index_format: wgpu::IndexFormat::Uint16,
+            vertex_buffers: &vertex_buffers,
+            sample_count: 1,
+            sample_mask: !0,
+            alpha_to_coverage_enabled: false,
+        });
+
+        let vertices = device
+            .create_buffer_mapped(quad::QUAD_VERTS.len(), wgpu::BufferUsage::VERTEX)
+            .fill_from_slice(&quad::QUAD_VERTS);
+
+        let indices = device
+            .create_buffer_mapped(quad::QUAD_INDICES.len(), wgpu::BufferUsage::INDEX)
+            .fill_from_slice(&quad::QUAD_INDICES);
+
+        Pipeline {
+            pipeline,
+            globals_layout,
+            uniforms_layout,
+            vertices,
+            indices,
+            stride: program.instance.stride,
+            uniforms_size: program.uniforms_size,
+        }
+    }
+
+    /// Returns the number of instances drawn with the given bytes: one if
+    /// the [`Program`] has no instances.
+    ///
+    /// [`Program`]: struct.Program.html
+    pub(crate) fn count(&self, instances: &[u8]) -> u32 {
+        (instances.len() as u64)
+            .checked_div(self.stride)
+            .map_or(1, |count| count as u32)
+    }
+
+    /// Draws a [`Primitive::Custom`].
+    ///
+    /// [`Primitive::Custom`]: ../enum.Primitive.html#variant.Custom
+    pub(crate) fn draw(&self,
+                device: &wgpu::Device,
+                belt: &mut Belt,
+                encoder: &mut wgpu::CommandEncoder,
+                globals: quad::Uniforms,
+                primitive: Draw<'_>,
+                target: &wgpu::TextureView,
+    ) {
+        let Draw {
+            bounds,
+            clip,
+            uniforms,
+            instances,
+        } = primitive;
+
+        let count = self.count(instances);
+
+        if count == 0 {
+            return;
+        }
+
+        let globals_slice = belt.uniforms(device, buffer::bytes(&[globals]));
+        let bounds_slice = belt.uniforms(
+            device,
+            buffer::bytes(&[bounds.x, bounds.y, bounds.width, bounds.height]),
+        );
+
+        let globals = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &self.globals_layout,
+            bindings: &[
+                wgpu::Binding {
+                    binding: 0,
+                    resource: wgpu::BindingResource::Buffer {
+                        buffer: globals_slice.buffer(),
+                        range: globals_slice.range(),
+                    },
+                },
+                wgpu::Binding {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Buffer {
+                        buffer: bounds_slice.buffer(),
+                        range: bounds_slice.range(),
+                    },
+                },
+            ],
+        });
+
+        let uniforms = self.uniforms_layout.as_ref().map(|layout| {
+            // Missing bytes are zeroed, extra ones are ignored
+            let mut bytes = uniforms.to_vec();
+            bytes.resize(self.uniforms_size as usize, 0);
+
+            let slice = belt.uniforms(device, &bytes);
+
+            device.create_bind_group(&wgpu::BindGroupDescriptor {
+                layout,
+                bindings: &[wgpu::Binding {
+                    binding: 0,
+                    resource: wgpu::BindingResource::Buffer {
+                        buffer: slice.buffer(),
+                        range: slice.range(),
+                    },
+                }],
+            })
+        });
+
+        let instances = if self.stride > 0 {
+            let length = (u64::from(count) * self.stride) as usize;
+
+            Some(belt.vertices(device, &instances[..length]))
+        } else {
+            None
+        };
+
+        let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            color_attachments: &[wgpu::RenderPassColorAttachmentDescriptor {
+                attachment: target,
+                resolve_target: None,
+                load_op: wgpu::LoadOp::Load,
+                store_op: wgpu::StoreOp::Store,
+                clear_color: wgpu::Color::WHITE,
+            }],
+            depth_stencil_attachment: None,
+        });
+        rpass.set_pipeline(&self.pipeline);
+        rpass.set_bind_group(0, &globals, &[]);
+
+        if let Some(uniforms) = &uniforms {
+            rpass.set_bind_group(1, uniforms, &[]);
+        }
+
+        match &instances {
+            Some(instances) => rpass.set_vertex_buffers(
+                0,
+                &[(&self.vertices, 0), (instances.buffer(), instances.offset())]),
+            None => rpass.set_vertex_buffers(0, &[(&self.vertices, 0)]),
+        }
+
+        rpass.set_index_buffer(&self.indices, 0);
+        rpass.set_scissor_rect(clip.x, clip.y, clip.width, clip.height);
+        rpass.draw_indexed(
+            0..quad::QUAD_INDICES.len() as u32,
+            0,
+            0..count,
+        )
+    }
+}
@@ -0,0 +1,399 @@
+//! Draw primitives on a texture of their own, then composite it.
+//!
+//! A [`Primitive::Layer`] is drawn as a whole: its content is drawn on a
+//! transparent texture first, which is then drawn over what is below it with
+//! an opacity, a [`Transformation`] and a [`Blend`] mode. Fading a layer
+//! fades its content as one image, so overlapping children do not show
+//! through each other.
+//!
+//! [`Primitive::Layer`]: ../enum.Primitive.html#variant.Layer
+//! [`Transformation`]: ../../transformation/struct.Transformation.html
+//! [`Blend`]: enum.Blend.html
+use crate::buffer::{self, Belt};
+use crate::core::rectangle::Rectangle;
+use crate::primitive::quad;
+use crate::shader;
+use crate::transformation::Transformation;
+use std::mem::size_of;
+use std::sync::Arc;
+
+/// The format of the textures of the layers.
+const FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Bgra8UnormSrgb;
+
+/// How the colors of a layer are combined with the ones below it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Blend {
+    /// The layer is drawn over what is below it.
+    #[default]
+    Normal,
+
+    /// The colors are multiplied, which darkens what is below the layer.
+    Multiply,
+
+    /// The inverted colors are multiplied, which lightens what is below the
+    /// layer.
+    Screen,
+
+    /// The colors are added.
+    Additive,
+}
+
+impl Blend {
+    const ALL: [Blend; 4] = [Blend::Normal, Blend::Multiply, Blend::Screen, Blend::Additive];
+
+    /// Returns how the premultiplied colors of a layer are blended.
+    fn descriptors(self) -> (wgpu::BlendDescriptor, wgpu::BlendDescriptor) {
+        let (src_factor, dst_factor) = match self {
+            Blend::Normal => (wgpu::BlendFactor::One, wgpu::BlendFactor::OneMinusSrcAlpha),
+            Blend::Multiply => (wgpu::BlendFactor::DstColor, wgpu::BlendFactor::OneMinusSrcAlpha),
+            Blend::Screen => (wgpu::BlendFactor::One, wgpu::BlendFactor::OneMinusSrcColor),
+            Blend::Additive => (wgpu::BlendFactor::One, wgpu::BlendFactor::One),
+        };
+
+        let alpha_dst_factor = match self {
+            Blend::Additive => wgpu::BlendFactor::One,
+            _ => wgpu::BlendFactor::OneMinusSrcAlpha,
+        };
+
+        (
+            wgpu::BlendDescriptor {
+                src_factor,
+                dst_factor,
+                operation: wgpu::BlendOperation::Add,
+            },
+            wgpu::BlendDescriptor {
+                src_factor: wgpu::BlendFactor::One,
+                dst_factor: alpha_dst_factor,
+                operation: wgpu::BlendOperation::Add,
+            },
+        )
+    }
+}
+
+/// A texture a layer is drawn on.
+#[derive(Debug)]
+pub(crate) struct Texture {
+    _texture: wgpu::Texture,
+    view: wgpu::TextureView,
+    width: u32,
+    height: u32,
+}
+
+impl Texture {
+    pub(crate) fn view(&self) -> &wgpu::TextureView {
+        &self.view
+    }
+
+    /// Clears the [`Texture`] to transparent, before a layer is drawn on it.
+    ///
+    /// [`Texture`]: struct.Texture.html
+    pub(crate) fn clear(&self, encoder: &mut wgpu::CommandEncoder) {
+        let _ = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            color_attachments: &[wgpu::RenderPassColorAttachmentDescriptor {
+                attachment: &self.view,
+                resolve_target: None,
+                load_op: wgpu::LoadOp::Clear,
+                store_op: wgpu::StoreOp::Store,
+                clear_color: wgpu::Color::TRANSPARENT,
+            }],
+            depth_stencil_attachment: None,
+        });
+    }
+}
+
+/// A layer to composite, drawn on a [`Texture`].
+///
+/// [`Texture`]: struct.Texture.html
+pub(crate) struct Composite<'a> {
+    /// The bounds of the layer, moved back by the offset of its clip.
+    pub bounds: Rectangle,
+
+    /// The physical pixels to draw to.
+    pub clip: Rectangle<u32>,
+
+    /// The opacity of the whole layer.
+    pub opacity: f32,
+
+    /// The transformation of the layer, relative to its top-left corner.
+    pub transformation: Transformation,
+
+    /// How the layer is blended with what is below it.
+    pub blend: Blend,
+
+    /// The texture the content of the layer was drawn on.
+    pub texture: &'a Texture,
+}
+
+#[derive(Debug)]
+pub(crate) struct Pipeline {
+    pipelines: Vec<(Blend, wgpu::RenderPipeline)>,
+    layout: wgpu::BindGroupLayout,
+    sampler: wgpu::Sampler,
+    vertices: wgpu::Buffer,
+    indices: wgpu::Buffer,
+    free: Vec<Arc<Texture>>,
+    used: Vec<Arc<Texture>>,
+}
+
+impl Pipeline {
+    pub(crate) fn new(device: &mut wgpu::Device) -> Pipeline {
+        let vs_module = shader::module(device, include_spirv!("layer.vert"));
+        let fs_module = shader::module(device, include_spirv!("layer.frag"));
+
+        let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            bindings: &[
+                wgpu::BindGroupLayoutBinding {
+                    binding: 0,
+                    visibility: wgpu::ShaderStage::VERTEX,
+                    ty: wgpu::BindingType::UniformBuffer { dynamic: false },
+                },
+                wgpu::BindGroupLayoutBinding {
+                    binding: 1,
+                    visibility: wgpu::ShaderStage::VERTEX,
+                    ty: wgpu::BindingType::UniformBuffer { dynamic: false },
+                },
+                wgpu::BindGroupLayoutBinding {
+                    binding: 2,
+                    visibility: wgpu::ShaderStage::FRAGMENT,
+                    ty: wgpu::BindingType::SampledTexture {
+                        multisampled: false,
+                        dimension: wgpu::TextureViewDimension::D2,
+                    },
+                },
+                wgpu::BindGroupLayoutBinding {
+                    binding: 3,
+                    visibility: wgpu::ShaderStage::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler,
+                },
+            ],
+        });
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            bind_group_layouts: &[&layout],
+        });
+
+        let pipelines = Blend::ALL
+            .iter()
+            .map(|&blend| {
+                let (color_blend, alpha_blend) = blend.descriptors();
+
+                let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                    layout: &pipeline_layout,
+                    vertex_stage: wgpu::ProgrammableStageDescriptor {
+                        module: &vs_module,
+                        entry_point: "main",
+                    },
+                    fragment_stage: Some(wgpu::ProgrammableStageDescriptor {
+                        module: &fs_module,
+                        entry_point: "main",
+                    }),
+                    rasterization_state: Some(wgpu::RasterizationStateDescriptor {
+                        front_face: wgpu::FrontFace::Cw,
+                        cull_mode: wgpu::CullMode::None,
+                        depth_bias: 0,
+                        depth_bias_slope_scale: 0.0,
+                        depth_bias_clamp: 0.0,
+                    }),
+                    primitive_topology: wgpu::PrimitiveTopology::TriangleList,
+                    color_states: &[wgpu::ColorStateDescriptor {
+                        format: FORMAT,
+                        color_blend,
+                        alpha_blend,
+                        write_mask: wgpu::ColorWrite::ALL,
+                    }],
+                    depth_stencil_state: None,
+                    index_format: wgpu::IndexFormat::Uint16,
+                    vertex_buffers: &[wgpu::VertexBufferDescriptor {
+                        stride: size_of::<quad::Vertex>() as u64,
+                        step_mode: wgpu::InputStepMode::Vertex,
+                        attributes: &[wgpu::VertexAttributeDescriptor {
+                            shader_location: 0,
+                            format: wgpu::VertexFormat::Float2,
+                            offset: 0,
+                        }],
+                    }],
+                    sample_count: 1,
+                    sample_mask: !0,
+                    alpha_to_coverage_enabled: false,
+                });
+
+                (blend, pipeline)
+            })
+            .collect();
+
+        // Transformed layers are sampled between texels
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            mipmap_filter: wgpu::FilterMode::Nearest,
+            lod_min_clamp: 0.0,
+            lod_max_clamp: 0.0,
+            compare_function: wgpu::CompareFunction::Always,
+        });
+
+        let vertices = device
+            .create_buffer_mapped(quad::QUAD_VERTS.len(), wgpu::BufferUsage::VERTEX)
+            .fill_from_slice(&quad::QUAD_VERTS);
+
+        let indices = device
+            .create_buffer_mapped(quad::QUAD_INDICES.len(), wgpu::BufferUsage::INDEX)
+            .fill_from_slice(&quad::QUAD_INDICES);
+
+        Pipeline {
+            pipelines,
+            layout,
+            sampler,
+            vertices,
+            indices,
+            free: Vec::new(),
+            used: Vec::new(),
+        }
+    }
+
+    /// Returns a [`Texture`] of the given size, in physical pixels, to draw
+    /// a layer on during the current frame.
+    ///
+    /// Textures are reused from one frame to the next.
+    ///
+    /// [`Texture`]: struct.Texture.html
+    pub(crate) fn texture(&mut self, device: &wgpu::Device, width: u32, height: u32) -> Arc<Texture> {
+        let texture = match self
+            .free
+            .iter()
+            .position(|texture| texture.width == width && texture.height == height)
+        {
+            Some(index) => self.free.swap_remove(index),
+            None => {
+                let texture = device.create_texture(&wgpu::TextureDescriptor {
+                    size: wgpu::Extent3d {
+                        width,
+                        height,
+                        depth: 1,
+                    },
+                    array_layer_count: 1,
+                    mip_level_count: 1,
+                    sample_count: 1,
+                    dimension: wgpu::TextureDimension::D2,
+                    format: FORMAT,
+                    usage: wgpu::TextureUsage::OUTPUT_ATTACHMENT | wgpu::TextureUsage::SAMPLED,
+                });
+
+                let view = texture.create_default_view();
+
+                Arc::new(Texture {
+                    _texture: texture,
+                    view,
+                    width,
+                    height,
+                })
+            }
+        };
+
+        self.used.push(Arc::clone(&texture));
+
+        texture
+    }
+
+    /// Makes the textures used during the frame available to the next one,
+    /// and drops the ones that were not used.
+    pub(crate) fn recall(&mut self) {
+        self.free = std::mem::take(&mut self.used);
+    }
+
+    /// Composites a layer drawn on a [`Texture`].
+    ///
+    /// [`Texture`]: struct.Texture.html
+    pub(crate) fn draw(&self,
+                device: &wgpu::Device,
+                belt: &mut Belt,
+                encoder: &mut wgpu::CommandEncoder,
+                globals: quad::Uniforms,
+                layer: Composite<'_>,
+                target: &wgpu::TextureView,
+    ) {
+        let Composite {
+            bounds,
+            clip,
+            opacity,
+            transformation,
+            blend,
+            texture,
+        } = layer;
+
+        let globals = belt.uniforms(device, buffer::bytes(&[globals]));
+        let uniforms = belt.uniforms(
+            device,
+            buffer::bytes(&[Uniforms {
+                transformation: *transformation.as_ref(),
+                bounds: [bounds.x, bounds.y, bounds.width, bounds.height],
+                opacity,
+                _padding: [0.0; 3],
+            }]),
+        );
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &self.layout,
+            bindings: &[
+                wgpu::Binding {
+                    binding: 0,
+                    resource: wgpu::BindingResource::Buffer {
+                        buffer: globals.buffer(),
+                        range: globals.range(),
+                    },
+                },
+                wgpu::Binding {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Buffer {
+                        buffer: uniforms.buffer(),
+                        range: uniforms.range(),
+                    },
+                },
+                wgpu::Binding {
+                    binding: 2,
+                    resource: wgpu::BindingResource::TextureView(texture.view()),
+                },
+                wgpu::Binding {
+                    binding: 3,
+                    resource: wgpu::BindingResource::Sampler(&self.sampler),
+                },
+            ],
+        });
+
+        let pipeline = self
+            .pipelines
+            .iter()
+            .find(|(mode, _)| *mode == blend)
+            .map(|(_, pipeline)| pipeline)
+            .expect("Pipeline for every blend mode");
+
+        let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            color_attachments: &[wgpu::RenderPassColorAttachmentDescriptor {
+                attachment: target,
+                resolve_target: None,
+                load_op: wgpu::LoadOp::Load,
+                store_op: wgpu::StoreOp::Store,
+                clear_color: wgpu::Color::WHITE,
+            }],
+            depth_stencil_attachment: None,
+        });
+        rpass.set_pipeline(pipeline);
+        rpass.set_bind_group(0, &bind_group, &[]);
+        rpass.set_vertex_buffers(0, &[(&self.vertices, 0)]);
+        rpass.set_index_buffer(&self.indices, 0);
+        rpass.set_scissor_rect(clip.x, clip.y, clip.width, clip.height);
+        rpass.draw_indexed(0..quad::QUAD_INDICES.len() as u32, 0, 0..1)
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+struct Uniforms {
+    transformation: [f32; 16],
+    bounds: [f32; 4],
+    opacity: f32,
+    // Uniform blocks are a multiple of 16 bytes long
+    _padding: [f32; 3],
+}
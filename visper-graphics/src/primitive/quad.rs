@@ -1,15 +1,26 @@
 use std::mem::size_of;
+use std::ops::Range;
+use crate::buffer::{self, Belt, Slice};
+use crate::shader;
 use crate::transformation::Transformation;
 use crate::core::rectangle::Rectangle;
 
 #[derive(Debug)]
 pub struct Pipeline {
     pipeline: wgpu::RenderPipeline,
-    constants: wgpu::BindGroup,
-    constants_buffer: wgpu::Buffer,
+    constant_layout: wgpu::BindGroupLayout,
     vertices: wgpu::Buffer,
     indices: wgpu::Buffer,
-    instances: wgpu::Buffer,
+}
+
+/// Quads uploaded by [`Pipeline::prepare`], with the projection of the
+/// target they are drawn on.
+///
+/// [`Pipeline::prepare`]: struct.Pipeline.html#method.prepare
+#[derive(Debug)]
+pub(crate) struct Batch {
+    constants: wgpu::BindGroup,
+    instances: Option<Slice>,
 }
 
 impl Pipeline {
@@ -17,17 +28,8 @@ impl Pipeline {
         let vs_module: wgpu::ShaderModule;
         let fs_module: wgpu::ShaderModule;
         let (vertex_stage, fragment_stage) = {
-            let vs = include_bytes!("shaders/hello/vert");
-            vs_module = device.create_shader_module(
-                &wgpu::read_spirv(std::io::Cursor::new(&vs[..]))
-                    .expect("Read quad vertex shader as SPIR-V"),
-            );
-
-            let fs = include_bytes!("shaders/hello/frag");
-            fs_module = device.create_shader_module(
-                &wgpu::read_spirv(std::io::Cursor::new(&fs[..]))
-                    .expect("Read quad fragment shader as SPIR-V"),
-            );
+            vs_module = shader::module(device, include_spirv!("quad.vert"));
+            fs_module = shader::module(device, include_spirv!("quad.frag"));
 
             let vertex_stage = wgpu::ProgrammableStageDescriptor {
                 module: &vs_module,
@@ -92,40 +94,18 @@ impl Pipeline {
             ]
         };
 
-        let (layout, bind_group, constants_buffer) = {
-            let constant_layout =
-                device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
-                    bindings: &[wgpu::BindGroupLayoutBinding {
-                        binding: 0,
-                        visibility: wgpu::ShaderStage::VERTEX,
-                        ty: wgpu::BindingType::UniformBuffer { dynamic: false },
-                    }],
-                });
-
-            let constants_buffer = device
-                .create_buffer_mapped(
-                    1,
-                    wgpu::BufferUsage::UNIFORM | wgpu::BufferUsage::COPY_DST,
-                )
-                .fill_from_slice(&[Uniforms::default()]);
-
-            let constants = device.create_bind_group(&wgpu::BindGroupDescriptor {
-                layout: &constant_layout,
-                bindings: &[wgpu::Binding {
+        let constant_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                bindings: &[wgpu::BindGroupLayoutBinding {
                     binding: 0,
-                    resource: wgpu::BindingResource::Buffer {
-                        buffer: &constants_buffer,
-                        range: 0..std::mem::size_of::<Uniforms>() as u64,
-                    },
+                    visibility: wgpu::ShaderStage::VERTEX,
+                    ty: wgpu::BindingType::UniformBuffer { dynamic: false },
                 }],
             });
 
-            let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
-                bind_group_layouts: &[&constant_layout],
-            });
-
-            (layout, constants, constants_buffer)
-        };
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            bind_group_layouts: &[&constant_layout],
+        });
 
         let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
             layout: &layout,
@@ -169,83 +149,91 @@ impl Pipeline {
             .create_buffer_mapped(QUAD_INDICES.len(), wgpu::BufferUsage::INDEX)
             .fill_from_slice(&QUAD_INDICES);
 
-        let instances = device.create_buffer(&wgpu::BufferDescriptor {
-            size: size_of::<Quad>() as u64 * Quad::MAX as u64,
-            usage: wgpu::BufferUsage::VERTEX | wgpu::BufferUsage::COPY_DST,
-        });
-
         Pipeline {
             pipeline,
-            constants: bind_group,
-            constants_buffer,
+            constant_layout,
             vertices,
             indices,
+        }
+    }
+
+    /// Uploads the quads drawn on a target at once through the [`Belt`].
+    ///
+    /// Each layer then draws its own range of the [`Batch`] with a single
+    /// draw call, however many quads it has.
+    ///
+    /// [`Belt`]: ../../buffer/struct.Belt.html
+    /// [`Batch`]: struct.Batch.html
+    pub(crate) fn prepare(&self,
+                   device: &wgpu::Device,
+                   belt: &mut Belt,
+                   transformation: Transformation,
+                   scale: f64,
+                   instances: &[Quad],
+    ) -> Batch {
+        let uniforms = belt.uniforms(device, buffer::bytes(&[Uniforms::new(transformation, scale)]));
+
+        let constants = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &self.constant_layout,
+            bindings: &[wgpu::Binding {
+                binding: 0,
+                resource: wgpu::BindingResource::Buffer {
+                    buffer: uniforms.buffer(),
+                    range: uniforms.range(),
+                },
+            }],
+        });
+
+        let instances = if instances.is_empty() {
+            None
+        } else {
+            Some(belt.vertices(device, buffer::bytes(instances)))
+        };
+
+        Batch {
+            constants,
             instances,
         }
     }
 
-    pub fn draw(&mut self,
-                device: &mut wgpu::Device,
+    /// Draws a range of the quads of a [`Batch`], clipped to the given
+    /// bounds.
+    ///
+    /// [`Batch`]: struct.Batch.html
+    pub(crate) fn draw(&self,
                 encoder: &mut wgpu::CommandEncoder,
-                transformation: Transformation,
-                scale: f64,
+                batch: &Batch,
                 bounds: Rectangle<u32>,
-                instances: &[Quad],
+                range: Range<u32>,
                 target: &wgpu::TextureView,
     ) {
-        let uniforms = Uniforms::new(transformation, scale);
-        let constants_buffer = device
-            .create_buffer_mapped(1, wgpu::BufferUsage::COPY_SRC)
-            .fill_from_slice(&[uniforms]);
-
-        encoder.copy_buffer_to_buffer(
-            &constants_buffer,
-            0,
-            &self.constants_buffer,
-            0,
-            std::mem::size_of::<Uniforms>() as u64,
-        );
-
-        let amount = 1;
-        let instance_buffer = device
-            .create_buffer_mapped(amount, wgpu::BufferUsage::COPY_SRC)
-            .fill_from_slice(&instances[0..amount]);
+        let instances = match &batch.instances {
+            Some(instances) => instances,
+            None => return,
+        };
 
-        encoder.copy_buffer_to_buffer(
-            &instance_buffer,
+        let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            color_attachments: &[wgpu::RenderPassColorAttachmentDescriptor {
+                attachment: target,
+                resolve_target: None,
+                load_op: wgpu::LoadOp::Load,
+                store_op: wgpu::StoreOp::Store,
+                clear_color: wgpu::Color::WHITE,
+            }],
+            depth_stencil_attachment: None,
+        });
+        rpass.set_pipeline(&self.pipeline);
+        rpass.set_bind_group(0, &batch.constants, &[]);
+        rpass.set_vertex_buffers(
             0,
-            &self.instances,
+            &[(&self.vertices, 0), (instances.buffer(), instances.offset())]);
+        rpass.set_index_buffer(&self.indices, 0);
+        rpass.set_scissor_rect(bounds.x, bounds.y, bounds.width, bounds.height);
+        rpass.draw_indexed(
+            0..QUAD_INDICES.len() as u32,
             0,
-            (size_of::<Quad>() * amount) as u64,
-        );
-
-        {
-            let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-                color_attachments: &[wgpu::RenderPassColorAttachmentDescriptor {
-                    attachment: target,
-                    resolve_target: None,
-                    load_op: wgpu::LoadOp::Clear,
-                    store_op: wgpu::StoreOp::Store,
-                    clear_color: wgpu::Color::WHITE,
-                }],
-                depth_stencil_attachment: None,
-            });
-            rpass.set_pipeline(&self.pipeline);
-            rpass.set_bind_group(0, &self.constants, &[]);
-            rpass.set_vertex_buffers(
-                0,
-                &[(&self.vertices, 0), (&self.instances, 0)]);
-//            rpass.set_vertex_buffers(
-//                0,
-//                &[(&self.vertices, 0)]);
-            rpass.set_index_buffer(&self.indices, 0);
-//            rpass.set_scissor_rect(bounds.x, bounds.y, bounds.width, bounds.height);
-            rpass.draw_indexed(
-                0..QUAD_INDICES.len() as u32,
-                0,
-                0..1 as u32,
-            )
-        }
+            range,
+        )
     }
 }
 
@@ -255,9 +243,9 @@ pub struct Vertex {
     _position: [f32; 2],
 }
 
-const QUAD_INDICES: [u16; 6] = [0, 1, 2, 0, 2, 3];
+pub(crate) const QUAD_INDICES: [u16; 6] = [0, 1, 2, 0, 2, 3];
 
-const QUAD_VERTS: [Vertex; 4] = [
+pub(crate) const QUAD_VERTS: [Vertex; 4] = [
     Vertex {
         _position: [0.0, 0.0],
     },
@@ -283,19 +271,15 @@ pub struct Quad {
     pub border_width: f32,
 }
 
-impl Quad {
-    const MAX: usize = 100_000;
-}
-
 #[repr(C)]
 #[derive(Debug, Clone, Copy)]
-struct Uniforms {
+pub(crate) struct Uniforms {
     transform: [f32; 16],
     scale: f32,
 }
 
 impl Uniforms {
-    fn new(transformation: Transformation, scale: f64) -> Uniforms {
+    pub(crate) fn new(transformation: Transformation, scale: f64) -> Uniforms {
         Self {
             transform: *transformation.as_ref(),
             scale: scale as f32,
@@ -0,0 +1,12 @@
+#version 450
+
+layout(location = 0) out vec2 o_Uv;
+
+// A triangle covering the whole target, without any vertex buffer
+void main() {
+    vec2 uv = vec2((gl_VertexIndex << 1) & 2, gl_VertexIndex & 2);
+
+    o_Uv = uv;
+
+    gl_Position = vec4(uv.x * 2.0 - 1.0, 1.0 - uv.y * 2.0, 0.0, 1.0);
+}
@@ -0,0 +1,14 @@
+#version 450
+
+layout(location = 0) in vec2 v_Uv;
+layout(location = 1) in float v_Opacity;
+
+layout(set = 0, binding = 2) uniform texture2D t_Layer;
+layout(set = 0, binding = 3) uniform sampler s_Layer;
+
+layout(location = 0) out vec4 o_Color;
+
+void main() {
+    // The layer holds premultiplied colors
+    o_Color = texture(sampler2D(t_Layer, s_Layer), v_Uv) * v_Opacity;
+}
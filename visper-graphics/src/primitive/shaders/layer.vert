@@ -0,0 +1,28 @@
+#version 450
+
+layout(location = 0) in vec2 v_Pos;
+
+layout (set = 0, binding = 0) uniform Globals {
+    mat4 u_Transform;
+    float u_Scale;
+};
+
+layout (set = 0, binding = 1) uniform Layer {
+    mat4 u_Layer;
+    vec4 u_Bounds;
+    float u_Opacity;
+};
+
+layout(location = 0) out vec2 o_Uv;
+layout(location = 1) out float o_Opacity;
+
+void main() {
+    // The transformation of the layer is relative to its top-left corner
+    vec4 local = u_Layer * vec4(v_Pos * u_Bounds.zw, 0.0, 1.0);
+    vec2 position = u_Bounds.xy + local.xy;
+
+    o_Uv = v_Pos;
+    o_Opacity = u_Opacity;
+
+    gl_Position = u_Transform * vec4(position * u_Scale, 0.0, 1.0);
+}
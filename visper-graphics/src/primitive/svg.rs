@@ -0,0 +1,572 @@
+//! Draw vector images.
+//!
+//! A [`Primitive::Svg`] is parsed once, then rasterized at the physical size
+//! it is drawn at on a target, so it stays crisp at any scale factor. The
+//! rasters are cached by [`Handle`] and size, and dropped when they have not
+//! been drawn for a while.
+//!
+//! Paths, basic shapes, fills, strokes, gradients and transforms are
+//! supported, through [`usvg`] and [`resvg`].
+//!
+//! [`Primitive::Svg`]: ../enum.Primitive.html#variant.Svg
+//! [`Handle`]: struct.Handle.html
+//! [`usvg`]: https://docs.rs/usvg
+//! [`resvg`]: https://docs.rs/resvg
+use crate::buffer::{self, Belt};
+use crate::core::rectangle::Rectangle;
+use crate::core::size::Size;
+use crate::primitive::quad;
+use crate::shader;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::mem::size_of;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// The number of frames a raster is kept for without being drawn.
+const KEEP_FRAMES: u64 = 120;
+
+/// A vector image to draw, loaded from a file or from memory.
+///
+/// Cloning a [`Handle`] is cheap. Handles to the same data are equal and
+/// share their cache.
+///
+/// [`Handle`]: struct.Handle.html
+#[derive(Debug, Clone)]
+pub struct Handle {
+    id: u64,
+    data: Arc<Data>,
+}
+
+/// Where the data of a [`Handle`] comes from.
+///
+/// [`Handle`]: struct.Handle.html
+#[derive(Clone, Hash, PartialEq, Eq)]
+pub enum Data {
+    /// A file, read when the image is first drawn.
+    Path(PathBuf),
+
+    /// The contents of an SVG document.
+    Bytes(Vec<u8>),
+}
+
+impl Handle {
+    /// Creates a [`Handle`] to the SVG file at the given path.
+    ///
+    /// [`Handle`]: struct.Handle.html
+    pub fn from_path<P: Into<PathBuf>>(path: P) -> Handle {
+        Handle::from_data(Data::Path(path.into()))
+    }
+
+    /// Creates a [`Handle`] to an SVG document in memory.
+    ///
+    /// [`Handle`]: struct.Handle.html
+    pub fn from_memory<B: Into<Vec<u8>>>(bytes: B) -> Handle {
+        Handle::from_data(Data::Bytes(bytes.into()))
+    }
+
+    fn from_data(data: Data) -> Handle {
+        let mut hasher = DefaultHasher::new();
+        data.hash(&mut hasher);
+
+        Handle {
+            id: hasher.finish(),
+            data: Arc::new(data),
+        }
+    }
+
+    /// Returns the identifier of the data of the [`Handle`].
+    ///
+    /// [`Handle`]: struct.Handle.html
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    pub fn data(&self) -> &Data {
+        &self.data
+    }
+}
+
+impl PartialEq for Handle {
+    fn eq(&self, other: &Handle) -> bool {
+        // Different data may hash to the same id
+        self.id == other.id && (Arc::ptr_eq(&self.data, &other.data) || self.data == other.data)
+    }
+}
+
+impl Eq for Handle {}
+
+impl Hash for Handle {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.id.hash(state);
+    }
+}
+
+impl fmt::Debug for Data {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Data::Path(path) => write!(f, "Path({:?})", path),
+            Data::Bytes(bytes) => write!(f, "Bytes({} bytes)", bytes.len()),
+        }
+    }
+}
+
+/// Returns the size of an image of the given size scaled to fit in the
+/// bounds, keeping its aspect ratio, and centered in them.
+pub(crate) fn fit(size: Size, bounds: Rectangle) -> Rectangle {
+    if size.width <= 0.0 || size.height <= 0.0 {
+        return bounds;
+    }
+
+    let scale = (bounds.width / size.width).min(bounds.height / size.height);
+    let width = size.width * scale;
+    let height = size.height * scale;
+
+    Rectangle {
+        x: bounds.x + (bounds.width - width) / 2.0,
+        y: bounds.y + (bounds.height - height) / 2.0,
+        width,
+        height,
+    }
+}
+
+/// A [`Primitive::Svg`] to draw, with its bounds relative to the target.
+///
+/// [`Primitive::Svg`]: ../enum.Primitive.html#variant.Svg
+#[derive(Debug)]
+pub(crate) struct Draw<'a> {
+    pub handle: &'a Handle,
+    pub bounds: Rectangle,
+    pub clip: Rectangle<u32>,
+    pub scale_factor: f64,
+}
+
+#[derive(Debug)]
+struct Raster {
+    _texture: wgpu::Texture,
+    _view: wgpu::TextureView,
+    bind_group: wgpu::BindGroup,
+    last_used: u64,
+}
+
+struct Tree {
+    tree: Option<usvg::Tree>,
+    last_used: u64,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+struct Instance {
+    position: [f32; 2],
+    scale: [f32; 2],
+}
+
+/// Rasterizes vector images and draws the rasters.
+pub(crate) struct Pipeline {
+    pipeline: wgpu::RenderPipeline,
+    constant_layout: wgpu::BindGroupLayout,
+    texture_layout: wgpu::BindGroupLayout,
+    sampler: wgpu::Sampler,
+    vertices: wgpu::Buffer,
+    indices: wgpu::Buffer,
+    trees: HashMap<Handle, Tree>,
+    rasters: HashMap<(Handle, u32, u32), Raster>,
+    frame: u64,
+}
+
+impl Pipeline {
+    pub(crate) fn new(device: &mut wgpu::Device) -> Pipeline {
+        let vs_module = shader::module(device, include_spirv!("image.vert"));
+        let fs_module = shader::module(device, include_spirv!("image.frag"));
+
+        let constant_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            bindings: &[
+                wgpu::BindGroupLayoutBinding {
+                    binding: 0,
+                    visibility: wgpu::ShaderStage::VERTEX,
+                    ty: wgpu::BindingType::UniformBuffer { dynamic: false },
+                },
+                wgpu::BindGroupLayoutBinding {
+                    binding: 1,
+                    visibility: wgpu::ShaderStage::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler,
+                },
+            ],
+        });
+
+        let texture_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            bindings: &[wgpu::BindGroupLayoutBinding {
+                binding: 0,
+                visibility: wgpu::ShaderStage::FRAGMENT,
+                ty: wgpu::BindingType::SampledTexture {
+                    multisampled: false,
+                    dimension: wgpu::TextureViewDimension::D2,
+                },
+            }],
+        });
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            bind_group_layouts: &[&constant_layout, &texture_layout],
+        });
+
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            layout: &pipeline_layout,
+            vertex_stage: wgpu::ProgrammableStageDescriptor {
+                module: &vs_module,
+                entry_point: "main",
+            },
+            fragment_stage: Some(wgpu::ProgrammableStageDescriptor {
+                module: &fs_module,
+                entry_point: "main",
+            }),
+            rasterization_state: Some(wgpu::RasterizationStateDescriptor {
+                front_face: wgpu::FrontFace::Cw,
+                cull_mode: wgpu::CullMode::None,
+                depth_bias: 0,
+                depth_bias_slope_scale: 0.0,
+                depth_bias_clamp: 0.0,
+            }),
+            primitive_topology: wgpu::PrimitiveTopology::TriangleList,
+            color_states: &[wgpu::ColorStateDescriptor {
+                format: wgpu::TextureFormat::Bgra8UnormSrgb,
+                color_blend: wgpu::BlendDescriptor {
+                    src_factor: wgpu::BlendFactor::SrcAlpha,
+                    dst_factor: wgpu::BlendFactor::OneMinusSrcAlpha,
+                    operation: wgpu::BlendOperation::Add,
+                },
+                alpha_blend: wgpu::BlendDescriptor {
+                    src_factor: wgpu::BlendFactor::One,
+                    dst_factor: wgpu::BlendFactor::OneMinusSrcAlpha,
+                    operation: wgpu::BlendOperation::Add,
+                },
+                write_mask: wgpu::ColorWrite::ALL,
+            }],
+            depth_stencil_state: None,
+            index_format: wgpu::IndexFormat::Uint16,
+            vertex_buffers: &[
+                wgpu::VertexBufferDescriptor {
+                    stride: size_of::<quad::Vertex>() as u64,
+                    step_mode: wgpu::InputStepMode::Vertex,
+                    attributes: &[wgpu::VertexAttributeDescriptor {
+                        shader_location: 0,
+                        format: wgpu::VertexFormat::Float2,
+                        offset: 0,
+                    }],
+                },
+                wgpu::VertexBufferDescriptor {
+                    stride: size_of::<Instance>() as u64,
+                    step_mode: wgpu::InputStepMode::Instance,
+                    attributes: &[
+                        wgpu::VertexAttributeDescriptor {
+                            shader_location: 1,
+                            format: wgpu::VertexFormat::Float2,
+                            offset: 0,
+                        },
+                        wgpu::VertexAttributeDescriptor {
+                            shader_location: 2,
+                            format: wgpu::VertexFormat::Float2,
+                            offset: 4 * 2,
+                        },
+                    ],
+                },
+            ],
+            sample_count: 1,
+            sample_mask: !0,
+            alpha_to_coverage_enabled: false,
+        });
+
+        // Rasters are drawn at their size, on whole pixels
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Nearest,
+            min_filter: wgpu::FilterMode::Nearest,
+            mipmap_filter: wgpu::FilterMode::Nearest,
+            lod_min_clamp: 0.0,
+            lod_max_clamp: 0.0,
+            compare_function: wgpu::CompareFunction::Always,
+        });
+
+        let vertices = device
+            .create_buffer_mapped(quad::QUAD_VERTS.len(), wgpu::BufferUsage::VERTEX)
+            .fill_from_slice(&quad::QUAD_VERTS);
+
+        let indices = device
+            .create_buffer_mapped(quad::QUAD_INDICES.len(), wgpu::BufferUsage::INDEX)
+            .fill_from_slice(&quad::QUAD_INDICES);
+
+        Pipeline {
+            pipeline,
+            constant_layout,
+            texture_layout,
+            sampler,
+            vertices,
+            indices,
+            trees: HashMap::new(),
+            rasters: HashMap::new(),
+            frame: 0,
+        }
+    }
+
+    /// Draws a vector image, rasterizing it first if it was not drawn at
+    /// this size recently.
+    ///
+    /// Images that cannot be read or parsed are not drawn.
+    ///
+    /// Returns whether the image was drawn.
+    pub(crate) fn draw(
+        &mut self,
+        device: &wgpu::Device,
+        belt: &mut Belt,
+        encoder: &mut wgpu::CommandEncoder,
+        globals: quad::Uniforms,
+        svg: Draw<'_>,
+        target: &wgpu::TextureView,
+    ) -> bool {
+        let frame = self.frame;
+        let tree = self.trees.entry(svg.handle.clone()).or_insert_with(|| Tree {
+            tree: parse(&svg.handle.data),
+            last_used: frame,
+        });
+        tree.last_used = frame;
+
+        let tree = match &tree.tree {
+            Some(tree) => tree,
+            None => return false,
+        };
+
+        let size = tree.svg_node().size;
+        let bounds = fit(
+            Size::new(size.width() as f32, size.height() as f32),
+            svg.bounds,
+        );
+
+        // Rasters are aligned to physical pixels to stay crisp
+        let scale = svg.scale_factor as f32;
+        let x = (bounds.x * scale).round();
+        let y = (bounds.y * scale).round();
+        let width = (bounds.width * scale).round() as u32;
+        let height = (bounds.height * scale).round() as u32;
+
+        if width == 0 || height == 0 {
+            return false;
+        }
+
+        let texture_layout = &self.texture_layout;
+        let raster = match self.rasters.entry((svg.handle.clone(), width, height)) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => {
+                match rasterize(device, encoder, texture_layout, tree, width, height) {
+                    Some(raster) => entry.insert(raster),
+                    None => return false,
+                }
+            }
+        };
+        raster.last_used = frame;
+
+        let globals = belt.uniforms(device, buffer::bytes(&[globals]));
+        let instance = belt.vertices(
+            device,
+            buffer::bytes(&[Instance {
+                position: [x, y],
+                scale: [width as f32, height as f32],
+            }]),
+        );
+
+        let constants = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &self.constant_layout,
+            bindings: &[
+                wgpu::Binding {
+                    binding: 0,
+                    resource: wgpu::BindingResource::Buffer {
+                        buffer: globals.buffer(),
+                        range: globals.range(),
+                    },
+                },
+                wgpu::Binding {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(&self.sampler),
+                },
+            ],
+        });
+
+        let clip = svg.clip;
+        let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            color_attachments: &[wgpu::RenderPassColorAttachmentDescriptor {
+                attachment: target,
+                resolve_target: None,
+                load_op: wgpu::LoadOp::Load,
+                store_op: wgpu::StoreOp::Store,
+                clear_color: wgpu::Color::WHITE,
+            }],
+            depth_stencil_attachment: None,
+        });
+        rpass.set_pipeline(&self.pipeline);
+        rpass.set_bind_group(0, &constants, &[]);
+        rpass.set_bind_group(1, &raster.bind_group, &[]);
+        rpass.set_vertex_buffers(
+            0,
+            &[(&self.vertices, 0), (instance.buffer(), instance.offset())],
+        );
+        rpass.set_index_buffer(&self.indices, 0);
+        rpass.set_scissor_rect(clip.x, clip.y, clip.width, clip.height);
+        rpass.draw_indexed(0..quad::QUAD_INDICES.len() as u32, 0, 0..1);
+
+        true
+    }
+
+    /// Drops the images and rasters that were not drawn recently, and starts
+    /// the next frame.
+    pub(crate) fn trim(&mut self) {
+        let frame = self.frame;
+
+        self.rasters
+            .retain(|_, raster| frame - raster.last_used <= KEEP_FRAMES);
+        self.trees
+            .retain(|_, tree| frame - tree.last_used <= KEEP_FRAMES);
+
+        self.frame += 1;
+    }
+}
+
+impl fmt::Debug for Pipeline {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Pipeline")
+            .field("trees", &self.trees.len())
+            .field("rasters", &self.rasters.len())
+            .field("frame", &self.frame)
+            .finish()
+    }
+}
+
+fn parse(data: &Data) -> Option<usvg::Tree> {
+    let bytes = match data {
+        Data::Path(path) => std::fs::read(path).ok()?,
+        Data::Bytes(bytes) => bytes.clone(),
+    };
+
+    usvg::Tree::from_data(&bytes, &usvg::Options::default()).ok()
+}
+
+/// Rasterizes a tree at the given size, in physical pixels, and uploads it
+/// before the commands recorded next.
+fn rasterize(
+    device: &wgpu::Device,
+    encoder: &mut wgpu::CommandEncoder,
+    layout: &wgpu::BindGroupLayout,
+    tree: &usvg::Tree,
+    width: u32,
+    height: u32,
+) -> Option<Raster> {
+    // The raster has the aspect ratio of the image, so fitting one side fits both
+    let fit_to = if width >= height {
+        usvg::FitTo::Width(width)
+    } else {
+        usvg::FitTo::Height(height)
+    };
+
+    let image = resvg::render(tree, fit_to, None)?;
+
+    // The image may be a pixel smaller than asked, to keep its aspect ratio
+    let extent = wgpu::Extent3d {
+        width: image.width(),
+        height: image.height(),
+        depth: 1,
+    };
+
+    let texture = device.create_texture(&wgpu::TextureDescriptor {
+        size: extent,
+        array_layer_count: 1,
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: wgpu::TextureFormat::Rgba8UnormSrgb,
+        usage: wgpu::TextureUsage::COPY_DST | wgpu::TextureUsage::SAMPLED,
+    });
+
+    let pixels = device
+        .create_buffer_mapped(image.data().len(), wgpu::BufferUsage::COPY_SRC)
+        .fill_from_slice(image.data());
+
+    encoder.copy_buffer_to_texture(
+        wgpu::BufferCopyView {
+            buffer: &pixels,
+            offset: 0,
+            row_pitch: 4 * extent.width,
+            image_height: extent.height,
+        },
+        wgpu::TextureCopyView {
+            texture: &texture,
+            mip_level: 0,
+            array_layer: 0,
+            origin: wgpu::Origin3d { x: 0.0, y: 0.0, z: 0.0 },
+        },
+        extent,
+    );
+
+    let view = texture.create_default_view();
+
+    let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+        layout,
+        bindings: &[wgpu::Binding {
+            binding: 0,
+            resource: wgpu::BindingResource::TextureView(&view),
+        }],
+    });
+
+    Some(Raster {
+        _texture: texture,
+        _view: view,
+        bind_group,
+        last_used: 0,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn handles_to_the_same_data_are_equal() {
+        let icon = b"<svg xmlns='http://www.w3.org/2000/svg' width='24' height='24'/>";
+
+        assert_eq!(Handle::from_memory(&icon[..]), Handle::from_memory(&icon[..]));
+        assert_ne!(Handle::from_memory(&icon[..]), Handle::from_path("icon.svg"));
+    }
+
+    #[test]
+    fn handles_with_the_same_id_compare_their_data() {
+        let icon = Handle::from_memory(&b"<svg/>"[..]);
+        let colliding = Handle {
+            id: icon.id,
+            data: Arc::new(Data::Bytes(b"<svg></svg>".to_vec())),
+        };
+
+        assert_ne!(icon, colliding);
+        assert_eq!(icon, icon.clone());
+    }
+
+    #[test]
+    fn images_fit_their_bounds() {
+        let bounds = Rectangle {
+            x: 10.0,
+            y: 10.0,
+            width: 40.0,
+            height: 20.0,
+        };
+
+        assert_eq!(
+            fit(Size::new(24.0, 24.0), bounds),
+            Rectangle {
+                x: 20.0,
+                y: 10.0,
+                width: 20.0,
+                height: 20.0,
+            }
+        );
+    }
+}
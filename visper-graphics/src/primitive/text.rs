@@ -0,0 +1,192 @@
+use crate::core::rectangle::Rectangle;
+use crate::core::vector::Vector;
+use crate::primitive::Primitive;
+use crate::text::font::{self, Face};
+use crate::text::rich::Layout;
+use crate::text::{HorizontalAlignment, VerticalAlignment, Weight, DEFAULT_FONT};
+use crate::transformation::Transformation;
+use std::collections::HashMap;
+use wgpu_glyph::rusttype::{self, point, GlyphId, Scale};
+use wgpu_glyph::FontId;
+
+pub struct Pipeline {
+    glyph_brush: wgpu_glyph::GlyphBrush<'static, ()>,
+    /// The fonts of the glyph brush for the faces drawn so far.
+    fonts: HashMap<font::Id, FontId>,
+}
+
+impl Pipeline {
+    pub fn new(device: &mut wgpu::Device) -> Pipeline {
+        let glyph_brush = wgpu_glyph::GlyphBrushBuilder::using_font_bytes(DEFAULT_FONT)
+            .expect("Load default font")
+            .build(device, wgpu::TextureFormat::Bgra8UnormSrgb);
+
+        Pipeline {
+            glyph_brush,
+            fonts: HashMap::new(),
+        }
+    }
+
+    /// Queues a [`Primitive::Text`] or a [`Primitive::RichText`] to be drawn
+    /// by the next call to [`draw`].
+    ///
+    /// The text is moved back by the given offset. Sections are laid out in
+    /// physical pixels so glyphs are rasterized at the resolution of the
+    /// target. Other primitives are ignored.
+    ///
+    /// [`Primitive::Text`]: ../enum.Primitive.html#variant.Text
+    /// [`Primitive::RichText`]: ../enum.Primitive.html#variant.RichText
+    /// [`draw`]: #method.draw
+    pub fn queue(&mut self, text: &Primitive, offset: Vector, scale: f64) {
+        if let Primitive::RichText { layout, bounds } = text {
+            let bounds = Rectangle {
+                x: bounds.x - offset.x,
+                y: bounds.y - offset.y,
+                ..*bounds
+            };
+
+            self.queue_layout(layout, bounds, scale as f32);
+        }
+
+        if let Primitive::Text {
+            content,
+            bounds,
+            color,
+            size,
+            horizontal_alignment,
+            vertical_alignment,
+        } = text
+        {
+            let scale = scale as f32;
+            let Rectangle { x, y, width, height } = *bounds;
+            let (x, y) = (x - offset.x, y - offset.y);
+
+            let x = match horizontal_alignment {
+                HorizontalAlignment::Left => x,
+                HorizontalAlignment::Center => x + width / 2.0,
+                HorizontalAlignment::Right => x + width,
+            };
+
+            let y = match vertical_alignment {
+                VerticalAlignment::Top => y,
+                VerticalAlignment::Center => y + height / 2.0,
+                VerticalAlignment::Bottom => y + height,
+            };
+
+            self.glyph_brush.queue(wgpu_glyph::Section {
+                text: content,
+                screen_position: (x * scale, y * scale),
+                bounds: (width * scale, height * scale),
+                scale: wgpu_glyph::Scale::uniform(size * scale),
+                color: color.into_linear(),
+                layout: wgpu_glyph::Layout::default()
+                    .h_align(match horizontal_alignment {
+                        HorizontalAlignment::Left => wgpu_glyph::HorizontalAlign::Left,
+                        HorizontalAlignment::Center => wgpu_glyph::HorizontalAlign::Center,
+                        HorizontalAlignment::Right => wgpu_glyph::HorizontalAlign::Right,
+                    })
+                    .v_align(match vertical_alignment {
+                        VerticalAlignment::Top => wgpu_glyph::VerticalAlign::Top,
+                        VerticalAlignment::Center => wgpu_glyph::VerticalAlign::Center,
+                        VerticalAlignment::Bottom => wgpu_glyph::VerticalAlign::Bottom,
+                    }),
+                ..Default::default()
+            });
+        }
+    }
+
+    /// Queues the glyphs of a [`Layout`] starting at the top-left corner of
+    /// the given bounds.
+    ///
+    /// Faces are added to the glyph brush the first time they are drawn, and
+    /// shared by all the targets. Bold glyphs of a lighter face are drawn
+    /// twice, side by side, to make them thicker.
+    ///
+    /// [`Layout`]: ../../text/rich/struct.Layout.html
+    fn queue_layout(&mut self, layout: &Layout, bounds: Rectangle, scale: f32) {
+        let mut glyphs = Vec::with_capacity(layout.glyphs().len());
+
+        for glyph in layout.glyphs() {
+            let (font_id, font) = match self.font(&glyph.face) {
+                Some(font) => font,
+                None => continue,
+            };
+
+            let positioned = font
+                .glyph(GlyphId(glyph.id))
+                .scaled(Scale::uniform(glyph.size * scale))
+                .positioned(point(
+                    (bounds.x + glyph.position.x) * scale,
+                    (bounds.y + glyph.position.y) * scale,
+                ));
+
+            let color = glyph.color.into_linear();
+
+            if glyph.weight >= Weight::SEMIBOLD && glyph.face.weight() < Weight::SEMIBOLD {
+                let mut emboldened = positioned.clone();
+                let mut position = emboldened.position();
+                position.x += (glyph.size * scale / 24.0).max(1.0);
+                emboldened.set_position(position);
+
+                glyphs.push((emboldened, color, font_id));
+            }
+
+            glyphs.push((positioned, color, font_id));
+        }
+
+        // Glyphs may go past the bounds of the text they are laid out in
+        let overflow = layout.overflow();
+
+        self.glyph_brush.queue_pre_positioned(
+            glyphs,
+            rusttype::Rect {
+                min: point((bounds.x - overflow) * scale, (bounds.y - overflow) * scale),
+                max: point(
+                    (bounds.x + bounds.width + overflow) * scale,
+                    (bounds.y + bounds.height + overflow) * scale,
+                ),
+            },
+            0.0,
+        );
+    }
+
+    /// Returns the font of the glyph brush for a [`Face`], adding it the first
+    /// time.
+    ///
+    /// [`Face`]: ../../text/font/struct.Face.html
+    fn font(&mut self, face: &Face) -> Option<(FontId, rusttype::Font<'static>)> {
+        let font = face.font()?;
+        let glyph_brush = &mut self.glyph_brush;
+        let id = *self
+            .fonts
+            .entry(face.id())
+            .or_insert_with(|| glyph_brush.add_font(font.clone()));
+
+        Some((id, font))
+    }
+
+    /// Draws the queued text, clipped to the given bounds in physical pixels.
+    pub fn draw(
+        &mut self,
+        device: &mut wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        transformation: Transformation,
+        bounds: Rectangle<u32>,
+        target: &wgpu::TextureView,
+    ) {
+        self.glyph_brush
+            .draw_queued_with_transform_and_scissoring(
+                device,
+                encoder,
+                target,
+                transformation.into(),
+                wgpu_glyph::Region {
+                    x: bounds.x,
+                    y: bounds.y,
+                    width: bounds.width,
+                    height: bounds.height,
+                },
+            )
+            .expect("Draw text");
+    }
+}
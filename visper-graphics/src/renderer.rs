@@ -1,24 +1,48 @@
-use crate::renderer::target::Target;
-use crate::primitive::quad;
+use crate::renderer::capture::Image;
+use crate::renderer::stats::{FrameStats, Phase};
+use crate::renderer::target::{Frame, Target};
+use crate::primitive::{custom, layer, quad, svg, text, Primitive};
 use wgpu::{
     Device, Adapter, RequestAdapterOptions, BackendBit, DeviceDescriptor, Limits,
     CommandEncoderDescriptor, PowerPreference, Extensions
 };
+use crate::buffer::{self, Belt};
+use crate::core::color::Color;
 use crate::core::rectangle::Rectangle;
+use crate::core::vector::Vector;
 use crate::primitive::quad::Quad;
+use crate::transformation::Transformation;
+use std::collections::HashMap;
+use std::ops::Range;
+use std::sync::{Arc, Mutex};
 
+mod blit;
+pub mod capture;
+pub mod damage;
+pub mod stats;
 pub mod target;
 
 pub struct Renderer {
     device: wgpu::Device,
     queue: wgpu::Queue,
     quad_pipeline: quad::Pipeline,
+    text_pipeline: text::Pipeline,
+    svg_pipeline: svg::Pipeline,
+    custom_pipelines: HashMap<custom::Id, custom::Pipeline>,
+    layer_pipeline: layer::Pipeline,
+    blit_pipeline: blit::Pipeline,
+    belt: Belt,
 }
 
 impl Renderer {
     pub fn device(&self) -> &Device {
         &self.device
     }
+
+    /// Returns what was uploaded to the GPU to draw the last frame.
+    pub fn uploads(&self) -> buffer::Stats {
+        self.belt.stats()
+    }
 }
 
 impl Renderer {
@@ -36,70 +60,769 @@ impl Renderer {
         });
 
         let quad_pipeline = quad::Pipeline::new(&mut device);
+        let text_pipeline = text::Pipeline::new(&mut device);
+        let svg_pipeline = svg::Pipeline::new(&mut device);
+        let layer_pipeline = layer::Pipeline::new(&mut device);
+        let blit_pipeline = blit::Pipeline::new(&mut device);
+        let belt = Belt::new(&device);
 
         Renderer {
             device,
             queue,
-            quad_pipeline
+            quad_pipeline,
+            text_pipeline,
+            svg_pipeline,
+            custom_pipelines: HashMap::new(),
+            layer_pipeline,
+            blit_pipeline,
+            belt,
         }
     }
 
-    pub fn draw(&mut self, target: &mut Target) {
+    /// Draws the given primitives on the [`Target`].
+    ///
+    /// Only the regions of the frame where the primitives differ from the
+    /// ones drawn last on the [`Target`] are drawn again, on its back buffer,
+    /// which is then copied to the window, unless the [`Target`] is
+    /// offscreen. The whole frame is drawn when the [`Target`] is new or was
+    /// resized.
+    ///
+    /// The primitives are split into layers, one per [`Primitive::Clip`] and
+    /// per [`Primitive::ZIndex`], then stably sorted by z-index. Primitives
+    /// at the same z-index and in the same clip share a layer, so they are
+    /// still drawn in batches. The quads of all the layers are uploaded
+    /// together.
+    ///
+    /// Then, for every damaged region, the region is cleared and the quads of
+    /// each layer are drawn in a single batch, followed by its vector images,
+    /// its custom primitives, its [`Primitive::Layer`]s and its text, clipped
+    /// to the bounds of the layer and of the region. The content of every
+    /// [`Primitive::Layer`] is drawn on a texture of its own first, the same
+    /// way.
+    ///
+    /// Returns what drawing the frame cost, as [`FrameStats`].
+    ///
+    /// [`Target`]: target/struct.Target.html
+    /// [`Primitive::Clip`]: ../primitive/enum.Primitive.html#variant.Clip
+    /// [`Primitive::ZIndex`]: ../primitive/enum.Primitive.html#variant.ZIndex
+    /// [`Primitive::Layer`]: ../primitive/enum.Primitive.html#variant.Layer
+    /// [`FrameStats`]: stats/struct.FrameStats.html
+    pub fn draw(&mut self, target: &mut Target, primitives: &[Primitive]) -> FrameStats {
+        let _frame = Phase::start("draw");
+        let mut stats = FrameStats::default();
+
         let (width, height) = target.dimensions();
+        let size = target.logical_size();
         let scale_factor = target.scale_factor();
-        let transformation = target.transformation();
-        let frame = target.next_frame();
+        let projection = target.transformation();
+        let Frame {
+            output,
+            back_buffer,
+            scene,
+        } = target.next_frame(&self.device, &self.blit_pipeline);
+
+        let scale = scale_factor as f32;
+        let viewport = Rectangle {
+            x: 0.0,
+            y: 0.0,
+            width: size.width,
+            height: size.height,
+        };
+
+        let phase = Phase::start("scene");
+        let next = damage::scene(primitives, viewport);
+        let damaged = match scene {
+            Some(previous) => damage::diff(previous, &next),
+            None => vec![viewport],
+        };
+        stats.primitives = next.len();
+        *scene = Some(next);
+
+        let regions: Vec<Rectangle<u32>> = damaged
+            .into_iter()
+            .map(|region| physical(region, scale, width, height))
+            .filter(|region| region.width > 0 && region.height > 0)
+            .collect();
+        stats.regions = regions.len();
+        stats.scene = phase.finish();
 
         let mut encoder = self.device
             .create_command_encoder(&CommandEncoderDescriptor { todo: 0 });
 
+        if !regions.is_empty() {
+            self.draw_regions(&mut encoder, &mut stats, Regions {
+                primitives,
+                regions: &regions,
+                viewport,
+                origin: Vector::default(),
+                dimensions: (width, height),
+                scale_factor,
+                projection,
+                background: Some(Color::WHITE),
+                target: back_buffer.view(),
+            });
+        }
 
-        // NOTE: it looks like we create two render passes; one in primitive and one here. Why?
-/*
-        let _ = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-            color_attachments: &[wgpu::RenderPassColorAttachmentDescriptor {
-                attachment: &frame.view,
-                resolve_target: None,
-                load_op: wgpu::LoadOp::Clear,
-                store_op: wgpu::StoreOp::Store,
-                clear_color: wgpu::Color {
-                    r: 1.0,
-                    g: 1.0,
-                    b: 1.0,
-                    a: 1.0,
-                },
-            }],
-            depth_stencil_attachment: None,
+        let phase = Phase::start("submit");
+        if let Some(output) = &output {
+            self.blit_pipeline.draw(&mut encoder, back_buffer, &output.view);
+            stats.draw_calls += 1;
+        }
+
+        let commands: Vec<wgpu::CommandBuffer> = self
+            .belt
+            .finish(&self.device)
+            .into_iter()
+            .chain(Some(encoder.finish()))
+            .collect();
+
+        self.queue.submit(&commands);
+        self.belt.recall();
+        self.layer_pipeline.recall();
+        self.svg_pipeline.trim();
+
+        stats.bytes = self.belt.stats().bytes;
+        stats.encode += phase.finish();
+
+        stats
+    }
+
+    /// Reads back the last frame drawn on the [`Target`], windowed or
+    /// offscreen, as an [`Image`].
+    ///
+    /// This waits for the GPU to finish drawing the frame. Returns `None` if
+    /// nothing was drawn on the [`Target`] since it was created or resized,
+    /// or if the frame could not be read back.
+    ///
+    /// [`Target`]: target/struct.Target.html
+    /// [`Image`]: capture/struct.Image.html
+    pub fn capture(&mut self, target: &Target) -> Option<Image> {
+        let back_buffer = target.back_buffer()?;
+
+        let (width, height) = back_buffer.dimensions();
+        let row_pitch = capture::row_pitch(width);
+        let size = u64::from(row_pitch) * u64::from(height);
+
+        let buffer = self.device.create_buffer(&wgpu::BufferDescriptor {
+            size,
+            usage: wgpu::BufferUsage::MAP_READ | wgpu::BufferUsage::COPY_DST,
         });
-*/
 
-        let bounds = Rectangle {
-            x: 30,
-            y: 30,
-            width: 100,
-            height: 100
-        };
-        let quad = Quad {
-            position: [30.0, 30.0],
-        scale: [100.0, 100.0],
-        color: [1.0, 0.0, 1.0, 1.0],
-        border_color: [0.0, 0.0, 1.0, 1.0],
-        border_radius: 5.03,
-        border_width: 3.05,
+        let mut encoder = self.device
+            .create_command_encoder(&CommandEncoderDescriptor { todo: 0 });
+
+        encoder.copy_texture_to_buffer(
+            wgpu::TextureCopyView {
+                texture: back_buffer.texture(),
+                mip_level: 0,
+                array_layer: 0,
+                origin: wgpu::Origin3d { x: 0.0, y: 0.0, z: 0.0 },
+            },
+            wgpu::BufferCopyView {
+                buffer: &buffer,
+                offset: 0,
+                row_pitch,
+                image_height: height,
+            },
+            wgpu::Extent3d {
+                width,
+                height,
+                depth: 1,
+            },
+        );
+
+        self.queue.submit(&[encoder.finish()]);
+
+        let image = Arc::new(Mutex::new(None));
+        let mapped = Arc::clone(&image);
+
+        buffer.map_read_async(0, size, move |result: wgpu::BufferMapAsyncResult<&[u8]>| {
+            if let Ok(mapping) = result {
+                *mapped.lock().unwrap() = Some(Image::from_bgra(width, height, mapping.data));
+            }
+        });
+
+        self.device.poll(true);
+
+        let image = image.lock().unwrap().take();
+
+        image
+    }
+
+    /// Draws the primitives again in the damaged regions of a texture.
+    fn draw_regions(
+        &mut self,
+        encoder: &mut wgpu::CommandEncoder,
+        stats: &mut FrameStats,
+        frame: Regions<'_>,
+    ) {
+        let scale = frame.scale_factor as f32;
+        let (width, height) = frame.dimensions;
+        let phase = Phase::start("layout");
+
+        let layers = layers(frame.primitives, frame.viewport, frame.origin);
+
+        // The background quad clears the damaged regions
+        let mut quads: Vec<Quad> = frame
+            .background
+            .map(|background| Quad {
+                position: [0.0, 0.0],
+                scale: [frame.viewport.width, frame.viewport.height],
+                color: background.into_linear(),
+                border_color: background.into_linear(),
+                border_radius: 0.0,
+                border_width: 0.0,
+            })
+            .into_iter()
+            .collect();
+        let background = 0..quads.len() as u32;
 
+        let ranges: Vec<Range<u32>> = layers
+            .iter()
+            .map(|layer| {
+                let start = quads.len() as u32;
+                quads.extend_from_slice(&layer.quads);
+
+                start..quads.len() as u32
+            })
+            .collect();
+
+        let batch = self.quad_pipeline.prepare(
+            &self.device,
+            &mut self.belt,
+            frame.projection,
+            frame.scale_factor,
+            &quads,
+        );
+
+        stats.layout += phase.finish();
+
+        // Layers are drawn on their own textures before being composited
+        let mut textures = Vec::with_capacity(layers.len());
+
+        for layer in &layers {
+            let mut drawn = Vec::with_capacity(layer.composites.len());
+
+            for primitive in &layer.composites {
+                drawn.push(self.draw_layer(encoder, stats, primitive, frame.scale_factor));
+            }
+
+            textures.push(drawn);
+        }
+
+        let phase = Phase::start("encode");
+
+        for region in frame.regions {
+            if background.end > background.start {
+                self.quad_pipeline.draw(encoder, &batch, *region, background.clone(), frame.target);
+                stats.draw_calls += 1;
+                stats.instances += 1;
+            }
+
+            for ((layer, range), textures) in layers.iter().zip(&ranges).zip(&textures) {
+                let bounds = match intersection(physical(layer.bounds, scale, width, height), *region) {
+                    Some(bounds) => bounds,
+                    None => continue,
+                };
+
+                if range.end > range.start {
+                    self.quad_pipeline.draw(encoder, &batch, bounds, range.clone(), frame.target);
+                    stats.draw_calls += 1;
+                    stats.instances += (range.end - range.start) as usize;
+                }
+
+                for primitive in &layer.svgs {
+                    if let Primitive::Svg {
+                        handle,
+                        bounds: svg_bounds,
+                    } = primitive
+                    {
+                        let drawn = self.svg_pipeline.draw(
+                            &self.device,
+                            &mut self.belt,
+                            encoder,
+                            quad::Uniforms::new(frame.projection, frame.scale_factor),
+                            svg::Draw {
+                                handle,
+                                bounds: Rectangle {
+                                    x: svg_bounds.x - layer.offset.x,
+                                    y: svg_bounds.y - layer.offset.y,
+                                    ..*svg_bounds
+                                },
+                                clip: bounds,
+                                scale_factor: frame.scale_factor,
+                            },
+                            frame.target,
+                        );
+
+                        if drawn {
+                            stats.draw_calls += 1;
+                            stats.instances += 1;
+                        }
+                    }
+                }
+
+                for primitive in &layer.custom {
+                    if let Primitive::Custom {
+                        bounds: primitive_bounds,
+                        program,
+                        uniforms,
+                        instances,
+                    } = primitive
+                    {
+                        let primitive_bounds = Rectangle {
+                            x: primitive_bounds.x - layer.offset.x,
+                            y: primitive_bounds.y - layer.offset.y,
+                            ..*primitive_bounds
+                        };
+
+                        let clip = match layer.bounds.intersection(&primitive_bounds) {
+                            Some(clip) => physical(clip, scale, width, height),
+                            None => continue,
+                        };
+
+                        let clip = match intersection(clip, *region) {
+                            Some(clip) => clip,
+                            None => continue,
+                        };
+
+                        let device = &mut self.device;
+                        let belt = &mut self.belt;
+                        let pipeline = self
+                            .custom_pipelines
+                            .entry(program.id())
+                            .or_insert_with(|| custom::Pipeline::new(device, program));
+
+                        let count = pipeline.count(instances);
+
+                        if count > 0 {
+                            stats.draw_calls += 1;
+                            stats.instances += count as usize;
+                        }
+
+                        pipeline.draw(
+                            device,
+                            belt,
+                            encoder,
+                            quad::Uniforms::new(frame.projection, frame.scale_factor),
+                            custom::Draw {
+                                bounds: primitive_bounds,
+                                clip,
+                                uniforms,
+                                instances,
+                            },
+                            frame.target,
+                        );
+                    }
+                }
+
+                for (primitive, texture) in layer.composites.iter().zip(textures) {
+                    if let (
+                        Primitive::Layer {
+                            bounds: layer_bounds,
+                            opacity,
+                            transformation,
+                            blend,
+                            ..
+                        },
+                        Some(texture),
+                    ) = (primitive, texture)
+                    {
+                        self.layer_pipeline.draw(
+                            &self.device,
+                            &mut self.belt,
+                            encoder,
+                            quad::Uniforms::new(frame.projection, frame.scale_factor),
+                            layer::Composite {
+                                bounds: Rectangle {
+                                    x: layer_bounds.x - layer.offset.x,
+                                    y: layer_bounds.y - layer.offset.y,
+                                    ..*layer_bounds
+                                },
+                                clip: bounds,
+                                opacity: *opacity,
+                                transformation: *transformation,
+                                blend: *blend,
+                                texture,
+                            },
+                            frame.target,
+                        );
+                        stats.draw_calls += 1;
+                        stats.instances += 1;
+                    }
+                }
+
+                if !layer.text.is_empty() {
+                    for text in &layer.text {
+                        self.text_pipeline.queue(text, layer.offset, frame.scale_factor);
+                    }
+
+                    self.text_pipeline.draw(
+                        &mut self.device,
+                        encoder,
+                        frame.projection,
+                        bounds,
+                        frame.target,
+                    );
+                    stats.draw_calls += 1;
+                }
+            }
+        }
+
+        stats.encode += phase.finish();
+    }
+
+    /// Draws the content of a [`Primitive::Layer`] on a texture of its own,
+    /// unless the layer is empty.
+    ///
+    /// [`Primitive::Layer`]: ../primitive/enum.Primitive.html#variant.Layer
+    fn draw_layer(
+        &mut self,
+        encoder: &mut wgpu::CommandEncoder,
+        stats: &mut FrameStats,
+        primitive: &Primitive,
+        scale_factor: f64,
+    ) -> Option<Arc<layer::Texture>> {
+        let (bounds, content) = match primitive {
+            Primitive::Layer { bounds, content, .. } => (bounds, content),
+            _ => return None,
         };
-        let quads = &[quad];
 
-        self.quad_pipeline.draw(
-            &mut self.device,
-            &mut encoder,
-            transformation,
+        let scale = scale_factor as f32;
+        let width = (bounds.width * scale).ceil() as u32;
+        let height = (bounds.height * scale).ceil() as u32;
+
+        if width == 0 || height == 0 {
+            return None;
+        }
+
+        let texture = self.layer_pipeline.texture(&self.device, width, height);
+        texture.clear(encoder);
+
+        self.draw_regions(encoder, stats, Regions {
+            primitives: std::slice::from_ref(content.as_ref()),
+            regions: &[Rectangle {
+                x: 0,
+                y: 0,
+                width,
+                height,
+            }],
+            viewport: Rectangle {
+                x: 0.0,
+                y: 0.0,
+                width: bounds.width,
+                height: bounds.height,
+            },
+            origin: Vector::new(bounds.x, bounds.y),
+            dimensions: (width, height),
             scale_factor,
+            projection: Transformation::orthographic(width as f32, height as f32),
+            background: None,
+            target: texture.view(),
+        });
+
+        Some(texture)
+    }
+}
+
+/// What [`Renderer::draw_regions`] draws, and where.
+///
+/// [`Renderer::draw_regions`]: struct.Renderer.html#method.draw_regions
+struct Regions<'a> {
+    primitives: &'a [Primitive],
+    regions: &'a [Rectangle<u32>],
+    viewport: Rectangle,
+    /// The point of the primitives drawn at the top-left corner.
+    origin: Vector,
+    dimensions: (u32, u32),
+    scale_factor: f64,
+    projection: Transformation,
+    /// The color the regions are cleared to, if they are not cleared yet.
+    background: Option<Color>,
+    target: &'a wgpu::TextureView,
+}
+
+/// The primitives drawn inside the same clip bounds, at the same z-index.
+struct Layer<'a> {
+    bounds: Rectangle,
+    offset: Vector,
+    z_index: i32,
+    quads: Vec<Quad>,
+    svgs: Vec<&'a Primitive>,
+    custom: Vec<&'a Primitive>,
+    composites: Vec<&'a Primitive>,
+    text: Vec<&'a Primitive>,
+}
+
+impl<'a> Layer<'a> {
+    fn new(bounds: Rectangle, offset: Vector, z_index: i32) -> Self {
+        Layer {
             bounds,
-            quads,
-            &frame.view
-        );
-        self.queue.submit(&[encoder.finish()]);
+            offset,
+            z_index,
+            quads: Vec::new(),
+            svgs: Vec::new(),
+            custom: Vec::new(),
+            composites: Vec::new(),
+            text: Vec::new(),
+        }
+    }
+}
+
+/// Splits the primitives into layers, in drawing order.
+fn layers(primitives: &[Primitive], viewport: Rectangle, origin: Vector) -> Vec<Layer<'_>> {
+    let mut layers = vec![Layer::new(viewport, origin, 0)];
+
+    for primitive in primitives {
+        collect(primitive, 0, &mut layers);
+    }
+
+    // The sort is stable, so layers at the same z-index keep their order
+    layers.sort_by_key(|layer| layer.z_index);
+
+    layers
+}
+
+/// Adds a primitive to the layer at the given index, opening a new layer for
+/// every clip and z-index.
+fn collect<'a>(primitive: &'a Primitive, layer: usize, layers: &mut Vec<Layer<'a>>) {
+    match primitive {
+        Primitive::None => {}
+        Primitive::Group { primitives } => {
+            for primitive in primitives {
+                collect(primitive, layer, layers);
+            }
+        }
+        Primitive::Quad {
+            bounds,
+            background,
+            border_radius,
+            border_width,
+            border_color,
+        } => {
+            let offset = layers[layer].offset;
+
+            layers[layer].quads.push(Quad {
+                position: [bounds.x - offset.x, bounds.y - offset.y],
+                scale: [bounds.width, bounds.height],
+                color: background.into_linear(),
+                border_color: border_color.into_linear(),
+                border_radius: *border_radius,
+                border_width: *border_width,
+            });
+        }
+        Primitive::Text { .. } => {
+            layers[layer].text.push(primitive);
+        }
+        Primitive::RichText { layout, bounds } => {
+            let offset = layers[layer].offset;
+
+            // Decorations are drawn as quads, below the glyphs
+            for decoration in layout.decorations() {
+                layers[layer].quads.push(Quad {
+                    position: [
+                        bounds.x + decoration.bounds.x - offset.x,
+                        bounds.y + decoration.bounds.y - offset.y,
+                    ],
+                    scale: [decoration.bounds.width, decoration.bounds.height],
+                    color: decoration.color.into_linear(),
+                    border_color: Color::TRANSPARENT.into_linear(),
+                    border_radius: 0.0,
+                    border_width: 0.0,
+                });
+            }
+
+            layers[layer].text.push(primitive);
+        }
+        Primitive::Svg { .. } => {
+            layers[layer].svgs.push(primitive);
+        }
+        Primitive::Custom { .. } => {
+            layers[layer].custom.push(primitive);
+        }
+        Primitive::Layer { .. } => {
+            layers[layer].composites.push(primitive);
+        }
+        Primitive::ZIndex { index, content } => {
+            let parent = &layers[layer];
+
+            if *index == parent.z_index {
+                return collect(content, layer, layers);
+            }
+
+            let (bounds, offset) = (parent.bounds, parent.offset);
+
+            // Only the last layer at a z-index is drawn after all the others
+            // at the same z-index, so only it can take more primitives
+            let last = layers
+                .iter()
+                .rposition(|other| other.z_index == *index)
+                .filter(|&last| layers[last].bounds == bounds && layers[last].offset == offset);
+
+            let target = match last {
+                Some(last) => last,
+                None => {
+                    layers.push(Layer::new(bounds, offset, *index));
+                    layers.len() - 1
+                }
+            };
+
+            collect(content, target, layers);
+        }
+        Primitive::Clip {
+            bounds,
+            offset,
+            content,
+        } => {
+            let parent = &layers[layer];
+            let bounds = Rectangle {
+                x: bounds.x - parent.offset.x,
+                y: bounds.y - parent.offset.y,
+                ..*bounds
+            };
+
+            if let Some(bounds) = parent.bounds.intersection(&bounds) {
+                let offset = parent.offset + *offset;
+
+                let z_index = parent.z_index;
+
+                layers.push(Layer::new(bounds, offset, z_index));
+                collect(content, layers.len() - 1, layers);
+            }
+        }
     }
 }
 
+/// Returns the area shared by two rectangles in physical pixels, if any.
+fn intersection(a: Rectangle<u32>, b: Rectangle<u32>) -> Option<Rectangle<u32>> {
+    let x = a.x.max(b.x);
+    let y = a.y.max(b.y);
+    let right = (a.x + a.width).min(b.x + b.width);
+    let bottom = (a.y + a.height).min(b.y + b.height);
+
+    if right > x && bottom > y {
+        Some(Rectangle {
+            x,
+            y,
+            width: right - x,
+            height: bottom - y,
+        })
+    } else {
+        None
+    }
+}
+
+/// Converts logical bounds to physical pixels inside the target.
+fn physical(bounds: Rectangle, scale: f32, width: u32, height: u32) -> Rectangle<u32> {
+    let x = ((bounds.x * scale).floor().max(0.0) as u32).min(width);
+    let y = ((bounds.y * scale).floor().max(0.0) as u32).min(height);
+    let right = (((bounds.x + bounds.width) * scale).ceil().max(0.0) as u32).min(width);
+    let bottom = (((bounds.y + bounds.height) * scale).ceil().max(0.0) as u32).min(height);
+
+    Rectangle {
+        x,
+        y,
+        width: right.saturating_sub(x),
+        height: bottom.saturating_sub(y),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VIEWPORT: Rectangle = Rectangle {
+        x: 0.0,
+        y: 0.0,
+        width: 800.0,
+        height: 600.0,
+    };
+
+    fn quad(x: f32) -> Primitive {
+        Primitive::Quad {
+            bounds: Rectangle {
+                x,
+                y: 0.0,
+                width: 10.0,
+                height: 10.0,
+            },
+            background: Color::BLACK,
+            border_radius: 0.0,
+            border_width: 0.0,
+            border_color: Color::BLACK,
+        }
+    }
+
+    fn z_index(index: i32, content: Primitive) -> Primitive {
+        Primitive::ZIndex {
+            index,
+            content: Box::new(content),
+        }
+    }
+
+    /// Returns the z-index of every layer, with the x of its quads.
+    fn describe(layers: &[Layer<'_>]) -> Vec<(i32, Vec<f32>)> {
+        layers
+            .iter()
+            .map(|layer| {
+                let quads = layer.quads.iter().map(|quad| quad.position[0]).collect();
+
+                (layer.z_index, quads)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn higher_z_indices_are_drawn_last() {
+        let primitives = [z_index(1, quad(0.0)), quad(20.0), z_index(-1, quad(40.0))];
+
+        assert_eq!(
+            describe(&layers(&primitives, VIEWPORT, Vector::default())),
+            vec![(-1, vec![40.0]), (0, vec![20.0]), (1, vec![0.0])]
+        );
+    }
+
+    #[test]
+    fn primitives_at_the_same_z_index_share_a_layer() {
+        let primitives = [
+            z_index(1, quad(0.0)),
+            quad(20.0),
+            z_index(1, Primitive::Group {
+                primitives: vec![quad(40.0), z_index(0, quad(60.0))],
+            }),
+        ];
+
+        assert_eq!(
+            describe(&layers(&primitives, VIEWPORT, Vector::default())),
+            vec![(0, vec![20.0, 60.0]), (1, vec![0.0, 40.0])]
+        );
+    }
+
+    #[test]
+    fn clips_keep_their_z_index() {
+        let clip = Primitive::Clip {
+            bounds: Rectangle {
+                x: 0.0,
+                y: 0.0,
+                width: 100.0,
+                height: 100.0,
+            },
+            offset: Vector::default(),
+            content: Box::new(quad(0.0)),
+        };
+
+        let group = Primitive::Group {
+            primitives: vec![clip, quad(20.0)],
+        };
+
+        let primitives = [z_index(2, group), z_index(2, quad(40.0))];
+
+        let layers = layers(&primitives, VIEWPORT, Vector::default());
+
+        assert_eq!(
+            describe(&layers),
+            vec![(0, vec![]), (2, vec![20.0]), (2, vec![0.0]), (2, vec![40.0])]
+        );
+        assert_eq!(layers[2].bounds.width, 100.0);
+    }
+}
@@ -0,0 +1,342 @@
+//! Read back what was drawn on a [`Target`], and record it.
+//!
+//! [`Renderer::capture`] returns the last frame drawn on a [`Target`] as an
+//! [`Image`], if any. With the `png` feature, an [`Image`] can be saved as a
+//! PNG file.
+//!
+//! A [`Recorder`] writes a sequence of frames, captured at a fixed rate, as
+//! numbered PNG files or as a raw Y4M stream that video tools like `ffmpeg`
+//! can read. Together with a clock that only advances by the timestep of the
+//! [`Recorder`], this renders animations the same way on every run.
+//!
+//! [`Target`]: ../target/struct.Target.html
+//! [`Renderer::capture`]: ../struct.Renderer.html#method.capture
+//! [`Image`]: struct.Image.html
+//! [`Recorder`]: struct.Recorder.html
+use std::fmt;
+use std::io::{self, Write};
+use std::time::Duration;
+
+#[cfg(feature = "png")]
+use std::path::{Path, PathBuf};
+
+/// The alignment of the rows of a texture copied to a buffer, in bytes.
+const ROW_ALIGNMENT: u32 = 256;
+
+/// An image in memory, in 8-bit sRGB RGBA, row by row from the top.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Image {
+    width: u32,
+    height: u32,
+    pixels: Vec<u8>,
+}
+
+impl Image {
+    /// Creates an [`Image`] of the given size from its RGBA pixels.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there are not four bytes per pixel.
+    ///
+    /// [`Image`]: struct.Image.html
+    pub fn new(width: u32, height: u32, pixels: Vec<u8>) -> Image {
+        assert_eq!(
+            pixels.len(),
+            width as usize * height as usize * 4,
+            "An image of {}x{} pixels needs {} bytes",
+            width,
+            height,
+            width as usize * height as usize * 4,
+        );
+
+        Image {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    /// Converts the rows of a BGRA texture copied to a buffer, which are
+    /// padded to the alignment of copies.
+    pub(crate) fn from_bgra(width: u32, height: u32, data: &[u8]) -> Image {
+        let row_pitch = row_pitch(width) as usize;
+        let mut pixels = Vec::with_capacity(width as usize * height as usize * 4);
+
+        for row in data.chunks(row_pitch).take(height as usize) {
+            for bgra in row[..width as usize * 4].chunks(4) {
+                pixels.extend_from_slice(&[bgra[2], bgra[1], bgra[0], bgra[3]]);
+            }
+        }
+
+        Image::new(width, height, pixels)
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Returns the RGBA pixels of the [`Image`].
+    ///
+    /// [`Image`]: struct.Image.html
+    pub fn pixels(&self) -> &[u8] {
+        &self.pixels
+    }
+
+    /// Returns the RGBA components of the pixel at the given position.
+    pub fn pixel(&self, x: u32, y: u32) -> [u8; 4] {
+        let index = (y as usize * self.width as usize + x as usize) * 4;
+        let pixel = &self.pixels[index..index + 4];
+
+        [pixel[0], pixel[1], pixel[2], pixel[3]]
+    }
+
+    /// Writes the [`Image`] as a PNG.
+    ///
+    /// [`Image`]: struct.Image.html
+    #[cfg(feature = "png")]
+    pub fn encode_png<W: Write>(&self, writer: W) -> io::Result<()> {
+        let mut encoder = png::Encoder::new(writer, self.width, self.height);
+        encoder.set_color(png::ColorType::RGBA);
+        encoder.set_depth(png::BitDepth::Eight);
+
+        encoder
+            .write_header()
+            .and_then(|mut writer| writer.write_image_data(&self.pixels))
+            .map_err(io::Error::other)
+    }
+
+    /// Saves the [`Image`] as a PNG file.
+    ///
+    /// [`Image`]: struct.Image.html
+    #[cfg(feature = "png")]
+    pub fn save_png<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let file = std::fs::File::create(path)?;
+
+        self.encode_png(io::BufWriter::new(file))
+    }
+}
+
+/// Returns the number of bytes between two rows of a texture of the given
+/// width copied to a buffer.
+pub(crate) fn row_pitch(width: u32) -> u32 {
+    (width * 4).div_ceil(ROW_ALIGNMENT) * ROW_ALIGNMENT
+}
+
+/// Writes frames captured at a fixed rate.
+///
+/// The [`Recorder`] does not capture anything itself: it is given the frames
+/// to write with [`record`], and tells how much time passes between two of
+/// them with [`timestep`].
+///
+/// [`Recorder`]: struct.Recorder.html
+/// [`record`]: #method.record
+/// [`timestep`]: #method.timestep
+pub struct Recorder {
+    output: Output,
+    frames_per_second: u32,
+    frames: u64,
+    dimensions: Option<(u32, u32)>,
+}
+
+enum Output {
+    #[cfg(feature = "png")]
+    Png(PathBuf),
+    Y4m(Box<dyn Write>),
+}
+
+impl Recorder {
+    /// Creates a [`Recorder`] that saves every frame as a PNG file in the
+    /// given directory, named after its number, like `frame-00042.png`.
+    ///
+    /// The directory is created if it does not exist.
+    ///
+    /// [`Recorder`]: struct.Recorder.html
+    #[cfg(feature = "png")]
+    pub fn png<P: Into<PathBuf>>(directory: P, frames_per_second: u32) -> io::Result<Recorder> {
+        let directory = directory.into();
+
+        std::fs::create_dir_all(&directory)?;
+
+        Ok(Recorder::new(Output::Png(directory), frames_per_second))
+    }
+
+    /// Creates a [`Recorder`] that writes the frames as an uncompressed Y4M
+    /// stream, in 4:4:4 YCbCr.
+    ///
+    /// [`Recorder`]: struct.Recorder.html
+    pub fn y4m<W: Write + 'static>(writer: W, frames_per_second: u32) -> Recorder {
+        Recorder::new(Output::Y4m(Box::new(writer)), frames_per_second)
+    }
+
+    fn new(output: Output, frames_per_second: u32) -> Recorder {
+        assert!(frames_per_second > 0, "A recording needs at least a frame per second");
+
+        Recorder {
+            output,
+            frames_per_second,
+            frames: 0,
+            dimensions: None,
+        }
+    }
+
+    /// Returns the time between two frames.
+    pub fn timestep(&self) -> Duration {
+        Duration::from_secs(1) / self.frames_per_second
+    }
+
+    /// Returns the number of frames recorded.
+    pub fn frames(&self) -> u64 {
+        self.frames
+    }
+
+    /// Returns the time of the next frame, from the start of the recording.
+    pub fn time(&self) -> Duration {
+        Duration::from_secs(self.frames) / self.frames_per_second
+    }
+
+    /// Writes the next frame.
+    ///
+    /// All the frames of a recording must have the same size.
+    pub fn record(&mut self, image: &Image) -> io::Result<()> {
+        let dimensions = (image.width, image.height);
+
+        let is_first = match self.dimensions {
+            Some(expected) if expected != dimensions => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!(
+                        "Frame of {}x{} pixels in a recording of {}x{} pixels",
+                        dimensions.0, dimensions.1, expected.0, expected.1,
+                    ),
+                ));
+            }
+            Some(_) => false,
+            None => {
+                self.dimensions = Some(dimensions);
+
+                true
+            }
+        };
+
+        match &mut self.output {
+            #[cfg(feature = "png")]
+            Output::Png(directory) => {
+                image.save_png(directory.join(format!("frame-{:05}.png", self.frames)))?
+            }
+            Output::Y4m(writer) => {
+                if is_first {
+                    y4m_header(writer, image.width, image.height, self.frames_per_second)?;
+                }
+
+                y4m_frame(writer, image)?
+            }
+        }
+
+        self.frames += 1;
+
+        Ok(())
+    }
+
+    /// Flushes the frames written so far.
+    pub fn flush(&mut self) -> io::Result<()> {
+        match &mut self.output {
+            #[cfg(feature = "png")]
+            Output::Png(_) => Ok(()),
+            Output::Y4m(writer) => writer.flush(),
+        }
+    }
+}
+
+impl fmt::Debug for Recorder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Recorder")
+            .field("frames_per_second", &self.frames_per_second)
+            .field("frames", &self.frames)
+            .field("dimensions", &self.dimensions)
+            .finish()
+    }
+}
+
+fn y4m_header<W: Write + ?Sized>(
+    writer: &mut W,
+    width: u32,
+    height: u32,
+    frames_per_second: u32,
+) -> io::Result<()> {
+    writeln!(
+        writer,
+        "YUV4MPEG2 W{} H{} F{}:1 Ip A1:1 C444",
+        width, height, frames_per_second
+    )
+}
+
+/// Writes the planes of an [`Image`] converted to BT.601 YCbCr, in the
+/// limited range video tools expect.
+///
+/// [`Image`]: struct.Image.html
+fn y4m_frame<W: Write + ?Sized>(writer: &mut W, image: &Image) -> io::Result<()> {
+    let pixels = image.pixels.len() / 4;
+    let mut planes = vec![0; pixels * 3];
+
+    for (i, rgba) in image.pixels.chunks(4).enumerate() {
+        let (r, g, b) = (i32::from(rgba[0]), i32::from(rgba[1]), i32::from(rgba[2]));
+
+        planes[i] = (((66 * r + 129 * g + 25 * b + 128) >> 8) + 16) as u8;
+        planes[pixels + i] = (((-38 * r - 74 * g + 112 * b + 128) >> 8) + 128) as u8;
+        planes[pixels * 2 + i] = (((112 * r - 94 * g - 18 * b + 128) >> 8) + 128) as u8;
+    }
+
+    writer.write_all(b"FRAME\n")?;
+    writer.write_all(&planes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bgra_rows_are_unpadded() {
+        let pitch = row_pitch(2) as usize;
+        let mut data = vec![0; pitch * 2];
+        data[..8].copy_from_slice(&[1, 2, 3, 4, 5, 6, 7, 8]);
+        data[pitch..pitch + 8].copy_from_slice(&[9, 10, 11, 12, 13, 14, 15, 16]);
+
+        let image = Image::from_bgra(2, 2, &data);
+
+        assert_eq!(pitch, 256);
+        assert_eq!(
+            image.pixels(),
+            &[3, 2, 1, 4, 7, 6, 5, 8, 11, 10, 9, 12, 15, 14, 13, 16][..]
+        );
+        assert_eq!(image.pixel(1, 1), [15, 14, 13, 16]);
+    }
+
+    #[test]
+    fn y4m_frames_are_planar() {
+        let image = Image::new(2, 1, vec![255, 255, 255, 255, 0, 0, 0, 255]);
+        let mut output = Vec::new();
+
+        y4m_header(&mut output, 2, 1, 30).unwrap();
+        y4m_frame(&mut output, &image).unwrap();
+
+        let header = b"YUV4MPEG2 W2 H1 F30:1 Ip A1:1 C444\nFRAME\n";
+
+        assert_eq!(&output[..header.len()], &header[..]);
+        assert_eq!(&output[header.len()..], &[235, 16, 128, 128, 128, 128][..]);
+    }
+
+    #[test]
+    fn frames_have_the_same_size() {
+        let mut recorder = Recorder::y4m(io::sink(), 60);
+
+        recorder.record(&Image::new(1, 1, vec![0; 4])).unwrap();
+
+        assert!(recorder.record(&Image::new(2, 1, vec![0; 8])).is_err());
+        assert_eq!(recorder.frames(), 1);
+        assert_eq!(recorder.time(), Duration::from_secs(1) / 60);
+    }
+}
@@ -0,0 +1,390 @@
+//! Find the regions of a frame that changed since the previous one.
+//!
+//! A [`Renderer`] keeps the scene it drew last in each [`Target`]. The next
+//! scene is compared to it primitive by primitive, in drawing order, and only
+//! the bounds of the primitives that were added, removed or changed are drawn
+//! again.
+//!
+//! Comparing in drawing order keeps the result exact: if a pixel looks
+//! different, some primitive covering it, in the previous or the next scene,
+//! is not at the same place in the order anymore, and so its bounds are
+//! damaged.
+//!
+//! Primitives are listed by z-index first, the way they are drawn, so moving
+//! one to another z-index damages its bounds too.
+//!
+//! [`Renderer`]: ../struct.Renderer.html
+//! [`Target`]: ../target/struct.Target.html
+use crate::core::point::Point;
+use crate::core::rectangle::Rectangle;
+use crate::core::vector::Vector;
+use crate::primitive::Primitive;
+use crate::transformation::Transformation;
+
+/// The most damaged regions drawn one by one, beyond which they are all
+/// drawn as the one region containing them.
+const MAX_REGIONS: usize = 4;
+
+/// How far, in logical pixels, the antialiased edges of a primitive are drawn
+/// outside of its bounds.
+const EDGE: f32 = 1.0;
+
+/// A primitive as it is drawn: moved back by the offset of the clips it is
+/// in, and clipped to their bounds.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Item {
+    clip: Rectangle,
+    z_index: i32,
+    primitive: Primitive,
+}
+
+impl Item {
+    /// Returns the area of the frame the [`Item`] draws to, in logical
+    /// pixels, if any.
+    ///
+    /// [`Item`]: struct.Item.html
+    pub fn bounds(&self) -> Option<Rectangle> {
+        let (bounds, overflow) = match &self.primitive {
+            Primitive::Quad { bounds, .. }
+            | Primitive::Svg { bounds, .. }
+            | Primitive::Custom { bounds, .. } => (*bounds, 0.0),
+            // Glyphs may go past the bounds of the text they are laid out in
+            Primitive::Text { bounds, size, .. } => (*bounds, size / 2.0),
+            Primitive::RichText { bounds, layout } => (*bounds, layout.overflow()),
+            Primitive::Layer {
+                bounds,
+                transformation,
+                ..
+            } => (transformed(*bounds, *transformation), 0.0),
+            _ => return None,
+        };
+
+        let margin = EDGE + overflow;
+
+        self.clip.intersection(&Rectangle {
+            x: bounds.x - margin,
+            y: bounds.y - margin,
+            width: bounds.width + margin * 2.0,
+            height: bounds.height + margin * 2.0,
+        })
+    }
+}
+
+/// Returns the bounds of a layer once transformed, relative to their top-left
+/// corner.
+fn transformed(bounds: Rectangle, transformation: Transformation) -> Rectangle {
+    let corners = [
+        Point::new(0.0, 0.0),
+        Point::new(bounds.width, 0.0),
+        Point::new(0.0, bounds.height),
+        Point::new(bounds.width, bounds.height),
+    ];
+
+    let mut points = corners
+        .iter()
+        .map(|corner| transformation.transform_point(*corner));
+
+    let first = points.next().expect("Corners of the bounds");
+    let first = Rectangle {
+        x: first.x,
+        y: first.y,
+        width: 0.0,
+        height: 0.0,
+    };
+
+    let area = points.fold(first, |area, point| {
+        area.union(&Rectangle {
+            x: point.x,
+            y: point.y,
+            width: 0.0,
+            height: 0.0,
+        })
+    });
+
+    Rectangle {
+        x: bounds.x + area.x,
+        y: bounds.y + area.y,
+        ..area
+    }
+}
+
+/// Lists the [`Item`]s drawn by the given primitives on a frame of the given
+/// bounds, in drawing order.
+///
+/// [`Item`]: struct.Item.html
+pub fn scene(primitives: &[Primitive], bounds: Rectangle) -> Vec<Item> {
+    let mut items = Vec::new();
+
+    for primitive in primitives {
+        flatten(primitive, bounds, Vector::default(), 0, &mut items);
+    }
+
+    // The sort is stable, so primitives at the same z-index keep their order
+    items.sort_by_key(|item| item.z_index);
+
+    items
+}
+
+fn flatten(primitive: &Primitive, clip: Rectangle, offset: Vector, z_index: i32, items: &mut Vec<Item>) {
+    match primitive {
+        Primitive::None => {}
+        Primitive::Group { primitives } => {
+            for primitive in primitives {
+                flatten(primitive, clip, offset, z_index, items);
+            }
+        }
+        Primitive::ZIndex { index, content } => {
+            flatten(content, clip, offset, *index, items);
+        }
+        Primitive::Clip {
+            bounds,
+            offset: scroll,
+            content,
+        } => {
+            let bounds = Rectangle {
+                x: bounds.x - offset.x,
+                y: bounds.y - offset.y,
+                ..*bounds
+            };
+
+            if let Some(clip) = clip.intersection(&bounds) {
+                flatten(content, clip, offset + *scroll, z_index, items);
+            }
+        }
+        _ => {
+            let mut primitive = primitive.clone();
+
+            match &mut primitive {
+                Primitive::Quad { bounds, .. }
+                | Primitive::Text { bounds, .. }
+                | Primitive::RichText { bounds, .. }
+                | Primitive::Svg { bounds, .. }
+                | Primitive::Custom { bounds, .. }
+                | Primitive::Layer { bounds, .. } => {
+                    bounds.x -= offset.x;
+                    bounds.y -= offset.y;
+                }
+                _ => {}
+            }
+
+            let item = Item {
+                clip,
+                z_index,
+                primitive,
+            };
+
+            // Primitives clipped away entirely never show up
+            if item.bounds().is_some() {
+                items.push(item);
+            }
+        }
+    }
+}
+
+/// Returns the regions of the frame, in logical pixels, that differ between
+/// the previous and the next scene.
+///
+/// Overlapping regions are merged, and so are all of them when there are too
+/// many to draw one by one.
+pub fn diff(previous: &[Item], next: &[Item]) -> Vec<Rectangle> {
+    let mut regions = Vec::new();
+
+    for (before, after) in previous.iter().zip(next) {
+        if before != after {
+            regions.extend(before.bounds());
+            regions.extend(after.bounds());
+        }
+    }
+
+    let common = previous.len().min(next.len());
+
+    for item in previous[common..].iter().chain(&next[common..]) {
+        regions.extend(item.bounds());
+    }
+
+    merge(regions)
+}
+
+fn merge(mut regions: Vec<Rectangle>) -> Vec<Rectangle> {
+    let mut merged: Vec<Rectangle> = Vec::with_capacity(regions.len());
+
+    while let Some(mut region) = regions.pop() {
+        // Growing a region may make it touch regions merged before
+        while let Some(index) = merged.iter().position(|other| touches(&region, other)) {
+            region = region.union(&merged.swap_remove(index));
+        }
+
+        merged.push(region);
+    }
+
+    if merged.len() > MAX_REGIONS {
+        let first = merged[0];
+
+        vec![merged[1..].iter().fold(first, |union, region| union.union(region))]
+    } else {
+        merged
+    }
+}
+
+fn touches(a: &Rectangle, b: &Rectangle) -> bool {
+    a.x <= b.x + b.width && b.x <= a.x + a.width && a.y <= b.y + b.height && b.y <= a.y + a.height
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::color::Color;
+
+    const FRAME: Rectangle = Rectangle {
+        x: 0.0,
+        y: 0.0,
+        width: 800.0,
+        height: 600.0,
+    };
+
+    fn quad(x: f32, y: f32, background: Color) -> Primitive {
+        Primitive::Quad {
+            bounds: Rectangle {
+                x,
+                y,
+                width: 10.0,
+                height: 10.0,
+            },
+            background,
+            border_radius: 0.0,
+            border_width: 0.0,
+            border_color: Color::BLACK,
+        }
+    }
+
+    fn damage(before: Vec<Primitive>, after: Vec<Primitive>) -> Vec<Rectangle> {
+        diff(&scene(&before, FRAME), &scene(&after, FRAME))
+    }
+
+    #[test]
+    fn only_changed_primitives_are_damaged() {
+        let before = vec![quad(0.0, 0.0, Color::WHITE), quad(100.0, 100.0, Color::WHITE)];
+        let after = vec![quad(0.0, 0.0, Color::WHITE), quad(100.0, 100.0, Color::BLACK)];
+
+        assert_eq!(damage(before.clone(), before.clone()), vec![]);
+        assert_eq!(
+            damage(before, after),
+            vec![Rectangle {
+                x: 99.0,
+                y: 99.0,
+                width: 12.0,
+                height: 12.0,
+            }]
+        );
+    }
+
+    #[test]
+    fn moved_primitives_damage_both_places() {
+        let mut regions = damage(
+            vec![quad(0.0, 0.0, Color::WHITE)],
+            vec![quad(200.0, 0.0, Color::WHITE)],
+        );
+
+        regions.sort_by(|a, b| a.x.partial_cmp(&b.x).unwrap());
+
+        assert_eq!(regions.len(), 2);
+        assert_eq!(regions[0].x, 0.0);
+        assert_eq!(regions[1].x, 199.0);
+    }
+
+    #[test]
+    fn scrolling_damages_the_clip_only() {
+        let scrolled = |y| Primitive::Clip {
+            bounds: Rectangle {
+                x: 0.0,
+                y: 0.0,
+                width: 100.0,
+                height: 50.0,
+            },
+            offset: Vector::new(0.0, y),
+            content: Box::new(Primitive::Group {
+                primitives: vec![quad(0.0, 20.0, Color::BLACK), quad(0.0, 500.0, Color::BLACK)],
+            }),
+        };
+
+        let regions = damage(vec![scrolled(0.0)], vec![scrolled(15.0)]);
+
+        assert_eq!(regions.len(), 2);
+        assert!(regions
+            .iter()
+            .all(|region| region.y >= 0.0 && region.y + region.height <= 50.0));
+    }
+
+    #[test]
+    fn layers_damage_their_transformed_bounds() {
+        let layer = |opacity| Primitive::Layer {
+            bounds: Rectangle {
+                x: 100.0,
+                y: 100.0,
+                width: 50.0,
+                height: 20.0,
+            },
+            opacity,
+            transformation: Transformation::scale(2.0, 2.0),
+            blend: crate::primitive::layer::Blend::Normal,
+            content: Box::new(quad(100.0, 100.0, Color::BLACK)),
+        };
+
+        assert_eq!(
+            damage(vec![layer(1.0)], vec![layer(0.5)]),
+            vec![Rectangle {
+                x: 99.0,
+                y: 99.0,
+                width: 102.0,
+                height: 42.0,
+            }]
+        );
+    }
+
+    #[test]
+    fn primitives_are_listed_by_z_index() {
+        let popover = Primitive::ZIndex {
+            index: 1,
+            content: Box::new(quad(0.0, 0.0, Color::BLACK)),
+        };
+
+        let items = scene(&[popover, quad(100.0, 0.0, Color::WHITE)], FRAME);
+
+        assert_eq!(items.iter().map(|item| item.z_index).collect::<Vec<_>>(), vec![0, 1]);
+        assert_eq!(
+            damage(
+                vec![quad(0.0, 0.0, Color::BLACK)],
+                vec![Primitive::ZIndex {
+                    index: 1,
+                    content: Box::new(quad(0.0, 0.0, Color::BLACK)),
+                }],
+            ),
+            vec![Rectangle {
+                x: 0.0,
+                y: 0.0,
+                width: 11.0,
+                height: 11.0,
+            }]
+        );
+    }
+
+    #[test]
+    fn too_many_regions_are_merged() {
+        let before: Vec<Primitive> = (0..10)
+            .map(|i| quad(i as f32 * 50.0, 0.0, Color::WHITE))
+            .collect();
+        let after: Vec<Primitive> = (0..10)
+            .map(|i| quad(i as f32 * 50.0, 0.0, Color::BLACK))
+            .collect();
+
+        assert_eq!(
+            damage(before, after),
+            vec![Rectangle {
+                x: 0.0,
+                y: 0.0,
+                width: 461.0,
+                height: 11.0,
+            }]
+        );
+    }
+}
@@ -0,0 +1,165 @@
+//! Measure what drawing a frame costs.
+//!
+//! Every call to [`Renderer::draw`] returns the [`FrameStats`] of the frame.
+//! With the `tracing` feature, the phases of the frame are also recorded as
+//! `tracing` spans.
+//!
+//! [`Renderer::draw`]: ../struct.Renderer.html#method.draw
+//! [`FrameStats`]: struct.FrameStats.html
+use crate::core::color::Color;
+use crate::core::point::Point;
+use crate::core::rectangle::Rectangle;
+use crate::primitive::Primitive;
+use crate::text::{self, HorizontalAlignment, VerticalAlignment};
+use std::time::{Duration, Instant};
+
+/// The size of the text of the overlay.
+const OVERLAY_TEXT_SIZE: f32 = 12.0;
+
+/// The space around the text of the overlay.
+const OVERLAY_PADDING: f32 = 6.0;
+
+/// What drawing a frame cost.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct FrameStats {
+    /// The number of primitives in the scene, not counting groups and clips.
+    pub primitives: usize,
+
+    /// The number of damaged regions drawn again.
+    pub regions: usize,
+
+    /// The number of draw calls.
+    pub draw_calls: usize,
+
+    /// The number of quads and custom instances drawn.
+    pub instances: usize,
+
+    /// The number of bytes uploaded to the GPU.
+    pub bytes: u64,
+
+    /// The CPU time spent comparing the scene to the previous one.
+    pub scene: Duration,
+
+    /// The CPU time spent splitting the scene into layers and uploading it.
+    pub layout: Duration,
+
+    /// The CPU time spent recording and submitting the commands.
+    pub encode: Duration,
+}
+
+impl FrameStats {
+    /// Returns the CPU time spent on the frame.
+    pub fn total(&self) -> Duration {
+        self.scene + self.layout + self.encode
+    }
+
+    /// Returns a primitive showing the [`FrameStats`] in a box whose top-left
+    /// corner is at the given position.
+    ///
+    /// [`FrameStats`]: struct.FrameStats.html
+    pub fn overlay(&self, position: Point) -> Primitive {
+        let content = format!(
+            "{:.2} ms scene\n{:.2} ms layout\n{:.2} ms encode\n\
+             {} primitives in {} regions\n{} draw calls\n{} instances\n{} bytes uploaded",
+            milliseconds(self.scene),
+            milliseconds(self.layout),
+            milliseconds(self.encode),
+            self.primitives,
+            self.regions,
+            self.draw_calls,
+            self.instances,
+            self.bytes,
+        );
+
+        let size = text::measure(&content, OVERLAY_TEXT_SIZE);
+
+        Primitive::Group {
+            primitives: vec![
+                Primitive::Quad {
+                    bounds: Rectangle {
+                        x: position.x,
+                        y: position.y,
+                        width: size.width + OVERLAY_PADDING * 2.0,
+                        height: size.height + OVERLAY_PADDING * 2.0,
+                    },
+                    background: Color::from_rgba(0.0, 0.0, 0.0, 0.7),
+                    border_radius: 4.0,
+                    border_width: 0.0,
+                    border_color: Color::BLACK,
+                },
+                Primitive::Text {
+                    content,
+                    bounds: Rectangle {
+                        x: position.x + OVERLAY_PADDING,
+                        y: position.y + OVERLAY_PADDING,
+                        width: size.width,
+                        height: size.height,
+                    },
+                    color: Color::WHITE,
+                    size: OVERLAY_TEXT_SIZE,
+                    horizontal_alignment: HorizontalAlignment::Left,
+                    vertical_alignment: VerticalAlignment::Top,
+                },
+            ],
+        }
+    }
+}
+
+fn milliseconds(duration: Duration) -> f32 {
+    duration.as_secs_f32() * 1000.0
+}
+
+/// A phase of a frame being timed.
+///
+/// With the `tracing` feature, the phase is also a `tracing` span, entered
+/// until the phase is finished.
+pub(crate) struct Phase {
+    start: Instant,
+    #[cfg(feature = "tracing")]
+    _span: tracing::span::EnteredSpan,
+}
+
+impl Phase {
+    pub(crate) fn start(name: &'static str) -> Phase {
+        #[cfg(not(feature = "tracing"))]
+        let _ = name;
+
+        Phase {
+            start: Instant::now(),
+            #[cfg(feature = "tracing")]
+            _span: tracing::debug_span!("frame", phase = name).entered(),
+        }
+    }
+
+    /// Returns the time since the phase started.
+    pub(crate) fn finish(self) -> Duration {
+        self.start.elapsed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn overlay_fits_its_text() {
+        let stats = FrameStats {
+            primitives: 12,
+            draw_calls: 3,
+            ..FrameStats::default()
+        };
+
+        match stats.overlay(Point::new(10.0, 10.0)) {
+            Primitive::Group { primitives } => match (&primitives[0], &primitives[1]) {
+                (Primitive::Quad { bounds: background, .. }, Primitive::Text { content, bounds, .. }) => {
+                    assert!(content.contains("12 primitives"));
+                    assert!(content.contains("3 draw calls"));
+                    assert_eq!(background.x, 10.0);
+                    assert!(background.width > bounds.width && background.height > bounds.height);
+                }
+                _ => panic!("Overlay is a quad behind some text"),
+            },
+            _ => panic!("Overlay is a group"),
+        }
+    }
+}
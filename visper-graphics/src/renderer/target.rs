@@ -1,62 +1,265 @@
+use crate::core::size::Size;
+use crate::renderer::blit::{self, BackBuffer};
+use crate::renderer::damage::Item;
 use crate::transformation::Transformation;
 use winit::window::Window;
 
+/// The surface of a window, or an offscreen texture, that a [`Renderer`]
+/// draws to.
+///
+/// The size of a [`Target`] is in physical pixels, while the primitives drawn
+/// on it are in logical pixels: the scale factor of the window converts one
+/// into the other.
+///
+/// A [`Target`] keeps what was drawn on it last, so the next frame only
+/// draws again what changed. Resizing it, or changing its scale factor,
+/// draws the next frame whole.
+///
+/// An offscreen [`Target`] has no window: its frames are only drawn on its
+/// back buffer, to be read back with [`Renderer::capture`].
+///
+/// [`Renderer`]: ../struct.Renderer.html
+/// [`Target`]: struct.Target.html
+/// [`Renderer::capture`]: ../struct.Renderer.html#method.capture
 #[derive(Debug)]
 pub struct Target {
-    surface: wgpu::Surface,
-    width: u16,
-    height: u16,
+    surface: Option<Surface>,
+    width: u32,
+    height: u32,
     scale_factor: f64,
-    transformation: Transformation,
+    is_outdated: bool,
+    back_buffer: Option<BackBuffer>,
+    scene: Option<Vec<Item>>,
+}
+
+#[derive(Debug)]
+struct Surface {
+    raw: wgpu::Surface,
     swap_chain: wgpu::SwapChain,
 }
 
+/// The textures to draw a frame of a [`Target`] on.
+///
+/// [`Target`]: struct.Target.html
+pub(crate) struct Frame<'a> {
+    /// The frame of the swap chain, to copy the back buffer to, unless the
+    /// [`Target`] is offscreen.
+    ///
+    /// [`Target`]: struct.Target.html
+    pub output: Option<wgpu::SwapChainOutput<'a>>,
+
+    /// The back buffer, which still holds the previous frame.
+    pub back_buffer: &'a BackBuffer,
+
+    /// The scene drawn on the back buffer, if it holds one.
+    pub scene: &'a mut Option<Vec<Item>>,
+}
+
 impl Target {
-    pub fn new(device: &wgpu::Device, window: &winit::window::Window, width: u16, height: u16, scale_factor: f64) -> Self {
+    /// Creates a [`Target`] for the given window, with its size in physical
+    /// pixels and its scale factor.
+    ///
+    /// [`Target`]: struct.Target.html
+    pub fn new(device: &wgpu::Device, window: &Window, width: u32, height: u32, scale_factor: f64) -> Self {
         let surface = wgpu::Surface::create(window);
         let swap_chain = new_swap_chain(device, &surface, width, height);
 
         Target {
-            surface,
+            surface: Some(Surface {
+                raw: surface,
+                swap_chain,
+            }),
+            width,
+            height,
+            scale_factor,
+            is_outdated: false,
+            back_buffer: None,
+            scene: None,
+        }
+    }
+
+    /// Creates an offscreen [`Target`], with its size in physical pixels and
+    /// its scale factor.
+    ///
+    /// [`Target`]: struct.Target.html
+    pub fn offscreen(width: u32, height: u32, scale_factor: f64) -> Self {
+        Target {
+            surface: None,
             width,
             height,
             scale_factor,
-            transformation: Transformation::orthographic(width, height),
-            swap_chain,
+            is_outdated: false,
+            back_buffer: None,
+            scene: None,
         }
     }
 
-    pub fn resize(&mut self, device: &wgpu::Device, width: u16, height: u16, scale_factor: f64) {
-        self.width = width;
-        self.height = height;
+    /// Returns whether the [`Target`] is offscreen.
+    ///
+    /// [`Target`]: struct.Target.html
+    pub fn is_offscreen(&self) -> bool {
+        self.surface.is_none()
+    }
+
+    /// Changes the size, in physical pixels, and the scale factor of the
+    /// [`Target`].
+    ///
+    /// The swap chain is only rebuilt when the next frame is requested, so a
+    /// burst of resizes between two frames rebuilds it once.
+    ///
+    /// [`Target`]: struct.Target.html
+    pub fn resize(&mut self, width: u32, height: u32, scale_factor: f64) {
+        if (width, height) != (self.width, self.height) {
+            self.width = width;
+            self.height = height;
+            self.is_outdated = true;
+            self.back_buffer = None;
+        }
+
+        if (scale_factor - self.scale_factor).abs() > f64::EPSILON {
+            self.scene = None;
+        }
+
         self.scale_factor = scale_factor;
-        self.transformation = Transformation::orthographic(width, height);
-        self.swap_chain = new_swap_chain(device, &self.surface, width, height);
     }
 
-    pub fn dimensions(&self) -> (u16, u16) { (self.width, self.height) }
+    /// Returns the width and height of the [`Target`], in physical pixels.
+    ///
+    /// [`Target`]: struct.Target.html
+    pub fn dimensions(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    /// Returns the size of the [`Target`], in logical pixels.
+    ///
+    /// [`Target`]: struct.Target.html
+    pub fn logical_size(&self) -> Size {
+        let scale = self.scale_factor as f32;
+
+        Size::new(self.width as f32 / scale, self.height as f32 / scale)
+    }
+
+    /// Returns the scale factor of the [`Target`], from logical to physical
+    /// pixels.
+    ///
+    /// [`Target`]: struct.Target.html
+    pub fn scale_factor(&self) -> f64 {
+        self.scale_factor
+    }
+
+    /// Returns the projection of the physical pixels of the [`Target`].
+    ///
+    /// The pipelines scale their vertices from logical pixels themselves.
+    ///
+    /// [`Target`]: struct.Target.html
+    pub fn transformation(&self) -> Transformation {
+        Transformation::orthographic(self.width as f32, self.height as f32)
+    }
 
-    pub fn scale_factor(&self) -> f64 { self.scale_factor }
+    /// Returns the textures to draw the next frame on, rebuilding the swap
+    /// chain and the back buffer first if the [`Target`] was resized.
+    ///
+    /// [`Target`]: struct.Target.html
+    pub(crate) fn next_frame(&mut self, device: &wgpu::Device, blit: &blit::Pipeline) -> Frame<'_> {
+        let (width, height) = (self.width, self.height);
 
-    pub fn transformation(&self) -> Transformation { self.transformation }
+        if self.is_outdated {
+            if let Some(surface) = &mut self.surface {
+                surface.swap_chain = new_swap_chain(device, &surface.raw, width, height);
+            }
 
-    pub fn next_frame(&mut self) -> wgpu::SwapChainOutput { self.swap_chain.get_next_texture() }
+            self.is_outdated = false;
+        }
+
+        if self.back_buffer.is_none() {
+            self.scene = None;
+        }
+
+        let back_buffer = self
+            .back_buffer
+            .get_or_insert_with(|| blit.back_buffer(device, width, height));
+
+        Frame {
+            output: self
+                .surface
+                .as_mut()
+                .map(|surface| surface.swap_chain.get_next_texture()),
+            back_buffer,
+            scene: &mut self.scene,
+        }
+    }
+
+    /// Returns the back buffer, if a frame was drawn on the [`Target`] since
+    /// it was created or resized.
+    ///
+    /// [`Target`]: struct.Target.html
+    pub(crate) fn back_buffer(&self) -> Option<&BackBuffer> {
+        self.back_buffer.as_ref()
+    }
 }
 
 fn new_swap_chain(
     device: &wgpu::Device,
     surface: &wgpu::Surface,
-    width: u16,
-    height: u16,
+    width: u32,
+    height: u32,
 ) -> wgpu::SwapChain {
+    // A minimized window has no area, but a swap chain needs some
     device.create_swap_chain(
-        &surface,
+        surface,
         &wgpu::SwapChainDescriptor {
             usage: wgpu::TextureUsage::OUTPUT_ATTACHMENT,
             format: wgpu::TextureFormat::Bgra8UnormSrgb,
-            width: u32::from(width),
-            height: u32::from(height),
+            width: width.max(1),
+            height: height.max(1),
             present_mode: wgpu::PresentMode::Vsync,
         },
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::point::Point;
+
+    #[test]
+    fn targets_have_no_frame_until_drawn() {
+        let mut target = Target::offscreen(800, 600, 1.0);
+
+        assert!(target.back_buffer().is_none());
+
+        target.resize(400, 300, 2.0);
+
+        assert!(target.back_buffer().is_none());
+    }
+
+    #[test]
+    fn resizes_defer_rebuilding_the_swap_chain() {
+        let mut target = Target::offscreen(800, 600, 1.0);
+
+        target.resize(800, 600, 2.0);
+
+        assert!(!target.is_outdated);
+
+        target.resize(640, 480, 2.0);
+        target.resize(320, 240, 2.0);
+
+        assert!(target.is_outdated);
+        assert_eq!(target.dimensions(), (320, 240));
+    }
+
+    #[test]
+    fn transformation_projects_the_physical_size() {
+        let mut target = Target::offscreen(800, 600, 1.0);
+
+        target.resize(400, 300, 2.0);
+
+        let corner = target.transformation() * Transformation::scale(2.0, 2.0);
+
+        assert_eq!(target.logical_size(), Size::new(200.0, 150.0));
+        assert_eq!(
+            corner.transform_point(Point::new(200.0, 150.0)),
+            Point::new(1.0, 1.0)
+        );
+    }
+}
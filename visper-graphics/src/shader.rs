@@ -0,0 +1,216 @@
+//! Load shaders from SPIR-V or GLSL.
+//!
+//! The shaders of the built-in pipelines are written in GLSL, in
+//! `src/primitive/shaders`. Their SPIR-V is checked in next to the sources,
+//! so building the crate needs no shader compiler. With the `glsl` feature,
+//! the build script compiles the sources instead, and the tests check that
+//! the checked-in SPIR-V matches them, writing it again when
+//! `VISPER_UPDATE_SHADERS` is set:
+//!
+//! ```text
+//! VISPER_UPDATE_SHADERS=1 cargo test --features glsl shader
+//! ```
+//!
+//! Custom shaders can be given as SPIR-V or, with the `glsl` feature,
+//! compiled from GLSL when the [`Shader`] is created.
+//!
+//! [`Shader`]: struct.Shader.html
+use std::fmt;
+use std::io;
+
+/// Embeds the SPIR-V checked in for one of the GLSL sources in
+/// `src/primitive/shaders`, like `quad.vert`.
+#[cfg(not(feature = "glsl"))]
+macro_rules! include_spirv {
+    ($name:literal) => {
+        include_bytes!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/src/primitive/shaders/",
+            $name,
+            ".spv"
+        ))
+    };
+}
+
+/// Embeds the SPIR-V compiled by the build script from one of the GLSL
+/// sources in `src/primitive/shaders`, like `quad.vert`.
+#[cfg(feature = "glsl")]
+macro_rules! include_spirv {
+    ($name:literal) => {
+        include_bytes!(concat!(env!("OUT_DIR"), "/shaders/", $name, ".spv"))
+    };
+}
+
+/// The stage of the pipeline a [`Shader`] runs in.
+///
+/// [`Shader`]: struct.Shader.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stage {
+    /// The shader runs once per vertex.
+    Vertex,
+
+    /// The shader runs once per fragment.
+    Fragment,
+}
+
+/// A compiled shader.
+#[derive(Clone, PartialEq, Eq)]
+pub struct Shader {
+    spirv: Vec<u32>,
+}
+
+impl fmt::Debug for Shader {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Shader({} words of SPIR-V)", self.spirv.len())
+    }
+}
+
+impl Shader {
+    /// Loads a [`Shader`] compiled to SPIR-V.
+    ///
+    /// [`Shader`]: struct.Shader.html
+    pub fn from_spirv(bytes: &[u8]) -> Result<Shader, Error> {
+        let spirv = wgpu::read_spirv(io::Cursor::new(bytes)).map_err(Error::Spirv)?;
+
+        Ok(Shader { spirv })
+    }
+
+    /// Compiles a [`Shader`] from GLSL source.
+    ///
+    /// This is only available with the `glsl` feature.
+    ///
+    /// [`Shader`]: struct.Shader.html
+    #[cfg(feature = "glsl")]
+    pub fn from_glsl(source: &str, stage: Stage) -> Result<Shader, Error> {
+        use std::io::Read;
+
+        let ty = match stage {
+            Stage::Vertex => glsl_to_spirv::ShaderType::Vertex,
+            Stage::Fragment => glsl_to_spirv::ShaderType::Fragment,
+        };
+
+        let mut compiled = glsl_to_spirv::compile(source, ty).map_err(Error::Glsl)?;
+        let mut bytes = Vec::new();
+
+        compiled.read_to_end(&mut bytes).map_err(Error::Spirv)?;
+
+        Self::from_spirv(&bytes)
+    }
+
+    /// Creates the module of the [`Shader`] on the given device.
+    ///
+    /// [`Shader`]: struct.Shader.html
+    pub fn module(&self, device: &wgpu::Device) -> wgpu::ShaderModule {
+        device.create_shader_module(&self.spirv)
+    }
+}
+
+/// An error loading a [`Shader`].
+///
+/// [`Shader`]: struct.Shader.html
+#[derive(Debug)]
+pub enum Error {
+    /// The SPIR-V could not be read.
+    Spirv(io::Error),
+
+    /// The GLSL source did not compile.
+    Glsl(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Spirv(error) => write!(f, "invalid SPIR-V: {}", error),
+            Error::Glsl(error) => write!(f, "GLSL compilation failed: {}", error),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// Creates the module of one of the built-in shaders.
+pub(crate) fn module(device: &wgpu::Device, bytes: &[u8]) -> wgpu::ShaderModule {
+    Shader::from_spirv(bytes)
+        .expect("Read built-in shader as SPIR-V")
+        .module(device)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Shader;
+
+    use std::fs;
+    use std::path::{Path, PathBuf};
+
+    /// Lists the GLSL sources of the built-in shaders with their checked-in
+    /// SPIR-V.
+    fn built_in_shaders() -> Vec<(PathBuf, PathBuf)> {
+        let directory = Path::new(env!("CARGO_MANIFEST_DIR")).join("src/primitive/shaders");
+        let mut shaders = Vec::new();
+
+        for entry in fs::read_dir(&directory).expect("Read shader directory") {
+            let path = entry.expect("Read shader directory entry").path();
+
+            match path.extension().and_then(|extension| extension.to_str()) {
+                Some("vert") | Some("frag") => {}
+                _ => continue,
+            }
+
+            let name = path.file_name().expect("Shader has a file name");
+            let spirv = directory.join(format!("{}.spv", name.to_string_lossy()));
+
+            shaders.push((path, spirv));
+        }
+
+        shaders
+    }
+
+    #[test]
+    fn built_in_shaders_are_checked_in() {
+        let shaders = built_in_shaders();
+
+        assert!(!shaders.is_empty());
+
+        for (source, spirv) in shaders {
+            let bytes = fs::read(&spirv)
+                .unwrap_or_else(|_| panic!("{} has no SPIR-V checked in", source.display()));
+
+            assert!(Shader::from_spirv(&bytes).is_ok(), "{}", spirv.display());
+        }
+    }
+
+    /// Compares two SPIR-V modules, ignoring the third word of the header,
+    /// which only names the compiler that generated them.
+    #[cfg(feature = "glsl")]
+    fn same_module(a: &[u8], b: &[u8]) -> bool {
+        a.len() == b.len() && a.len() >= 12 && a[..8] == b[..8] && a[12..] == b[12..]
+    }
+
+    #[test]
+    #[cfg(feature = "glsl")]
+    fn built_in_shaders_match_their_sources() {
+        let compiled = Path::new(env!("OUT_DIR")).join("shaders");
+        let update = std::env::var_os("VISPER_UPDATE_SHADERS").is_some();
+        let mut outdated = Vec::new();
+
+        for (source, spirv) in built_in_shaders() {
+            let name = spirv.file_name().expect("Shader has a file name");
+            let expected = fs::read(compiled.join(name)).expect("Read compiled shader");
+
+            if !same_module(&fs::read(&spirv).unwrap_or_default(), &expected) {
+                if update {
+                    fs::write(&spirv, &expected).expect("Write compiled shader");
+                } else {
+                    outdated.push(source.display().to_string());
+                }
+            }
+        }
+
+        assert!(
+            outdated.is_empty(),
+            "The checked-in SPIR-V does not match these shaders, run the tests again with \
+             VISPER_UPDATE_SHADERS set to update it: {:?}",
+            outdated
+        );
+    }
+}
@@ -0,0 +1,147 @@
+//! Measure text on the CPU.
+pub mod font;
+pub mod rich;
+
+use crate::core::size::Size;
+use rusttype::{point, Font, Scale};
+
+/// The font bundled with visper, DejaVu Sans.
+pub const DEFAULT_FONT: &[u8] = include_bytes!("../fonts/DejaVuSans.ttf");
+
+thread_local! {
+    static FONT: Font<'static> =
+        Font::from_bytes(DEFAULT_FONT).expect("Load default font");
+}
+
+/// The horizontal alignment of some text inside its bounds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HorizontalAlignment {
+    /// Align left.
+    Left,
+
+    /// Horizontally centered.
+    Center,
+
+    /// Align right.
+    Right,
+}
+
+/// The weight of a font, from 100 for the thinnest to 900 for the boldest.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Weight(pub u16);
+
+impl Weight {
+    pub const THIN: Weight = Weight(100);
+    pub const EXTRA_LIGHT: Weight = Weight(200);
+    pub const LIGHT: Weight = Weight(300);
+    pub const NORMAL: Weight = Weight(400);
+    pub const MEDIUM: Weight = Weight(500);
+    pub const SEMIBOLD: Weight = Weight(600);
+    pub const BOLD: Weight = Weight(700);
+    pub const EXTRA_BOLD: Weight = Weight(800);
+    pub const BLACK: Weight = Weight(900);
+}
+
+impl Default for Weight {
+    fn default() -> Weight {
+        Weight::NORMAL
+    }
+}
+
+/// The vertical alignment of some text inside its bounds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VerticalAlignment {
+    /// Align top.
+    Top,
+
+    /// Vertically centered.
+    Center,
+
+    /// Align bottom.
+    Bottom,
+}
+
+/// Returns the height of a line of text of the given size.
+pub fn line_height(size: f32) -> f32 {
+    FONT.with(|font| {
+        let v_metrics = font.v_metrics(Scale::uniform(size));
+
+        (v_metrics.ascent - v_metrics.descent + v_metrics.line_gap).ceil()
+    })
+}
+
+/// Returns the horizontal advance of a single line of text.
+pub fn advance(line: &str, size: f32) -> f32 {
+    FONT.with(|font| {
+        font.layout(line, Scale::uniform(size), point(0.0, 0.0))
+            .last()
+            .map(|glyph| {
+                glyph.position().x + glyph.unpositioned().h_metrics().advance_width
+            })
+            .unwrap_or(0.0)
+    })
+}
+
+/// Returns the byte index of the character boundary of a single line of text
+/// closest to the given horizontal position.
+pub fn hit_test(line: &str, size: f32, x: f32) -> usize {
+    FONT.with(|font| {
+        let boundaries = font
+            .layout(line, Scale::uniform(size), point(0.0, 0.0))
+            .zip(line.char_indices())
+            .map(|(glyph, (index, _))| (index, glyph.position().x))
+            .chain(std::iter::once((line.len(), advance(line, size))));
+
+        let mut closest = (0, f32::INFINITY);
+
+        for (index, position) in boundaries {
+            let distance = (position - x).abs();
+
+            if distance < closest.1 {
+                closest = (index, distance);
+            }
+        }
+
+        closest.0
+    })
+}
+
+/// Returns the size of the given text, with a line per line break.
+pub fn measure(content: &str, size: f32) -> Size {
+    let (width, lines) = content
+        .split('\n')
+        .fold((0.0f32, 0), |(width, lines), line| {
+            (width.max(advance(line, size)), lines + 1)
+        });
+
+    Size::new(width.ceil(), line_height(size) * lines as f32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn measure_grows_with_content() {
+        let short = measure("Hi", 20.0);
+        let long = measure("Hello, world!", 20.0);
+        let two_lines = measure("Hi\nHi", 20.0);
+
+        assert!(long.width > short.width);
+        assert_eq!(short.height, line_height(20.0));
+        assert_eq!(two_lines.height, line_height(20.0) * 2.0);
+        assert_eq!(measure("", 20.0).width, 0.0);
+    }
+
+    #[test]
+    fn hit_test_snaps_to_closest_boundary() {
+        let line = "añb";
+        let a = advance("a", 20.0);
+
+        assert_eq!(hit_test(line, 20.0, -5.0), 0);
+        assert_eq!(hit_test(line, 20.0, a + 1.0), 1);
+        assert_eq!(hit_test(line, 20.0, advance("añ", 20.0)), 3);
+        assert_eq!(hit_test(line, 20.0, 1000.0), line.len());
+        assert_eq!(hit_test("", 20.0, 10.0), 0);
+    }
+}
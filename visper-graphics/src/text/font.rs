@@ -0,0 +1,1005 @@
+//! Find the faces to draw text with.
+//!
+//! A [`FontDatabase`] holds faces loaded from memory or found in the font
+//! directories of the system, and picks the one closest to a [`Query`] by
+//! family, weight and style. Characters missing from that face are drawn
+//! with the first face of a chain of fallback families that has them, like
+//! fonts of emoji or of Chinese, Japanese and Korean.
+//!
+//! Faces found on disk are only read when text is drawn with them.
+//!
+//! [`FontDatabase`]: struct.FontDatabase.html
+//! [`Query`]: struct.Query.html
+use super::{Weight, DEFAULT_FONT};
+use rusttype::{Font, FontCollection};
+use std::collections::HashSet;
+use std::fmt;
+use std::io;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+
+lazy_static::lazy_static! {
+    static ref SHARED: FontDatabase = FontDatabase::new();
+}
+
+/// The identifier of the next face loaded.
+static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+
+/// The identifier of a [`Face`], unique in the whole application.
+///
+/// [`Face`]: struct.Face.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Id(usize);
+
+/// The slant of a face.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Style {
+    /// Upright.
+    #[default]
+    Normal,
+
+    /// Cursive, with letters designed for it.
+    Italic,
+
+    /// Upright letters, slanted.
+    Oblique,
+}
+
+/// A family of faces.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Family {
+    /// The family of the given name, like `"Noto Sans"`.
+    Name(String),
+
+    /// A family without serifs, the first one available of some common ones.
+    SansSerif,
+
+    /// A family with serifs, the first one available of some common ones.
+    Serif,
+
+    /// A family of characters of the same width, the first one available of
+    /// some common ones.
+    Monospace,
+}
+
+impl Family {
+    /// Returns the names of the families the [`Family`] stands for, in order
+    /// of preference.
+    ///
+    /// [`Family`]: enum.Family.html
+    fn names(&self) -> Vec<&str> {
+        match self {
+            Family::Name(name) => vec![name],
+            Family::SansSerif => vec![
+                "DejaVu Sans",
+                "Noto Sans",
+                "Liberation Sans",
+                "Helvetica",
+                "Arial",
+                "Segoe UI",
+            ],
+            Family::Serif => vec![
+                "DejaVu Serif",
+                "Noto Serif",
+                "Liberation Serif",
+                "Times",
+                "Times New Roman",
+            ],
+            Family::Monospace => vec![
+                "DejaVu Sans Mono",
+                "Noto Sans Mono",
+                "Liberation Mono",
+                "Menlo",
+                "Consolas",
+                "Courier New",
+            ],
+        }
+    }
+}
+
+/// What a face is looked for with.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Query {
+    /// The families to look into, in order of preference.
+    pub families: Vec<Family>,
+
+    /// The weight of the face.
+    pub weight: Weight,
+
+    /// The style of the face.
+    pub style: Style,
+}
+
+impl Default for Query {
+    fn default() -> Query {
+        Query {
+            families: vec![Family::SansSerif],
+            weight: Weight::NORMAL,
+            style: Style::Normal,
+        }
+    }
+}
+
+/// An error loading a font.
+#[derive(Debug)]
+pub enum Error {
+    /// The font could not be read.
+    Io(io::Error),
+
+    /// The data is not a font, or has no outlines to draw.
+    Unsupported,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io(error) => write!(f, "could not read font: {}", error),
+            Error::Unsupported => write!(f, "unsupported font"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<io::Error> for Error {
+    fn from(error: io::Error) -> Error {
+        Error::Io(error)
+    }
+}
+
+/// A face of a font: a family in a weight and a style.
+pub struct Face {
+    id: Id,
+    family: String,
+    weight: Weight,
+    style: Style,
+    monospaced: bool,
+    /// The ranges of characters the face has glyphs for, in order.
+    coverage: Vec<Range<u32>>,
+    source: Source,
+    index: u32,
+    loaded: Mutex<Option<Loaded>>,
+}
+
+enum Source {
+    Memory(Arc<[u8]>),
+    File(PathBuf),
+}
+
+#[derive(Clone)]
+struct Loaded {
+    data: Arc<[u8]>,
+    font: Font<'static>,
+}
+
+impl Face {
+    pub fn id(&self) -> Id {
+        self.id
+    }
+
+    /// Returns the name of the family of the [`Face`].
+    ///
+    /// [`Face`]: struct.Face.html
+    pub fn family(&self) -> &str {
+        &self.family
+    }
+
+    pub fn weight(&self) -> Weight {
+        self.weight
+    }
+
+    pub fn style(&self) -> Style {
+        self.style
+    }
+
+    /// Returns whether all the characters of the [`Face`] have the same
+    /// width.
+    ///
+    /// [`Face`]: struct.Face.html
+    pub fn is_monospaced(&self) -> bool {
+        self.monospaced
+    }
+
+    /// Returns the file the [`Face`] was found in, if it was not loaded from
+    /// memory.
+    ///
+    /// [`Face`]: struct.Face.html
+    pub fn path(&self) -> Option<&Path> {
+        match &self.source {
+            Source::Memory(_) => None,
+            Source::File(path) => Some(path),
+        }
+    }
+
+    /// Returns the index of the [`Face`] in its font collection, or 0.
+    ///
+    /// [`Face`]: struct.Face.html
+    pub fn index(&self) -> u32 {
+        self.index
+    }
+
+    /// Returns whether the [`Face`] has a glyph for the given character.
+    ///
+    /// [`Face`]: struct.Face.html
+    pub fn has_glyph(&self, character: char) -> bool {
+        let character = character as u32;
+
+        self.coverage
+            .binary_search_by(|range| {
+                if range.end <= character {
+                    std::cmp::Ordering::Less
+                } else if range.start > character {
+                    std::cmp::Ordering::Greater
+                } else {
+                    std::cmp::Ordering::Equal
+                }
+            })
+            .is_ok()
+    }
+
+    /// Returns the data of the font of the [`Face`], reading it from its file
+    /// the first time.
+    ///
+    /// [`Face`]: struct.Face.html
+    pub fn data(&self) -> Result<Arc<[u8]>, Error> {
+        self.load().map(|loaded| loaded.data)
+    }
+
+    /// Returns the [`Face`] ready to draw glyphs with, once it is loaded.
+    ///
+    /// [`Face`]: struct.Face.html
+    pub(crate) fn font(&self) -> Option<Font<'static>> {
+        self.load().ok().map(|loaded| loaded.font)
+    }
+
+    fn load(&self) -> Result<Loaded, Error> {
+        let mut loaded = self.loaded.lock().expect("Lock face");
+
+        if let Some(loaded) = loaded.as_ref() {
+            return Ok(loaded.clone());
+        }
+
+        let data = match &self.source {
+            Source::Memory(data) => data.clone(),
+            Source::File(path) => Arc::from(std::fs::read(path)?),
+        };
+
+        let font = FontCollection::from_bytes(data.clone())
+            .and_then(|collection| collection.font_at(self.index as usize))
+            .map_err(|_| Error::Unsupported)?;
+
+        *loaded = Some(Loaded { data, font });
+
+        Ok(loaded.clone().expect("Loaded face"))
+    }
+}
+
+impl fmt::Debug for Face {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Face")
+            .field("id", &self.id)
+            .field("family", &self.family)
+            .field("weight", &self.weight)
+            .field("style", &self.style)
+            .field("path", &self.path())
+            .field("index", &self.index)
+            .finish()
+    }
+}
+
+impl PartialEq for Face {
+    fn eq(&self, other: &Face) -> bool {
+        self.id == other.id
+    }
+}
+
+/// The faces text can be drawn with.
+///
+/// A [`FontDatabase`] is a handle: its clones share the same faces, which
+/// are only loaded once. Every window of an application can draw with the
+/// [`shared`] one.
+///
+/// [`FontDatabase`]: struct.FontDatabase.html
+/// [`shared`]: #method.shared
+#[derive(Clone)]
+pub struct FontDatabase {
+    inner: Arc<RwLock<Inner>>,
+}
+
+struct Inner {
+    faces: Vec<Arc<Face>>,
+    fallbacks: Vec<Family>,
+}
+
+impl FontDatabase {
+    /// Creates a [`FontDatabase`] with the bundled font only, DejaVu Sans,
+    /// and the default fallback families.
+    ///
+    /// [`FontDatabase`]: struct.FontDatabase.html
+    pub fn new() -> FontDatabase {
+        let database = FontDatabase {
+            inner: Arc::new(RwLock::new(Inner {
+                faces: Vec::new(),
+                fallbacks: default_fallbacks(),
+            })),
+        };
+
+        let _ = database.load(DEFAULT_FONT).expect("Load default font");
+
+        database
+    }
+
+    /// Returns the [`FontDatabase`] shared by the whole application, which
+    /// text is laid out with by default.
+    ///
+    /// [`FontDatabase`]: struct.FontDatabase.html
+    pub fn shared() -> FontDatabase {
+        SHARED.clone()
+    }
+
+    /// Loads the faces of a font, or of a font collection, from memory.
+    ///
+    /// Returns the identifiers of the faces loaded.
+    pub fn load<D: Into<Arc<[u8]>>>(&self, data: D) -> Result<Vec<Id>, Error> {
+        let data = data.into();
+
+        self.add(&data, || Source::Memory(data.clone()))
+    }
+
+    /// Loads the faces of a font file.
+    ///
+    /// The file is read again when text is first drawn with one of its
+    /// faces.
+    ///
+    /// Returns the identifiers of the faces loaded.
+    pub fn load_file<P: AsRef<Path>>(&self, path: P) -> Result<Vec<Id>, Error> {
+        let path = path.as_ref();
+        let data = std::fs::read(path)?;
+
+        self.add(&data, || Source::File(path.to_path_buf()))
+    }
+
+    /// Loads the fonts of a directory and of its subdirectories, skipping the
+    /// files that are not fonts.
+    ///
+    /// Returns the number of faces loaded.
+    pub fn load_directory<P: AsRef<Path>>(&self, path: P) -> usize {
+        let mut visited = HashSet::new();
+
+        self.walk(path.as_ref(), &mut visited)
+    }
+
+    /// Loads the fonts of the directories of the system. See
+    /// [`system_directories`].
+    ///
+    /// Returns the number of faces loaded.
+    ///
+    /// [`system_directories`]: fn.system_directories.html
+    pub fn load_system_fonts(&self) -> usize {
+        let mut visited = HashSet::new();
+
+        system_directories()
+            .iter()
+            .map(|directory| self.walk(directory, &mut visited))
+            .sum()
+    }
+
+    /// Returns all the faces, in the order they were loaded in.
+    pub fn faces(&self) -> Vec<Arc<Face>> {
+        self.read().faces.clone()
+    }
+
+    /// Returns the [`Face`] with the given identifier.
+    ///
+    /// [`Face`]: struct.Face.html
+    pub fn face(&self, id: Id) -> Option<Arc<Face>> {
+        self.read().faces.iter().find(|face| face.id == id).cloned()
+    }
+
+    /// Returns the names of the families of the faces, in alphabetical order.
+    pub fn families(&self) -> Vec<String> {
+        let mut families: Vec<String> =
+            self.read().faces.iter().map(|face| face.family.clone()).collect();
+
+        families.sort();
+        families.dedup();
+        families
+    }
+
+    /// Returns the [`Face`] closest to the [`Query`].
+    ///
+    /// The first family of the query with a face is picked, then the face of
+    /// that family with the closest style and weight, following the rules of
+    /// CSS. Sans-serif faces are used when no family has a face.
+    ///
+    /// [`Face`]: struct.Face.html
+    /// [`Query`]: struct.Query.html
+    pub fn query(&self, query: &Query) -> Arc<Face> {
+        let candidates: Vec<Arc<Face>> = {
+            let inner = self.read();
+
+            query
+                .families
+                .iter()
+                .chain(std::iter::once(&Family::SansSerif))
+                .map(Some)
+                .chain(std::iter::once(None))
+                .flat_map(|family| inner.candidates(family, query, |_| true))
+                .collect()
+        };
+
+        first_loaded(candidates).expect("A font database has the default font")
+    }
+
+    /// Sets the families characters missing from a face are looked for in,
+    /// in order.
+    pub fn set_fallbacks(&self, families: Vec<Family>) {
+        self.inner.write().expect("Write font database").fallbacks = families;
+    }
+
+    /// Returns the [`Face`] to draw a character missing from the face of a
+    /// [`Query`] with.
+    ///
+    /// The fallback families are tried first, in order, then any face with
+    /// a glyph for the character.
+    ///
+    /// [`Face`]: struct.Face.html
+    /// [`Query`]: struct.Query.html
+    pub fn fallback(&self, query: &Query, character: char) -> Option<Arc<Face>> {
+        let candidates: Vec<Arc<Face>> = {
+            let inner = self.read();
+            let has_glyph = |face: &Face| face.has_glyph(character);
+
+            inner
+                .fallbacks
+                .iter()
+                .map(Some)
+                .chain(std::iter::once(None))
+                .flat_map(|family| inner.candidates(family, query, has_glyph))
+                .collect()
+        };
+
+        first_loaded(candidates)
+    }
+
+    fn read(&self) -> std::sync::RwLockReadGuard<'_, Inner> {
+        self.inner.read().expect("Read font database")
+    }
+
+    /// Adds the faces of some font data, found in the given source.
+    fn add(&self, data: &[u8], source: impl Fn() -> Source) -> Result<Vec<Id>, Error> {
+        let count = ttf_parser::fonts_in_collection(data).unwrap_or(1);
+        let faces: Vec<Arc<Face>> = (0..count)
+            .filter_map(|index| {
+                let face = parse(data, index)?;
+
+                Some(Arc::new(Face {
+                    source: source(),
+                    ..face
+                }))
+            })
+            .collect();
+
+        if faces.is_empty() {
+            return Err(Error::Unsupported);
+        }
+
+        let ids = faces.iter().map(|face| face.id).collect();
+
+        self.inner
+            .write()
+            .expect("Write font database")
+            .faces
+            .extend(faces);
+
+        Ok(ids)
+    }
+
+    fn walk(&self, directory: &Path, visited: &mut HashSet<PathBuf>) -> usize {
+        // Links may lead back to a directory already walked
+        match directory.canonicalize() {
+            Ok(canonical) if !visited.contains(&canonical) => {
+                visited.insert(canonical);
+            }
+            _ => return 0,
+        }
+
+        let entries = match std::fs::read_dir(directory) {
+            Ok(entries) => entries,
+            Err(_) => return 0,
+        };
+
+        let mut loaded = 0;
+
+        for entry in entries.filter_map(Result::ok) {
+            let path = entry.path();
+
+            if path.is_dir() {
+                loaded += self.walk(&path, visited);
+            } else if is_font(&path) {
+                loaded += self.load_file(&path).map(|ids| ids.len()).unwrap_or(0);
+            }
+        }
+
+        loaded
+    }
+}
+
+impl Default for FontDatabase {
+    fn default() -> FontDatabase {
+        FontDatabase::new()
+    }
+}
+
+impl fmt::Debug for FontDatabase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let inner = self.read();
+
+        f.debug_struct("FontDatabase")
+            .field("faces", &inner.faces.len())
+            .field("fallbacks", &inner.fallbacks)
+            .finish()
+    }
+}
+
+impl PartialEq for FontDatabase {
+    fn eq(&self, other: &FontDatabase) -> bool {
+        Arc::ptr_eq(&self.inner, &other.inner)
+    }
+}
+
+impl Inner {
+    /// Returns the faces of a family, or of any family, among the ones
+    /// accepted, from the closest to the style and weight of a query.
+    fn candidates(
+        &self,
+        family: Option<&Family>,
+        query: &Query,
+        accept: impl Fn(&Face) -> bool,
+    ) -> Vec<Arc<Face>> {
+        let names = family.map(Family::names);
+        let mut candidates: Vec<&Arc<Face>> = self
+            .faces
+            .iter()
+            .filter(|face| match &names {
+                Some(names) => names
+                    .iter()
+                    .any(|name| face.family.eq_ignore_ascii_case(name)),
+                None => true,
+            })
+            .filter(|face| accept(face))
+            .collect();
+
+        // Generic families list their names in order of preference
+        let family_rank = |face: &Face| {
+            names
+                .as_ref()
+                .and_then(|names| {
+                    names
+                        .iter()
+                        .position(|name| face.family.eq_ignore_ascii_case(name))
+                })
+                .unwrap_or(0)
+        };
+
+        candidates.sort_by_key(|face| {
+            (
+                family_rank(face),
+                style_rank(query.style, face.style),
+                weight_rank(query.weight, face.weight),
+            )
+        });
+
+        candidates.into_iter().cloned().collect()
+    }
+}
+
+/// Returns the first face that can be loaded.
+///
+/// Faces are read from their files the first time, so this is called without
+/// holding the lock of the database: the faces keep what they read.
+fn first_loaded(candidates: Vec<Arc<Face>>) -> Option<Arc<Face>> {
+    candidates.into_iter().find(|face| face.load().is_ok())
+}
+
+/// Ranks the style of a face for the one looked for: italic faces are
+/// preferred to oblique ones for italic text, and the other way around.
+fn style_rank(wanted: Style, style: Style) -> u8 {
+    let order = match wanted {
+        Style::Normal => [Style::Normal, Style::Oblique, Style::Italic],
+        Style::Italic => [Style::Italic, Style::Oblique, Style::Normal],
+        Style::Oblique => [Style::Oblique, Style::Italic, Style::Normal],
+    };
+
+    order.iter().position(|&other| other == style).unwrap_or(0) as u8
+}
+
+/// Ranks the weight of a face for the one looked for, following CSS: light
+/// weights prefer lighter faces, bold weights prefer bolder ones, and
+/// weights between 400 and 500 prefer the faces up to 500 first.
+fn weight_rank(wanted: Weight, weight: Weight) -> (u8, u16) {
+    let (wanted, weight) = (wanted.0, weight.0);
+
+    if (400..=500).contains(&wanted) {
+        if weight >= wanted && weight <= 500 {
+            (0, weight - wanted)
+        } else if weight < wanted {
+            (1, wanted - weight)
+        } else {
+            (2, weight - wanted)
+        }
+    } else if wanted < 400 {
+        if weight <= wanted {
+            (0, wanted - weight)
+        } else {
+            (1, weight - wanted)
+        }
+    } else if weight >= wanted {
+        (0, weight - wanted)
+    } else {
+        (1, wanted - weight)
+    }
+}
+
+/// Reads the properties of a face of some font data, if it has outlines to
+/// draw.
+///
+/// The face is in memory until its source is set.
+fn parse(data: &[u8], index: u32) -> Option<Face> {
+    let face = ttf_parser::Face::from_slice(data, index).ok()?;
+
+    // Faces of bitmaps only, like most color emoji, cannot be drawn
+    if !face.has_table(ttf_parser::TableName::GlyphData)
+        && !face.has_table(ttf_parser::TableName::CompactFontFormat)
+    {
+        return None;
+    }
+
+    let name = |id| {
+        face.names()
+            .filter(|name| name.name_id() == id)
+            .filter_map(|name| name.to_string())
+            .next()
+    };
+
+    let family = name(ttf_parser::name_id::TYPOGRAPHIC_FAMILY)
+        .or_else(|| name(ttf_parser::name_id::FAMILY))?;
+
+    let style = if face.is_italic() {
+        Style::Italic
+    } else if face.is_oblique() {
+        Style::Oblique
+    } else {
+        Style::Normal
+    };
+
+    let mut characters = Vec::new();
+
+    for subtable in face.character_mapping_subtables() {
+        if subtable.is_unicode() {
+            subtable.codepoints(|character| characters.push(character));
+        }
+    }
+
+    Some(Face {
+        id: Id(NEXT_ID.fetch_add(1, Ordering::Relaxed)),
+        family,
+        weight: Weight(face.weight().to_number()),
+        style,
+        monospaced: face.is_monospaced(),
+        coverage: coverage(characters),
+        source: Source::Memory(Arc::from(&[][..])),
+        index,
+        loaded: Mutex::new(None),
+    })
+}
+
+/// Merges characters into ranges.
+fn coverage(mut characters: Vec<u32>) -> Vec<Range<u32>> {
+    characters.sort_unstable();
+
+    let mut ranges: Vec<Range<u32>> = Vec::new();
+
+    for character in characters {
+        match ranges.last_mut() {
+            Some(range) if range.end >= character => {
+                range.end = range.end.max(character + 1);
+            }
+            _ => ranges.push(character..character + 1),
+        }
+    }
+
+    ranges
+}
+
+fn is_font(path: &Path) -> bool {
+    match path.extension().and_then(|extension| extension.to_str()) {
+        Some(extension) => ["ttf", "otf", "ttc", "otc"]
+            .iter()
+            .any(|font| extension.eq_ignore_ascii_case(font)),
+        None => false,
+    }
+}
+
+/// The families characters missing from a face are looked for in by
+/// default: the common ones for emoji, then for Chinese, Japanese and
+/// Korean.
+fn default_fallbacks() -> Vec<Family> {
+    [
+        "Noto Emoji",
+        "Twemoji Mozilla",
+        "Segoe UI Emoji",
+        "Segoe UI Symbol",
+        "Symbola",
+        "Noto Sans CJK SC",
+        "Noto Sans CJK JP",
+        "Noto Sans CJK KR",
+        "Noto Sans CJK TC",
+        "Source Han Sans",
+        "WenQuanYi Micro Hei",
+        "Droid Sans Fallback",
+        "PingFang SC",
+        "Hiragino Sans",
+        "Microsoft YaHei",
+        "Yu Gothic",
+        "Malgun Gothic",
+    ]
+    .iter()
+    .map(|name| Family::Name(name.to_string()))
+    .collect()
+}
+
+/// Returns the directories the system keeps fonts in.
+///
+/// On Linux and other Unix systems, these are the directories listed in the
+/// configuration of fontconfig, along with the usual ones.
+pub fn system_directories() -> Vec<PathBuf> {
+    let home = std::env::var_os("HOME").map(PathBuf::from);
+    let mut directories = Vec::new();
+
+    if cfg!(target_os = "windows") {
+        let windows = std::env::var_os("WINDIR").unwrap_or_else(|| "C:\\Windows".into());
+        directories.push(Path::new(&windows).join("Fonts"));
+
+        if let Some(local) = std::env::var_os("LOCALAPPDATA") {
+            directories.push(Path::new(&local).join("Microsoft\\Windows\\Fonts"));
+        }
+    } else if cfg!(target_os = "macos") {
+        directories.push(PathBuf::from("/System/Library/Fonts"));
+        directories.push(PathBuf::from("/Library/Fonts"));
+
+        if let Some(home) = &home {
+            directories.push(home.join("Library/Fonts"));
+        }
+    } else {
+        let data = std::env::var_os("XDG_DATA_HOME")
+            .map(PathBuf::from)
+            .or_else(|| home.as_ref().map(|home| home.join(".local/share")));
+
+        let mut configs = vec![PathBuf::from("/etc/fonts/fonts.conf")];
+
+        if let Ok(entries) = std::fs::read_dir("/etc/fonts/conf.d") {
+            let mut entries: Vec<PathBuf> = entries
+                .filter_map(Result::ok)
+                .map(|entry| entry.path())
+                .filter(|path| path.extension() == Some("conf".as_ref()))
+                .collect();
+
+            entries.sort();
+            configs.extend(entries);
+        }
+
+        for config in configs {
+            if let Ok(config) = std::fs::read_to_string(config) {
+                directories.extend(fontconfig_directories(&config, home.as_deref(), data.as_deref()));
+            }
+        }
+
+        directories.push(PathBuf::from("/usr/share/fonts"));
+        directories.push(PathBuf::from("/usr/local/share/fonts"));
+
+        if let Some(data) = &data {
+            directories.push(data.join("fonts"));
+        }
+
+        if let Some(home) = &home {
+            directories.push(home.join(".fonts"));
+        }
+    }
+
+    let mut seen = HashSet::new();
+    directories.retain(|directory| seen.insert(directory.clone()));
+    directories
+}
+
+/// Returns the font directories of a fontconfig configuration, from its
+/// `<dir>` elements.
+///
+/// Directories starting with `~` are in the home directory, and the ones
+/// with the `xdg` prefix in the data directory of the user.
+fn fontconfig_directories(config: &str, home: Option<&Path>, data: Option<&Path>) -> Vec<PathBuf> {
+    let mut directories = Vec::new();
+    let mut rest = config;
+
+    while let Some(start) = rest.find("<dir") {
+        rest = &rest[start + 4..];
+
+        // Skip other elements starting the same way, like <dirname>
+        let (attributes, content) = match rest.find('>') {
+            Some(end) if rest.starts_with('>') || rest.starts_with(' ') => {
+                (&rest[..end], &rest[end + 1..])
+            }
+            _ => continue,
+        };
+
+        if attributes.ends_with('/') {
+            continue;
+        }
+
+        let path = match content.find("</dir>") {
+            Some(end) => content[..end].trim(),
+            None => break,
+        };
+
+        let directory = if attributes.contains("prefix=\"xdg\"") {
+            data.map(|data| data.join(path))
+        } else if path == "~" {
+            home.map(Path::to_path_buf)
+        } else if path.starts_with("~/") {
+            home.map(|home| home.join(&path[2..]))
+        } else {
+            Some(PathBuf::from(path))
+        };
+
+        directories.extend(directory);
+    }
+
+    directories
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Adds a face of the default font, under another family and with
+    /// other properties.
+    fn add(database: &FontDatabase, family: &str, weight: Weight, style: Style, coverage: &str) -> Id {
+        let face = Face {
+            family: family.to_string(),
+            weight,
+            style,
+            coverage: super::coverage(coverage.chars().map(|c| c as u32).collect()),
+            source: Source::Memory(Arc::from(DEFAULT_FONT)),
+            ..parse(DEFAULT_FONT, 0).unwrap()
+        };
+
+        let id = face.id;
+        database.inner.write().unwrap().faces.push(Arc::new(face));
+        id
+    }
+
+    fn query(family: &str, weight: Weight, style: Style) -> Query {
+        Query {
+            families: vec![Family::Name(family.to_string())],
+            weight,
+            style,
+        }
+    }
+
+    #[test]
+    fn default_font_is_loaded() {
+        let database = FontDatabase::new();
+        let face = database.query(&Query::default());
+
+        assert_eq!(database.faces().len(), 1);
+        assert_eq!(face.family(), "DejaVu Sans");
+        assert_eq!(face.weight(), Weight::NORMAL);
+        assert_eq!(face.style(), Style::Normal);
+        assert!(face.has_glyph('a'));
+        assert!(!face.has_glyph('\u{4E2D}'));
+        assert!(face.font().is_some());
+    }
+
+    #[test]
+    fn query_picks_the_closest_face() {
+        let database = FontDatabase::new();
+        let regular = add(&database, "Sample", Weight::NORMAL, Style::Normal, "");
+        let semibold = add(&database, "Sample", Weight::SEMIBOLD, Style::Normal, "");
+        let black = add(&database, "Sample", Weight::BLACK, Style::Normal, "");
+        let oblique = add(&database, "Sample", Weight::NORMAL, Style::Oblique, "");
+
+        let id = |weight, style| database.query(&query("sample", weight, style)).id();
+
+        assert_eq!(id(Weight::MEDIUM, Style::Normal), regular);
+        assert_eq!(id(Weight::BOLD, Style::Normal), black);
+        assert_eq!(id(Weight::LIGHT, Style::Normal), regular);
+        assert_eq!(id(Weight::SEMIBOLD, Style::Normal), semibold);
+        assert_eq!(id(Weight::NORMAL, Style::Italic), oblique);
+
+        // Unknown families fall back to sans-serif
+        assert_eq!(
+            database.query(&query("Unknown", Weight::NORMAL, Style::Normal)).family(),
+            "DejaVu Sans"
+        );
+    }
+
+    #[test]
+    fn missing_characters_fall_back() {
+        let database = FontDatabase::new();
+        let cjk = add(&database, "Other CJK", Weight::NORMAL, Style::Normal, "\u{4E2D}");
+        let preferred = add(&database, "Noto Sans CJK JP", Weight::NORMAL, Style::Normal, "\u{4E2D}");
+
+        let fallback = |character| {
+            database
+                .fallback(&Query::default(), character)
+                .map(|face| face.id())
+        };
+
+        assert_eq!(fallback('\u{4E2D}'), Some(preferred));
+        assert_eq!(fallback('\u{4E00}'), None);
+
+        database.set_fallbacks(Vec::new());
+
+        assert_eq!(fallback('\u{4E2D}'), Some(cjk));
+    }
+
+    #[test]
+    fn faces_that_cannot_be_read_are_skipped() {
+        let database = FontDatabase::new();
+        let cjk = add(&database, "Other CJK", Weight::NORMAL, Style::Normal, "\u{4E2D}");
+
+        database.inner.write().unwrap().faces.push(Arc::new(Face {
+            family: String::from("Noto Sans CJK JP"),
+            coverage: super::coverage(vec!['\u{4E2D}' as u32]),
+            source: Source::File(PathBuf::from("/missing/NotoSansCJK.ttc")),
+            ..parse(DEFAULT_FONT, 0).unwrap()
+        }));
+
+        let face = database.fallback(&Query::default(), '\u{4E2D}');
+
+        assert_eq!(face.map(|face| face.id()), Some(cjk));
+    }
+
+    #[test]
+    fn clones_share_faces() {
+        let database = FontDatabase::new();
+        let clone = database.clone();
+
+        let ids = clone.load(DEFAULT_FONT).unwrap();
+
+        assert_eq!(database.faces().len(), 2);
+        assert_eq!(database.face(ids[0]).unwrap().family(), "DejaVu Sans");
+        assert_eq!(database.families(), vec!["DejaVu Sans".to_string()]);
+        assert!(database.load(vec![0; 16]).is_err());
+    }
+
+    #[test]
+    fn fontconfig_lists_directories() {
+        let config = r#"
+            <fontconfig>
+                <dir>/usr/share/fonts</dir>
+                <dir prefix="xdg">fonts</dir>
+                <dir>~/.fonts</dir>
+                <dirname>/ignored</dirname>
+                <cachedir>/var/cache/fontconfig</cachedir>
+            </fontconfig>
+        "#;
+
+        let directories = fontconfig_directories(
+            config,
+            Some(Path::new("/home/user")),
+            Some(Path::new("/home/user/.local/share")),
+        );
+
+        assert_eq!(
+            directories,
+            vec![
+                PathBuf::from("/usr/share/fonts"),
+                PathBuf::from("/home/user/.local/share/fonts"),
+                PathBuf::from("/home/user/.fonts"),
+            ]
+        );
+    }
+}
@@ -0,0 +1,1384 @@
+//! Lay out paragraphs of styled text.
+//!
+//! A [`Paragraph`] is a sequence of [`Span`]s, each with a style of its own.
+//! Its [`Layout`] positions every glyph of the paragraph, along with the
+//! decorations of its spans, and maps positions back to characters for
+//! cursors and selections.
+//!
+//! Text is shaped with the OpenType tables of its faces, so scripts with
+//! contextual forms like Arabic and Persian are joined, and ordered with the
+//! Unicode Bidirectional Algorithm, so right-to-left and left-to-right text
+//! can be mixed in the same paragraph. Characters missing from the face of
+//! their span are drawn with a fallback face of the [`FontDatabase`].
+//!
+//! [`Paragraph`]: struct.Paragraph.html
+//! [`Span`]: struct.Span.html
+//! [`Layout`]: struct.Layout.html
+//! [`FontDatabase`]: ../font/struct.FontDatabase.html
+use super::font::{Face, Family, FontDatabase, Query, Style};
+use super::{HorizontalAlignment, Weight};
+use crate::core::color::Color;
+use crate::core::point::Point;
+use crate::core::rectangle::Rectangle;
+use crate::core::size::Size;
+use rusttype::Scale;
+use std::ops::Range;
+use std::sync::Arc;
+use unicode_bidi::{BidiInfo, Level};
+
+/// What replaces the end of a truncated paragraph.
+const ELLIPSIS: &str = "\u{2026}";
+const ELLIPSIS_CHARACTER: char = '\u{2026}';
+
+/// A run of text with a style of its own.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Span {
+    text: String,
+    size: Option<f32>,
+    color: Option<Color>,
+    family: Option<Family>,
+    weight: Weight,
+    style: Style,
+    underline: bool,
+    strikethrough: bool,
+    highlight: Option<Color>,
+}
+
+impl Span {
+    /// Creates a [`Span`] of the given text, in the style of its paragraph.
+    ///
+    /// [`Span`]: struct.Span.html
+    pub fn new(text: impl Into<String>) -> Self {
+        Span {
+            text: text.into(),
+            size: None,
+            color: None,
+            family: None,
+            weight: Weight::NORMAL,
+            style: Style::Normal,
+            underline: false,
+            strikethrough: false,
+            highlight: None,
+        }
+    }
+
+    /// Sets the size of the [`Span`], overriding the size of its paragraph.
+    ///
+    /// [`Span`]: struct.Span.html
+    pub fn size(mut self, size: f32) -> Self {
+        self.size = Some(size);
+        self
+    }
+
+    /// Sets the color of the [`Span`], overriding the color of its paragraph.
+    ///
+    /// [`Span`]: struct.Span.html
+    pub fn color(mut self, color: Color) -> Self {
+        self.color = Some(color);
+        self
+    }
+
+    /// Sets the family of the [`Span`], overriding the family of its
+    /// paragraph.
+    ///
+    /// [`Span`]: struct.Span.html
+    pub fn family(mut self, family: Family) -> Self {
+        self.family = Some(family);
+        self
+    }
+
+    /// Sets the weight of the [`Span`].
+    ///
+    /// [`Span`]: struct.Span.html
+    pub fn weight(mut self, weight: Weight) -> Self {
+        self.weight = weight;
+        self
+    }
+
+    /// Makes the [`Span`] bold.
+    ///
+    /// [`Span`]: struct.Span.html
+    pub fn bold(self) -> Self {
+        self.weight(Weight::BOLD)
+    }
+
+    /// Makes the [`Span`] italic.
+    ///
+    /// [`Span`]: struct.Span.html
+    pub fn italic(mut self) -> Self {
+        self.style = Style::Italic;
+        self
+    }
+
+    /// Draws a line under the [`Span`].
+    ///
+    /// [`Span`]: struct.Span.html
+    pub fn underline(mut self) -> Self {
+        self.underline = true;
+        self
+    }
+
+    /// Draws a line through the [`Span`].
+    ///
+    /// [`Span`]: struct.Span.html
+    pub fn strikethrough(mut self) -> Self {
+        self.strikethrough = true;
+        self
+    }
+
+    /// Fills the lines behind the [`Span`] with the given color.
+    ///
+    /// [`Span`]: struct.Span.html
+    pub fn highlight(mut self, color: Color) -> Self {
+        self.highlight = Some(color);
+        self
+    }
+}
+
+/// The height of the lines of a [`Paragraph`].
+///
+/// [`Paragraph`]: struct.Paragraph.html
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LineHeight {
+    /// A multiple of the natural height of a line, which fits the largest
+    /// text in it.
+    Relative(f32),
+
+    /// A height in logical pixels, whatever the size of the text.
+    Absolute(f32),
+}
+
+impl Default for LineHeight {
+    fn default() -> LineHeight {
+        LineHeight::Relative(1.0)
+    }
+}
+
+/// A paragraph of styled text.
+///
+/// Lines are broken at line breaks and, if the [`Paragraph`] has a width,
+/// between words that do not fit in it. A word wider than the paragraph is
+/// broken between characters.
+///
+/// [`Paragraph`]: struct.Paragraph.html
+#[derive(Debug, Clone, PartialEq)]
+pub struct Paragraph {
+    spans: Vec<Span>,
+    size: f32,
+    color: Color,
+    family: Family,
+    fonts: FontDatabase,
+    line_height: LineHeight,
+    width: Option<f32>,
+    max_lines: Option<usize>,
+    alignment: Option<HorizontalAlignment>,
+}
+
+impl Paragraph {
+    /// The default size of text, in logical pixels.
+    pub const DEFAULT_SIZE: f32 = 16.0;
+
+    /// Creates an empty [`Paragraph`].
+    ///
+    /// [`Paragraph`]: struct.Paragraph.html
+    pub fn new() -> Self {
+        Paragraph {
+            spans: Vec::new(),
+            size: Self::DEFAULT_SIZE,
+            color: Color::BLACK,
+            family: Family::SansSerif,
+            fonts: FontDatabase::shared(),
+            line_height: LineHeight::default(),
+            width: None,
+            max_lines: None,
+            alignment: None,
+        }
+    }
+
+    /// Adds a [`Span`] at the end of the [`Paragraph`].
+    ///
+    /// [`Span`]: struct.Span.html
+    /// [`Paragraph`]: struct.Paragraph.html
+    pub fn push(mut self, span: Span) -> Self {
+        self.spans.push(span);
+        self
+    }
+
+    /// Sets the size of the spans of the [`Paragraph`] without a size.
+    ///
+    /// [`Paragraph`]: struct.Paragraph.html
+    pub fn size(mut self, size: f32) -> Self {
+        self.size = size;
+        self
+    }
+
+    /// Sets the color of the spans of the [`Paragraph`] without a color.
+    ///
+    /// [`Paragraph`]: struct.Paragraph.html
+    pub fn color(mut self, color: Color) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// Sets the family of the spans of the [`Paragraph`] without a family.
+    ///
+    /// [`Paragraph`]: struct.Paragraph.html
+    pub fn family(mut self, family: Family) -> Self {
+        self.family = family;
+        self
+    }
+
+    /// Sets the [`FontDatabase`] the faces of the [`Paragraph`] are taken
+    /// from, instead of the shared one.
+    ///
+    /// [`FontDatabase`]: ../font/struct.FontDatabase.html
+    /// [`Paragraph`]: struct.Paragraph.html
+    pub fn fonts(mut self, fonts: FontDatabase) -> Self {
+        self.fonts = fonts;
+        self
+    }
+
+    /// Sets the [`LineHeight`] of the [`Paragraph`].
+    ///
+    /// [`LineHeight`]: enum.LineHeight.html
+    /// [`Paragraph`]: struct.Paragraph.html
+    pub fn line_height(mut self, line_height: LineHeight) -> Self {
+        self.line_height = line_height;
+        self
+    }
+
+    /// Sets the width the lines of the [`Paragraph`] are wrapped to.
+    ///
+    /// [`Paragraph`]: struct.Paragraph.html
+    pub fn width(mut self, width: f32) -> Self {
+        self.width = Some(width);
+        self
+    }
+
+    /// Sets the maximum number of lines of the [`Paragraph`].
+    ///
+    /// The last line of a longer paragraph ends with an ellipsis.
+    ///
+    /// [`Paragraph`]: struct.Paragraph.html
+    pub fn max_lines(mut self, max_lines: usize) -> Self {
+        self.max_lines = Some(max_lines);
+        self
+    }
+
+    /// Sets the alignment of the lines of the [`Paragraph`].
+    ///
+    /// By default, lines start on the side of the direction of their text:
+    /// on the left for English, on the right for Arabic.
+    ///
+    /// [`Paragraph`]: struct.Paragraph.html
+    pub fn alignment(mut self, alignment: HorizontalAlignment) -> Self {
+        self.alignment = Some(alignment);
+        self
+    }
+
+    /// Returns the text of the [`Paragraph`], with its spans joined.
+    ///
+    /// The character indices of a [`Layout`] are byte indices in this text.
+    ///
+    /// [`Paragraph`]: struct.Paragraph.html
+    /// [`Layout`]: struct.Layout.html
+    pub fn content(&self) -> String {
+        self.spans.iter().map(|span| span.text.as_str()).collect()
+    }
+
+    /// Lays out the [`Paragraph`].
+    ///
+    /// [`Paragraph`]: struct.Paragraph.html
+    pub fn layout(&self) -> Layout {
+        let content = self.content();
+        let styles: Vec<SpanStyle> = self.spans.iter().map(|span| self.style(span)).collect();
+        let ranges = span_ranges(&self.spans);
+
+        let mut lines = Vec::new();
+        let bidi = BidiInfo::new(&content, None);
+
+        for paragraph in &bidi.paragraphs {
+            let end = if content[..paragraph.range.end].ends_with('\n') {
+                paragraph.range.end - 1
+            } else {
+                paragraph.range.end
+            };
+
+            let range = paragraph.range.start..end;
+            let mut clusters = Vec::new();
+
+            for run in self.runs(&content, range.clone(), &bidi.levels, &ranges, &styles) {
+                let size = styles[run.span].size;
+
+                clusters.extend(shape(&content, run, size));
+            }
+
+            lines.extend(wrap(clusters, self.width).into_iter().map(|clusters| {
+                LogicalLine::new(clusters, range.start, paragraph.level)
+            }));
+        }
+
+        // Like an editor, an empty last line follows a final line break
+        if content.is_empty() || content.ends_with('\n') {
+            let level = bidi
+                .paragraphs
+                .last()
+                .map(|paragraph| paragraph.level)
+                .unwrap_or_else(Level::ltr);
+
+            lines.push(LogicalLine::new(Vec::new(), content.len(), level));
+        }
+
+        let truncated = match self.max_lines {
+            Some(max_lines) if lines.len() > max_lines => {
+                lines.truncate(max_lines);
+
+                if let Some(line) = lines.last_mut() {
+                    let span = span_at(&ranges, line.range.end.saturating_sub(1));
+                    let style = &styles[span];
+                    let face = if style.face.has_glyph(ELLIPSIS_CHARACTER) {
+                        Some(style.face.clone())
+                    } else {
+                        self.fonts.fallback(&style.query, ELLIPSIS_CHARACTER)
+                    };
+
+                    line.ellipsize(
+                        Run {
+                            range: 0..ELLIPSIS.len(),
+                            level: line.level,
+                            span,
+                            face: face.unwrap_or_else(|| style.face.clone()),
+                        },
+                        style.size,
+                        self.width,
+                    );
+                }
+
+                true
+            }
+            _ => false,
+        };
+
+        self.place(&content, &styles, &ranges, lines, truncated)
+    }
+
+    /// Splits a range of text in runs of the same embedding level, span and
+    /// face.
+    fn runs(
+        &self,
+        content: &str,
+        range: Range<usize>,
+        levels: &[Level],
+        ranges: &[Range<usize>],
+        styles: &[SpanStyle],
+    ) -> Vec<Run> {
+        let mut runs: Vec<Run> = Vec::new();
+
+        for (index, character) in content[range.clone()].char_indices() {
+            let index = range.start + index;
+            let end = index + character.len_utf8();
+            let (level, span) = (levels[index], span_at(ranges, index));
+            let style = &styles[span];
+
+            let previous = runs
+                .last()
+                .filter(|run| run.span == span)
+                .map(|run| &run.face);
+
+            let face = match previous {
+                Some(previous) if joins(character) => previous.clone(),
+                _ if style.face.has_glyph(character) || character.is_whitespace() => {
+                    style.face.clone()
+                }
+                Some(previous) if previous.has_glyph(character) => previous.clone(),
+                _ => self
+                    .fonts
+                    .fallback(&style.query, character)
+                    .unwrap_or_else(|| style.face.clone()),
+            };
+
+            match runs.last_mut() {
+                Some(run) if run.level == level && run.span == span && run.face == face => {
+                    run.range.end = end;
+                }
+                _ => runs.push(Run {
+                    range: index..end,
+                    level,
+                    span,
+                    face,
+                }),
+            }
+        }
+
+        runs
+    }
+
+    fn style(&self, span: &Span) -> SpanStyle {
+        let query = Query {
+            families: vec![span.family.clone().unwrap_or_else(|| self.family.clone())],
+            weight: span.weight,
+            style: span.style,
+        };
+
+        SpanStyle {
+            face: self.fonts.query(&query),
+            query,
+            size: span.size.unwrap_or(self.size),
+            color: span.color.unwrap_or(self.color),
+            underline: span.underline,
+            strikethrough: span.strikethrough,
+            highlight: span.highlight,
+        }
+    }
+
+    /// Places the glyphs of the lines in visual order, one line below the
+    /// other.
+    fn place(
+        &self,
+        content: &str,
+        styles: &[SpanStyle],
+        ranges: &[Range<usize>],
+        lines: Vec<LogicalLine>,
+        truncated: bool,
+    ) -> Layout {
+        let visible: Vec<f32> = lines.iter().map(LogicalLine::visible_advance).collect();
+        let width = self
+            .width
+            .unwrap_or_else(|| visible.iter().cloned().fold(0.0, f32::max));
+
+        // Empty paragraphs still have the height of a line
+        let primary = self.fonts.query(&Query {
+            families: vec![self.family.clone()],
+            ..Query::default()
+        });
+
+        let mut layout = Layout {
+            size: Size::new(visible.iter().cloned().fold(0.0, f32::max), 0.0),
+            lines: Vec::with_capacity(lines.len()),
+            glyphs: Vec::new(),
+            decorations: Vec::new(),
+            truncated,
+        };
+
+        for (line, visible) in lines.into_iter().zip(visible) {
+            let text: Vec<(&Face, f32)> = if line.clusters.is_empty() {
+                match ranges.len() {
+                    0 => vec![(&primary, self.size)],
+                    _ => {
+                        let style = &styles[span_at(ranges, line.range.start)];
+
+                        vec![(&style.face, style.size)]
+                    }
+                }
+            } else {
+                line.clusters
+                    .iter()
+                    .map(|cluster| (&*cluster.face, styles[cluster.span].size))
+                    .collect()
+            };
+
+            let metrics = Metrics::of(&text);
+            let top = layout.size.height;
+            let height = match self.line_height {
+                LineHeight::Relative(factor) => metrics.height * factor,
+                LineHeight::Absolute(height) => height,
+            };
+            let baseline = top + (height - (metrics.ascent - metrics.descent)) / 2.0 + metrics.ascent;
+
+            let alignment = self.alignment.unwrap_or(if line.level.is_rtl() {
+                HorizontalAlignment::Right
+            } else {
+                HorizontalAlignment::Left
+            });
+
+            let start = match alignment {
+                HorizontalAlignment::Left => 0.0,
+                HorizontalAlignment::Center => (width - visible) / 2.0,
+                HorizontalAlignment::Right => width - visible,
+            };
+
+            // Trailing whitespace hangs past the end of the line, which is on
+            // the left of a right-to-left line
+            let hanging = line.hanging();
+            let mut x = if line.level.is_rtl() {
+                start - line.advance(hanging.clone())
+            } else {
+                start
+            };
+
+            let mut carets = Vec::new();
+
+            if line.clusters.is_empty() {
+                carets.push((x, line.range.start));
+            }
+
+            for index in line.visual_order() {
+                let cluster = &line.clusters[index];
+                let style = &styles[cluster.span];
+
+                for glyph in &cluster.glyphs {
+                    layout.glyphs.push(Glyph {
+                        face: cluster.face.clone(),
+                        id: glyph.id,
+                        position: Point::new(x + glyph.x, baseline - glyph.y),
+                        size: style.size,
+                        color: style.color,
+                        weight: style.query.weight,
+                        style: style.query.style,
+                    });
+                }
+
+                if !hanging.contains(&index) {
+                    let bounds = Rectangle {
+                        x,
+                        y: top,
+                        width: cluster.advance,
+                        height,
+                    };
+
+                    for decoration in style.decorations(bounds, baseline) {
+                        layout.decorate(decoration);
+                    }
+                }
+
+                cluster.carets(content, x, &mut carets);
+                x += cluster.advance;
+            }
+
+            layout.lines.push(Line {
+                range: line.range,
+                bounds: Rectangle {
+                    x: start,
+                    y: top,
+                    width: visible,
+                    height,
+                },
+                baseline,
+                carets,
+            });
+
+            layout.size.height += height;
+        }
+
+        layout
+    }
+}
+
+impl Default for Paragraph {
+    fn default() -> Paragraph {
+        Paragraph::new()
+    }
+}
+
+/// The glyphs and decorations of a laid out [`Paragraph`], relative to its
+/// top-left corner.
+///
+/// [`Paragraph`]: struct.Paragraph.html
+#[derive(Debug, Clone, PartialEq)]
+pub struct Layout {
+    size: Size,
+    lines: Vec<Line>,
+    glyphs: Vec<Glyph>,
+    decorations: Vec<Decoration>,
+    truncated: bool,
+}
+
+impl Layout {
+    /// Returns the size of the [`Layout`], from its widest line, without
+    /// trailing whitespace, to the bottom of its last line.
+    ///
+    /// [`Layout`]: struct.Layout.html
+    pub fn size(&self) -> Size {
+        self.size
+    }
+
+    /// Returns the lines of the [`Layout`], from top to bottom.
+    ///
+    /// [`Layout`]: struct.Layout.html
+    pub fn lines(&self) -> &[Line] {
+        &self.lines
+    }
+
+    /// Returns the glyphs to draw, in visual order.
+    pub fn glyphs(&self) -> &[Glyph] {
+        &self.glyphs
+    }
+
+    /// Returns the decorations to draw behind the glyphs.
+    pub fn decorations(&self) -> &[Decoration] {
+        &self.decorations
+    }
+
+    /// Returns whether the paragraph has more lines than its maximum.
+    pub fn is_truncated(&self) -> bool {
+        self.truncated
+    }
+
+    /// Returns the byte index of the character boundary closest to the given
+    /// point.
+    ///
+    /// Points above or below the [`Layout`] hit its first or last line.
+    ///
+    /// [`Layout`]: struct.Layout.html
+    pub fn hit_test(&self, point: Point) -> usize {
+        let line = match self
+            .lines
+            .iter()
+            .find(|line| point.y < line.bounds.y + line.bounds.height)
+            .or_else(|| self.lines.last())
+        {
+            Some(line) => line,
+            None => return 0,
+        };
+
+        let mut closest = (line.range.start, f32::INFINITY);
+
+        for &(x, index) in &line.carets {
+            let distance = (x - point.x).abs();
+
+            if distance < closest.1 {
+                closest = (index, distance);
+            }
+        }
+
+        closest.0
+    }
+
+    /// Returns the position of a caret before the character at the given byte
+    /// index, at the top of its line.
+    ///
+    /// An index between two wrapped lines is at the start of the second one.
+    pub fn position(&self, index: usize) -> Point {
+        let line = match self
+            .lines
+            .iter()
+            .rev()
+            .find(|line| line.range.start <= index)
+            .or_else(|| self.lines.first())
+        {
+            Some(line) => line,
+            None => return Point::new(0.0, 0.0),
+        };
+
+        // Indices inside a ligature or cut by an ellipsis go to the closest
+        // boundary there is
+        let x = line
+            .carets
+            .iter()
+            .min_by_key(|(_, caret)| (*caret as isize - index as isize).abs())
+            .map(|(x, _)| *x)
+            .unwrap_or(line.bounds.x);
+
+        Point::new(x, line.bounds.y)
+    }
+
+    /// Returns how far glyphs may go past the lines they are laid out in.
+    pub(crate) fn overflow(&self) -> f32 {
+        self.glyphs.iter().fold(0.0, |overflow, glyph| overflow.max(glyph.size / 2.0))
+    }
+
+    /// Adds a [`Decoration`], merging it with the previous one if they are
+    /// next to each other.
+    ///
+    /// [`Decoration`]: struct.Decoration.html
+    fn decorate(&mut self, decoration: Decoration) {
+        if let Some(last) = self.decorations.iter_mut().rev().find(|last| {
+            last.kind == decoration.kind
+                && last.color == decoration.color
+                && last.bounds.y == decoration.bounds.y
+                && last.bounds.height == decoration.bounds.height
+                && (last.bounds.x + last.bounds.width - decoration.bounds.x).abs() < 0.01
+        }) {
+            last.bounds.width += decoration.bounds.width;
+        } else {
+            self.decorations.push(decoration);
+        }
+    }
+}
+
+/// A line of a [`Layout`].
+///
+/// [`Layout`]: struct.Layout.html
+#[derive(Debug, Clone, PartialEq)]
+pub struct Line {
+    range: Range<usize>,
+    bounds: Rectangle,
+    baseline: f32,
+    /// The horizontal positions of the character boundaries, in visual order.
+    carets: Vec<(f32, usize)>,
+}
+
+impl Line {
+    /// Returns the byte range of the text of the [`Line`].
+    ///
+    /// [`Line`]: struct.Line.html
+    pub fn range(&self) -> Range<usize> {
+        self.range.clone()
+    }
+
+    /// Returns the bounds of the [`Line`], without trailing whitespace.
+    ///
+    /// [`Line`]: struct.Line.html
+    pub fn bounds(&self) -> Rectangle {
+        self.bounds
+    }
+
+    /// Returns the vertical position of the baseline of the [`Line`].
+    ///
+    /// [`Line`]: struct.Line.html
+    pub fn baseline(&self) -> f32 {
+        self.baseline
+    }
+}
+
+/// A glyph of a [`Layout`].
+///
+/// Glyphs are taken from the face closest to their weight and style. Bold
+/// glyphs of a lighter face are drawn thicker, but italic glyphs of an
+/// upright face are drawn upright.
+///
+/// [`Layout`]: struct.Layout.html
+#[derive(Debug, Clone, PartialEq)]
+pub struct Glyph {
+    /// The face of the glyph.
+    pub face: Arc<Face>,
+
+    /// The identifier of the glyph in its face.
+    pub id: u32,
+
+    /// The position of the origin of the glyph, on the baseline.
+    pub position: Point,
+
+    /// The size of the glyph.
+    pub size: f32,
+
+    /// The color of the glyph.
+    pub color: Color,
+
+    /// The weight of the text of the glyph.
+    pub weight: Weight,
+
+    /// The style of the text of the glyph.
+    pub style: Style,
+}
+
+/// A rectangle drawn with some text.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Decoration {
+    /// The bounds of the decoration.
+    pub bounds: Rectangle,
+
+    /// The color of the decoration.
+    pub color: Color,
+
+    /// What the decoration is for.
+    pub kind: DecorationKind,
+}
+
+/// What a [`Decoration`] is for.
+///
+/// [`Decoration`]: struct.Decoration.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DecorationKind {
+    /// The background of some highlighted text.
+    Highlight,
+
+    /// A line under some text.
+    Underline,
+
+    /// A line through some text.
+    Strikethrough,
+}
+
+/// The style of a span, with the defaults of its paragraph applied.
+#[derive(Debug, Clone)]
+struct SpanStyle {
+    /// The face closest to the query of the span.
+    face: Arc<Face>,
+    query: Query,
+    size: f32,
+    color: Color,
+    underline: bool,
+    strikethrough: bool,
+    highlight: Option<Color>,
+}
+
+impl SpanStyle {
+    /// Returns the decorations of some text in the given bounds of a line.
+    fn decorations(&self, bounds: Rectangle, baseline: f32) -> Vec<Decoration> {
+        let thickness = (self.size / 14.0).max(1.0);
+        let line = |y: f32, kind| Decoration {
+            bounds: Rectangle {
+                x: bounds.x,
+                y: y - thickness / 2.0,
+                width: bounds.width,
+                height: thickness,
+            },
+            color: self.color,
+            kind,
+        };
+
+        let mut decorations = Vec::new();
+
+        if let Some(color) = self.highlight {
+            decorations.push(Decoration {
+                bounds,
+                color,
+                kind: DecorationKind::Highlight,
+            });
+        }
+
+        if self.underline {
+            decorations.push(line(baseline + self.size * 0.1, DecorationKind::Underline));
+        }
+
+        if self.strikethrough {
+            decorations.push(line(baseline - self.size * 0.3, DecorationKind::Strikethrough));
+        }
+
+        decorations
+    }
+}
+
+/// The vertical metrics of a line, in logical pixels.
+struct Metrics {
+    ascent: f32,
+    descent: f32,
+    height: f32,
+}
+
+impl Metrics {
+    /// Returns the metrics of a line fitting text of the given faces and
+    /// sizes.
+    fn of(text: &[(&Face, f32)]) -> Metrics {
+        let empty = Metrics {
+            ascent: 0.0,
+            descent: 0.0,
+            height: 0.0,
+        };
+
+        text.iter().fold(empty, |metrics, (face, size)| {
+            let v_metrics = match face.font() {
+                Some(font) => font.v_metrics(Scale::uniform(*size)),
+                None => return metrics,
+            };
+
+            Metrics {
+                ascent: metrics.ascent.max(v_metrics.ascent),
+                descent: metrics.descent.min(v_metrics.descent),
+                height: metrics
+                    .height
+                    .max((v_metrics.ascent - v_metrics.descent + v_metrics.line_gap).ceil()),
+            }
+        })
+    }
+}
+
+/// A group of glyphs shaped from characters that cannot be split, like a
+/// letter and its accents or a ligature.
+#[derive(Debug, Clone)]
+struct Cluster {
+    range: Range<usize>,
+    span: usize,
+    level: Level,
+    face: Arc<Face>,
+    advance: f32,
+    glyphs: Vec<Shaped>,
+    is_whitespace: bool,
+}
+
+impl Cluster {
+    /// Adds the positions of the character boundaries of the [`Cluster`],
+    /// laid out from the given horizontal position.
+    ///
+    /// The glyphs of a ligature are shared by its characters, which get an
+    /// equal part of its advance.
+    ///
+    /// [`Cluster`]: struct.Cluster.html
+    fn carets(&self, content: &str, x: f32, carets: &mut Vec<(f32, usize)>) {
+        let mut boundaries: Vec<usize> = content[self.range.clone()]
+            .char_indices()
+            .map(|(index, _)| self.range.start + index)
+            .collect();
+        boundaries.push(self.range.end);
+
+        if self.level.is_rtl() {
+            boundaries.reverse();
+        }
+
+        let parts = (boundaries.len() - 1).max(1) as f32;
+
+        for (i, &index) in boundaries.iter().enumerate() {
+            carets.push((x + self.advance * (i as f32 / parts).min(1.0), index));
+        }
+    }
+}
+
+/// A run of text shaped with the same face, in the same direction.
+#[derive(Debug, Clone)]
+struct Run {
+    range: Range<usize>,
+    level: Level,
+    span: usize,
+    face: Arc<Face>,
+}
+
+/// A glyph positioned relative to the left of its cluster, on the baseline.
+#[derive(Debug, Clone, Copy)]
+struct Shaped {
+    id: u32,
+    x: f32,
+    y: f32,
+}
+
+/// A line of clusters, in logical order.
+#[derive(Debug)]
+struct LogicalLine {
+    clusters: Vec<Cluster>,
+    range: Range<usize>,
+    level: Level,
+}
+
+impl LogicalLine {
+    /// Creates a [`LogicalLine`], which starts at the given byte index if it
+    /// is empty.
+    ///
+    /// [`LogicalLine`]: struct.LogicalLine.html
+    fn new(clusters: Vec<Cluster>, start: usize, level: Level) -> LogicalLine {
+        let range = match (clusters.first(), clusters.last()) {
+            (Some(first), Some(last)) => first.range.start..last.range.end,
+            _ => start..start,
+        };
+
+        LogicalLine {
+            clusters,
+            range,
+            level,
+        }
+    }
+
+    /// Returns the indices of the whitespace clusters at the end of the
+    /// line.
+    fn hanging(&self) -> Range<usize> {
+        let visible = self
+            .clusters
+            .iter()
+            .rposition(|cluster| !cluster.is_whitespace)
+            .map(|index| index + 1)
+            .unwrap_or(0);
+
+        visible..self.clusters.len()
+    }
+
+    fn advance(&self, clusters: Range<usize>) -> f32 {
+        self.clusters[clusters].iter().map(|cluster| cluster.advance).sum()
+    }
+
+    /// Returns the advance of the line, without trailing whitespace.
+    fn visible_advance(&self) -> f32 {
+        self.advance(0..self.hanging().start)
+    }
+
+    /// Replaces the end of the line with an ellipsis, removing as many
+    /// clusters as needed for it to fit in the given width.
+    fn ellipsize(&mut self, run: Run, size: f32, width: Option<f32>) {
+        let ellipsis = shape(ELLIPSIS, run, size);
+        let advance: f32 = ellipsis.iter().map(|cluster| cluster.advance).sum();
+
+        self.clusters.truncate(self.hanging().start);
+
+        if let Some(width) = width {
+            while !self.clusters.is_empty() && self.visible_advance() + advance > width {
+                self.clusters.pop();
+                self.clusters.truncate(self.hanging().start);
+            }
+        }
+
+        let end = self
+            .clusters
+            .last()
+            .map(|cluster| cluster.range.end)
+            .unwrap_or(self.range.start);
+
+        self.range.end = end;
+
+        // The ellipsis stands for no character of the text
+        self.clusters.extend(ellipsis.into_iter().map(|cluster| Cluster {
+            range: end..end,
+            is_whitespace: false,
+            ..cluster
+        }));
+    }
+
+    /// Returns the indices of the clusters from left to right, following
+    /// rules L1 and L2 of the Unicode Bidirectional Algorithm.
+    fn visual_order(&self) -> Vec<usize> {
+        let hanging = self.hanging();
+        let levels: Vec<u8> = self
+            .clusters
+            .iter()
+            .enumerate()
+            .map(|(index, cluster)| {
+                if hanging.contains(&index) {
+                    self.level.number()
+                } else {
+                    cluster.level.number()
+                }
+            })
+            .collect();
+
+        let mut order: Vec<usize> = (0..self.clusters.len()).collect();
+
+        let highest = levels.iter().cloned().max().unwrap_or(0);
+        let lowest_odd = levels.iter().cloned().min().unwrap_or(0) | 1;
+
+        for level in (lowest_odd..=highest).rev() {
+            let mut start = 0;
+
+            while start < order.len() {
+                if levels[order[start]] < level {
+                    start += 1;
+                    continue;
+                }
+
+                let end = (start..order.len())
+                    .find(|&i| levels[order[i]] < level)
+                    .unwrap_or(order.len());
+
+                order[start..end].reverse();
+                start = end;
+            }
+        }
+
+        order
+    }
+}
+
+/// Returns the byte ranges of the spans in the text of their paragraph.
+fn span_ranges(spans: &[Span]) -> Vec<Range<usize>> {
+    let mut start = 0;
+
+    spans
+        .iter()
+        .map(|span| {
+            let range = start..start + span.text.len();
+            start = range.end;
+            range
+        })
+        .collect()
+}
+
+/// Returns the index of the span containing the given byte index.
+fn span_at(ranges: &[Range<usize>], index: usize) -> usize {
+    ranges
+        .iter()
+        .position(|range| index < range.end)
+        .unwrap_or_else(|| ranges.len().saturating_sub(1))
+}
+
+/// Returns whether a character is part of the same glyph as the previous
+/// one, like the joiner in an emoji sequence or a variation selector.
+fn joins(character: char) -> bool {
+    matches!(
+        character,
+        '\u{200C}' | '\u{200D}' | '\u{FE00}'..='\u{FE0F}' | '\u{1F3FB}'..='\u{1F3FF}' | '\u{E0100}'..='\u{E01EF}'
+    )
+}
+
+/// Shapes a run of text of the same direction and size into clusters, in
+/// logical order.
+fn shape(content: &str, run: Run, size: f32) -> Vec<Cluster> {
+    let Run {
+        range,
+        level,
+        span,
+        face,
+    } = run;
+
+    // Faces are loaded when they are picked, so they can be read
+    let (data, font) = match (face.data(), face.font()) {
+        (Ok(data), Some(font)) => (data, font),
+        _ => return Vec::new(),
+    };
+
+    let shaper = match rustybuzz::Face::from_slice(&data, face.index()) {
+        Some(shaper) => shaper,
+        None => return Vec::new(),
+    };
+
+    let v_metrics = font.v_metrics_unscaled();
+    let scale = size / (v_metrics.ascent - v_metrics.descent);
+
+    let mut buffer = rustybuzz::UnicodeBuffer::new();
+    buffer.push_str(&content[range.clone()]);
+    buffer.guess_segment_properties();
+    buffer.set_direction(if level.is_rtl() {
+        rustybuzz::Direction::RightToLeft
+    } else {
+        rustybuzz::Direction::LeftToRight
+    });
+
+    let glyphs = rustybuzz::shape(&shaper, &[], buffer);
+
+    // Glyphs come in visual order, and the glyphs of a cluster together
+    let mut clusters: Vec<Cluster> = Vec::new();
+
+    for (info, position) in glyphs.glyph_infos().iter().zip(glyphs.glyph_positions()) {
+        let start = range.start + info.cluster as usize;
+        let advance = position.x_advance as f32 * scale;
+        let mut glyph = Shaped {
+            id: info.codepoint,
+            x: position.x_offset as f32 * scale,
+            y: position.y_offset as f32 * scale,
+        };
+
+        match clusters.last_mut() {
+            Some(cluster) if cluster.range.start == start => {
+                glyph.x += cluster.advance;
+                cluster.advance += advance;
+                cluster.glyphs.push(glyph);
+            }
+            _ => clusters.push(Cluster {
+                range: start..start,
+                span,
+                level,
+                face: face.clone(),
+                advance,
+                glyphs: vec![glyph],
+                is_whitespace: false,
+            }),
+        }
+    }
+
+    clusters.sort_by_key(|cluster| cluster.range.start);
+
+    if let Some(first) = clusters.first_mut() {
+        first.range.start = range.start;
+    }
+
+    for i in 0..clusters.len() {
+        let end = clusters
+            .get(i + 1)
+            .map(|next| next.range.start)
+            .unwrap_or(range.end);
+
+        let cluster = &mut clusters[i];
+        cluster.range.end = end;
+        cluster.is_whitespace = content[cluster.range.clone()].chars().all(char::is_whitespace);
+    }
+
+    clusters
+}
+
+/// Breaks clusters in lines no wider than the given width, after whitespace
+/// if possible.
+fn wrap(clusters: Vec<Cluster>, width: Option<f32>) -> Vec<Vec<Cluster>> {
+    let mut lines = Vec::new();
+    let mut line: Vec<Cluster> = Vec::new();
+    let mut advance = 0.0;
+    // Where the current line can be broken, after its last whitespace
+    let mut opportunity = None;
+
+    for cluster in clusters {
+        if let Some(width) = width {
+            if !cluster.is_whitespace && !line.is_empty() && advance + cluster.advance > width {
+                let rest = line.split_off(opportunity.unwrap_or(line.len()));
+
+                lines.push(line);
+                advance = rest.iter().map(|cluster| cluster.advance).sum();
+                line = rest;
+                opportunity = None;
+            }
+        }
+
+        advance += cluster.advance;
+
+        if cluster.is_whitespace {
+            opportunity = Some(line.len() + 1);
+        }
+
+        line.push(cluster);
+    }
+
+    lines.push(line);
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::text::{advance, line_height};
+
+    /// Returns the glyph of a character in the default font.
+    fn glyph_id(character: char) -> u32 {
+        let face = FontDatabase::shared().query(&Query::default());
+
+        face.font().unwrap().glyph(character).id().0
+    }
+
+    #[test]
+    fn spans_keep_their_style() {
+        let red = Color::from_rgb(1.0, 0.0, 0.0);
+        let layout = Paragraph::new()
+            .push(Span::new("Hello, "))
+            .push(Span::new("world").size(32.0).color(red).bold().underline())
+            .layout();
+
+        let glyphs = layout.glyphs();
+
+        assert_eq!(glyphs.len(), 12);
+        assert_eq!(glyphs[0].size, 16.0);
+        assert_eq!(glyphs[0].color, Color::BLACK);
+        assert_eq!(glyphs[7].size, 32.0);
+        assert_eq!(glyphs[7].color, red);
+        assert_eq!(glyphs[7].weight, Weight::BOLD);
+
+        // Text of different sizes shares the same baseline
+        assert_eq!(glyphs[0].position.y, glyphs[7].position.y);
+        assert_eq!(layout.lines().len(), 1);
+        assert_eq!(layout.size().height, line_height(32.0));
+
+        let underline = &layout.decorations()[0];
+
+        assert_eq!(layout.decorations().len(), 1);
+        assert_eq!(underline.kind, DecorationKind::Underline);
+        assert!((underline.bounds.x - glyphs[7].position.x).abs() < 0.01);
+        assert!(underline.bounds.y > glyphs[7].position.y);
+    }
+
+    #[test]
+    fn shaping_matches_measuring() {
+        let layout = Paragraph::new().push(Span::new("Hello, world!")).layout();
+
+        assert!((layout.size().width - advance("Hello, world!", 16.0)).abs() < 0.5);
+    }
+
+    #[test]
+    fn lines_wrap_between_words() {
+        let layout = Paragraph::new()
+            .push(Span::new("one two three"))
+            .width(advance("one two", 16.0) + 1.0)
+            .layout();
+
+        let ranges: Vec<_> = layout.lines().iter().map(Line::range).collect();
+
+        assert_eq!(ranges, vec![0..8, 8..13]);
+        assert_eq!(layout.size().height, line_height(16.0) * 2.0);
+        assert!(layout.lines()[0].bounds().width < advance("one two", 16.0) + 1.0);
+
+        let broken = Paragraph::new().push(Span::new("abcdef")).width(1.0).layout();
+
+        assert_eq!(broken.lines().len(), 6);
+    }
+
+    #[test]
+    fn line_breaks_start_lines() {
+        let layout = Paragraph::new().push(Span::new("a\n\nb\n")).layout();
+        let ranges: Vec<_> = layout.lines().iter().map(Line::range).collect();
+
+        assert_eq!(ranges, vec![0..1, 2..2, 3..4, 5..5]);
+        assert_eq!(Paragraph::new().layout().lines().len(), 1);
+    }
+
+    #[test]
+    fn truncated_lines_end_with_an_ellipsis() {
+        let width = advance("one two\u{2026}", 16.0) + 1.0;
+        let layout = Paragraph::new()
+            .push(Span::new("one two three four"))
+            .width(width)
+            .max_lines(1)
+            .layout();
+
+        assert!(layout.is_truncated());
+        assert_eq!(layout.lines().len(), 1);
+        assert_eq!(layout.lines()[0].range(), 0..7);
+        assert!(layout.lines()[0].bounds().width <= width);
+
+        let last = layout.glyphs().last().unwrap();
+        let ellipsis = glyph_id('\u{2026}');
+
+        assert_eq!(last.id, ellipsis);
+        assert!(!Paragraph::new().push(Span::new("one")).max_lines(1).layout().is_truncated());
+
+        // Words that do not fit with the ellipsis are cut between characters
+        let cut = Paragraph::new()
+            .push(Span::new("one two three"))
+            .width(advance("one two", 16.0))
+            .max_lines(1)
+            .layout();
+
+        assert_eq!(cut.lines()[0].range(), 0..5);
+    }
+
+    #[test]
+    fn hit_test_and_position_round_trip() {
+        let content = "Hello, wörld!\nSecond line";
+        let layout = Paragraph::new()
+            .push(Span::new("Hello, "))
+            .push(Span::new("wörld!\nSecond").size(24.0))
+            .push(Span::new(" line"))
+            .layout();
+
+        for (index, _) in content.char_indices() {
+            let position = layout.position(index);
+
+            assert_eq!(layout.hit_test(Point::new(position.x, position.y + 1.0)), index);
+        }
+
+        assert_eq!(layout.position(0), Point::new(0.0, 0.0));
+        assert_eq!(layout.hit_test(Point::new(-10.0, -10.0)), 0);
+        assert_eq!(layout.hit_test(Point::new(1000.0, 1000.0)), content.len());
+        assert_eq!(layout.lines()[1].bounds().y, layout.position(15).y);
+    }
+
+    #[test]
+    fn right_to_left_text_is_reversed() {
+        // "Salam" in Persian, then a Latin word
+        let layout = Paragraph::new()
+            .push(Span::new("سلام abc"))
+            .width(500.0)
+            .layout();
+
+        let line = &layout.lines()[0];
+
+        // A right-to-left paragraph starts on the right
+        assert!((line.bounds().x + line.bounds().width - 500.0).abs() < 0.01);
+
+        // Its first character is on the right of the Latin word on its left
+        let first = layout.position(0);
+        let latin = layout.position("سلام ".len());
+
+        assert!(first.x > latin.x);
+        assert!(layout.position("س".len()).x < first.x);
+        assert_eq!(layout.hit_test(Point::new(first.x + 5.0, 1.0)), 0);
+    }
+
+    #[test]
+    fn arabic_letters_are_joined() {
+        let isolated = glyph_id('\u{0644}');
+        let layout = Paragraph::new().push(Span::new("\u{0644}\u{0644}\u{0644}")).layout();
+
+        // Every lam takes a contextual form, none of them the isolated one
+        assert_eq!(layout.glyphs().len(), 3);
+        assert!(layout.glyphs().iter().all(|glyph| glyph.id != isolated));
+    }
+
+    #[test]
+    fn line_height_can_be_absolute() {
+        let layout = Paragraph::new()
+            .push(Span::new("a\nb"))
+            .line_height(LineHeight::Absolute(40.0))
+            .layout();
+
+        assert_eq!(layout.size().height, 80.0);
+        assert_eq!(layout.lines()[1].bounds().y, 40.0);
+
+        let relative = Paragraph::new()
+            .push(Span::new("a").highlight(Color::WHITE))
+            .line_height(LineHeight::Relative(2.0))
+            .layout();
+
+        assert_eq!(relative.size().height, line_height(16.0) * 2.0);
+        assert_eq!(relative.decorations()[0].bounds.height, relative.size().height);
+    }
+}
@@ -1,3 +1,4 @@
+use crate::core::point::Point;
 use glam::{Mat4, Vec3, Vec4};
 use std::ops::Mul;
 
@@ -11,12 +12,15 @@ impl Transformation {
         Transformation(Mat4::identity())
     }
 
-    /// Creates an orthographic projection.
+    /// Creates an orthographic projection of an area of the given size.
+    ///
+    /// The size may be fractional, like the size of a window in logical
+    /// pixels.
     #[rustfmt::skip]
-    pub fn orthographic(width: u16, height: u16) -> Transformation {
+    pub fn orthographic(width: f32, height: f32) -> Transformation {
         Transformation(Mat4::from_cols(
-            Vec4::new(2.0 / f32::from(width), 0.0, 0.0, 0.0),
-            Vec4::new(0.0, 2.0 / f32::from(height), 0.0, 0.0),
+            Vec4::new(2.0 / width, 0.0, 0.0, 0.0),
+            Vec4::new(0.0, 2.0 / height, 0.0, 0.0),
             Vec4::new(0.0, 0.0, -1.0, 0.0),
             Vec4::new(-1.0, -1.0, 0.0, 1.0)
         ))
@@ -31,6 +35,20 @@ impl Transformation {
     pub fn scale(x: f32, y: f32) -> Transformation {
         Transformation(Mat4::from_scale(Vec3::new(x, y, 1.0)))
     }
+
+    /// Creates a rotation around the origin, by the given angle in radians.
+    pub fn rotate(angle: f32) -> Transformation {
+        Transformation(Mat4::from_rotation_z(angle))
+    }
+
+    /// Applies the transformation to a [`Point`].
+    ///
+    /// [`Point`]: ../core/point/struct.Point.html
+    pub fn transform_point(&self, point: Point) -> Point {
+        let transformed = self.0 * Vec4::new(point.x, point.y, 0.0, 1.0);
+
+        Point::new(transformed.x(), transformed.y())
+    }
 }
 
 impl Mul for Transformation {
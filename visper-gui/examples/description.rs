@@ -0,0 +1,122 @@
+use ron::ser::PrettyConfig;
+use serde::de::DeserializeOwned;
+use std::path::{Path, PathBuf};
+use visper_gui::description::{Bindings, Node, Snapshot};
+use visper_gui::widget::{Column, Text};
+use visper_graphics::core::size::Size;
+use visper_gui::reload::Reload;
+use visper_gui::widget::Container;
+use visper_gui::{Application, Command, Element, Length, Theme, UiTree};
+
+fn main() {
+    Described::run()
+}
+
+const SCREEN: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/examples/description/screen.ron");
+const THEME: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/examples/description/theme.ron");
+
+struct Described {
+    screen: Node,
+    theme: Theme,
+    count: usize,
+}
+
+fn load<T: DeserializeOwned>(path: &str) -> Result<T, String> {
+    let source = std::fs::read_to_string(path).map_err(|error| error.to_string())?;
+
+    ron::de::from_str(&source).map_err(|error| error.to_string())
+}
+
+#[derive(Debug, Clone)]
+enum Message {
+    Increment,
+    Dump,
+}
+
+impl Application for Described {
+    type Message = Message;
+
+    fn new() -> Self {
+        Described {
+            screen: load(SCREEN).expect("Load screen"),
+            theme: load(THEME).expect("Load theme"),
+            count: 0,
+        }
+    }
+
+    fn title(&self) -> String {
+        String::from("Description")
+    }
+
+    fn update(&mut self, message: Message) -> Command {
+        match message {
+            Message::Increment => self.count += 1,
+            Message::Dump => {
+                let mut tree = UiTree::new(self.view());
+                tree.layout(Size::new(800.0, 600.0));
+
+                let snapshot = Snapshot::new(&tree);
+
+                println!(
+                    "{}",
+                    ron::ser::to_string_pretty(&snapshot, PrettyConfig::default())
+                        .expect("Serialize snapshot")
+                );
+            }
+        }
+
+        Command::None
+    }
+
+    fn theme(&self) -> Theme {
+        self.theme
+    }
+
+    fn watch(&self) -> Vec<PathBuf> {
+        vec![PathBuf::from(SCREEN), PathBuf::from(THEME)]
+    }
+
+    fn reload(&mut self, path: &Path) -> Reload {
+        // Keep the last valid version while a file is being edited
+        if path == Path::new(SCREEN) {
+            match load(SCREEN) {
+                Ok(screen) => {
+                    self.screen = screen;
+                    Reload::Node(String::from("screen"))
+                }
+                Err(error) => {
+                    eprintln!("Invalid screen: {}", error);
+                    Reload::Nothing
+                }
+            }
+        } else {
+            match load(THEME) {
+                Ok(theme) => {
+                    self.theme = theme;
+                    Reload::Theme
+                }
+                Err(error) => {
+                    eprintln!("Invalid theme: {}", error);
+                    Reload::Nothing
+                }
+            }
+        }
+    }
+
+    fn view(&mut self) -> Element<Message> {
+        let bindings = Bindings::new()
+            .bind("increment", Message::Increment)
+            .bind("dump", Message::Dump);
+
+        let screen = match self.screen.build(&bindings) {
+            Ok(screen) => screen,
+            Err(error) => Text::new(error.to_string()).into(),
+        };
+
+        Column::new()
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .append(Container::new().width(Length::Fill).append(screen).key("screen"))
+            .append(Text::new(format!("Count: {}", self.count)))
+    }
+}
@@ -1,72 +1,124 @@
-use winit::{
-    event::Event::{ WindowEvent, RedrawRequested },
-    event_loop::{ControlFlow, EventLoop},
-};
-use visper_graphics::renderer::Renderer;
-use visper_graphics::renderer::target::Target;
-use std::future::Future;
-use visper_gui::proxy::Proxy;
+use visper_gui::widget::{Button, Checkbox, Column, Container, Radio, Row, Scrollable, Slider, Text, TextInput, Toggle};
+use visper_gui::window;
+use visper_gui::{Application, Command, Element, Length};
 
 fn main() {
-    let event_loop = EventLoop::<UiEvent>::with_user_event();
-    let ep = event_loop.create_proxy();
-    let p = Proxy::new(ep);
-    let mut external_messages = Vec::new();
+    Controls::run()
+}
 
-    let window = winit::window::Window::new(&event_loop).unwrap();
-//    let window = winit::window::WindowBuilder::new().build(&event_loop).expect("error window");
-    let size = window.inner_size();
+#[derive(Debug, Default)]
+struct Controls {
+    presses: usize,
+    is_checked: bool,
+    choice: Option<Choice>,
+    is_on: bool,
+    value: f32,
+    name: String,
+    panel: Option<window::Id>,
+}
 
-    let mut renderer = Renderer::new();
-    let mut target = Target::new(renderer.device(), &window, size.width as u16, size.height as u16, window.scale_factor());
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Choice {
+    Left,
+    Right,
+}
 
-    let mut events = Vec::new();
-    window.request_redraw();
+#[derive(Debug, Clone)]
+enum Message {
+    Pressed,
+    Checked(bool),
+    Chose(Choice),
+    Toggled(bool),
+    Changed(f32),
+    NameChanged(String),
+    OpenPanel,
+    ClosePanel,
+}
 
-    event_loop.run(move |event, _, control_flow| match event {
-        winit::event::Event::MainEventsCleared => {
-            events.iter().for_each(| event | {
-                match event {
-                    UiEvent::Resized(w, h) => {
-                        println!("kir");
-                        target = Target::new(renderer.device(), &window, w.to_owned(), h.to_owned(), window.scale_factor());
-                        renderer.draw(&mut target);
-                    }
-                }
+impl Application for Controls {
+    type Message = Message;
 
-            });
+    fn new() -> Self {
+        Controls::default()
+    }
 
-        },
-        WindowEvent {
-            event: winit::event::WindowEvent::CloseRequested,
-            ..
-        } => *control_flow = winit::event_loop::ControlFlow::Exit,
+    fn title(&self) -> String {
+        String::from("Controls")
+    }
 
-        WindowEvent {
-            event: winit::event::WindowEvent::Resized(physicalSize),
-            ..
-        } => {
-//            events.push(winit::event::WindowEvent::Resized(physicalSize));
-            events.push(UiEvent::Resized(physicalSize.width as u16, physicalSize.height as u16));
-//            target = Target::new(renderer.device(), &window, physicalSize.width as u16, physicalSize.height as u16, window.scale_factor());
-//            renderer.draw(&mut target)
-//            window.request_redraw();
-        },
-        winit::event::Event::UserEvent(message) => {
-            external_messages.push(message);
-        }
+    fn update(&mut self, message: Message) -> Command {
+        match message {
+            Message::Pressed => self.presses += 1,
+            Message::Checked(is_checked) => self.is_checked = is_checked,
+            Message::Chose(choice) => self.choice = Some(choice),
+            Message::Toggled(is_on) => self.is_on = is_on,
+            Message::Changed(value) => self.value = value,
+            Message::NameChanged(name) => self.name = name,
+            Message::OpenPanel => {
+                if self.panel.is_none() {
+                    let (id, command) = Command::open_window(window::Settings {
+                        title: String::from("Panel"),
+                        size: (240, 120),
+                        ..window::Settings::default()
+                    });
 
-        RedrawRequested(_) => {
-            renderer.draw(&mut target)
+                    self.panel = Some(id);
+                    return command;
+                }
+            }
+            Message::ClosePanel => {
+                if let Some(id) = self.panel.take() {
+                    return Command::CloseWindow(id);
+                }
+            }
         }
 
-        _ => {
-            *control_flow = winit::event_loop::ControlFlow::Wait;
+        Command::None
+    }
+
+    fn close_requested(&mut self, window: window::Id) -> Command {
+        if self.panel == Some(window) {
+            self.panel = None;
         }
-    })
-}
 
-#[derive(Debug, Clone, Copy)]
-pub enum UiEvent {
-    Resized(u16, u16)
-}
\ No newline at end of file
+        Command::CloseWindow(window)
+    }
+
+    fn view(&mut self) -> Element<Message> {
+        let controls = Column::new()
+            .width(Length::Fill)
+            .spacing(10.0)
+            .append(Text::new(format!("Hello, {}!", self.name)))
+            .append(TextInput::new("Your name", &self.name, Message::NameChanged))
+            .append(Text::new(format!("Pressed {} times", self.presses)))
+            .append(Button::new("Press me").on_press(Message::Pressed))
+            .append(Checkbox::new(self.is_checked, "Check me", Message::Checked))
+            .append(
+                Row::new()
+                    .spacing(20.0)
+                    .append(Radio::new(Choice::Left, "Left", self.choice, Message::Chose))
+                    .append(Radio::new(Choice::Right, "Right", self.choice, Message::Chose)),
+            )
+            .append(Toggle::new(self.is_on, "Toggle me", Message::Toggled))
+            .append(Text::new(format!("Value: {:.0}", self.value)))
+            .append(Slider::new(0.0..=100.0, self.value, Message::Changed).step(5.0))
+            .append(Button::new("Open panel").on_press(Message::OpenPanel));
+
+        Scrollable::new().append(
+            Container::new()
+                .width(Length::Fill)
+                .padding(20.0)
+                .append(controls),
+        )
+    }
+
+    fn view_window(&mut self, _window: window::Id) -> Element<Message> {
+        Column::new()
+            .width(Length::Fill)
+            .padding(20.0)
+            .spacing(10.0)
+            .append(Text::new(format!("Value: {:.0}", self.value)))
+            .append(Button::new("Close").on_press(Message::ClosePanel))
+            .into()
+    }
+}
@@ -0,0 +1,121 @@
+use std::rc::Rc;
+use visper_gui::widget::table::{self, Order, Table};
+use visper_gui::widget::{Column, Text};
+use visper_gui::{Application, Command, Element, Length};
+
+fn main() {
+    Files::run()
+}
+
+const COUNT: usize = 200_000;
+
+struct File {
+    name: String,
+    size: u64,
+}
+
+struct Files {
+    files: Rc<Vec<File>>,
+    order: Rc<Vec<usize>>,
+    sort: Option<(usize, Order)>,
+    selected: Option<usize>,
+}
+
+#[derive(Debug, Clone)]
+enum Message {
+    Sorted(usize, Order),
+    Selected(usize),
+}
+
+impl Application for Files {
+    type Message = Message;
+
+    fn new() -> Self {
+        let files = (0..COUNT)
+            .map(|i| File {
+                name: format!("file-{:06}.txt", i),
+                size: (i as u64 * 7_919) % 1_000_003,
+            })
+            .collect();
+
+        Files {
+            files: Rc::new(files),
+            order: Rc::new((0..COUNT).collect()),
+            sort: None,
+            selected: None,
+        }
+    }
+
+    fn title(&self) -> String {
+        String::from("Table")
+    }
+
+    fn update(&mut self, message: Message) -> Command {
+        match message {
+            Message::Sorted(column, order) => {
+                let files = &self.files;
+                let mut sorted: Vec<usize> = (0..COUNT).collect();
+
+                match column {
+                    0 => sorted.sort_by(|&a, &b| files[a].name.cmp(&files[b].name)),
+                    _ => sorted.sort_by_key(|&i| files[i].size),
+                }
+
+                if order == Order::Descending {
+                    sorted.reverse();
+                }
+
+                self.order = Rc::new(sorted);
+                self.sort = Some((column, order));
+                self.selected = None;
+            }
+            Message::Selected(row) => self.selected = Some(row),
+        }
+
+        Command::None
+    }
+
+    fn view(&mut self) -> Element<Message> {
+        let files = Rc::clone(&self.files);
+        let order = Rc::clone(&self.order);
+
+        let mut table = Table::new(
+            vec![
+                table::Column::new("Name", 240.0).sortable(),
+                table::Column::new("Size", 120.0).sortable(),
+            ],
+            COUNT,
+            move |row, column| {
+                let file = &files[order[row]];
+
+                match column {
+                    0 => Text::new(file.name.clone()).into(),
+                    _ => Text::new(format!("{} B", file.size)).into(),
+                }
+            },
+        )
+        .on_sort(Message::Sorted)
+        .on_select(Message::Selected);
+
+        if let Some((column, order)) = self.sort {
+            table = table.sort(column, order);
+        }
+
+        if let Some(row) = self.selected {
+            table = table.selected(row);
+        }
+
+        let selected = match self.selected {
+            Some(row) => format!("Selected {}", self.files[self.order[row]].name),
+            None => String::from("Nothing selected"),
+        };
+
+        Column::new()
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .padding(20.0)
+            .spacing(10.0)
+            .append(Text::new(selected))
+            .append(table)
+    }
+}
@@ -0,0 +1,400 @@
+//! Expose the widget tree to assistive technologies.
+//!
+//! A [`UiTree`] describes itself as an accessibility [`Tree`]: every node
+//! gets a [`Role`], a name, a [`Value`], some [`State`] and its laid out
+//! bounds. An [`Adapter`] hands the [`Tree`] to a screen reader and
+//! collects the [`Action`]s it requests, which the [`UiTree`] delivers to
+//! the widgets as an [`Event::Accessibility`].
+//!
+//! With the `accesskit` feature, the [`accesskit`] module converts the
+//! [`Tree`] and the requested actions to and from [AccessKit].
+//!
+//! [`UiTree`]: ../tree/struct.UiTree.html
+//! [`Tree`]: struct.Tree.html
+//! [`Role`]: enum.Role.html
+//! [`Value`]: enum.Value.html
+//! [`State`]: struct.State.html
+//! [`Adapter`]: trait.Adapter.html
+//! [`Action`]: enum.Action.html
+//! [`Event::Accessibility`]: ../event/enum.Event.html#variant.Accessibility
+//! [`accesskit`]: accesskit/index.html
+//! [AccessKit]: https://accesskit.dev
+use std::collections::HashMap;
+use visper_graphics::core::rectangle::Rectangle;
+
+#[cfg(feature = "accesskit")]
+pub mod accesskit;
+
+/// The identifier of a node of an accessibility [`Tree`].
+///
+/// It is derived from the position of the node in the widget tree, keys
+/// included, so it stays the same when the tree is rebuilt.
+///
+/// [`Tree`]: struct.Tree.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Id(pub u64);
+
+/// What a widget is, for assistive technologies.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Role {
+    /// A widget that only groups other widgets.
+    Group,
+
+    /// Some text.
+    Text,
+
+    /// A button.
+    Button,
+
+    /// A checkbox.
+    CheckBox,
+
+    /// A radio button.
+    RadioButton,
+
+    /// An on/off switch.
+    Switch,
+
+    /// A slider.
+    Slider,
+
+    /// A field of editable text.
+    TextInput,
+
+    /// A field of editable text that hides its contents.
+    PasswordInput,
+
+    /// A region that scrolls its children.
+    ScrollView,
+
+    /// A list of rows.
+    List,
+
+    /// A table.
+    Table,
+
+    /// A row of a list or a table.
+    Row,
+}
+
+/// The value of a widget.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    /// Some text, like the contents of a text input.
+    Text(String),
+
+    /// A number in a range, like the value of a slider.
+    Number {
+        /// The current value.
+        value: f32,
+
+        /// The smallest value.
+        min: f32,
+
+        /// The largest value.
+        max: f32,
+
+        /// The amount the value changes by, if it moves in steps.
+        step: Option<f32>,
+    },
+}
+
+/// The state of a widget.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct State {
+    /// Whether the widget is checked, for widgets that can be.
+    pub checked: Option<bool>,
+
+    /// Whether the widget is selected, like the row of a table.
+    pub is_selected: bool,
+
+    /// Whether the widget ignores interaction, like a button without a
+    /// message.
+    pub is_disabled: bool,
+}
+
+/// The accessibility properties a widget reports about itself.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Properties {
+    /// The [`Role`] of the widget.
+    ///
+    /// [`Role`]: enum.Role.html
+    pub role: Role,
+
+    /// The [`Value`] of the widget, if any.
+    ///
+    /// [`Value`]: enum.Value.html
+    pub value: Option<Value>,
+
+    /// The [`State`] of the widget.
+    ///
+    /// [`State`]: struct.State.html
+    pub state: State,
+}
+
+impl Properties {
+    /// Creates new [`Properties`] with the given [`Role`], no value and the
+    /// default state.
+    ///
+    /// [`Properties`]: struct.Properties.html
+    /// [`Role`]: enum.Role.html
+    pub fn new(role: Role) -> Self {
+        Properties {
+            role,
+            value: None,
+            state: State::default(),
+        }
+    }
+
+    /// Sets the [`Value`] of the [`Properties`].
+    ///
+    /// [`Value`]: enum.Value.html
+    /// [`Properties`]: struct.Properties.html
+    pub fn value(mut self, value: Value) -> Self {
+        self.value = Some(value);
+        self
+    }
+
+    /// Sets whether the widget is checked.
+    pub fn checked(mut self, checked: bool) -> Self {
+        self.state.checked = Some(checked);
+        self
+    }
+
+    /// Sets whether the widget is selected.
+    pub fn selected(mut self, is_selected: bool) -> Self {
+        self.state.is_selected = is_selected;
+        self
+    }
+
+    /// Sets whether the widget is disabled.
+    pub fn disabled(mut self, is_disabled: bool) -> Self {
+        self.state.is_disabled = is_disabled;
+        self
+    }
+}
+
+/// A node of an accessibility [`Tree`].
+///
+/// [`Tree`]: struct.Tree.html
+#[derive(Debug, Clone, PartialEq)]
+pub struct Node {
+    /// The [`Role`] of the widget of the node.
+    ///
+    /// [`Role`]: enum.Role.html
+    pub role: Role,
+
+    /// The name of the node, like the label of a button.
+    pub name: Option<String>,
+
+    /// The [`Value`] of the widget of the node, if any.
+    ///
+    /// [`Value`]: enum.Value.html
+    pub value: Option<Value>,
+
+    /// The [`State`] of the widget of the node.
+    ///
+    /// [`State`]: struct.State.html
+    pub state: State,
+
+    /// Whether the node can receive the keyboard focus.
+    pub is_focusable: bool,
+
+    /// The laid out bounds of the node, in logical pixels.
+    pub bounds: Rectangle,
+
+    /// The children of the node, in order.
+    pub children: Vec<Id>,
+}
+
+/// The accessibility tree of a [`UiTree`].
+///
+/// [`UiTree`]: ../tree/struct.UiTree.html
+#[derive(Debug, Clone, PartialEq)]
+pub struct Tree {
+    /// The root node.
+    pub root: Id,
+
+    /// The node that has the keyboard focus, if any.
+    pub focus: Option<Id>,
+
+    /// The nodes of the tree.
+    pub nodes: HashMap<Id, Node>,
+}
+
+impl Tree {
+    /// Returns the node with the given [`Id`], if any.
+    ///
+    /// [`Id`]: struct.Id.html
+    pub fn get(&self, id: Id) -> Option<&Node> {
+        self.nodes.get(&id)
+    }
+
+    /// Returns the first node with the given name, in tree order.
+    pub fn find(&self, name: &str) -> Option<Id> {
+        let mut pending = vec![self.root];
+
+        while let Some(id) = pending.pop() {
+            let node = &self.nodes[&id];
+
+            if node.name.as_deref() == Some(name) {
+                return Some(id);
+            }
+
+            pending.extend(node.children.iter().rev());
+        }
+
+        None
+    }
+}
+
+/// An action requested by an assistive technology.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Action {
+    /// Activate the widget, like a click.
+    Click,
+
+    /// Give the keyboard focus to the widget.
+    Focus,
+
+    /// Replace the value of the widget.
+    SetValue(Value),
+}
+
+/// An [`Action`] requested on a node.
+///
+/// [`Action`]: enum.Action.html
+#[derive(Debug, Clone, PartialEq)]
+pub struct Request {
+    /// The node the action is requested on.
+    pub target: Id,
+
+    /// The requested action.
+    pub action: Action,
+}
+
+/// A connection to the assistive technologies of a platform.
+pub trait Adapter {
+    /// Receives the accessibility [`Tree`] after it changed.
+    ///
+    /// [`Tree`]: struct.Tree.html
+    fn update(&mut self, tree: &Tree);
+
+    /// Returns the actions requested since the last call.
+    fn requests(&mut self) -> Vec<Request>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tree::UiTree;
+    use crate::widget::{Button, Checkbox, Column, Element, Slider, TextInput};
+    use visper_graphics::core::size::Size;
+
+    #[derive(Debug, Clone, PartialEq)]
+    enum Message {
+        Saved,
+        Toggled(bool),
+        Volume(f32),
+        Name(String),
+    }
+
+    fn form() -> UiTree<Message> {
+        let mut tree = UiTree::new(
+            Column::new()
+                .append(Button::new("Save").on_press(Message::Saved))
+                .append(Button::new("Cancel"))
+                .append(Checkbox::new(false, "Remember me", Message::Toggled))
+                .append(Slider::new(0.0..=10.0, 5.0, Message::Volume).step(1.0))
+                .append(Element::from(TextInput::new("Name", "Ada", Message::Name)).key("name")),
+        );
+
+        tree.layout(Size::new(200.0, 300.0));
+        tree
+    }
+
+    fn find(tree: &Tree, role: Role) -> Id {
+        let mut ids: Vec<_> = tree.nodes.keys().copied().collect();
+        ids.sort();
+
+        ids.into_iter()
+            .find(|id| tree.nodes[id].role == role)
+            .unwrap()
+    }
+
+    #[test]
+    fn tree_describes_the_widgets() {
+        let tree = form().accessibility();
+        let root = &tree.nodes[&tree.root];
+
+        assert_eq!(root.role, Role::Group);
+        assert_eq!(root.children.len(), 5);
+
+        let save = &tree.nodes[&tree.find("Save").unwrap()];
+        let cancel = &tree.nodes[&tree.find("Cancel").unwrap()];
+        let checkbox = &tree.nodes[&tree.find("Remember me").unwrap()];
+        let slider = &tree.nodes[&find(&tree, Role::Slider)];
+        let input = &tree.nodes[&tree.find("Name").unwrap()];
+
+        assert_eq!(save.role, Role::Button);
+        assert!(save.is_focusable);
+        assert!(!save.state.is_disabled);
+        assert!(cancel.state.is_disabled);
+        assert_eq!(checkbox.state.checked, Some(false));
+        assert_eq!(
+            slider.value,
+            Some(Value::Number {
+                value: 5.0,
+                min: 0.0,
+                max: 10.0,
+                step: Some(1.0),
+            })
+        );
+        assert_eq!(input.role, Role::TextInput);
+        assert_eq!(input.value, Some(Value::Text(String::from("Ada"))));
+        assert_eq!(tree.focus, None);
+    }
+
+    #[test]
+    fn actions_are_routed_to_the_widgets() {
+        let mut tree = form();
+        let accessibility = tree.accessibility();
+        let mut messages = Vec::new();
+
+        let mut perform = |tree: &mut UiTree<Message>, target, action| {
+            tree.perform_action(Request { target, action }, &mut messages);
+        };
+
+        perform(&mut tree, accessibility.find("Save").unwrap(), Action::Click);
+        perform(&mut tree, accessibility.find("Cancel").unwrap(), Action::Click);
+        perform(&mut tree, accessibility.find("Remember me").unwrap(), Action::Click);
+        perform(
+            &mut tree,
+            find(&accessibility, Role::Slider),
+            Action::SetValue(Value::Number {
+                value: 7.4,
+                min: 0.0,
+                max: 10.0,
+                step: Some(1.0),
+            }),
+        );
+        perform(
+            &mut tree,
+            accessibility.find("Name").unwrap(),
+            Action::SetValue(Value::Text(String::from("Grace"))),
+        );
+        perform(&mut tree, accessibility.find("Name").unwrap(), Action::Focus);
+
+        assert_eq!(
+            messages,
+            vec![
+                Message::Saved,
+                Message::Toggled(true),
+                Message::Volume(7.0),
+                Message::Name(String::from("Grace")),
+            ]
+        );
+        assert_eq!(tree.focused(), tree.find_key("name"));
+        assert_eq!(tree.accessibility().focus, accessibility.find("Name"));
+    }
+}
@@ -0,0 +1,239 @@
+//! Expose the accessibility tree with [AccessKit].
+//!
+//! [`AccessKit`] is an [`Adapter`] that converts the accessibility [`Tree`]
+//! to AccessKit tree updates and hands them to a platform adapter, like the
+//! ones of `accesskit_windows` or `accesskit_macos`. The platform adapter
+//! forwards the actions requested by screen readers to the [`Actions`]
+//! handler, and the [`AccessKit`] adapter queues them for the [`UiTree`].
+//!
+//! [AccessKit]: https://accesskit.dev
+//! [`AccessKit`]: struct.AccessKit.html
+//! [`Adapter`]: ../trait.Adapter.html
+//! [`Tree`]: ../struct.Tree.html
+//! [`Actions`]: struct.Actions.html
+//! [`UiTree`]: ../../tree/struct.UiTree.html
+use super::{Action, Adapter, Id, Request, Role, Tree, Value};
+use std::sync::{Arc, Mutex};
+
+/// An [`Adapter`] for AccessKit.
+///
+/// [`Adapter`]: ../trait.Adapter.html
+pub struct AccessKit {
+    on_update: Box<dyn FnMut(::accesskit::TreeUpdate)>,
+    tree: Option<Tree>,
+    requests: Arc<Mutex<Vec<::accesskit::ActionRequest>>>,
+}
+
+impl AccessKit {
+    /// Creates a new [`AccessKit`] adapter that hands its tree updates to
+    /// the given function, usually the `update` method of a platform
+    /// adapter.
+    ///
+    /// [`AccessKit`]: struct.AccessKit.html
+    pub fn new(on_update: impl FnMut(::accesskit::TreeUpdate) + 'static) -> Self {
+        AccessKit {
+            on_update: Box::new(on_update),
+            tree: None,
+            requests: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    /// Returns the [`Actions`] handler to give to the platform adapter.
+    ///
+    /// [`Actions`]: struct.Actions.html
+    pub fn actions(&self) -> Actions {
+        Actions {
+            requests: Arc::clone(&self.requests),
+        }
+    }
+}
+
+impl Adapter for AccessKit {
+    fn update(&mut self, tree: &Tree) {
+        (self.on_update)(tree_update(tree));
+
+        self.tree = Some(tree.clone());
+    }
+
+    fn requests(&mut self) -> Vec<Request> {
+        let requests: Vec<_> = self
+            .requests
+            .lock()
+            .expect("Lock action requests")
+            .drain(..)
+            .collect();
+
+        match &self.tree {
+            Some(tree) => requests
+                .into_iter()
+                .filter_map(|raw| request(tree, raw))
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+}
+
+/// The handler of the actions requested through a platform adapter.
+///
+/// It can be sent to the thread of the platform adapter. The requests it
+/// receives are performed by the [`AccessKit`] adapter it comes from.
+///
+/// [`AccessKit`]: struct.AccessKit.html
+#[derive(Debug, Clone)]
+pub struct Actions {
+    requests: Arc<Mutex<Vec<::accesskit::ActionRequest>>>,
+}
+
+impl ::accesskit::ActionHandler for Actions {
+    fn do_action(&mut self, request: ::accesskit::ActionRequest) {
+        self.requests
+            .lock()
+            .expect("Lock action requests")
+            .push(request);
+    }
+}
+
+/// Converts an accessibility [`Tree`] to a full AccessKit tree update.
+///
+/// Bounds are in logical pixels; platform adapters that expect physical
+/// pixels need a scale transform on the root node.
+///
+/// [`Tree`]: ../struct.Tree.html
+pub fn tree_update(tree: &Tree) -> ::accesskit::TreeUpdate {
+    let mut classes = ::accesskit::NodeClassSet::new();
+
+    let nodes = tree
+        .nodes
+        .iter()
+        .map(|(&id, node)| {
+            let mut builder = ::accesskit::NodeBuilder::new(role(node.role));
+
+            if let Some(name) = &node.name {
+                builder.set_name(name.as_str());
+            }
+
+            match &node.value {
+                Some(Value::Text(text)) => builder.set_value(text.as_str()),
+                Some(Value::Number {
+                    value,
+                    min,
+                    max,
+                    step,
+                }) => {
+                    builder.set_numeric_value(f64::from(*value));
+                    builder.set_min_numeric_value(f64::from(*min));
+                    builder.set_max_numeric_value(f64::from(*max));
+
+                    if let Some(step) = step {
+                        builder.set_numeric_value_step(f64::from(*step));
+                    }
+                }
+                None => {}
+            }
+
+            if let Some(checked) = node.state.checked {
+                builder.set_checked(if checked {
+                    ::accesskit::Checked::True
+                } else {
+                    ::accesskit::Checked::False
+                });
+            }
+
+            if node.state.is_selected {
+                builder.set_selected(true);
+            }
+
+            if node.state.is_disabled {
+                builder.set_disabled();
+            }
+
+            if node.is_focusable {
+                builder.add_action(::accesskit::Action::Focus);
+            }
+
+            match node.role {
+                Role::Button | Role::CheckBox | Role::RadioButton | Role::Switch | Role::Row => {
+                    builder.add_action(::accesskit::Action::Default)
+                }
+                Role::Slider | Role::TextInput | Role::PasswordInput => {
+                    builder.add_action(::accesskit::Action::SetValue)
+                }
+                _ => {}
+            }
+
+            builder.set_bounds(::accesskit::Rect {
+                x0: f64::from(node.bounds.x),
+                y0: f64::from(node.bounds.y),
+                x1: f64::from(node.bounds.x + node.bounds.width),
+                y1: f64::from(node.bounds.y + node.bounds.height),
+            });
+            builder.set_children(
+                node.children
+                    .iter()
+                    .map(|&child| node_id(child))
+                    .collect::<Vec<_>>(),
+            );
+
+            (node_id(id), builder.build(&mut classes))
+        })
+        .collect();
+
+    ::accesskit::TreeUpdate {
+        nodes,
+        tree: Some(::accesskit::Tree::new(node_id(tree.root))),
+        focus: node_id(tree.focus.unwrap_or(tree.root)),
+    }
+}
+
+/// Converts an AccessKit action request on the given [`Tree`] to a
+/// [`Request`], if it is supported.
+///
+/// [`Tree`]: ../struct.Tree.html
+/// [`Request`]: ../struct.Request.html
+pub fn request(tree: &Tree, request: ::accesskit::ActionRequest) -> Option<Request> {
+    let target = Id(request.target.0);
+
+    let action = match (request.action, request.data) {
+        (::accesskit::Action::Default, _) => Action::Click,
+        (::accesskit::Action::Focus, _) => Action::Focus,
+        (::accesskit::Action::SetValue, Some(::accesskit::ActionData::Value(value))) => {
+            Action::SetValue(Value::Text(String::from(value)))
+        }
+        (::accesskit::Action::SetValue, Some(::accesskit::ActionData::NumericValue(value))) => {
+            match tree.get(target)?.value {
+                Some(Value::Number { min, max, step, .. }) => Action::SetValue(Value::Number {
+                    value: value as f32,
+                    min,
+                    max,
+                    step,
+                }),
+                _ => return None,
+            }
+        }
+        _ => return None,
+    };
+
+    Some(Request { target, action })
+}
+
+fn node_id(id: Id) -> ::accesskit::NodeId {
+    ::accesskit::NodeId(id.0)
+}
+
+fn role(role: Role) -> ::accesskit::Role {
+    match role {
+        Role::Group => ::accesskit::Role::Group,
+        Role::Text => ::accesskit::Role::StaticText,
+        Role::Button => ::accesskit::Role::Button,
+        Role::CheckBox => ::accesskit::Role::CheckBox,
+        Role::RadioButton => ::accesskit::Role::RadioButton,
+        Role::Switch => ::accesskit::Role::Switch,
+        Role::Slider => ::accesskit::Role::Slider,
+        Role::TextInput => ::accesskit::Role::TextInput,
+        Role::PasswordInput => ::accesskit::Role::PasswordInput,
+        Role::ScrollView => ::accesskit::Role::ScrollView,
+        Role::List => ::accesskit::Role::List,
+        Role::Table => ::accesskit::Role::Table,
+        Role::Row => ::accesskit::Role::Row,
+    }
+}
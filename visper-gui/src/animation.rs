@@ -0,0 +1,470 @@
+//! Animate values over time.
+//!
+//! An [`Animated`] value moves towards its target following a
+//! [`Transition`], either a tween with an [`Easing`] curve or a physical
+//! [`Spring`]. The time is read from a [`Clock`], which tests can replace
+//! with a [`Manual`] one.
+//!
+//! [`Animated`]: struct.Animated.html
+//! [`Transition`]: enum.Transition.html
+//! [`Easing`]: enum.Easing.html
+//! [`Spring`]: struct.Spring.html
+//! [`Clock`]: trait.Clock.html
+//! [`Manual`]: struct.Manual.html
+use std::cell::Cell;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+use visper_graphics::core::color::Color;
+use visper_graphics::core::point::Point;
+use visper_graphics::core::rectangle::Rectangle;
+use visper_graphics::core::vector::Vector;
+
+/// The time between two frames while something is animating.
+pub const FRAME: Duration = Duration::from_millis(16);
+
+/// The distance from its target under which a spring is at rest, relative to
+/// the distance it travels.
+const REST: f32 = 0.001;
+
+/// A source of time.
+pub trait Clock {
+    /// Returns the current time.
+    fn now(&self) -> Instant;
+}
+
+/// The clock of the operating system.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct System;
+
+impl Clock for System {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+/// A clock that only moves when told to.
+///
+/// Clones of a [`Manual`] clock share the same time, so a test can keep one
+/// to move the clock it gave to a [`UiTree`].
+///
+/// [`Manual`]: struct.Manual.html
+/// [`UiTree`]: ../tree/struct.UiTree.html
+#[derive(Debug, Clone)]
+pub struct Manual {
+    now: Rc<Cell<Instant>>,
+}
+
+impl Manual {
+    /// Creates a new [`Manual`] clock stopped at the current time.
+    ///
+    /// [`Manual`]: struct.Manual.html
+    pub fn new() -> Self {
+        Manual {
+            now: Rc::new(Cell::new(Instant::now())),
+        }
+    }
+
+    /// Moves the clock forward by the given duration.
+    pub fn advance(&self, duration: Duration) {
+        self.now.set(self.now.get() + duration);
+    }
+}
+
+impl Default for Manual {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clock for Manual {
+    fn now(&self) -> Instant {
+        self.now.get()
+    }
+}
+
+/// A value that can be blended with another one.
+pub trait Interpolate {
+    /// Returns the value at the given fraction of the way to another value.
+    ///
+    /// The fraction can be outside of `0.0..=1.0`, like when a spring
+    /// overshoots its target.
+    fn interpolate(&self, other: &Self, t: f32) -> Self;
+}
+
+impl Interpolate for f32 {
+    fn interpolate(&self, other: &f32, t: f32) -> f32 {
+        self + (other - self) * t
+    }
+}
+
+impl Interpolate for Color {
+    fn interpolate(&self, other: &Color, t: f32) -> Color {
+        Color {
+            r: self.r.interpolate(&other.r, t),
+            g: self.g.interpolate(&other.g, t),
+            b: self.b.interpolate(&other.b, t),
+            a: self.a.interpolate(&other.a, t),
+        }
+    }
+}
+
+impl Interpolate for Point {
+    fn interpolate(&self, other: &Point, t: f32) -> Point {
+        Point::new(self.x.interpolate(&other.x, t), self.y.interpolate(&other.y, t))
+    }
+}
+
+impl Interpolate for Vector {
+    fn interpolate(&self, other: &Vector, t: f32) -> Vector {
+        Vector::new(self.x.interpolate(&other.x, t), self.y.interpolate(&other.y, t))
+    }
+}
+
+impl Interpolate for Rectangle {
+    fn interpolate(&self, other: &Rectangle, t: f32) -> Rectangle {
+        Rectangle {
+            x: self.x.interpolate(&other.x, t),
+            y: self.y.interpolate(&other.y, t),
+            width: self.width.interpolate(&other.width, t),
+            height: self.height.interpolate(&other.height, t),
+        }
+    }
+}
+
+/// A curve that maps the elapsed fraction of a tween to its progress.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Easing {
+    /// Constant speed.
+    Linear,
+
+    /// Starts slowly and accelerates.
+    EaseIn,
+
+    /// Starts quickly and decelerates.
+    EaseOut,
+
+    /// Accelerates, then decelerates.
+    EaseInOut,
+
+    /// A cubic Bézier curve from `(0, 0)` to `(1, 1)` with the given control
+    /// points, like `cubic-bezier` in CSS.
+    CubicBezier(f32, f32, f32, f32),
+}
+
+impl Easing {
+    /// Returns the progress at the given elapsed fraction, between 0 and 1.
+    pub fn apply(self, t: f32) -> f32 {
+        let t = t.max(0.0).min(1.0);
+
+        match self {
+            Easing::Linear => t,
+            Easing::EaseIn => t * t * t,
+            Easing::EaseOut => 1.0 - (1.0 - t).powi(3),
+            Easing::EaseInOut => {
+                if t < 0.5 {
+                    4.0 * t * t * t
+                } else {
+                    1.0 - (-2.0 * t + 2.0).powi(3) / 2.0
+                }
+            }
+            Easing::CubicBezier(x1, y1, x2, y2) => {
+                let bezier = |a: f32, b: f32, s: f32| {
+                    3.0 * a * s * (1.0 - s).powi(2) + 3.0 * b * s * s * (1.0 - s) + s.powi(3)
+                };
+
+                // The curve is monotonic in x, so its parameter can be found
+                // by bisection
+                let (mut low, mut high) = (0.0, 1.0);
+
+                for _ in 0..24 {
+                    let middle = (low + high) / 2.0;
+
+                    if bezier(x1, x2, middle) < t {
+                        low = middle;
+                    } else {
+                        high = middle;
+                    }
+                }
+
+                bezier(y1, y2, (low + high) / 2.0)
+            }
+        }
+    }
+}
+
+/// A damped spring.
+///
+/// A spring has no fixed duration: it settles when its oscillations become
+/// imperceptible. Lower damping makes it overshoot its target and bounce.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Spring {
+    /// How strongly the spring pulls towards its target.
+    pub stiffness: f32,
+
+    /// How strongly the spring resists motion.
+    pub damping: f32,
+
+    /// The mass attached to the spring.
+    pub mass: f32,
+}
+
+impl Default for Spring {
+    fn default() -> Self {
+        Spring {
+            stiffness: 170.0,
+            damping: 26.0,
+            mass: 1.0,
+        }
+    }
+}
+
+impl Spring {
+    /// Creates a new [`Spring`] with the given stiffness and damping and a
+    /// unit mass.
+    ///
+    /// [`Spring`]: struct.Spring.html
+    pub fn new(stiffness: f32, damping: f32) -> Self {
+        Spring {
+            stiffness,
+            damping,
+            mass: 1.0,
+        }
+    }
+
+    fn frequency(self) -> f32 {
+        (self.stiffness / self.mass).sqrt()
+    }
+
+    fn damping_ratio(self) -> f32 {
+        self.damping / (2.0 * (self.stiffness * self.mass).sqrt())
+    }
+
+    /// Returns the distance left to the target at the given time, starting
+    /// at rest one unit away from it.
+    fn displacement(self, t: f32) -> f32 {
+        let omega = self.frequency();
+        let zeta = self.damping_ratio();
+
+        if zeta < 1.0 {
+            let omega_d = omega * (1.0 - zeta * zeta).sqrt();
+
+            (-zeta * omega * t).exp()
+                * ((omega_d * t).cos() + zeta * omega / omega_d * (omega_d * t).sin())
+        } else if zeta > 1.0 {
+            let root = (zeta * zeta - 1.0).sqrt();
+            let r1 = -omega * (zeta - root);
+            let r2 = -omega * (zeta + root);
+
+            (r2 * (r1 * t).exp() - r1 * (r2 * t).exp()) / (r2 - r1)
+        } else {
+            (-omega * t).exp() * (1.0 + omega * t)
+        }
+    }
+
+    /// Returns the largest distance to the target the spring can still reach
+    /// from the given time on.
+    fn amplitude(self, t: f32) -> f32 {
+        let omega = self.frequency();
+        let zeta = self.damping_ratio();
+
+        if zeta < 1.0 {
+            let omega_d = omega * (1.0 - zeta * zeta).sqrt();
+
+            (-zeta * omega * t).exp() * (1.0 + (zeta * omega / omega_d).powi(2)).sqrt()
+        } else {
+            self.displacement(t).abs()
+        }
+    }
+}
+
+/// The way an [`Animated`] value moves towards its target.
+///
+/// [`Animated`]: struct.Animated.html
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Transition {
+    /// Move in the given duration following an [`Easing`] curve.
+    ///
+    /// [`Easing`]: enum.Easing.html
+    Tween {
+        /// The duration of the transition.
+        duration: Duration,
+
+        /// The curve followed by the transition.
+        easing: Easing,
+    },
+
+    /// Move as if pulled by a [`Spring`].
+    ///
+    /// [`Spring`]: struct.Spring.html
+    Spring(Spring),
+}
+
+impl Transition {
+    /// Creates a tween that lasts the given duration and eases in and out.
+    pub fn ease(duration: Duration) -> Self {
+        Transition::Tween {
+            duration,
+            easing: Easing::EaseInOut,
+        }
+    }
+
+    /// Returns the progress of the transition after the given time.
+    ///
+    /// The progress goes from 0 to 1, and may overshoot 1 for springs.
+    pub fn progress(self, elapsed: Duration) -> f32 {
+        match self {
+            Transition::Tween { duration, easing } => {
+                if duration == Duration::from_secs(0) {
+                    1.0
+                } else {
+                    easing.apply(elapsed.as_secs_f32() / duration.as_secs_f32())
+                }
+            }
+            Transition::Spring(spring) => {
+                if self.is_finished(elapsed) {
+                    1.0
+                } else {
+                    1.0 - spring.displacement(elapsed.as_secs_f32())
+                }
+            }
+        }
+    }
+
+    /// Returns true if the transition is over after the given time.
+    pub fn is_finished(self, elapsed: Duration) -> bool {
+        match self {
+            Transition::Tween { duration, .. } => elapsed >= duration,
+            Transition::Spring(spring) => spring.amplitude(elapsed.as_secs_f32()) < REST,
+        }
+    }
+}
+
+/// A value moving towards a target.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Animated<T> {
+    from: T,
+    to: T,
+    start: Option<Instant>,
+    transition: Transition,
+}
+
+impl<T: Interpolate + Clone + PartialEq> Animated<T> {
+    /// Creates a new [`Animated`] value at rest, which will follow the given
+    /// [`Transition`] when its target changes.
+    ///
+    /// [`Animated`]: struct.Animated.html
+    /// [`Transition`]: enum.Transition.html
+    pub fn new(value: T, transition: Transition) -> Self {
+        Animated {
+            from: value.clone(),
+            to: value,
+            start: None,
+            transition,
+        }
+    }
+
+    /// Returns the target of the [`Animated`] value.
+    ///
+    /// [`Animated`]: struct.Animated.html
+    pub fn target(&self) -> &T {
+        &self.to
+    }
+
+    /// Returns the value at the given time.
+    pub fn value(&self, now: Instant) -> T {
+        match self.start {
+            Some(start) => {
+                let elapsed = now.checked_duration_since(start).unwrap_or_default();
+
+                self.from.interpolate(&self.to, self.transition.progress(elapsed))
+            }
+            None => self.to.clone(),
+        }
+    }
+
+    /// Starts moving from the current value to a new target.
+    pub fn set(&mut self, target: T, now: Instant) {
+        if target == self.to {
+            return;
+        }
+
+        self.from = self.value(now);
+        self.to = target;
+        self.start = Some(now);
+    }
+
+    /// Jumps to the given value without animating.
+    pub fn snap(&mut self, value: T) {
+        self.from = value.clone();
+        self.to = value;
+        self.start = None;
+    }
+
+    /// Returns true if the value is still moving at the given time.
+    pub fn is_animating(&self, now: Instant) -> bool {
+        self.start.map_or(false, |start| {
+            let elapsed = now.checked_duration_since(start).unwrap_or_default();
+
+            !self.transition.is_finished(elapsed)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tween_follows_its_easing() {
+        let clock = Manual::new();
+        let transition = Transition::Tween {
+            duration: Duration::from_millis(100),
+            easing: Easing::EaseIn,
+        };
+        let mut opacity = Animated::new(0.0, transition);
+
+        opacity.set(1.0, clock.now());
+        clock.advance(Duration::from_millis(50));
+
+        assert_eq!(opacity.value(clock.now()), 0.125);
+        assert!(opacity.is_animating(clock.now()));
+
+        // Retargeting starts from the current value
+        opacity.set(0.0, clock.now());
+        assert_eq!(opacity.value(clock.now()), 0.125);
+
+        clock.advance(Duration::from_millis(100));
+        assert_eq!(opacity.value(clock.now()), 0.0);
+        assert!(!opacity.is_animating(clock.now()));
+    }
+
+    #[test]
+    fn cubic_bezier_matches_its_control_points() {
+        let linear = Easing::CubicBezier(0.25, 0.25, 0.75, 0.75);
+        let ease = Easing::CubicBezier(0.42, 0.0, 0.58, 1.0);
+
+        assert!((linear.apply(0.3) - 0.3).abs() < 1e-4);
+        assert!((ease.apply(0.5) - 0.5).abs() < 1e-4);
+        assert!(ease.apply(0.2) < 0.2);
+        assert_eq!(ease.apply(1.0), 1.0);
+    }
+
+    #[test]
+    fn spring_overshoots_then_settles() {
+        let clock = Manual::new();
+        let bouncy = Transition::Spring(Spring::new(200.0, 8.0));
+        let mut position = Animated::new(Point::new(0.0, 0.0), bouncy);
+
+        position.set(Point::new(100.0, 0.0), clock.now());
+
+        let mut overshot = false;
+
+        while position.is_animating(clock.now()) {
+            clock.advance(FRAME);
+            overshot |= position.value(clock.now()).x > 100.0;
+        }
+
+        assert!(overshot);
+        assert_eq!(position.value(clock.now()), Point::new(100.0, 0.0));
+    }
+}
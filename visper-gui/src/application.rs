@@ -0,0 +1,148 @@
+use crate::accessibility::Adapter;
+use crate::clipboard::{self, Clipboard};
+use crate::command::Command;
+use crate::proxy::Proxy;
+use crate::reload::Reload;
+use crate::runtime;
+use crate::theme::Theme;
+use crate::widget::{Column, Element};
+use crate::window;
+use std::path::{Path, PathBuf};
+
+/// An interactive application.
+///
+/// The state of the application lives in the implementor. Every time a
+/// message is published, [`update`] is called and the widget tree is rebuilt
+/// from [`view`].
+///
+/// [`update`]: #tymethod.update
+/// [`view`]: #tymethod.view
+pub trait Application: Sized + 'static {
+    /// The type of the messages produced by the user interface.
+    type Message: std::fmt::Debug + Send + 'static;
+
+    /// Initializes the state of the [`Application`].
+    ///
+    /// [`Application`]: trait.Application.html
+    fn new() -> Self;
+
+    /// Returns the title of the window of the [`Application`].
+    ///
+    /// [`Application`]: trait.Application.html
+    fn title(&self) -> String;
+
+    /// Handles a message and updates the state of the [`Application`].
+    ///
+    /// The returned [`Command`] is performed once the widget tree has been
+    /// rebuilt.
+    ///
+    /// [`Application`]: trait.Application.html
+    /// [`Command`]: ../command/enum.Command.html
+    fn update(&mut self, message: Self::Message) -> Command;
+
+    /// Starts the work of the [`Application`] that runs outside of the event
+    /// loop, like a thread streaming log lines, with a [`Proxy`] to send its
+    /// messages with.
+    ///
+    /// By default, nothing is started.
+    ///
+    /// [`Application`]: trait.Application.html
+    /// [`Proxy`]: ../proxy/struct.Proxy.html
+    fn start(&mut self, _proxy: Proxy<Self::Message>) {}
+
+    /// Returns how many messages sent with the [`Proxy`] given to [`start`]
+    /// can wait for the event loop at once, or `None` for no limit.
+    ///
+    /// Once the limit is reached, senders wait instead of flooding the event
+    /// loop.
+    ///
+    /// [`Proxy`]: ../proxy/struct.Proxy.html
+    /// [`start`]: #method.start
+    fn capacity(&self) -> Option<usize> {
+        None
+    }
+
+    /// Returns the widgets to display in the main window.
+    fn view(&mut self) -> Element<Self::Message>;
+
+    /// Returns the widgets to display in a window opened with
+    /// [`Command::OpenWindow`].
+    ///
+    /// By default, the window is empty.
+    ///
+    /// [`Command::OpenWindow`]: ../command/enum.Command.html#variant.OpenWindow
+    fn view_window(&mut self, _window: window::Id) -> Element<Self::Message> {
+        Column::new().into()
+    }
+
+    /// Handles the request of the user to close a window.
+    ///
+    /// By default, the window is closed.
+    fn close_requested(&mut self, window: window::Id) -> Command {
+        Command::CloseWindow(window)
+    }
+
+    /// Returns the [`Theme`] of the [`Application`].
+    ///
+    /// [`Theme`]: ../theme/struct.Theme.html
+    /// [`Application`]: trait.Application.html
+    fn theme(&self) -> Theme {
+        Theme::default()
+    }
+
+    /// Returns the [`Clipboard`] used by the text inputs of the
+    /// [`Application`].
+    ///
+    /// By default, text is only shared inside the [`Application`].
+    ///
+    /// [`Clipboard`]: ../clipboard/trait.Clipboard.html
+    /// [`Application`]: trait.Application.html
+    fn clipboard(&self) -> Box<dyn Clipboard> {
+        Box::new(clipboard::Local::default())
+    }
+
+    /// Returns the [`Adapter`] that exposes the given window of the
+    /// [`Application`] to assistive technologies, if any.
+    ///
+    /// It is called once for every window, when it opens, and the [`Adapter`]
+    /// only receives the widget tree of that window.
+    ///
+    /// By default, the [`Application`] is not exposed.
+    ///
+    /// [`Adapter`]: ../accessibility/trait.Adapter.html
+    /// [`Application`]: trait.Application.html
+    fn accessibility(&self, _window: window::Id) -> Option<Box<dyn Adapter>> {
+        None
+    }
+
+    /// Returns whether the windows of the [`Application`] show what drawing
+    /// their last frame cost, in their top-left corner.
+    ///
+    /// [`Application`]: trait.Application.html
+    fn show_stats(&self) -> bool {
+        false
+    }
+
+    /// Returns the files the [`Application`] is built from, like layout
+    /// descriptions and themes, to reload them when they change.
+    ///
+    /// [`Application`]: trait.Application.html
+    fn watch(&self) -> Vec<PathBuf> {
+        Vec::new()
+    }
+
+    /// Reloads a watched file that changed on disk and returns what needs to
+    /// be rebuilt.
+    ///
+    /// [`Application`]: trait.Application.html
+    fn reload(&mut self, _path: &Path) -> Reload {
+        Reload::Nothing
+    }
+
+    /// Runs the [`Application`] until its window is closed.
+    ///
+    /// [`Application`]: trait.Application.html
+    fn run() {
+        runtime::run::<Self>()
+    }
+}
@@ -0,0 +1,35 @@
+//! Share text with a clipboard.
+
+/// A clipboard that widgets can read text from and write text to.
+///
+/// Implement this trait to connect the text inputs of an application to the
+/// clipboard of the platform.
+pub trait Clipboard {
+    /// Returns the text in the [`Clipboard`], if any.
+    ///
+    /// [`Clipboard`]: trait.Clipboard.html
+    fn read(&self) -> Option<String>;
+
+    /// Replaces the contents of the [`Clipboard`] with the given text.
+    ///
+    /// [`Clipboard`]: trait.Clipboard.html
+    fn write(&mut self, contents: String);
+}
+
+/// A [`Clipboard`] that only shares text inside the application.
+///
+/// [`Clipboard`]: trait.Clipboard.html
+#[derive(Debug, Clone, Default)]
+pub struct Local {
+    contents: Option<String>,
+}
+
+impl Clipboard for Local {
+    fn read(&self) -> Option<String> {
+        self.contents.clone()
+    }
+
+    fn write(&mut self, contents: String) {
+        self.contents = Some(contents);
+    }
+}
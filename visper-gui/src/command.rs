@@ -0,0 +1,72 @@
+use crate::window;
+
+/// An action the runtime performs on behalf of an [`Application`].
+///
+/// [`Application`]: ../application/trait.Application.html
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    /// Do nothing.
+    None,
+
+    /// Focus the node with the given key.
+    Focus(String),
+
+    /// Move the focus to the next node in tab order.
+    FocusNext,
+
+    /// Move the focus to the previous node in tab order.
+    FocusPrevious,
+
+    /// Remove the focus from the focused node.
+    Unfocus,
+
+    /// Open a new window with the given [`Id`] and [`Settings`].
+    ///
+    /// [`Id`]: ../window/struct.Id.html
+    /// [`Settings`]: ../window/struct.Settings.html
+    OpenWindow(window::Id, window::Settings),
+
+    /// Close the window with the given [`Id`].
+    ///
+    /// Closing the main window closes the [`Application`].
+    ///
+    /// [`Id`]: ../window/struct.Id.html
+    /// [`Application`]: ../application/trait.Application.html
+    CloseWindow(window::Id),
+
+    /// Perform several commands in order.
+    Batch(Vec<Command>),
+}
+
+impl Command {
+    /// Creates a [`Command`] that focuses the node with the given key.
+    ///
+    /// [`Command`]: enum.Command.html
+    pub fn focus(key: impl Into<String>) -> Command {
+        Command::Focus(key.into())
+    }
+
+    /// Creates a [`Command`] that opens a new window, and returns the
+    /// [`Id`] of the window along with it.
+    ///
+    /// [`Command`]: enum.Command.html
+    /// [`Id`]: ../window/struct.Id.html
+    pub fn open_window(settings: window::Settings) -> (window::Id, Command) {
+        let id = window::Id::unique();
+
+        (id, Command::OpenWindow(id, settings))
+    }
+
+    /// Creates a [`Command`] that performs all the given commands.
+    ///
+    /// [`Command`]: enum.Command.html
+    pub fn batch(commands: impl IntoIterator<Item = Command>) -> Command {
+        Command::Batch(commands.into_iter().collect())
+    }
+}
+
+impl Default for Command {
+    fn default() -> Self {
+        Command::None
+    }
+}
@@ -0,0 +1,151 @@
+//! Convert winit events into visper events.
+use crate::event::{keyboard, mouse, touch, Event};
+use visper_graphics::core::point::Point;
+use winit::dpi::{LogicalPosition, PhysicalPosition};
+
+/// Converts a winit [`WindowEvent`] into a visper [`Event`].
+///
+/// Physical positions are converted to logical pixels with the given scale
+/// factor. Window events that have no visper counterpart produce `None`.
+///
+/// [`WindowEvent`]: https://docs.rs/winit/0.20.0/winit/event/enum.WindowEvent.html
+/// [`Event`]: ../event/enum.Event.html
+pub fn window_event(
+    event: &winit::event::WindowEvent<'_>,
+    scale_factor: f64,
+    modifiers: keyboard::ModifiersState,
+) -> Option<Event> {
+    use winit::event::WindowEvent;
+
+    match event {
+        WindowEvent::CursorEntered { .. } => {
+            Some(Event::Mouse(mouse::Event::CursorEntered))
+        }
+        WindowEvent::CursorLeft { .. } => {
+            Some(Event::Mouse(mouse::Event::CursorLeft))
+        }
+        WindowEvent::CursorMoved { position, .. } => {
+            let position = PhysicalPosition::new(f64::from(position.x), f64::from(position.y));
+
+            Some(Event::Mouse(mouse::Event::CursorMoved {
+                position: point(position, scale_factor),
+            }))
+        }
+        WindowEvent::MouseInput { button, state, .. } => {
+            let button = mouse_button(*button);
+
+            Some(Event::Mouse(match state {
+                winit::event::ElementState::Pressed => {
+                    mouse::Event::ButtonPressed(button)
+                }
+                winit::event::ElementState::Released => {
+                    mouse::Event::ButtonReleased(button)
+                }
+            }))
+        }
+        WindowEvent::MouseWheel { delta, .. } => {
+            Some(Event::Mouse(mouse::Event::WheelScrolled {
+                delta: scroll_delta(*delta),
+            }))
+        }
+        WindowEvent::ReceivedCharacter(c) if !c.is_control() => {
+            Some(Event::Keyboard(keyboard::Event::CharacterReceived(*c)))
+        }
+        WindowEvent::KeyboardInput {
+            input:
+                winit::event::KeyboardInput {
+                    virtual_keycode: Some(key_code),
+                    state,
+                    ..
+                },
+            ..
+        } => Some(Event::Keyboard(match state {
+            winit::event::ElementState::Pressed => keyboard::Event::KeyPressed {
+                key_code: *key_code,
+                modifiers,
+            },
+            winit::event::ElementState::Released => {
+                keyboard::Event::KeyReleased {
+                    key_code: *key_code,
+                    modifiers,
+                }
+            }
+        })),
+        WindowEvent::Touch(touch) => Some(Event::Touch(touch_event(*touch, scale_factor))),
+        _ => None,
+    }
+}
+
+/// Converts winit modifiers into visper [`ModifiersState`].
+///
+/// [`ModifiersState`]: ../event/keyboard/struct.ModifiersState.html
+pub fn modifiers_state(
+    modifiers: winit::event::ModifiersState,
+) -> keyboard::ModifiersState {
+    keyboard::ModifiersState {
+        shift: modifiers.shift(),
+        control: modifiers.ctrl(),
+        alt: modifiers.alt(),
+        logo: modifiers.logo(),
+    }
+}
+
+/// Converts a winit mouse button into a visper mouse [`Button`].
+///
+/// [`Button`]: ../event/mouse/enum.Button.html
+pub fn mouse_button(button: winit::event::MouseButton) -> mouse::Button {
+    match button {
+        winit::event::MouseButton::Left => mouse::Button::Left,
+        winit::event::MouseButton::Right => mouse::Button::Right,
+        winit::event::MouseButton::Middle => mouse::Button::Middle,
+        winit::event::MouseButton::Other(other) => mouse::Button::Other(other),
+    }
+}
+
+/// Converts a winit scroll delta into a visper [`ScrollDelta`].
+///
+/// [`ScrollDelta`]: ../event/mouse/enum.ScrollDelta.html
+pub fn scroll_delta(delta: winit::event::MouseScrollDelta) -> mouse::ScrollDelta {
+    match delta {
+        winit::event::MouseScrollDelta::LineDelta(x, y) => {
+            mouse::ScrollDelta::Lines { x, y }
+        }
+        winit::event::MouseScrollDelta::PixelDelta(position) => {
+            let position: LogicalPosition<f64> = position;
+
+            mouse::ScrollDelta::Pixels {
+                x: position.x as f32,
+                y: position.y as f32,
+            }
+        }
+    }
+}
+
+/// Converts a winit touch into a visper touch [`Event`].
+///
+/// [`Event`]: ../event/touch/enum.Event.html
+pub fn touch_event(touch: winit::event::Touch, scale_factor: f64) -> touch::Event {
+    let id = touch::Finger(touch.id);
+    let position = point(touch.location, scale_factor);
+
+    match touch.phase {
+        winit::event::TouchPhase::Started => {
+            touch::Event::FingerPressed { id, position }
+        }
+        winit::event::TouchPhase::Moved => {
+            touch::Event::FingerMoved { id, position }
+        }
+        winit::event::TouchPhase::Ended => {
+            touch::Event::FingerLifted { id, position }
+        }
+        winit::event::TouchPhase::Cancelled => {
+            touch::Event::FingerLost { id, position }
+        }
+    }
+}
+
+fn point(position: PhysicalPosition<f64>, scale_factor: f64) -> Point {
+    let logical: LogicalPosition<f64> = position.to_logical(scale_factor);
+
+    Point::new(logical.x as f32, logical.y as f32)
+}
@@ -0,0 +1,417 @@
+//! Describe widget trees with data.
+//!
+//! A [`Node`] describes a tree of rows, columns, containers, texts and
+//! buttons that can be loaded with any `serde` format, like RON or JSON, and
+//! built into an [`Element`]. The messages published by the widgets are
+//! referred to by name and looked up in some [`Bindings`].
+//!
+//! A [`Snapshot`] captures the nodes of a [`UiTree`], with their laid out
+//! bounds, so the tree can be dumped for debugging.
+//!
+//! This module is only available with the `serde` feature.
+//!
+//! [`Node`]: enum.Node.html
+//! [`Element`]: ../widget/struct.Element.html
+//! [`Bindings`]: struct.Bindings.html
+//! [`Snapshot`]: struct.Snapshot.html
+//! [`UiTree`]: ../tree/struct.UiTree.html
+use crate::style::{Length, Style};
+use crate::tree::{NodeId, UiTree};
+use crate::widget::{Button, Column, Container, Element, Row, Text};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use visper_graphics::core::color::Color;
+use visper_graphics::core::rectangle::Rectangle;
+
+/// The description of a widget and its children.
+///
+/// In RON, a small screen looks like:
+///
+/// ```ron
+/// Column(
+///     style: (width: Fill, padding: 20.0, spacing: 10.0),
+///     children: [
+///         Text(content: "Hello!", size: Some(24.0)),
+///         Button(label: "Press me", on_press: Some("pressed")),
+///     ],
+/// )
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Node {
+    /// A [`Row`] and its children.
+    ///
+    /// [`Row`]: ../widget/row/struct.Row.html
+    Row {
+        /// The key of the node.
+        #[serde(default)]
+        key: Option<String>,
+
+        /// The style of the row.
+        #[serde(default)]
+        style: Style,
+
+        /// The children of the row.
+        #[serde(default)]
+        children: Vec<Node>,
+    },
+
+    /// A [`Column`] and its children.
+    ///
+    /// [`Column`]: ../widget/column/struct.Column.html
+    Column {
+        /// The key of the node.
+        #[serde(default)]
+        key: Option<String>,
+
+        /// The style of the column.
+        #[serde(default)]
+        style: Style,
+
+        /// The children of the column.
+        #[serde(default)]
+        children: Vec<Node>,
+    },
+
+    /// A [`Container`] and its children.
+    ///
+    /// [`Container`]: ../widget/container/struct.Container.html
+    Container {
+        /// The key of the node.
+        #[serde(default)]
+        key: Option<String>,
+
+        /// The style of the container.
+        #[serde(default)]
+        style: Style,
+
+        /// The children of the container.
+        #[serde(default)]
+        children: Vec<Node>,
+    },
+
+    /// A [`Text`].
+    ///
+    /// [`Text`]: ../widget/text/struct.Text.html
+    Text {
+        /// The key of the node.
+        #[serde(default)]
+        key: Option<String>,
+
+        /// The content of the text.
+        content: String,
+
+        /// The font size of the text, if not the default one.
+        #[serde(default)]
+        size: Option<f32>,
+
+        /// The color of the text, if not the one of the theme.
+        #[serde(default)]
+        color: Option<Color>,
+
+        /// The width of the text.
+        #[serde(default)]
+        width: Length,
+
+        /// The height of the text.
+        #[serde(default)]
+        height: Length,
+    },
+
+    /// A [`Button`].
+    ///
+    /// [`Button`]: ../widget/button/struct.Button.html
+    Button {
+        /// The key of the node.
+        #[serde(default)]
+        key: Option<String>,
+
+        /// The label of the button.
+        label: String,
+
+        /// The name of the message published when the button is pressed.
+        #[serde(default)]
+        on_press: Option<String>,
+
+        /// The width of the button.
+        #[serde(default)]
+        width: Length,
+
+        /// The height of the button.
+        #[serde(default)]
+        height: Length,
+    },
+}
+
+impl Node {
+    /// Builds the [`Element`] described by the [`Node`], looking up the
+    /// messages it refers to in the given [`Bindings`].
+    ///
+    /// [`Element`]: ../widget/struct.Element.html
+    /// [`Node`]: enum.Node.html
+    /// [`Bindings`]: struct.Bindings.html
+    pub fn build<Message: Clone + 'static>(
+        &self,
+        bindings: &Bindings<Message>,
+    ) -> Result<Element<Message>, Error> {
+        let (element, key) = match self {
+            Node::Row {
+                key,
+                style,
+                children,
+            } => (
+                append(Row::new().style(*style).into(), children, bindings)?,
+                key,
+            ),
+            Node::Column {
+                key,
+                style,
+                children,
+            } => (
+                append(Column::new().style(*style).into(), children, bindings)?,
+                key,
+            ),
+            Node::Container {
+                key,
+                style,
+                children,
+            } => (
+                append(Container::new().style(*style).into(), children, bindings)?,
+                key,
+            ),
+            Node::Text {
+                key,
+                content,
+                size,
+                color,
+                width,
+                height,
+            } => {
+                let mut text = Text::new(content.clone()).width(*width).height(*height);
+
+                if let Some(size) = size {
+                    text = text.size(*size);
+                }
+
+                if let Some(color) = color {
+                    text = text.color(*color);
+                }
+
+                (text.into(), key)
+            }
+            Node::Button {
+                key,
+                label,
+                on_press,
+                width,
+                height,
+            } => {
+                let mut button = Button::new(label.clone()).width(*width).height(*height);
+
+                if let Some(name) = on_press {
+                    button = button.on_press(bindings.get(name)?);
+                }
+
+                (button.into(), key)
+            }
+        };
+
+        Ok(match key {
+            Some(key) => element.key(key.clone()),
+            None => element,
+        })
+    }
+}
+
+fn append<Message: Clone + 'static>(
+    element: Element<Message>,
+    children: &[Node],
+    bindings: &Bindings<Message>,
+) -> Result<Element<Message>, Error> {
+    children.iter().try_fold(element, |element, child| {
+        Ok(element.append(child.build(bindings)?))
+    })
+}
+
+/// The messages a [`Node`] can refer to, by name.
+///
+/// [`Node`]: enum.Node.html
+#[derive(Debug, Clone)]
+pub struct Bindings<Message> {
+    messages: HashMap<String, Message>,
+}
+
+impl<Message: Clone> Bindings<Message> {
+    /// Creates empty [`Bindings`].
+    ///
+    /// [`Bindings`]: struct.Bindings.html
+    pub fn new() -> Self {
+        Bindings {
+            messages: HashMap::new(),
+        }
+    }
+
+    /// Binds a message to the given name.
+    pub fn bind(mut self, name: impl Into<String>, message: Message) -> Self {
+        let _ = self.messages.insert(name.into(), message);
+        self
+    }
+
+    fn get(&self, name: &str) -> Result<Message, Error> {
+        self.messages
+            .get(name)
+            .cloned()
+            .ok_or_else(|| Error::UnboundMessage(String::from(name)))
+    }
+}
+
+impl<Message: Clone> Default for Bindings<Message> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// An error building a [`Node`].
+///
+/// [`Node`]: enum.Node.html
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    /// A node refers to a message that is not bound to any name.
+    UnboundMessage(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::UnboundMessage(name) => write!(f, "no message is bound to `{}`", name),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// The description of a laid out node of a [`UiTree`] and its children.
+///
+/// [`UiTree`]: ../tree/struct.UiTree.html
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Snapshot {
+    /// The name of the widget of the node.
+    pub widget: String,
+
+    /// The key of the node, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key: Option<String>,
+
+    /// The laid out bounds of the node.
+    pub bounds: Rectangle,
+
+    /// The style of the widget of the node.
+    pub style: Style,
+
+    /// The children of the node.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<Snapshot>,
+}
+
+impl Snapshot {
+    /// Captures the nodes of the given [`UiTree`].
+    ///
+    /// [`UiTree`]: ../tree/struct.UiTree.html
+    pub fn new<Message>(tree: &UiTree<Message>) -> Self {
+        Self::node(tree, tree.root())
+    }
+
+    fn node<Message>(tree: &UiTree<Message>, id: NodeId) -> Self {
+        let node = tree.node(id);
+
+        Snapshot {
+            widget: String::from(node.widget().name()),
+            key: node.key().map(String::from),
+            bounds: node.bounds(),
+            style: node.widget().style(),
+            children: tree
+                .children(id)
+                .map(|child| Self::node(tree, child))
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use visper_graphics::core::size::Size;
+
+    #[derive(Debug, Clone, PartialEq)]
+    enum Message {
+        Saved,
+    }
+
+    fn screen() -> Node {
+        Node::Column {
+            key: None,
+            style: Style::new().width(Length::Fill).padding(10.0),
+            children: vec![
+                Node::Text {
+                    key: Some(String::from("title")),
+                    content: String::from("Settings"),
+                    size: None,
+                    color: None,
+                    width: Length::Shrink,
+                    height: Length::Shrink,
+                },
+                Node::Button {
+                    key: None,
+                    label: String::from("Save"),
+                    on_press: Some(String::from("save")),
+                    width: Length::Units(80.0),
+                    height: Length::Units(30.0),
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn node_builds_with_its_bindings() {
+        let bindings = Bindings::new().bind("save", Message::Saved);
+        let mut tree = UiTree::new(screen().build(&bindings).unwrap());
+
+        tree.layout(Size::new(200.0, 100.0));
+
+        let snapshot = Snapshot::new(&tree);
+        let button = &snapshot.children[1];
+
+        assert_eq!(snapshot.widget, "Column");
+        assert_eq!(snapshot.style.padding, 10.0);
+        assert_eq!(snapshot.children[0].key.as_deref(), Some("title"));
+        assert_eq!(button.widget, "Button");
+        assert_eq!(button.bounds.width, 80.0);
+    }
+
+    #[test]
+    fn node_reports_unbound_messages() {
+        let error = screen().build(&Bindings::<Message>::new()).err();
+
+        assert_eq!(error, Some(Error::UnboundMessage(String::from("save"))));
+    }
+
+    #[test]
+    fn node_round_trips_through_ron() {
+        let screen = screen();
+        let text = ron::ser::to_string(&screen).unwrap();
+
+        assert_eq!(ron::de::from_str::<Node>(&text).unwrap(), screen);
+    }
+
+    #[test]
+    fn snapshot_round_trips_through_ron() {
+        let mut tree = UiTree::new(screen().build(&Bindings::new().bind("save", Message::Saved)).unwrap());
+
+        tree.layout(Size::new(200.0, 100.0));
+
+        let snapshot = Snapshot::new(&tree);
+        let text = ron::ser::to_string(&snapshot).unwrap();
+
+        assert_eq!(ron::de::from_str::<Snapshot>(&text).unwrap(), snapshot);
+    }
+}
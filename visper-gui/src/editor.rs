@@ -0,0 +1,670 @@
+//! Edit text with a cursor, a selection and an undo history.
+//!
+//! The [`Editor`] knows nothing about fonts or rendering, so the editing
+//! behavior of text inputs can be tested on its own.
+//!
+//! [`Editor`]: struct.Editor.html
+use std::ops::Range;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// The maximum number of edits that can be undone.
+const HISTORY: usize = 100;
+
+/// A range of selected text, from the anchor where the selection started to
+/// the head where the cursor is.
+///
+/// Both ends are byte indices on grapheme boundaries. The [`Selection`] is
+/// collapsed into a caret when they are equal.
+///
+/// [`Selection`]: struct.Selection.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Selection {
+    /// The end of the [`Selection`] that stays in place when it is extended.
+    ///
+    /// [`Selection`]: struct.Selection.html
+    pub anchor: usize,
+
+    /// The end of the [`Selection`] where the cursor is.
+    ///
+    /// [`Selection`]: struct.Selection.html
+    pub head: usize,
+}
+
+impl Selection {
+    /// Creates a collapsed [`Selection`] at the given index.
+    ///
+    /// [`Selection`]: struct.Selection.html
+    pub fn caret(index: usize) -> Self {
+        Selection {
+            anchor: index,
+            head: index,
+        }
+    }
+
+    /// Returns the first selected index.
+    pub fn start(&self) -> usize {
+        self.anchor.min(self.head)
+    }
+
+    /// Returns the index after the last selected one.
+    pub fn end(&self) -> usize {
+        self.anchor.max(self.head)
+    }
+
+    /// Returns the selected range of bytes.
+    pub fn range(&self) -> Range<usize> {
+        self.start()..self.end()
+    }
+
+    /// Returns true if nothing is selected.
+    pub fn is_collapsed(&self) -> bool {
+        self.anchor == self.head
+    }
+}
+
+/// A movement of the cursor of an [`Editor`].
+///
+/// [`Editor`]: struct.Editor.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Motion {
+    /// To the previous grapheme.
+    Left,
+
+    /// To the next grapheme.
+    Right,
+
+    /// To the start of the previous word.
+    WordLeft,
+
+    /// To the end of the next word.
+    WordRight,
+
+    /// To the same column of the previous line, or to the start of the text
+    /// on the first line.
+    Up,
+
+    /// To the same column of the next line, or to the end of the text on the
+    /// last line.
+    Down,
+
+    /// To the start of the line.
+    LineStart,
+
+    /// To the end of the line.
+    LineEnd,
+
+    /// To the start of the text.
+    Start,
+
+    /// To the end of the text.
+    End,
+}
+
+/// The kind of an edit, used to group consecutive edits in a single undo
+/// step.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Edit {
+    Typing,
+    Deleting,
+    Other,
+}
+
+#[derive(Debug, Clone)]
+struct Snapshot {
+    value: String,
+    selection: Selection,
+}
+
+/// A text buffer with a cursor, a selection and an undo history.
+///
+/// All the indices of an [`Editor`] are byte indices in its value. The cursor
+/// always lands on grapheme boundaries, so a character made of several code
+/// points is moved over and deleted as a whole.
+///
+/// [`Editor`]: struct.Editor.html
+#[derive(Debug, Clone)]
+pub struct Editor {
+    value: String,
+    selection: Selection,
+    is_multiline: bool,
+    column: Option<usize>,
+    undo: Vec<Snapshot>,
+    redo: Vec<Snapshot>,
+    last_edit: Option<Edit>,
+}
+
+impl Editor {
+    /// Creates a new single-line [`Editor`] with the cursor at the end of the
+    /// given value.
+    ///
+    /// [`Editor`]: struct.Editor.html
+    pub fn new(value: impl Into<String>) -> Self {
+        let value = value.into();
+        let end = value.len();
+
+        Editor {
+            value,
+            selection: Selection::caret(end),
+            is_multiline: false,
+            column: None,
+            undo: Vec::new(),
+            redo: Vec::new(),
+            last_edit: None,
+        }
+    }
+
+    /// Allows line breaks in the value of the [`Editor`].
+    ///
+    /// Single-line editors replace the line breaks of inserted text with
+    /// spaces.
+    ///
+    /// [`Editor`]: struct.Editor.html
+    pub fn multiline(mut self) -> Self {
+        self.is_multiline = true;
+        self
+    }
+
+    /// Returns true if the [`Editor`] allows line breaks.
+    ///
+    /// [`Editor`]: struct.Editor.html
+    pub fn is_multiline(&self) -> bool {
+        self.is_multiline
+    }
+
+    /// Returns the value of the [`Editor`].
+    ///
+    /// [`Editor`]: struct.Editor.html
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+
+    /// Returns the current [`Selection`].
+    ///
+    /// [`Selection`]: struct.Selection.html
+    pub fn selection(&self) -> Selection {
+        self.selection
+    }
+
+    /// Returns the index of the cursor.
+    pub fn cursor(&self) -> usize {
+        self.selection.head
+    }
+
+    /// Returns the selected text.
+    pub fn selected_text(&self) -> &str {
+        &self.value[self.selection.range()]
+    }
+
+    /// Replaces the value of the [`Editor`] from the outside, keeping the
+    /// cursor where it was when possible.
+    ///
+    /// Nothing happens if the value is the same. Otherwise the change can be
+    /// undone like any other edit.
+    ///
+    /// [`Editor`]: struct.Editor.html
+    pub fn set_value(&mut self, value: &str) {
+        if value == self.value {
+            return;
+        }
+
+        self.record(Edit::Other);
+        self.value = value.to_owned();
+
+        let Selection { anchor, head } = self.selection;
+        self.selection = Selection {
+            anchor: self.snap(anchor),
+            head: self.snap(head),
+        };
+    }
+
+    /// Selects the given range, snapped to grapheme boundaries.
+    pub fn select(&mut self, selection: Selection) {
+        self.selection = Selection {
+            anchor: self.snap(selection.anchor),
+            head: self.snap(selection.head),
+        };
+        self.column = None;
+        self.last_edit = None;
+    }
+
+    /// Selects the whole value.
+    pub fn select_all(&mut self) {
+        self.select(Selection {
+            anchor: 0,
+            head: self.value.len(),
+        });
+    }
+
+    /// Moves the cursor to the given index.
+    ///
+    /// When `extend` is true the anchor of the selection stays in place,
+    /// otherwise the selection is collapsed.
+    pub fn move_to(&mut self, index: usize, extend: bool) {
+        let anchor = self.selection.anchor;
+        let head = self.snap(index);
+
+        self.select(Selection {
+            anchor: if extend { anchor } else { head },
+            head,
+        });
+    }
+
+    /// Moves the cursor with the given [`Motion`].
+    ///
+    /// When `extend` is true the selection grows or shrinks. Otherwise it is
+    /// collapsed, and moving left or right collapses a selection onto its
+    /// start or end instead of moving the cursor.
+    ///
+    /// [`Motion`]: enum.Motion.html
+    pub fn move_cursor(&mut self, motion: Motion, extend: bool) {
+        let column = self.column.take();
+
+        if !extend && !self.selection.is_collapsed() {
+            match motion {
+                Motion::Left => return self.move_to(self.selection.start(), false),
+                Motion::Right => return self.move_to(self.selection.end(), false),
+                _ => {}
+            }
+        }
+
+        let head = self.selection.head;
+
+        let (index, column) = match motion {
+            Motion::Up | Motion::Down => {
+                let column = column.unwrap_or_else(|| self.column_of(head));
+
+                (self.vertical(head, motion == Motion::Up, column), Some(column))
+            }
+            _ => (self.target(head, motion), None),
+        };
+
+        self.move_to(index, extend);
+        self.column = column;
+    }
+
+    /// Inserts some text at the cursor, replacing the selection.
+    ///
+    /// Returns false if nothing changed.
+    pub fn insert(&mut self, text: &str) -> bool {
+        let text = if self.is_multiline {
+            text.replace("\r\n", "\n").replace('\r', "\n")
+        } else {
+            text.replace("\r\n", " ").replace(&['\r', '\n'][..], " ")
+        };
+
+        if text.is_empty() && self.selection.is_collapsed() {
+            return false;
+        }
+
+        let is_typing = self.selection.is_collapsed()
+            && text.graphemes(true).count() == 1
+            && !text.chars().all(char::is_whitespace);
+
+        let edit = if is_typing { Edit::Typing } else { Edit::Other };
+
+        self.replace(self.selection.range(), &text, edit);
+
+        true
+    }
+
+    /// Deletes the selection or, when nothing is selected, the text between
+    /// the cursor and the given [`Motion`].
+    ///
+    /// Returns false if nothing changed.
+    ///
+    /// [`Motion`]: enum.Motion.html
+    pub fn delete(&mut self, motion: Motion) -> bool {
+        let (range, edit) = if self.selection.is_collapsed() {
+            let head = self.selection.head;
+            let target = self.target(head, motion);
+
+            let edit = match motion {
+                Motion::Left | Motion::Right => Edit::Deleting,
+                _ => Edit::Other,
+            };
+
+            (head.min(target)..head.max(target), edit)
+        } else {
+            (self.selection.range(), Edit::Other)
+        };
+
+        if range.start == range.end {
+            return false;
+        }
+
+        self.replace(range, "", edit);
+
+        true
+    }
+
+    /// Removes the selected text and returns it.
+    pub fn cut(&mut self) -> Option<String> {
+        if self.selection.is_collapsed() {
+            return None;
+        }
+
+        let text = self.selected_text().to_owned();
+        let _ = self.delete(Motion::Left);
+
+        Some(text)
+    }
+
+    /// Undoes the last group of edits.
+    ///
+    /// Returns false if there was nothing to undo.
+    pub fn undo(&mut self) -> bool {
+        match self.undo.pop() {
+            Some(snapshot) => {
+                let current = self.restore(snapshot);
+
+                self.redo.push(current);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Redoes the last undone group of edits.
+    ///
+    /// Returns false if there was nothing to redo.
+    pub fn redo(&mut self) -> bool {
+        match self.redo.pop() {
+            Some(snapshot) => {
+                let current = self.restore(snapshot);
+
+                self.undo.push(current);
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn replace(&mut self, range: Range<usize>, text: &str, edit: Edit) {
+        self.record(edit);
+
+        let start = range.start;
+
+        self.value.replace_range(range, text);
+        self.selection = Selection::caret(start + text.len());
+        self.column = None;
+    }
+
+    /// Saves the current state in the undo history, unless the edit
+    /// continues the previous one.
+    fn record(&mut self, edit: Edit) {
+        let continues = edit != Edit::Other && self.last_edit == Some(edit);
+
+        if !continues {
+            if self.undo.len() == HISTORY {
+                let _ = self.undo.remove(0);
+            }
+
+            self.undo.push(Snapshot {
+                value: self.value.clone(),
+                selection: self.selection,
+            });
+        }
+
+        self.redo.clear();
+        self.last_edit = Some(edit);
+    }
+
+    fn restore(&mut self, snapshot: Snapshot) -> Snapshot {
+        let current = Snapshot {
+            value: std::mem::replace(&mut self.value, snapshot.value),
+            selection: self.selection,
+        };
+
+        self.selection = snapshot.selection;
+        self.column = None;
+        self.last_edit = None;
+
+        current
+    }
+
+    /// Returns the index the cursor lands on after moving from the given
+    /// index, for every motion but vertical ones.
+    fn target(&self, index: usize, motion: Motion) -> usize {
+        match motion {
+            Motion::Left => self.previous_grapheme(index),
+            Motion::Right => self.next_grapheme(index),
+            Motion::WordLeft => self.previous_word(index),
+            Motion::WordRight => self.next_word(index),
+            Motion::Up => self.vertical(index, true, self.column_of(index)),
+            Motion::Down => self.vertical(index, false, self.column_of(index)),
+            Motion::LineStart => self.line_start(index),
+            Motion::LineEnd => self.line_end(index),
+            Motion::Start => 0,
+            Motion::End => self.value.len(),
+        }
+    }
+
+    /// Returns the closest grapheme boundary at or before the given index.
+    fn snap(&self, index: usize) -> usize {
+        let index = index.min(self.value.len());
+
+        self.value
+            .grapheme_indices(true)
+            .map(|(start, _)| start)
+            .take_while(|&start| start <= index)
+            .last()
+            .filter(|&start| {
+                start + self.value[start..].graphemes(true).next().map_or(0, str::len) > index
+            })
+            .unwrap_or(index)
+    }
+
+    fn previous_grapheme(&self, index: usize) -> usize {
+        self.value[..index]
+            .grapheme_indices(true)
+            .next_back()
+            .map_or(0, |(start, _)| start)
+    }
+
+    fn next_grapheme(&self, index: usize) -> usize {
+        self.value[index..]
+            .graphemes(true)
+            .next()
+            .map_or(self.value.len(), |grapheme| index + grapheme.len())
+    }
+
+    fn previous_word(&self, index: usize) -> usize {
+        self.value[..index]
+            .split_word_bound_indices()
+            .rev()
+            .find(|(_, word)| !is_blank(word))
+            .map_or(0, |(start, _)| start)
+    }
+
+    fn next_word(&self, index: usize) -> usize {
+        self.value[index..]
+            .split_word_bound_indices()
+            .find(|(_, word)| !is_blank(word))
+            .map_or(self.value.len(), |(start, word)| index + start + word.len())
+    }
+
+    fn line_start(&self, index: usize) -> usize {
+        self.value[..index].rfind('\n').map_or(0, |i| i + 1)
+    }
+
+    fn line_end(&self, index: usize) -> usize {
+        self.value[index..]
+            .find('\n')
+            .map_or(self.value.len(), |i| index + i)
+    }
+
+    /// Returns the number of graphemes between the start of the line and
+    /// the given index.
+    fn column_of(&self, index: usize) -> usize {
+        self.value[self.line_start(index)..index].graphemes(true).count()
+    }
+
+    fn vertical(&self, index: usize, up: bool, column: usize) -> usize {
+        let line_start = if up {
+            let start = self.line_start(index);
+
+            if start == 0 {
+                return 0;
+            }
+
+            self.line_start(start - 1)
+        } else {
+            let end = self.line_end(index);
+
+            if end == self.value.len() {
+                return end;
+            }
+
+            end + 1
+        };
+
+        let line_end = self.line_end(line_start);
+
+        self.value[line_start..line_end]
+            .grapheme_indices(true)
+            .nth(column)
+            .map_or(line_end, |(start, _)| line_start + start)
+    }
+}
+
+fn is_blank(word: &str) -> bool {
+    word.chars().all(char::is_whitespace)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn typed(text: &str) -> Editor {
+        let mut editor = Editor::new("");
+
+        for grapheme in text.graphemes(true) {
+            let _ = editor.insert(grapheme);
+        }
+
+        editor
+    }
+
+    #[test]
+    fn cursor_moves_over_graphemes() {
+        let mut editor = Editor::new("ae\u{301}o");
+
+        editor.move_cursor(Motion::Left, false);
+        assert_eq!(editor.cursor(), 4);
+
+        editor.move_cursor(Motion::Left, false);
+        assert_eq!(editor.cursor(), 1);
+
+        assert!(editor.delete(Motion::Right));
+        assert_eq!(editor.value(), "ao");
+    }
+
+    #[test]
+    fn cursor_moves_over_words() {
+        let mut editor = Editor::new("hello, big world");
+
+        editor.move_cursor(Motion::WordLeft, false);
+        assert_eq!(editor.cursor(), 11);
+
+        editor.move_cursor(Motion::WordLeft, true);
+        assert_eq!(editor.selected_text(), "big ");
+
+        editor.move_cursor(Motion::Start, false);
+        editor.move_cursor(Motion::WordRight, false);
+        assert_eq!(editor.cursor(), 5);
+
+        assert!(editor.delete(Motion::WordRight));
+        assert_eq!(editor.value(), "hello big world");
+    }
+
+    #[test]
+    fn typing_replaces_the_selection() {
+        let mut editor = Editor::new("hello world");
+
+        editor.select(Selection { anchor: 6, head: 11 });
+        assert!(editor.insert("there"));
+        assert_eq!(editor.value(), "hello there");
+        assert_eq!(editor.selection(), Selection::caret(11));
+
+        editor.move_cursor(Motion::Left, true);
+        editor.move_cursor(Motion::Left, false);
+        assert_eq!(editor.cursor(), 10);
+    }
+
+    #[test]
+    fn single_line_editor_rejects_line_breaks() {
+        let mut single = Editor::new("");
+        let mut multi = Editor::new("").multiline();
+
+        let _ = single.insert("a\r\nb\nc");
+        let _ = multi.insert("a\r\nb\nc");
+
+        assert_eq!(single.value(), "a b c");
+        assert_eq!(multi.value(), "a\nb\nc");
+    }
+
+    #[test]
+    fn vertical_motion_keeps_the_column() {
+        let mut editor = Editor::new("abcd\nx\nabcd").multiline();
+
+        editor.move_cursor(Motion::Left, false);
+        editor.move_cursor(Motion::Up, false);
+        assert_eq!(editor.cursor(), 6);
+
+        editor.move_cursor(Motion::Up, false);
+        assert_eq!(editor.cursor(), 3);
+
+        editor.move_cursor(Motion::Up, false);
+        assert_eq!(editor.cursor(), 0);
+
+        editor.move_cursor(Motion::LineEnd, false);
+        editor.move_cursor(Motion::Down, true);
+        assert_eq!(editor.selected_text(), "\nx");
+    }
+
+    #[test]
+    fn undo_groups_consecutive_typing() {
+        let mut editor = typed("hello world");
+
+        assert!(editor.undo());
+        assert_eq!(editor.value(), "hello ");
+
+        assert!(editor.undo());
+        assert!(editor.undo());
+        assert_eq!(editor.value(), "");
+        assert!(!editor.undo());
+
+        assert!(editor.redo());
+        assert_eq!(editor.value(), "hello");
+        assert_eq!(editor.cursor(), 5);
+
+        let _ = editor.insert("!");
+        assert!(!editor.redo());
+    }
+
+    #[test]
+    fn cut_removes_the_selection() {
+        let mut editor = Editor::new("copy me");
+
+        assert_eq!(editor.cut(), None);
+
+        editor.select_all();
+        assert_eq!(editor.cut().as_deref(), Some("copy me"));
+        assert_eq!(editor.value(), "");
+
+        assert!(editor.undo());
+        assert_eq!(editor.value(), "copy me");
+    }
+
+    #[test]
+    fn set_value_keeps_the_cursor_in_bounds() {
+        let mut editor = Editor::new("hello");
+
+        editor.move_to(2, false);
+        editor.set_value("hé");
+        assert_eq!(editor.cursor(), 1);
+
+        editor.set_value("");
+        assert_eq!(editor.selection(), Selection::caret(0));
+    }
+}
@@ -0,0 +1,59 @@
+//! Handle user input from the windowing system.
+pub mod focus;
+pub mod keyboard;
+pub mod mouse;
+pub mod touch;
+
+use crate::accessibility;
+use std::time::Instant;
+
+/// A user interface event.
+///
+/// Positions are always in logical pixels, relative to the top-left corner of
+/// the window.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Event {
+    /// A mouse event.
+    Mouse(mouse::Event),
+
+    /// A keyboard event.
+    Keyboard(keyboard::Event),
+
+    /// A touch event.
+    Touch(touch::Event),
+
+    /// A focus event.
+    Focus(focus::Event),
+
+    /// A frame is about to be drawn at the given time.
+    ///
+    /// It is only delivered to the widgets that requested it while handling a
+    /// previous event.
+    Frame(Instant),
+
+    /// An assistive technology requested an action on the target widget.
+    ///
+    /// It is only delivered through [`UiTree::perform_action`].
+    ///
+    /// [`UiTree::perform_action`]: ../tree/struct.UiTree.html#method.perform_action
+    Accessibility(accessibility::Action),
+}
+
+/// The phase of the dispatch an [`Event`] is currently in.
+///
+/// An [`Event`] first travels from the root of the tree down to the parent of
+/// its target (capture), then it is delivered to the target itself, and
+/// finally it travels back up to the root (bubble).
+///
+/// [`Event`]: enum.Event.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    /// The event is travelling from the root towards the target.
+    Capture,
+
+    /// The event has reached its target.
+    Target,
+
+    /// The event is travelling from the target back to the root.
+    Bubble,
+}
@@ -0,0 +1,12 @@
+//! Listen to keyboard focus changes.
+
+/// A focus event, delivered to a widget when it gains or loses the keyboard
+/// focus.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Event {
+    /// The widget gained the focus.
+    Gained,
+
+    /// The widget lost the focus.
+    Lost,
+}
@@ -0,0 +1,45 @@
+//! Listen to keyboard events.
+
+/// The code of a key, as reported by the windowing system.
+pub use winit::event::VirtualKeyCode as KeyCode;
+
+/// A keyboard event.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Event {
+    /// A key was pressed.
+    KeyPressed {
+        /// The key identifier.
+        key_code: KeyCode,
+
+        /// The state of the modifier keys.
+        modifiers: ModifiersState,
+    },
+
+    /// A key was released.
+    KeyReleased {
+        /// The key identifier.
+        key_code: KeyCode,
+
+        /// The state of the modifier keys.
+        modifiers: ModifiersState,
+    },
+
+    /// A unicode character was received.
+    CharacterReceived(char),
+}
+
+/// The state of the modifier keys.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct ModifiersState {
+    /// Whether a shift key is pressed.
+    pub shift: bool,
+
+    /// Whether a control key is pressed.
+    pub control: bool,
+
+    /// Whether an alt key is pressed.
+    pub alt: bool,
+
+    /// Whether a logo key is pressed (Windows key, Command key...).
+    pub logo: bool,
+}
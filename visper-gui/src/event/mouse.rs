@@ -0,0 +1,68 @@
+//! Listen to mouse events.
+use visper_graphics::core::point::Point;
+
+/// A mouse event.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Event {
+    /// The cursor entered the bounds of a widget or the window.
+    CursorEntered,
+
+    /// The cursor left the bounds of a widget or the window.
+    CursorLeft,
+
+    /// The cursor moved.
+    CursorMoved {
+        /// The new position of the cursor.
+        position: Point,
+    },
+
+    /// A mouse button was pressed.
+    ButtonPressed(Button),
+
+    /// A mouse button was released.
+    ButtonReleased(Button),
+
+    /// The mouse wheel was scrolled.
+    WheelScrolled {
+        /// The scroll movement.
+        delta: ScrollDelta,
+    },
+}
+
+/// A mouse button.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Button {
+    /// The left mouse button.
+    Left,
+
+    /// The right mouse button.
+    Right,
+
+    /// The middle (wheel) button.
+    Middle,
+
+    /// Some other button.
+    Other(u8),
+}
+
+/// A scroll movement.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ScrollDelta {
+    /// A line-based scroll movement, usually produced by a mouse wheel.
+    Lines {
+        /// The number of horizontal lines scrolled.
+        x: f32,
+
+        /// The number of vertical lines scrolled.
+        y: f32,
+    },
+
+    /// A pixel-based scroll movement, usually produced by a touchpad.
+    Pixels {
+        /// The number of horizontal pixels scrolled.
+        x: f32,
+
+        /// The number of vertical pixels scrolled.
+        y: f32,
+    },
+}
@@ -0,0 +1,73 @@
+//! Listen to touch events.
+use visper_graphics::core::point::Point;
+
+/// A touch event.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Event {
+    /// A finger touched the screen.
+    FingerPressed {
+        /// The finger.
+        id: Finger,
+
+        /// The position of the touch.
+        position: Point,
+    },
+
+    /// A finger moved on the screen.
+    FingerMoved {
+        /// The finger.
+        id: Finger,
+
+        /// The new position of the touch.
+        position: Point,
+    },
+
+    /// A finger was lifted from the screen.
+    FingerLifted {
+        /// The finger.
+        id: Finger,
+
+        /// The last position of the touch.
+        position: Point,
+    },
+
+    /// The system cancelled the tracking of a finger.
+    FingerLost {
+        /// The finger.
+        id: Finger,
+
+        /// The last position of the touch.
+        position: Point,
+    },
+}
+
+/// A unique identifier for a finger on the screen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Finger(pub u64);
+
+impl Event {
+    /// Returns the [`Finger`] of the [`Event`].
+    ///
+    /// [`Finger`]: struct.Finger.html
+    /// [`Event`]: enum.Event.html
+    pub fn finger(&self) -> Finger {
+        match self {
+            Event::FingerPressed { id, .. }
+            | Event::FingerMoved { id, .. }
+            | Event::FingerLifted { id, .. }
+            | Event::FingerLost { id, .. } => *id,
+        }
+    }
+
+    /// Returns the position of the [`Event`].
+    ///
+    /// [`Event`]: enum.Event.html
+    pub fn position(&self) -> Point {
+        match self {
+            Event::FingerPressed { position, .. }
+            | Event::FingerMoved { position, .. }
+            | Event::FingerLifted { position, .. }
+            | Event::FingerLost { position, .. } => *position,
+        }
+    }
+}
@@ -0,0 +1,268 @@
+//! Position the nodes of a [`UiTree`].
+//!
+//! [`UiTree`]: ../tree/struct.UiTree.html
+use crate::style::Length;
+use crate::tree::Node;
+use indextree::{Arena, NodeId};
+use visper_graphics::core::rectangle::Rectangle;
+use visper_graphics::core::size::Size;
+use visper_graphics::core::vector::Vector;
+
+/// The direction children are laid out in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Axis {
+    /// Children are placed from left to right.
+    Horizontal,
+
+    /// Children are placed from top to bottom.
+    Vertical,
+}
+
+impl Axis {
+    fn main(self, size: Size) -> f32 {
+        match self {
+            Axis::Horizontal => size.width,
+            Axis::Vertical => size.height,
+        }
+    }
+
+    fn cross(self, size: Size) -> f32 {
+        match self {
+            Axis::Horizontal => size.height,
+            Axis::Vertical => size.width,
+        }
+    }
+
+    fn main_length(self, width: Length, height: Length) -> Length {
+        match self {
+            Axis::Horizontal => width,
+            Axis::Vertical => height,
+        }
+    }
+
+    fn cross_length(self, width: Length, height: Length) -> Length {
+        match self {
+            Axis::Horizontal => height,
+            Axis::Vertical => width,
+        }
+    }
+
+    fn pack(self, main: f32, cross: f32) -> (f32, f32) {
+        match self {
+            Axis::Horizontal => (main, cross),
+            Axis::Vertical => (cross, main),
+        }
+    }
+}
+
+/// The scroll position of the children of a widget.
+///
+/// The children of a scrolling widget are laid out without limits along its
+/// scrolling directions, moved back by the offset and clipped to the bounds of
+/// the widget.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Scroll {
+    /// The amount the children are moved back by.
+    pub offset: Vector,
+
+    /// Whether the children can overflow horizontally.
+    pub horizontal: bool,
+
+    /// Whether the children can overflow vertically.
+    pub vertical: bool,
+}
+
+/// Computes the preferred size of a node given the available space.
+///
+/// [`Length::Fill`] is treated as [`Length::Shrink`] here; filling is resolved
+/// by [`arrange`] once the space left by the siblings is known.
+///
+/// [`Length::Fill`]: ../style/enum.Length.html#variant.Fill
+/// [`Length::Shrink`]: ../style/enum.Length.html#variant.Shrink
+/// [`arrange`]: fn.arrange.html
+pub(crate) fn measure<Message>(
+    arena: &Arena<Node<Message>>,
+    id: NodeId,
+    available: Size,
+) -> Size {
+    let widget = &arena[id].get().widget;
+    let style = widget.style();
+    let inner = available.pad(style.padding);
+
+    let content = if id.children(arena).next().is_none() {
+        widget.measure(inner)
+    } else {
+        content(arena, id, unbounded(inner, widget.scroll()))
+    };
+
+    Size::new(
+        preferred(style.width, content.width + style.padding * 2.0),
+        preferred(style.height, content.height + style.padding * 2.0),
+    )
+}
+
+/// Returns the size taken by the children of a node, laid out along its axis.
+fn content<Message>(arena: &Arena<Node<Message>>, id: NodeId, available: Size) -> Size {
+    let widget = &arena[id].get().widget;
+    let style = widget.style();
+    let axis = widget.axis();
+
+    let mut main = 0.0f32;
+    let mut cross = 0.0f32;
+    let mut count = 0;
+
+    for child in id.children(arena) {
+        let size = measure(arena, child, available);
+
+        main += axis.main(size);
+        cross = cross.max(axis.cross(size));
+        count += 1;
+    }
+
+    if count > 0 {
+        main += style.spacing * (count - 1) as f32;
+    }
+
+    let (width, height) = axis.pack(main, cross);
+
+    Size::new(width, height)
+}
+
+/// Lifts the limits of the available space along the scrolling directions.
+fn unbounded(available: Size, scroll: Option<Scroll>) -> Size {
+    match scroll {
+        Some(scroll) => Size::new(
+            if scroll.horizontal { std::f32::INFINITY } else { available.width },
+            if scroll.vertical { std::f32::INFINITY } else { available.height },
+        ),
+        None => available,
+    }
+}
+
+/// Assigns the given bounds to a node and lays out its descendants.
+///
+/// The children of a scrolling node are laid out in the space they need,
+/// moved back by its scroll offset.
+pub(crate) fn arrange<Message>(
+    arena: &mut Arena<Node<Message>>,
+    id: NodeId,
+    bounds: Rectangle,
+) {
+    let (style, axis) = {
+        let node = arena[id].get_mut();
+        node.bounds = bounds;
+
+        (node.widget.style(), node.widget.axis())
+    };
+
+    let inner = Rectangle {
+        x: bounds.x + style.padding,
+        y: bounds.y + style.padding,
+        width: (bounds.width - style.padding * 2.0).max(0.0),
+        height: (bounds.height - style.padding * 2.0).max(0.0),
+    };
+
+    let inner = match arena[id].get().widget.scroll() {
+        Some(scroll) => {
+            let viewport = Size::new(inner.width, inner.height);
+            let content = content(arena, id, unbounded(viewport, Some(scroll)));
+
+            let widget = &mut arena[id].get_mut().widget;
+            widget.set_content_size(viewport, content);
+
+            let offset = widget.scroll().unwrap_or(scroll).offset;
+
+            Rectangle {
+                x: inner.x - offset.x,
+                y: inner.y - offset.y,
+                width: if scroll.horizontal {
+                    inner.width.max(content.width)
+                } else {
+                    inner.width
+                },
+                height: if scroll.vertical {
+                    inner.height.max(content.height)
+                } else {
+                    inner.height
+                },
+            }
+        }
+        None => inner,
+    };
+
+    let children: Vec<NodeId> = id.children(arena).collect();
+
+    if children.is_empty() {
+        return;
+    }
+
+    let available = Size::new(inner.width, inner.height);
+
+    let mut sizes = Vec::with_capacity(children.len());
+    let mut fixed = style.spacing * (children.len() - 1) as f32;
+    let mut fill_count = 0;
+
+    for &child in &children {
+        let child_style = arena[child].get().widget.style();
+        let preferred = measure(arena, child, available);
+
+        let main_length = axis.main_length(child_style.width, child_style.height);
+        let cross_length = axis.cross_length(child_style.width, child_style.height);
+
+        let main = match main_length {
+            Length::Fill => {
+                fill_count += 1;
+                None
+            }
+            _ => {
+                fixed += axis.main(preferred);
+                Some(axis.main(preferred))
+            }
+        };
+
+        let cross = match cross_length {
+            Length::Fill => axis.cross(available),
+            _ => axis.cross(preferred).min(axis.cross(available)),
+        };
+
+        sizes.push((main, cross));
+    }
+
+    let remaining = (axis.main(available) - fixed).max(0.0);
+    let fill = if fill_count > 0 {
+        remaining / fill_count as f32
+    } else {
+        0.0
+    };
+
+    let total: f32 = fixed + fill * fill_count as f32;
+    let mut offset = style.justify_content.offset(axis.main(available), total);
+
+    for (child, (main, cross)) in children.into_iter().zip(sizes) {
+        let main = main.unwrap_or(fill);
+        let cross_offset = style.align_items.offset(axis.cross(available), cross);
+
+        let (x, y) = axis.pack(offset, cross_offset);
+        let (width, height) = axis.pack(main, cross);
+
+        arrange(
+            arena,
+            child,
+            Rectangle {
+                x: inner.x + x,
+                y: inner.y + y,
+                width,
+                height,
+            },
+        );
+
+        offset += main + style.spacing;
+    }
+}
+
+fn preferred(length: Length, content: f32) -> f32 {
+    match length {
+        Length::Units(units) => units,
+        Length::Fill | Length::Shrink => content,
+    }
+}
@@ -1,6 +1,35 @@
 use winit::window::CursorIcon::NoDrop;
 
+pub mod accessibility;
+pub mod animation;
+pub mod application;
+pub mod clipboard;
+pub mod command;
+pub mod conversion;
+#[cfg(feature = "serde")]
+pub mod description;
+pub mod editor;
+pub mod event;
+pub mod layout;
 pub mod proxy;
+pub mod reload;
+pub mod runtime;
+pub mod style;
+pub mod testing;
+pub mod theme;
+pub mod tree;
+mod view;
+pub mod widget;
+pub mod window;
+
+pub use application::Application;
+pub use clipboard::Clipboard;
+pub use command::Command;
+pub use event::Event;
+pub use style::{Align, Length, Style};
+pub use theme::Theme;
+pub use tree::UiTree;
+pub use widget::{Element, Widget};
 
 #[derive(Clone, Eq, PartialEq, Debug)]
 struct Node {
@@ -9,25 +38,6 @@ struct Node {
     pub next_sibling: Option<Box<Node>>,
 }
 
-/*
-let container = Container::new();
-container
-    .append(Row::new())
-    .append(Col::new())
-
-let container = Row::new()
-    .append(Col::new())
-
-let container = Container::Row {
-    style: Style::new()
-
-}.append(Container::Col {
-
-}).append(Container::Col{})
-let w = Window::new(container);
-
-*/
-
 #[cfg(test)]
 mod tests {
     use super::*;
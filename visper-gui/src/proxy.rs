@@ -1,43 +1,141 @@
-use futures::channel::mpsc;
-use std::task::{Poll, Context };
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::task::{Poll, Context, Waker};
 use std::pin::Pin;
 use futures_sink::Sink;
+use winit::event_loop::{EventLoopClosed, EventLoopProxy};
 
+use crate::runtime::UserEvent;
+
+/// A [`Sink`] that forwards messages to a winit event loop.
+///
+/// By default a [`Proxy`] is unbounded: every message is pushed straight into
+/// the winit queue. A bounded [`Proxy`] keeps track of the messages that have
+/// not been acknowledged by the event loop yet and stops accepting new ones
+/// once its capacity is reached, so fast producers wait instead of flooding
+/// the queue.
+///
+/// [`Sink`]: https://docs.rs/futures/0.3/futures/sink/trait.Sink.html
+/// [`Proxy`]: struct.Proxy.html
 pub struct Proxy<Message: 'static> {
-    raw: winit::event_loop::EventLoopProxy<Message>,
+    raw: Raw<Message>,
+    slot: Slot,
+}
+
+/// The event loop a [`Proxy`] sends its messages to.
+///
+/// [`Proxy`]: struct.Proxy.html
+enum Raw<Message: 'static> {
+    /// An event loop of messages.
+    Messages(EventLoopProxy<Message>),
+
+    /// The event loop of the runtime, which is also woken up for other
+    /// reasons.
+    Runtime(EventLoopProxy<UserEvent<Message>>),
+}
+
+impl<Message: 'static> Raw<Message> {
+    fn send_event(&self, message: Message) -> Result<(), EventLoopClosed<Message>> {
+        match self {
+            Raw::Messages(raw) => raw.send_event(message),
+            Raw::Runtime(raw) => raw
+                .send_event(UserEvent::Message(message))
+                .map_err(|EventLoopClosed(event)| match event {
+                    UserEvent::Message(message) => EventLoopClosed(message),
+                    UserEvent::Reload => unreachable!("Only messages are sent"),
+                }),
+        }
+    }
 }
 
 impl<Message: 'static> Clone for Proxy<Message> {
     fn clone(&self) -> Self {
         Self {
-            raw: self.raw.clone(),
+            raw: match &self.raw {
+                Raw::Messages(raw) => Raw::Messages(raw.clone()),
+                Raw::Runtime(raw) => Raw::Runtime(raw.clone()),
+            },
+            slot: self.slot.clone(),
         }
     }
 }
 
+// The proxy is never pinned structurally
+impl<Message: 'static> Unpin for Proxy<Message> {}
+
 impl<Message: 'static> Proxy<Message> {
-    pub fn new(raw: winit::event_loop::EventLoopProxy<Message>) -> Self {
-        Self { raw }
+    /// Creates an unbounded [`Proxy`].
+    ///
+    /// [`Proxy`]: struct.Proxy.html
+    pub fn new(raw: EventLoopProxy<Message>) -> Self {
+        Self {
+            raw: Raw::Messages(raw),
+            slot: Slot::new(None),
+        }
+    }
+
+    /// Creates a [`Proxy`] that allows at most `capacity` messages in flight.
+    ///
+    /// The event loop must call [`acknowledge`] for every message it receives,
+    /// otherwise the senders will stay pending forever.
+    ///
+    /// [`Proxy`]: struct.Proxy.html
+    /// [`acknowledge`]: #method.acknowledge
+    pub fn bounded(raw: EventLoopProxy<Message>, capacity: usize) -> Self {
+        Self {
+            raw: Raw::Messages(raw),
+            slot: Slot::new(Some(Arc::new(Backpressure::new(capacity)))),
+        }
+    }
+
+    /// Creates a [`Proxy`] to the event loop of the runtime, bounded if a
+    /// capacity is given.
+    ///
+    /// [`Proxy`]: struct.Proxy.html
+    pub(crate) fn runtime(raw: EventLoopProxy<UserEvent<Message>>, capacity: Option<usize>) -> Self {
+        Self {
+            raw: Raw::Runtime(raw),
+            slot: Slot::new(capacity.map(|capacity| Arc::new(Backpressure::new(capacity)))),
+        }
+    }
+
+    /// Marks the oldest message sent but not acknowledged yet as handled by
+    /// the event loop. If it was sent with a slot reserved by [`poll_ready`],
+    /// the slot is released and any sender waiting for capacity is woken up.
+    ///
+    /// It is a no-op for an unbounded [`Proxy`].
+    ///
+    /// [`Proxy`]: struct.Proxy.html
+    /// [`poll_ready`]: #method.poll_ready
+    pub fn acknowledge(&self) {
+        self.slot.acknowledge();
+    }
+
+    /// Returns the number of messages sent but not acknowledged yet.
+    pub fn in_flight(&self) -> usize {
+        self.slot.in_flight()
     }
 }
 
 impl<Message: 'static> Sink<Message> for Proxy<Message> {
-    type Error = mpsc::SendError;
+    type Error = EventLoopClosed<Message>;
 
     fn poll_ready(
         self: Pin<&mut Self>,
-        _cx: &mut Context<'_>,
+        cx: &mut Context<'_>,
     ) -> Poll<Result<(), Self::Error>> {
-        Poll::Ready(Ok(()))
+        self.get_mut().slot.poll_reserve(cx.waker()).map(Ok)
     }
 
     fn start_send(
         self: Pin<&mut Self>,
         message: Message,
     ) -> Result<(), Self::Error> {
-        let _ = self.raw.send_event(message);
+        let proxy = self.get_mut();
+        let raw = &proxy.raw;
 
-        Ok(())
+        proxy.slot.send(|| raw.send_event(message))
     }
 
     fn poll_flush(
@@ -54,3 +152,255 @@ impl<Message: 'static> Sink<Message> for Proxy<Message> {
         Poll::Ready(Ok(()))
     }
 }
+
+/// The capacity a [`Proxy`] takes between [`poll_ready`] and [`start_send`].
+///
+/// [`Proxy`]: struct.Proxy.html
+/// [`poll_ready`]: struct.Proxy.html#method.poll_ready
+/// [`start_send`]: struct.Proxy.html#method.start_send
+#[derive(Debug)]
+struct Slot {
+    backpressure: Option<Arc<Backpressure>>,
+    is_reserved: bool,
+}
+
+impl Slot {
+    fn new(backpressure: Option<Arc<Backpressure>>) -> Self {
+        Self {
+            backpressure,
+            is_reserved: false,
+        }
+    }
+
+    fn in_flight(&self) -> usize {
+        self.backpressure
+            .as_ref()
+            .map(|backpressure| backpressure.in_flight())
+            .unwrap_or(0)
+    }
+
+    /// Reserves a slot for the next message, unless one is reserved already.
+    fn poll_reserve(&mut self, waker: &Waker) -> Poll<()> {
+        match &self.backpressure {
+            Some(backpressure) if !self.is_reserved => match backpressure.poll_reserve(waker) {
+                Poll::Ready(()) => {
+                    self.is_reserved = true;
+
+                    Poll::Ready(())
+                }
+                Poll::Pending => Poll::Pending,
+            },
+            _ => Poll::Ready(()),
+        }
+    }
+
+    /// Sends a message to the event loop, handing the reserved slot, if any,
+    /// over to it until it is acknowledged.
+    fn send<Message>(
+        &mut self,
+        send: impl FnOnce() -> Result<(), EventLoopClosed<Message>>,
+    ) -> Result<(), EventLoopClosed<Message>> {
+        let is_reserved = std::mem::replace(&mut self.is_reserved, false);
+
+        match &self.backpressure {
+            Some(backpressure) => backpressure.send(is_reserved, send),
+            None => send(),
+        }
+    }
+
+    fn acknowledge(&self) {
+        if let Some(backpressure) = &self.backpressure {
+            backpressure.acknowledge();
+        }
+    }
+}
+
+// Clones share the capacity, but not the slot reserved for the next message
+impl Clone for Slot {
+    fn clone(&self) -> Self {
+        Self::new(self.backpressure.clone())
+    }
+}
+
+#[derive(Debug)]
+struct Backpressure {
+    capacity: usize,
+    in_flight: AtomicUsize,
+    // Whether each message waiting for the event loop holds a slot, in the
+    // order the event loop receives them
+    sent: Mutex<VecDeque<bool>>,
+    wakers: Mutex<Vec<Waker>>,
+}
+
+impl Backpressure {
+    fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            in_flight: AtomicUsize::new(0),
+            sent: Mutex::new(VecDeque::new()),
+            wakers: Mutex::new(Vec::new()),
+        }
+    }
+
+    fn in_flight(&self) -> usize {
+        self.in_flight.load(Ordering::Acquire)
+    }
+
+    fn try_reserve(&self) -> bool {
+        let mut current = self.in_flight.load(Ordering::Acquire);
+
+        while current < self.capacity {
+            match self.in_flight.compare_exchange_weak(
+                current,
+                current + 1,
+                Ordering::AcqRel,
+                Ordering::Acquire,
+            ) {
+                Ok(_) => return true,
+                Err(actual) => current = actual,
+            }
+        }
+
+        false
+    }
+
+    fn poll_reserve(&self, waker: &Waker) -> Poll<()> {
+        if self.try_reserve() {
+            return Poll::Ready(());
+        }
+
+        self.wakers.lock().expect("Lock proxy wakers").push(waker.clone());
+
+        // A slot may have been released while the waker was being registered.
+        if self.try_reserve() {
+            Poll::Ready(())
+        } else {
+            Poll::Pending
+        }
+    }
+
+    fn send<Message>(
+        &self,
+        is_reserved: bool,
+        send: impl FnOnce() -> Result<(), EventLoopClosed<Message>>,
+    ) -> Result<(), EventLoopClosed<Message>> {
+        // Sending under the lock keeps the order of `sent` and of the queue of
+        // the event loop the same
+        let mut sent = self.sent.lock().expect("Lock sent messages");
+        let result = send();
+
+        match result {
+            Ok(()) => sent.push_back(is_reserved),
+            // The event loop is gone, the slot would never be acknowledged
+            Err(_) if is_reserved => self.release(),
+            Err(_) => {}
+        }
+
+        result
+    }
+
+    fn acknowledge(&self) {
+        let is_reserved = self
+            .sent
+            .lock()
+            .expect("Lock sent messages")
+            .pop_front()
+            .unwrap_or(false);
+
+        if is_reserved {
+            self.release();
+        }
+    }
+
+    fn release(&self) {
+        self.in_flight.fetch_sub(1, Ordering::AcqRel);
+
+        let wakers = std::mem::take(
+            &mut *self.wakers.lock().expect("Lock proxy wakers"),
+        );
+
+        wakers.into_iter().for_each(Waker::wake);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::task::noop_waker;
+
+    #[test]
+    fn backpressure_is_bounded_by_capacity() {
+        let backpressure = Backpressure::new(2);
+        let waker = noop_waker();
+
+        assert_eq!(backpressure.poll_reserve(&waker), Poll::Ready(()));
+        assert_eq!(backpressure.poll_reserve(&waker), Poll::Ready(()));
+        assert_eq!(backpressure.poll_reserve(&waker), Poll::Pending);
+
+        backpressure.release();
+
+        assert_eq!(backpressure.in_flight(), 1);
+        assert_eq!(backpressure.poll_reserve(&waker), Poll::Ready(()));
+    }
+
+    #[test]
+    fn polling_again_keeps_the_reserved_slot() {
+        let mut slot = Slot::new(Some(Arc::new(Backpressure::new(2))));
+        let waker = noop_waker();
+
+        assert_eq!(slot.poll_reserve(&waker), Poll::Ready(()));
+        assert_eq!(slot.poll_reserve(&waker), Poll::Ready(()));
+        assert_eq!(slot.in_flight(), 1);
+
+        assert!(slot.send::<()>(|| Ok(())).is_ok());
+        assert_eq!(slot.in_flight(), 1);
+
+        assert_eq!(slot.poll_reserve(&waker), Poll::Ready(()));
+        assert_eq!(slot.in_flight(), 2);
+    }
+
+    #[test]
+    fn closed_event_loops_return_the_message() {
+        let mut slot = Slot::new(Some(Arc::new(Backpressure::new(1))));
+        let waker = noop_waker();
+
+        assert_eq!(slot.poll_reserve(&waker), Poll::Ready(()));
+
+        match slot.send(|| Err(EventLoopClosed("Hello"))) {
+            Err(EventLoopClosed(message)) => assert_eq!(message, "Hello"),
+            Ok(()) => panic!("Sent to a closed event loop"),
+        }
+
+        assert_eq!(slot.in_flight(), 0);
+        assert_eq!(slot.poll_reserve(&waker), Poll::Ready(()));
+    }
+
+    #[test]
+    fn only_messages_sent_with_a_slot_release_one() {
+        let mut slot = Slot::new(Some(Arc::new(Backpressure::new(1))));
+        let waker = noop_waker();
+
+        // Sent without polling for a slot first
+        assert!(slot.send::<()>(|| Ok(())).is_ok());
+        assert_eq!(slot.in_flight(), 0);
+
+        assert_eq!(slot.poll_reserve(&waker), Poll::Ready(()));
+        assert!(slot.send::<()>(|| Ok(())).is_ok());
+        assert_eq!(slot.in_flight(), 1);
+
+        slot.acknowledge();
+
+        assert_eq!(slot.in_flight(), 1);
+        assert_eq!(slot.poll_reserve(&waker), Poll::Pending);
+
+        slot.acknowledge();
+
+        assert_eq!(slot.in_flight(), 0);
+        assert_eq!(slot.poll_reserve(&waker), Poll::Ready(()));
+
+        // Acknowledging more messages than were sent does not free more slots
+        slot.acknowledge();
+
+        assert_eq!(slot.in_flight(), 1);
+    }
+}
@@ -0,0 +1,337 @@
+//! Reload parts of an application when the files they come from change.
+//!
+//! An [`Application`] lists the files it depends on, like layout
+//! descriptions and themes, in [`Application::watch`]. The runtime watches
+//! them with a [`Watcher`] and calls [`Application::reload`] when one
+//! changes, which returns what needs to be [`Reload`]ed.
+//!
+//! [`Application`]: ../application/trait.Application.html
+//! [`Application::watch`]: ../application/trait.Application.html#method.watch
+//! [`Application::reload`]: ../application/trait.Application.html#method.reload
+//! [`Watcher`]: struct.Watcher.html
+//! [`Reload`]: enum.Reload.html
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+
+/// The time between two checks for changes.
+pub const INTERVAL: Duration = Duration::from_millis(250);
+
+/// What an [`Application`] needs to reload after a file changed.
+///
+/// [`Application`]: ../application/trait.Application.html
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Reload {
+    /// Nothing depends on the file.
+    Nothing,
+
+    /// The [`Theme`] changed.
+    ///
+    /// [`Theme`]: ../theme/struct.Theme.html
+    Theme,
+
+    /// The nodes under the node with the given key changed.
+    ///
+    /// Only that node and its descendants are rebuilt. The state of the
+    /// widgets found at the same position under it, like scroll offsets and
+    /// the contents of text inputs, is kept.
+    Node(String),
+
+    /// The whole view changed.
+    View,
+}
+
+/// Watches some files for changes.
+///
+/// With the `notify` feature, the [`Watcher`] is notified of changes by the
+/// operating system, and wakes up the event loop when one happens.
+/// Otherwise, or if the operating system cannot watch the files, it polls
+/// their modification times every [`INTERVAL`].
+///
+/// [`Watcher`]: struct.Watcher.html
+/// [`INTERVAL`]: constant.INTERVAL.html
+pub struct Watcher {
+    backend: Backend,
+    last_check: Instant,
+}
+
+enum Backend {
+    Polling(HashMap<PathBuf, Option<Stamp>>),
+    #[cfg(feature = "notify")]
+    Native(native::Watcher),
+}
+
+/// The modification time and length of a file, or `None` if it is missing.
+type Stamp = (Option<SystemTime>, u64);
+
+impl Watcher {
+    /// Creates a [`Watcher`] for the given files, which calls `wake` when
+    /// the operating system reports a change.
+    ///
+    /// [`Watcher`]: struct.Watcher.html
+    pub fn new<F>(paths: Vec<PathBuf>, wake: F) -> Self
+    where
+        F: Fn() + Send + 'static,
+    {
+        #[cfg(feature = "notify")]
+        {
+            if let Some(watcher) = native::Watcher::new(&paths, wake) {
+                return Watcher {
+                    backend: Backend::Native(watcher),
+                    last_check: Instant::now(),
+                };
+            }
+        }
+
+        #[cfg(not(feature = "notify"))]
+        let _ = wake;
+
+        Self::polling(paths)
+    }
+
+    /// Creates a [`Watcher`] that polls the modification times of the given
+    /// files.
+    ///
+    /// [`Watcher`]: struct.Watcher.html
+    pub fn polling(paths: Vec<PathBuf>) -> Self {
+        let stamps = paths
+            .into_iter()
+            .map(|path| {
+                let stamp = stamp(&path);
+
+                (path, stamp)
+            })
+            .collect();
+
+        Watcher {
+            backend: Backend::Polling(stamps),
+            last_check: Instant::now(),
+        }
+    }
+
+    /// Returns true if the [`Watcher`] watches no files.
+    ///
+    /// [`Watcher`]: struct.Watcher.html
+    pub fn is_empty(&self) -> bool {
+        match &self.backend {
+            Backend::Polling(stamps) => stamps.is_empty(),
+            #[cfg(feature = "notify")]
+            Backend::Native(watcher) => watcher.is_empty(),
+        }
+    }
+
+    /// Returns when the files should be polled again, or `None` if there is
+    /// nothing to watch or if the operating system reports the changes.
+    pub fn next_check(&self) -> Option<Instant> {
+        if self.is_polling() && !self.is_empty() {
+            Some(self.last_check + INTERVAL)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the files that changed since the last check, if it is time to
+    /// check them again.
+    pub fn changed(&mut self, now: Instant) -> Vec<PathBuf> {
+        if self.is_polling() {
+            if self.next_check().is_none_or(|next_check| now < next_check) {
+                return Vec::new();
+            }
+
+            self.last_check = now;
+        }
+
+        self.check()
+    }
+
+    fn is_polling(&self) -> bool {
+        match &self.backend {
+            Backend::Polling(_) => true,
+            #[cfg(feature = "notify")]
+            Backend::Native(_) => false,
+        }
+    }
+
+    /// Returns the files that changed since the last check.
+    pub fn check(&mut self) -> Vec<PathBuf> {
+        match &mut self.backend {
+            Backend::Polling(stamps) => {
+                let mut changed = Vec::new();
+
+                for (path, last) in stamps.iter_mut() {
+                    let current = stamp(path);
+
+                    if current != *last {
+                        *last = current;
+                        changed.push(path.clone());
+                    }
+                }
+
+                changed.sort();
+                changed
+            }
+            #[cfg(feature = "notify")]
+            Backend::Native(watcher) => watcher.changed(),
+        }
+    }
+}
+
+fn stamp(path: &Path) -> Option<Stamp> {
+    fs::metadata(path)
+        .ok()
+        .map(|metadata| (metadata.modified().ok(), metadata.len()))
+}
+
+#[cfg(feature = "notify")]
+mod native {
+    use notify::{DebouncedEvent, RecommendedWatcher, RecursiveMode, Watcher as _};
+    use std::collections::HashMap;
+    use std::path::{Path, PathBuf};
+    use std::sync::mpsc::{self, Receiver};
+    use std::thread;
+    use std::time::Duration;
+
+    /// Watches the directories of the files rather than the files
+    /// themselves, so files replaced by editors on save are still followed.
+    pub struct Watcher {
+        _raw: RecommendedWatcher,
+        changes: Receiver<PathBuf>,
+        is_empty: bool,
+    }
+
+    impl Watcher {
+        pub fn new<F>(paths: &[PathBuf], wake: F) -> Option<Self>
+        where
+            F: Fn() + Send + 'static,
+        {
+            let (sender, events) = mpsc::channel();
+            let (forward, changes) = mpsc::channel();
+            let mut raw = notify::watcher(sender, Duration::from_millis(50)).ok()?;
+            let mut watched = HashMap::new();
+
+            for path in paths {
+                let directory = match path.parent() {
+                    Some(parent) if parent != Path::new("") => parent,
+                    _ => Path::new("."),
+                };
+                let directory = directory.canonicalize().ok()?;
+
+                raw.watch(&directory, RecursiveMode::NonRecursive).ok()?;
+
+                let _ = watched.insert(directory.join(path.file_name()?), path.clone());
+            }
+
+            let is_empty = watched.is_empty();
+
+            // Stops once the watcher, or the receiver of its changes, is dropped
+            let _ = thread::spawn(move || {
+                for event in events {
+                    let path = match event {
+                        DebouncedEvent::Create(path)
+                        | DebouncedEvent::Write(path)
+                        | DebouncedEvent::Remove(path)
+                        | DebouncedEvent::Rename(_, path) => path,
+                        _ => continue,
+                    };
+
+                    if let Some(path) = watched.get(&path) {
+                        if forward.send(path.clone()).is_err() {
+                            break;
+                        }
+
+                        wake();
+                    }
+                }
+            });
+
+            Some(Watcher {
+                _raw: raw,
+                changes,
+                is_empty,
+            })
+        }
+
+        pub fn is_empty(&self) -> bool {
+            self.is_empty
+        }
+
+        pub fn changed(&mut self) -> Vec<PathBuf> {
+            let mut changed: Vec<PathBuf> = self.changes.try_iter().collect();
+
+            changed.sort();
+            changed.dedup();
+            changed
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn polling_watcher_reports_changed_files() {
+        let directory = std::env::temp_dir().join(format!("visper-reload-{}", std::process::id()));
+        let layout = directory.join("layout.ron");
+        let theme = directory.join("theme.ron");
+
+        fs::create_dir_all(&directory).unwrap();
+        fs::write(&layout, "Column()").unwrap();
+        fs::write(&theme, "()").unwrap();
+
+        let mut watcher = Watcher::polling(vec![layout.clone(), theme.clone()]);
+        let start = Instant::now();
+
+        assert!(watcher.check().is_empty());
+
+        fs::write(&layout, "Column(style: (padding: 10.0))").unwrap();
+        assert!(watcher.changed(start).is_empty());
+        assert_eq!(watcher.changed(start + INTERVAL), vec![layout.clone()]);
+
+        fs::remove_file(&theme).unwrap();
+        assert_eq!(watcher.check(), vec![theme]);
+        assert!(watcher.check().is_empty());
+
+        fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn polling_watcher_checks_once_per_interval() {
+        let mut watcher = Watcher::polling(vec![PathBuf::from("missing.ron")]);
+        let next_check = watcher.next_check().unwrap();
+
+        assert!(watcher.changed(next_check - Duration::from_millis(1)).is_empty());
+        assert_eq!(watcher.next_check(), Some(next_check));
+
+        let late = next_check + INTERVAL / 2;
+
+        assert!(watcher.changed(late).is_empty());
+        assert_eq!(watcher.next_check(), Some(late + INTERVAL));
+        assert_eq!(Watcher::polling(Vec::new()).next_check(), None);
+    }
+
+    #[test]
+    #[cfg(feature = "notify")]
+    fn native_watcher_wakes_up_the_event_loop() {
+        let directory = std::env::temp_dir().join(format!("visper-notify-{}", std::process::id()));
+        let layout = directory.join("layout.ron");
+
+        fs::create_dir_all(&directory).unwrap();
+        fs::write(&layout, "Column()").unwrap();
+
+        let (wake, woken) = std::sync::mpsc::channel();
+        let mut watcher = Watcher::new(vec![layout.clone()], move || {
+            let _ = wake.send(());
+        });
+
+        assert_eq!(watcher.next_check(), None);
+
+        fs::write(&layout, "Column(style: (padding: 10.0))").unwrap();
+
+        assert!(woken.recv_timeout(Duration::from_secs(5)).is_ok());
+        assert_eq!(watcher.changed(Instant::now()), vec![layout]);
+
+        fs::remove_dir_all(&directory).unwrap();
+    }
+}
@@ -0,0 +1,637 @@
+use crate::accessibility::{self, Adapter};
+use crate::application::Application;
+use crate::clipboard::Clipboard;
+use crate::command::Command;
+use crate::conversion;
+use crate::event::keyboard;
+use crate::proxy::Proxy;
+use crate::reload::{Reload, Watcher};
+use crate::tree::UiTree;
+use crate::widget::Element;
+use crate::window;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::rc::Rc;
+use std::time::Instant;
+use visper_graphics::core::point::Point;
+use visper_graphics::renderer::stats::FrameStats;
+use visper_graphics::renderer::target::Target;
+use visper_graphics::renderer::Renderer;
+use winit::{
+    dpi::LogicalSize,
+    event::{DeviceEvent, Event, WindowEvent},
+    event_loop::{ControlFlow, EventLoop, EventLoopWindowTarget},
+    window::{WindowBuilder, WindowId},
+};
+
+/// Runs an [`Application`] in a new window.
+///
+/// Every window has its own [`Target`], widget tree and accessibility
+/// [`Adapter`], but they all share the same [`Renderer`], and so its device
+/// and atlases, and the same clipboard.
+///
+/// [`Application`]: ../application/trait.Application.html
+/// [`Adapter`]: ../accessibility/trait.Adapter.html
+/// [`Target`]: ../../visper_graphics/renderer/target/struct.Target.html
+/// [`Renderer`]: ../../visper_graphics/renderer/struct.Renderer.html
+pub fn run<A: Application>() {
+    let event_loop = EventLoop::<UserEvent<A::Message>>::with_user_event();
+    let mut application = A::new();
+    let mut renderer = Renderer::new();
+
+    let proxy = Proxy::runtime(event_loop.create_proxy(), application.capacity());
+    application.start(proxy.clone());
+
+    let raw = WindowBuilder::new()
+        .with_title(application.title())
+        .build(&event_loop)
+        .expect("Open window");
+
+    let mut theme = application.theme();
+    let clipboard = Shared::new(application.clipboard());
+    let mut windows = Windows::new();
+    windows.open(Window::new(
+        window::Id::MAIN,
+        raw,
+        &renderer,
+        application.view(),
+        &clipboard,
+        application.accessibility(window::Id::MAIN),
+    ));
+
+    let reload = event_loop.create_proxy();
+    let mut watcher = Watcher::new(application.watch(), move || {
+        let _ = reload.send_event(UserEvent::Reload);
+    });
+    let mut modifiers = keyboard::ModifiersState::default();
+    let mut messages = Vec::new();
+    let mut commands = Vec::new();
+
+    event_loop.run(move |event, event_loop, control_flow| {
+        match event {
+            Event::WindowEvent { window_id, event } => {
+                let id = match windows.find(window_id) {
+                    Some(id) => id,
+                    None => return,
+                };
+
+                match &event {
+                    WindowEvent::CloseRequested => {
+                        commands.push(application.close_requested(id));
+                    }
+                    WindowEvent::Focused(true) => {
+                        windows.focus(id);
+                    }
+                    _ => {}
+                }
+
+                let window = windows.get_mut(id);
+
+                match &event {
+                    WindowEvent::Resized(size) => {
+                        window.resize(size.width, size.height, window.raw.scale_factor());
+                    }
+                    WindowEvent::ScaleFactorChanged {
+                        scale_factor,
+                        new_inner_size,
+                    } => {
+                        window.resize(new_inner_size.width, new_inner_size.height, *scale_factor);
+                    }
+                    _ => {}
+                }
+
+                if let Some(event) = conversion::window_event(
+                    &event,
+                    window.target.scale_factor(),
+                    modifiers,
+                ) {
+                    window.layout();
+                    window.tree.dispatch(&event, &mut messages);
+                    window.raw.request_redraw();
+                }
+            }
+            Event::DeviceEvent {
+                event: DeviceEvent::ModifiersChanged(new_modifiers),
+                ..
+            } => {
+                modifiers = conversion::modifiers_state(new_modifiers);
+            }
+            Event::UserEvent(UserEvent::Message(message)) => {
+                proxy.acknowledge();
+                messages.push(message);
+            }
+            Event::UserEvent(UserEvent::Reload) => {
+                // The changed files are reloaded once the events are cleared
+            }
+            Event::MainEventsCleared => {
+                for path in watcher.changed(Instant::now()) {
+                    match application.reload(&path) {
+                        Reload::Nothing => continue,
+                        Reload::Theme => theme = application.theme(),
+                        Reload::Node(key) => {
+                            let mut is_found = false;
+
+                            for window in windows.iter_mut() {
+                                let root = view(&mut application, window.id);
+
+                                is_found |= window.tree.rebuild_key(&key, root);
+                            }
+
+                            if !is_found {
+                                windows.rebuild(&mut application);
+                            }
+                        }
+                        Reload::View => windows.rebuild(&mut application),
+                    }
+
+                    windows.request_redraw();
+                }
+
+                for window in windows.iter_mut() {
+                    window.perform_requests(&mut messages);
+                    window.layout();
+
+                    if window.tree.is_animating() {
+                        window.tree.frame(&mut messages);
+                        window.raw.request_redraw();
+                    }
+                }
+
+                if !messages.is_empty() || !commands.is_empty() {
+                    commands.extend(
+                        messages
+                            .drain(..)
+                            .map(|message| application.update(message)),
+                    );
+
+                    theme = application.theme();
+                    windows.rebuild(&mut application);
+
+                    for command in commands.drain(..) {
+                        windows.perform(
+                            command,
+                            &mut application,
+                            event_loop,
+                            &renderer,
+                            &clipboard,
+                            &mut messages,
+                        );
+                    }
+
+                    windows.request_redraw();
+                }
+
+                if !windows.contains(window::Id::MAIN) {
+                    *control_flow = ControlFlow::Exit;
+                    return;
+                }
+
+                for window in windows.iter_mut() {
+                    window.expose();
+                }
+
+                let next_frame = windows
+                    .iter()
+                    .filter_map(|window| window.tree.next_frame())
+                    .min();
+                let next_check = watcher.next_check();
+
+                *control_flow = match next_frame.into_iter().chain(next_check).min() {
+                    Some(next) => ControlFlow::WaitUntil(next),
+                    None => ControlFlow::Wait,
+                };
+            }
+            Event::RedrawRequested(window_id) => {
+                if let Some(id) = windows.find(window_id) {
+                    let window = windows.get_mut(id);
+                    window.layout();
+
+                    let mut primitives = vec![window.tree.draw(&theme)];
+
+                    if application.show_stats() {
+                        primitives.push(window.stats.overlay(Point::new(8.0, 8.0)));
+                    }
+
+                    window.stats = renderer.draw(&mut window.target, &primitives);
+                }
+            }
+            _ => {}
+        }
+    })
+}
+
+/// The events the event loop of the runtime is woken up with.
+#[derive(Debug)]
+pub(crate) enum UserEvent<Message> {
+    /// A message sent with a [`Proxy`].
+    ///
+    /// [`Proxy`]: ../proxy/struct.Proxy.html
+    Message(Message),
+
+    /// Some watched files changed.
+    Reload,
+}
+
+/// The widgets to display in the given window.
+fn view<A: Application>(application: &mut A, id: window::Id) -> Element<A::Message> {
+    if id == window::Id::MAIN {
+        application.view()
+    } else {
+        application.view_window(id)
+    }
+}
+
+/// An open window, with what is drawn in it.
+///
+/// The [`Target`] is declared before the window so it is dropped before the
+/// window it draws to.
+///
+/// [`Target`]: ../../visper_graphics/renderer/target/struct.Target.html
+struct Window<Message> {
+    id: window::Id,
+    target: Target,
+    tree: UiTree<Message>,
+    is_resized: bool,
+    stats: FrameStats,
+    adapter: Option<Box<dyn Adapter>>,
+    exposed: Option<accessibility::Tree>,
+    raw: winit::window::Window,
+}
+
+impl<Message: 'static> Window<Message> {
+    fn new(
+        id: window::Id,
+        raw: winit::window::Window,
+        renderer: &Renderer,
+        root: Element<Message>,
+        clipboard: &Shared,
+        adapter: Option<Box<dyn Adapter>>,
+    ) -> Self {
+        let size = raw.inner_size();
+        let target = Target::new(
+            renderer.device(),
+            &raw,
+            size.width,
+            size.height,
+            raw.scale_factor(),
+        );
+
+        let mut tree = UiTree::new(root);
+        tree.set_clipboard(Box::new(clipboard.clone()));
+        tree.layout(target.logical_size());
+
+        Window {
+            id,
+            target,
+            tree,
+            is_resized: false,
+            stats: FrameStats::default(),
+            adapter,
+            exposed: None,
+            raw,
+        }
+    }
+
+    /// Resizes the [`Target`] of the window.
+    ///
+    /// The widget tree is only laid out again before the next event or
+    /// frame, so a burst of resizes lays it out once.
+    ///
+    /// [`Target`]: ../../visper_graphics/renderer/target/struct.Target.html
+    fn resize(&mut self, width: u32, height: u32, scale_factor: f64) {
+        self.target.resize(width, height, scale_factor);
+        self.is_resized = true;
+        self.raw.request_redraw();
+    }
+
+    /// Lays out the widget tree again if the window was resized.
+    fn layout(&mut self) {
+        if self.is_resized {
+            self.tree.layout(self.target.logical_size());
+            self.is_resized = false;
+        }
+    }
+
+    /// Delivers the actions requested by the [`Adapter`] of the window.
+    ///
+    /// [`Adapter`]: ../accessibility/trait.Adapter.html
+    fn perform_requests(&mut self, messages: &mut Vec<Message>) {
+        if let Some(adapter) = &mut self.adapter {
+            for request in adapter.requests() {
+                self.tree.perform_action(request, messages);
+                self.raw.request_redraw();
+            }
+        }
+    }
+
+    /// Hands the accessibility [`Tree`] of the window to its [`Adapter`],
+    /// if it changed since it was last handed.
+    ///
+    /// [`Tree`]: ../accessibility/struct.Tree.html
+    /// [`Adapter`]: ../accessibility/trait.Adapter.html
+    fn expose(&mut self) {
+        if let Some(adapter) = &mut self.adapter {
+            let current = self.tree.accessibility();
+
+            if self.exposed.as_ref() != Some(&current) {
+                adapter.update(&current);
+                self.exposed = Some(current);
+            }
+        }
+    }
+}
+
+/// The open windows of an application.
+///
+/// Every window is known by its [`window::Id`] and by the key the windowing
+/// system gives it, which its events are routed with.
+///
+/// [`window::Id`]: ../window/struct.Id.html
+struct Windows<W, K> {
+    windows: HashMap<window::Id, W>,
+    ids: HashMap<K, window::Id>,
+    focused: window::Id,
+}
+
+impl<W, K: Copy + Eq + Hash> Windows<W, K> {
+    fn new() -> Self {
+        Windows {
+            windows: HashMap::new(),
+            ids: HashMap::new(),
+            focused: window::Id::MAIN,
+        }
+    }
+
+    fn insert(&mut self, id: window::Id, key: K, window: W) {
+        let _ = self.ids.insert(key, id);
+        let _ = self.windows.insert(id, window);
+    }
+
+    /// Returns the window with the given key, if it is open.
+    fn find(&self, key: K) -> Option<window::Id> {
+        self.ids.get(&key).copied()
+    }
+
+    fn contains(&self, id: window::Id) -> bool {
+        self.windows.contains_key(&id)
+    }
+
+    fn get_mut(&mut self, id: window::Id) -> &mut W {
+        self.windows.get_mut(&id).expect("Window is open")
+    }
+
+    fn iter(&self) -> impl Iterator<Item = &W> {
+        self.windows.values()
+    }
+
+    fn iter_mut(&mut self) -> impl Iterator<Item = &mut W> {
+        self.windows.values_mut()
+    }
+
+    /// Makes the given window the focused one, if it is open.
+    fn focus(&mut self, id: window::Id) {
+        if self.contains(id) {
+            self.focused = id;
+        }
+    }
+
+    fn focused_mut(&mut self) -> Option<&mut W> {
+        self.windows.get_mut(&self.focused)
+    }
+
+    /// Closes the given window.
+    ///
+    /// Closing the main window closes every other one. If the focused window
+    /// is closed, the main window is focused.
+    fn close(&mut self, id: window::Id) {
+        if id == window::Id::MAIN {
+            self.windows.clear();
+        } else {
+            let _ = self.windows.remove(&id);
+        }
+
+        let windows = &self.windows;
+        self.ids.retain(|_, id| windows.contains_key(id));
+
+        if !self.contains(self.focused) {
+            self.focused = window::Id::MAIN;
+        }
+    }
+}
+
+impl<Message: 'static> Windows<Window<Message>, WindowId> {
+    fn open(&mut self, window: Window<Message>) {
+        self.insert(window.id, window.raw.id(), window);
+    }
+
+    fn request_redraw(&self) {
+        for window in self.iter() {
+            window.raw.request_redraw();
+        }
+    }
+
+    /// Rebuilds the widget tree of every window.
+    fn rebuild<A>(&mut self, application: &mut A)
+    where
+        A: Application<Message = Message>,
+    {
+        for window in self.iter_mut() {
+            window.tree.rebuild(view(application, window.id));
+        }
+    }
+
+    /// Performs a [`Command`].
+    ///
+    /// Window commands open and close windows, the other ones are performed
+    /// by the widget tree of the focused window.
+    ///
+    /// [`Command`]: ../command/enum.Command.html
+    fn perform<A>(
+        &mut self,
+        command: Command,
+        application: &mut A,
+        event_loop: &EventLoopWindowTarget<UserEvent<Message>>,
+        renderer: &Renderer,
+        clipboard: &Shared,
+        messages: &mut Vec<Message>,
+    ) where
+        A: Application<Message = Message>,
+    {
+        match command {
+            Command::OpenWindow(id, settings) => {
+                if self.contains(id) {
+                    return;
+                }
+
+                let (width, height) = settings.size;
+                let raw = WindowBuilder::new()
+                    .with_title(settings.title)
+                    .with_inner_size(LogicalSize::new(f64::from(width), f64::from(height)))
+                    .with_resizable(settings.is_resizable)
+                    .with_decorations(settings.has_decorations)
+                    .build(event_loop)
+                    .expect("Open window");
+
+                let root = application.view_window(id);
+                let adapter = application.accessibility(id);
+
+                self.open(Window::new(id, raw, renderer, root, clipboard, adapter));
+            }
+            Command::CloseWindow(id) => self.close(id),
+            Command::Batch(commands) => {
+                for command in commands {
+                    self.perform(
+                        command,
+                        application,
+                        event_loop,
+                        renderer,
+                        clipboard,
+                        messages,
+                    );
+                }
+            }
+            command => {
+                if let Some(window) = self.focused_mut() {
+                    window.tree.perform(command, messages);
+                }
+            }
+        }
+    }
+}
+
+/// A [`Clipboard`] shared by the widget trees of all the windows.
+///
+/// [`Clipboard`]: ../clipboard/trait.Clipboard.html
+#[derive(Clone)]
+struct Shared(Rc<RefCell<Box<dyn Clipboard>>>);
+
+impl Shared {
+    fn new(clipboard: Box<dyn Clipboard>) -> Self {
+        Shared(Rc::new(RefCell::new(clipboard)))
+    }
+}
+
+impl Clipboard for Shared {
+    fn read(&self) -> Option<String> {
+        self.0.borrow().read()
+    }
+
+    fn write(&mut self, contents: String) {
+        self.0.borrow_mut().write(contents)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::Harness;
+    use crate::widget::Button;
+    use visper_graphics::core::size::Size;
+
+    /// An application that only shows a button naming its window.
+    struct Panel {
+        window: window::Id,
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    enum Message {
+        Pressed(window::Id),
+    }
+
+    impl Application for Panel {
+        type Message = Message;
+
+        fn new() -> Self {
+            Panel {
+                window: window::Id::MAIN,
+            }
+        }
+
+        fn title(&self) -> String {
+            String::from("Panel")
+        }
+
+        fn update(&mut self, _message: Message) -> Command {
+            Command::None
+        }
+
+        fn view(&mut self) -> Element<Message> {
+            Button::new("Press")
+                .on_press(Message::Pressed(self.window))
+                .into()
+        }
+    }
+
+    fn open(windows: &mut Windows<Harness<Panel>, u32>, id: window::Id, key: u32) {
+        let panel = Harness::with(Panel { window: id }, Size::new(200.0, 100.0));
+
+        windows.insert(id, key, panel);
+    }
+
+    #[test]
+    fn events_are_routed_by_key() {
+        let mut windows = Windows::new();
+        let panel = window::Id::unique();
+
+        open(&mut windows, window::Id::MAIN, 1);
+        open(&mut windows, panel, 2);
+
+        assert_eq!(windows.find(1), Some(window::Id::MAIN));
+        assert_eq!(windows.find(2), Some(panel));
+        assert_eq!(windows.find(3), None);
+
+        let id = windows.find(2).unwrap();
+        windows.get_mut(id).click_label("Press");
+
+        assert_eq!(windows.get_mut(panel).messages(), &[Message::Pressed(panel)]);
+        assert!(windows.get_mut(window::Id::MAIN).messages().is_empty());
+    }
+
+    #[test]
+    fn closing_a_window_forgets_its_key() {
+        let mut windows = Windows::new();
+        let panel = window::Id::unique();
+
+        open(&mut windows, window::Id::MAIN, 1);
+        open(&mut windows, panel, 2);
+        windows.focus(panel);
+        windows.close(panel);
+
+        assert!(!windows.contains(panel));
+        assert_eq!(windows.find(2), None);
+        assert_eq!(windows.find(1), Some(window::Id::MAIN));
+        assert_eq!(windows.focused, window::Id::MAIN);
+    }
+
+    #[test]
+    fn closing_the_main_window_closes_every_window() {
+        let mut windows = Windows::new();
+
+        open(&mut windows, window::Id::MAIN, 1);
+        open(&mut windows, window::Id::unique(), 2);
+        open(&mut windows, window::Id::unique(), 3);
+        windows.close(window::Id::MAIN);
+
+        assert_eq!(windows.iter().count(), 0);
+        assert_eq!(windows.find(1), None);
+        assert_eq!(windows.find(3), None);
+    }
+
+    #[test]
+    fn only_open_windows_are_focused() {
+        let mut windows = Windows::new();
+        let panel = window::Id::unique();
+
+        open(&mut windows, window::Id::MAIN, 1);
+        windows.focus(panel);
+
+        assert_eq!(windows.focused, window::Id::MAIN);
+
+        open(&mut windows, panel, 2);
+        windows.focus(panel);
+        windows.focused_mut().unwrap().click_label("Press");
+
+        assert_eq!(windows.get_mut(panel).messages(), &[Message::Pressed(panel)]);
+    }
+}
@@ -0,0 +1,143 @@
+//! Describe how a widget is sized and positioned.
+
+/// The strategy used to fill space in a specific dimension.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Length {
+    /// Fill all the remaining space.
+    Fill,
+
+    /// Fill the least amount of space.
+    Shrink,
+
+    /// Fill a fixed amount of space.
+    Units(f32),
+}
+
+impl Default for Length {
+    fn default() -> Self {
+        Length::Shrink
+    }
+}
+
+impl From<f32> for Length {
+    fn from(units: f32) -> Self {
+        Length::Units(units)
+    }
+}
+
+/// The alignment of children inside their parent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Align {
+    /// Align at the start of the axis.
+    Start,
+
+    /// Align at the center of the axis.
+    Center,
+
+    /// Align at the end of the axis.
+    End,
+}
+
+impl Default for Align {
+    fn default() -> Self {
+        Align::Start
+    }
+}
+
+impl Align {
+    /// Returns the offset of an item of the given length inside the given
+    /// available space.
+    pub fn offset(self, available: f32, length: f32) -> f32 {
+        match self {
+            Align::Start => 0.0,
+            Align::Center => ((available - length) / 2.0).max(0.0),
+            Align::End => (available - length).max(0.0),
+        }
+    }
+}
+
+/// The layout properties of a widget.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct Style {
+    /// The width of the widget.
+    pub width: Length,
+
+    /// The height of the widget.
+    pub height: Length,
+
+    /// The space between the bounds of the widget and its content.
+    pub padding: f32,
+
+    /// The space between the children of the widget.
+    pub spacing: f32,
+
+    /// The alignment of the children along the main axis.
+    pub justify_content: Align,
+
+    /// The alignment of the children along the cross axis.
+    pub align_items: Align,
+}
+
+impl Style {
+    /// Creates a new [`Style`] that shrinks to its content.
+    ///
+    /// [`Style`]: struct.Style.html
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the width of the [`Style`].
+    ///
+    /// [`Style`]: struct.Style.html
+    pub fn width(mut self, width: impl Into<Length>) -> Self {
+        self.width = width.into();
+        self
+    }
+
+    /// Sets the height of the [`Style`].
+    ///
+    /// [`Style`]: struct.Style.html
+    pub fn height(mut self, height: impl Into<Length>) -> Self {
+        self.height = height.into();
+        self
+    }
+
+    /// Sets the padding of the [`Style`].
+    ///
+    /// [`Style`]: struct.Style.html
+    pub fn padding(mut self, padding: f32) -> Self {
+        self.padding = padding;
+        self
+    }
+
+    /// Sets the spacing of the [`Style`].
+    ///
+    /// [`Style`]: struct.Style.html
+    pub fn spacing(mut self, spacing: f32) -> Self {
+        self.spacing = spacing;
+        self
+    }
+
+    /// Sets the main axis alignment of the [`Style`].
+    ///
+    /// [`Style`]: struct.Style.html
+    pub fn justify_content(mut self, align: Align) -> Self {
+        self.justify_content = align;
+        self
+    }
+
+    /// Sets the cross axis alignment of the [`Style`].
+    ///
+    /// [`Style`]: struct.Style.html
+    pub fn align_items(mut self, align: Align) -> Self {
+        self.align_items = align;
+        self
+    }
+}
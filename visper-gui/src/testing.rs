@@ -0,0 +1,510 @@
+//! Drive an application in tests, without a window.
+//!
+//! A [`Harness`] runs an [`Application`] like the runtime does, but
+//! synchronously and at a fixed size: the events it is given are dispatched
+//! to the widget tree, the messages they publish go through
+//! [`Application::update`] and the tree is rebuilt before the next event.
+//! Tests can then look at the published messages, the state of the
+//! application and the primitives that render it, or record its frames.
+//!
+//! ```ignore
+//! let mut harness = Harness::<Counter>::new(Size::new(400.0, 300.0));
+//!
+//! harness.click_label("Increment");
+//!
+//! assert_eq!(harness.messages(), &[Message::Increment]);
+//! assert_eq!(harness.application().count, 1);
+//! ```
+//!
+//! [`Harness`]: struct.Harness.html
+//! [`Application`]: ../application/trait.Application.html
+//! [`Application::update`]: ../application/trait.Application.html#tymethod.update
+use crate::animation::{self, Manual};
+use crate::application::Application;
+use crate::event::{keyboard, mouse, Event};
+use crate::theme::Theme;
+use crate::tree::{NodeId, UiTree};
+use std::io;
+use std::time::Duration;
+use visper_graphics::core::point::Point;
+use visper_graphics::core::size::Size;
+use visper_graphics::core::vector::Vector;
+use visper_graphics::primitive::Primitive;
+use visper_graphics::renderer::capture::Recorder;
+use visper_graphics::renderer::target::Target;
+use visper_graphics::renderer::Renderer;
+
+/// The number of frames [`Harness::settle`] waits for animations to end.
+///
+/// [`Harness::settle`]: struct.Harness.html#method.settle
+const MAX_FRAMES: usize = 1_000;
+
+/// An [`Application`] running without a window.
+///
+/// The [`Harness`] animates with a [`Manual`] clock, so time only passes
+/// when [`advance`] or [`settle`] are called.
+///
+/// [`Application`]: ../application/trait.Application.html
+/// [`Harness`]: struct.Harness.html
+/// [`Manual`]: ../animation/struct.Manual.html
+/// [`advance`]: #method.advance
+/// [`settle`]: #method.settle
+pub struct Harness<A: Application> {
+    application: A,
+    tree: UiTree<A::Message>,
+    theme: Theme,
+    clock: Manual,
+    published: Vec<A::Message>,
+}
+
+impl<A: Application> Harness<A>
+where
+    A::Message: Clone,
+{
+    /// Creates a new [`Application`] and lays it out at the given size.
+    ///
+    /// [`Application`]: ../application/trait.Application.html
+    pub fn new(size: Size) -> Self {
+        Self::with(A::new(), size)
+    }
+
+    /// Lays out the given [`Application`] at the given size.
+    ///
+    /// [`Application`]: ../application/trait.Application.html
+    pub fn with(mut application: A, size: Size) -> Self {
+        let clock = Manual::new();
+        let theme = application.theme();
+        let mut tree = UiTree::new(application.view());
+
+        tree.set_clipboard(application.clipboard());
+        tree.set_clock(Box::new(clock.clone()));
+        tree.layout(size);
+
+        Harness {
+            application,
+            tree,
+            theme,
+            clock,
+            published: Vec::new(),
+        }
+    }
+
+    /// Returns the [`Application`].
+    ///
+    /// [`Application`]: ../application/trait.Application.html
+    pub fn application(&self) -> &A {
+        &self.application
+    }
+
+    /// Returns the widget tree of the [`Application`].
+    ///
+    /// [`Application`]: ../application/trait.Application.html
+    pub fn tree(&self) -> &UiTree<A::Message> {
+        &self.tree
+    }
+
+    /// Returns the messages published since the [`Harness`] was created or
+    /// the messages were last taken, in order.
+    ///
+    /// [`Harness`]: struct.Harness.html
+    pub fn messages(&self) -> &[A::Message] {
+        &self.published
+    }
+
+    /// Takes the messages published so far.
+    pub fn take_messages(&mut self) -> Vec<A::Message> {
+        std::mem::take(&mut self.published)
+    }
+
+    /// Returns the primitives that render the [`Application`].
+    ///
+    /// [`Application`]: ../application/trait.Application.html
+    pub fn draw(&self) -> Primitive {
+        self.tree.draw(&self.theme)
+    }
+
+    /// Lays out the [`Application`] at a new size.
+    ///
+    /// [`Application`]: ../application/trait.Application.html
+    pub fn resize(&mut self, size: Size) {
+        self.tree.layout(size);
+    }
+
+    /// Publishes a message, as if a widget had.
+    pub fn publish(&mut self, message: A::Message) {
+        self.process(vec![message]);
+    }
+
+    /// Dispatches an [`Event`], then updates the [`Application`] with the
+    /// messages it published.
+    ///
+    /// [`Event`]: ../event/enum.Event.html
+    /// [`Application`]: ../application/trait.Application.html
+    pub fn dispatch(&mut self, event: Event) {
+        let mut messages = Vec::new();
+
+        self.tree.dispatch(&event, &mut messages);
+        self.process(messages);
+    }
+
+    /// Moves the cursor to the given position.
+    pub fn move_to(&mut self, position: Point) {
+        self.dispatch(Event::Mouse(mouse::Event::CursorMoved { position }));
+    }
+
+    /// Presses and releases the left button at the given position.
+    pub fn click(&mut self, position: Point) {
+        self.move_to(position);
+        self.dispatch(Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Left)));
+        self.dispatch(Event::Mouse(mouse::Event::ButtonReleased(mouse::Button::Left)));
+    }
+
+    /// Scrolls the wheel by the given amount of pixels at the given position.
+    pub fn wheel(&mut self, position: Point, delta: Vector) {
+        self.move_to(position);
+        self.dispatch(Event::Mouse(mouse::Event::WheelScrolled {
+            delta: mouse::ScrollDelta::Pixels {
+                x: delta.x,
+                y: delta.y,
+            },
+        }));
+    }
+
+    /// Presses and releases a key.
+    pub fn key(&mut self, key_code: keyboard::KeyCode) {
+        self.key_with(key_code, keyboard::ModifiersState::default());
+    }
+
+    /// Presses and releases a key while holding the given modifiers.
+    pub fn key_with(&mut self, key_code: keyboard::KeyCode, modifiers: keyboard::ModifiersState) {
+        self.dispatch(Event::Keyboard(keyboard::Event::KeyPressed {
+            key_code,
+            modifiers,
+        }));
+        self.dispatch(Event::Keyboard(keyboard::Event::KeyReleased {
+            key_code,
+            modifiers,
+        }));
+    }
+
+    /// Types the given text, one character at a time.
+    pub fn type_text(&mut self, text: &str) {
+        for c in text.chars() {
+            self.dispatch(Event::Keyboard(keyboard::Event::CharacterReceived(c)));
+        }
+    }
+
+    /// Returns the first node with the given key, in tree order.
+    pub fn find_key(&self, key: &str) -> Option<NodeId> {
+        self.tree.find_key(key)
+    }
+
+    /// Returns the first node whose widget has the given label, in tree
+    /// order.
+    pub fn find_label(&self, label: &str) -> Option<NodeId> {
+        self.tree.find_label(label)
+    }
+
+    /// Clicks the center of the given node.
+    ///
+    /// The click goes to whatever is drawn there, which may not be the node
+    /// if it is scrolled out of view or covered by another node.
+    pub fn click_node(&mut self, id: NodeId) {
+        let bounds = self.tree.node(id).bounds();
+
+        self.click(Point::new(
+            bounds.x + bounds.width / 2.0,
+            bounds.y + bounds.height / 2.0,
+        ));
+    }
+
+    /// Clicks the first node with the given key.
+    ///
+    /// # Panics
+    ///
+    /// Panics if no node has the key.
+    pub fn click_key(&mut self, key: &str) {
+        let id = self
+            .find_key(key)
+            .unwrap_or_else(|| panic!("No node has the key `{}`", key));
+
+        self.click_node(id);
+    }
+
+    /// Clicks the first widget with the given label.
+    ///
+    /// # Panics
+    ///
+    /// Panics if no widget has the label.
+    pub fn click_label(&mut self, label: &str) {
+        let id = self
+            .find_label(label)
+            .unwrap_or_else(|| panic!("No widget has the label `{}`", label));
+
+        self.click_node(id);
+    }
+
+    /// Lets the given amount of time pass, delivering the frames the
+    /// widgets ask for along the way.
+    pub fn advance(&mut self, duration: Duration) {
+        let frames = (duration.as_secs_f32() / animation::FRAME.as_secs_f32()).ceil() as u32;
+
+        for _ in 0..frames {
+            self.clock.advance(duration / frames);
+            self.frame();
+        }
+    }
+
+    /// Lets time pass until nothing is animating anymore.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the animations do not end after a thousand frames.
+    pub fn settle(&mut self) {
+        for _ in 0..MAX_FRAMES {
+            if !self.tree.is_animating() {
+                return;
+            }
+
+            self.clock.advance(animation::FRAME);
+            self.frame();
+        }
+
+        panic!("Animations did not settle after {} frames", MAX_FRAMES);
+    }
+
+    /// Draws the given number of frames on the [`Target`] and records them,
+    /// letting the timestep of the [`Recorder`] pass after each one.
+    ///
+    /// Since time only passes between frames, the same application records
+    /// the same frames on every run.
+    ///
+    /// [`Target`]: ../../visper_graphics/renderer/target/struct.Target.html
+    /// [`Recorder`]: ../../visper_graphics/renderer/capture/struct.Recorder.html
+    pub fn record(
+        &mut self,
+        renderer: &mut Renderer,
+        target: &mut Target,
+        recorder: &mut Recorder,
+        frames: usize,
+    ) -> io::Result<()> {
+        for _ in 0..frames {
+            renderer.draw(target, &[self.draw()]);
+
+            let frame = renderer
+                .capture(target)
+                .ok_or_else(|| io::Error::other("The frame could not be read back"))?;
+
+            recorder.record(&frame)?;
+
+            self.clock.advance(recorder.timestep());
+            self.frame();
+        }
+
+        recorder.flush()
+    }
+
+    fn frame(&mut self) {
+        let mut messages = Vec::new();
+
+        self.tree.frame(&mut messages);
+        self.process(messages);
+    }
+
+    /// Updates the [`Application`] with the given messages and the messages
+    /// published by the commands they return, until there are none left.
+    ///
+    /// [`Application`]: ../application/trait.Application.html
+    fn process(&mut self, mut messages: Vec<A::Message>) {
+        while !messages.is_empty() {
+            let commands: Vec<_> = messages
+                .drain(..)
+                .map(|message| {
+                    self.published.push(message.clone());
+                    self.application.update(message)
+                })
+                .collect();
+
+            self.theme = self.application.theme();
+            self.tree.rebuild(self.application.view());
+
+            for command in commands {
+                self.tree.perform(command, &mut messages);
+            }
+        }
+    }
+}
+
+/// Fixtures for the tests of widgets.
+#[cfg(test)]
+pub(crate) mod fixture {
+    use super::Harness;
+    use crate::application::Application;
+    use crate::command::Command;
+    use crate::style::Length;
+    use crate::widget::{Column, Element};
+    use visper_graphics::core::size::Size;
+    use visper_graphics::primitive::Primitive;
+
+    /// An [`Application`] that only shows the widgets built by a function,
+    /// filling its window, and ignores the messages they publish.
+    ///
+    /// [`Application`]: ../../application/trait.Application.html
+    pub(crate) struct Fixture<Message> {
+        view: Box<dyn Fn() -> Element<Message>>,
+    }
+
+    impl<Message> Application for Fixture<Message>
+    where
+        Message: std::fmt::Debug + Send + 'static,
+    {
+        type Message = Message;
+
+        fn new() -> Self {
+            Fixture {
+                view: Box::new(|| Column::new().into()),
+            }
+        }
+
+        fn title(&self) -> String {
+            String::from("Fixture")
+        }
+
+        fn update(&mut self, _message: Message) -> Command {
+            Command::None
+        }
+
+        fn view(&mut self) -> Element<Message> {
+            Column::new()
+                .width(Length::Fill)
+                .height(Length::Fill)
+                .append((self.view)())
+        }
+    }
+
+    impl<Message> Harness<Fixture<Message>>
+    where
+        Message: std::fmt::Debug + Clone + Send + 'static,
+    {
+        /// Lays out the widgets built by the given function in a 200×100
+        /// window.
+        pub(crate) fn widget<E>(view: impl Fn() -> E + 'static) -> Self
+        where
+            E: Into<Element<Message>>,
+        {
+            let fixture = Fixture {
+                view: Box::new(move || view().into()),
+            };
+
+            Harness::with(fixture, Size::new(200.0, 100.0))
+        }
+    }
+
+    /// Returns the contents of the text primitives in the given [`Primitive`],
+    /// in drawing order.
+    ///
+    /// [`Primitive`]: ../../../visper_graphics/primitive/enum.Primitive.html
+    pub(crate) fn texts(primitive: &Primitive) -> Vec<String> {
+        match primitive {
+            Primitive::Text { content, .. } => vec![content.clone()],
+            Primitive::Group { primitives } => primitives.iter().flat_map(texts).collect(),
+            Primitive::Clip { content, .. }
+            | Primitive::Layer { content, .. }
+            | Primitive::ZIndex { content, .. } => texts(content),
+            _ => Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::fixture::texts;
+    use super::*;
+    use crate::command::Command;
+    use crate::style::Length;
+    use crate::widget::{Button, Column, Element, Scrollable, Text, TextInput};
+
+    #[derive(Default)]
+    struct Counter {
+        count: usize,
+        name: String,
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    enum Message {
+        Increment,
+        NameChanged(String),
+    }
+
+    impl Application for Counter {
+        type Message = Message;
+
+        fn new() -> Self {
+            Counter::default()
+        }
+
+        fn title(&self) -> String {
+            String::from("Counter")
+        }
+
+        fn update(&mut self, message: Message) -> Command {
+            match message {
+                Message::Increment => self.count += 1,
+                Message::NameChanged(name) => self.name = name,
+            }
+
+            Command::None
+        }
+
+        fn view(&mut self) -> Element<Message> {
+            let rows = (0..20).map(|i| Text::new(format!("Row {}", i)).height(20.0));
+
+            Column::new()
+                .width(Length::Fill)
+                .height(Length::Fill)
+                .append(Text::new(format!("Count: {}", self.count)))
+                .append(Button::new("Increment").on_press(Message::Increment))
+                .append(Element::from(TextInput::new("Name", &self.name, Message::NameChanged)).key("name"))
+                .append(rows.fold(Element::from(Scrollable::new().height(100.0)), Element::append).key("rows"))
+        }
+    }
+
+    #[test]
+    fn harness_clicks_and_types() {
+        let mut harness = Harness::<Counter>::new(Size::new(300.0, 400.0));
+
+        harness.click_label("Increment");
+        harness.click_label("Increment");
+
+        assert_eq!(harness.take_messages(), vec![Message::Increment, Message::Increment]);
+        assert_eq!(harness.application().count, 2);
+
+        assert!(texts(&harness.draw()).contains(&String::from("Count: 2")));
+
+        harness.click_key("name");
+        harness.type_text("Ada");
+        harness.key(keyboard::KeyCode::Back);
+
+        assert_eq!(harness.application().name, "Ad");
+        assert_eq!(harness.messages().last(), Some(&Message::NameChanged(String::from("Ad"))));
+    }
+
+    #[test]
+    fn harness_scrolls_with_the_wheel() {
+        let mut harness = Harness::<Counter>::new(Size::new(300.0, 400.0));
+        let rows = harness.find_key("rows").unwrap();
+        let bounds = harness.tree().node(rows).bounds();
+        let first_row = |harness: &Harness<Counter>| {
+            let row = harness.tree().children(rows).next().unwrap();
+
+            harness.tree().node(row).bounds().y
+        };
+
+        let top = first_row(&harness);
+        harness.wheel(Point::new(bounds.x + 10.0, bounds.y + 10.0), Vector::new(0.0, -40.0));
+        harness.settle();
+
+        assert_eq!(first_row(&harness), top - 40.0);
+        assert!(harness.messages().is_empty());
+    }
+}
@@ -0,0 +1,203 @@
+//! Share the visual appearance of widgets.
+use visper_graphics::core::color::Color;
+
+/// The visual appearance shared by the widgets of an application.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct Theme {
+    /// The colors of the widgets.
+    pub palette: Palette,
+
+    /// The radius of the corners of buttons and other controls.
+    pub border_radius: f32,
+
+    /// The indicator drawn around the focused widget.
+    pub focus_ring: FocusRing,
+
+    /// The scrollbars of scrollable widgets.
+    pub scrollbar: Scrollbar,
+
+    /// The headers and rows of tables.
+    pub table: Table,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme {
+            palette: Palette::default(),
+            border_radius: 4.0,
+            focus_ring: FocusRing::default(),
+            scrollbar: Scrollbar::default(),
+            table: Table::default(),
+        }
+    }
+}
+
+/// The colors of a [`Theme`].
+///
+/// [`Theme`]: struct.Theme.html
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct Palette {
+    /// The background of text inputs and other editable fields.
+    pub background: Color,
+
+    /// The background of controls, like the track of a slider.
+    pub surface: Color,
+
+    /// The accent color of active controls.
+    pub primary: Color,
+
+    /// The color of text and icons drawn on top of the primary color.
+    pub on_primary: Color,
+
+    /// The color of text.
+    pub text: Color,
+
+    /// The color of borders.
+    pub border: Color,
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        Palette {
+            background: Color::WHITE,
+            surface: Color::from_rgb8(0xe5, 0xe7, 0xeb),
+            primary: Color::from_rgb8(0x25, 0x63, 0xeb),
+            on_primary: Color::WHITE,
+            text: Color::from_rgb8(0x11, 0x18, 0x27),
+            border: Color::from_rgb8(0x9c, 0xa3, 0xaf),
+        }
+    }
+}
+
+impl Palette {
+    /// Returns the color of a control in the given interaction state.
+    pub fn interactive(color: Color, is_hovered: bool, is_pressed: bool) -> Color {
+        let amount = |is_on: bool| if is_on { 1.0 } else { 0.0 };
+
+        Self::highlight(color, amount(is_hovered), amount(is_pressed))
+    }
+
+    /// Returns the color of a control highlighted by the given amounts of
+    /// hover and press, from 0 to 1.
+    ///
+    /// The amounts usually come from a [`DrawCtx`], so the color fades when
+    /// the control is hovered or pressed.
+    ///
+    /// [`DrawCtx`]: ../widget/struct.DrawCtx.html
+    pub fn highlight(color: Color, hover: f32, press: f32) -> Color {
+        let factor = 1.0 - 0.1 * hover - 0.1 * press;
+
+        Color {
+            r: color.r * factor,
+            g: color.g * factor,
+            b: color.b * factor,
+            a: color.a,
+        }
+    }
+}
+
+/// The indicator drawn around a widget that was focused with the keyboard.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct FocusRing {
+    /// The color of the ring.
+    pub color: Color,
+
+    /// The width of the ring.
+    pub width: f32,
+
+    /// The gap between the bounds of the widget and the ring.
+    pub offset: f32,
+
+    /// The radius of the corners of the ring.
+    pub border_radius: f32,
+}
+
+impl Default for FocusRing {
+    fn default() -> Self {
+        FocusRing {
+            color: Color::from_rgb8(0x3b, 0x82, 0xf6),
+            width: 2.0,
+            offset: 2.0,
+            border_radius: 4.0,
+        }
+    }
+}
+
+/// The scrollbars of a scrollable widget.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct Scrollbar {
+    /// The radius of the corners of the track and the thumb.
+    pub border_radius: f32,
+
+    /// The color of the track the thumb slides along.
+    pub track: Color,
+
+    /// The color of the thumb.
+    pub thumb: Color,
+}
+
+impl Default for Scrollbar {
+    fn default() -> Self {
+        Scrollbar {
+            border_radius: 4.0,
+            track: Color::from_rgba8(0x00, 0x00, 0x00, 0.05),
+            thumb: Color::from_rgba8(0x6b, 0x72, 0x80, 0.6),
+        }
+    }
+}
+
+/// The headers and rows of a table.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct Table {
+    /// The background of the header.
+    pub header: Color,
+
+    /// The color of the lines between columns and below the header.
+    pub divider: Color,
+
+    /// The background of every other row.
+    pub stripe: Color,
+
+    /// The background of the row under the cursor.
+    pub hover: Color,
+
+    /// The background of the selected row.
+    pub selection: Color,
+}
+
+impl Default for Table {
+    fn default() -> Self {
+        Table {
+            header: Color::from_rgb8(0xf3, 0xf4, 0xf6),
+            divider: Color::from_rgb8(0xd1, 0xd5, 0xdb),
+            stripe: Color::from_rgb8(0xf9, 0xfa, 0xfb),
+            hover: Color::from_rgba8(0x25, 0x63, 0xeb, 0.08),
+            selection: Color::from_rgba8(0x25, 0x63, 0xeb, 0.2),
+        }
+    }
+}
@@ -0,0 +1,1470 @@
+//! Keep widgets in a tree and route events through it.
+use crate::accessibility;
+use crate::animation::{self, Animated, Clock, Transition};
+use crate::clipboard::{self, Clipboard};
+use crate::command::Command;
+use crate::event::{focus, keyboard, mouse, touch, Event, Phase};
+use crate::layout;
+use crate::theme::{FocusRing, Theme};
+use crate::widget::{DrawCtx, Element, EventCtx, Widget};
+use indextree::Arena;
+use std::any::Any;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::time::{Duration, Instant};
+use visper_graphics::core::color::Color;
+use visper_graphics::core::point::Point;
+use visper_graphics::core::rectangle::Rectangle;
+use visper_graphics::core::size::Size;
+use visper_graphics::core::vector::Vector;
+use visper_graphics::primitive::Primitive;
+
+pub use indextree::NodeId;
+
+/// A node of a [`UiTree`].
+///
+/// [`UiTree`]: struct.UiTree.html
+pub struct Node<Message> {
+    pub(crate) widget: Box<dyn Widget<Message>>,
+    pub(crate) key: Option<String>,
+    pub(crate) tab_index: Option<i32>,
+    pub(crate) bounds: Rectangle,
+}
+
+impl<Message> Node<Message> {
+    /// Returns the widget of the [`Node`].
+    ///
+    /// [`Node`]: struct.Node.html
+    pub fn widget(&self) -> &dyn Widget<Message> {
+        self.widget.as_ref()
+    }
+
+    /// Returns the key of the [`Node`], if any.
+    ///
+    /// [`Node`]: struct.Node.html
+    pub fn key(&self) -> Option<&str> {
+        self.key.as_deref()
+    }
+
+    /// Returns the laid out bounds of the [`Node`].
+    ///
+    /// [`Node`]: struct.Node.html
+    pub fn bounds(&self) -> Rectangle {
+        self.bounds
+    }
+
+    /// Returns the tab index of the [`Node`], or `None` if it cannot be
+    /// focused.
+    ///
+    /// [`Node`]: struct.Node.html
+    pub fn tab_index(&self) -> Option<i32> {
+        self.tab_index
+            .or_else(|| if self.widget.focusable() { Some(0) } else { None })
+    }
+}
+
+/// A retained tree of widgets.
+///
+/// The [`UiTree`] lays out its nodes, hit-tests cursor positions against their
+/// bounds and dispatches events to them in capture and bubble phases. It also
+/// keeps track of the single node that has the keyboard focus.
+///
+/// [`UiTree`]: struct.UiTree.html
+pub struct UiTree<Message> {
+    arena: Arena<Node<Message>>,
+    root: NodeId,
+    size: Size,
+    cursor_position: Option<Point>,
+    hovered: Option<NodeId>,
+    captured: Option<NodeId>,
+    focused: Option<NodeId>,
+    focus_visible: bool,
+    fingers: HashMap<touch::Finger, NodeId>,
+    clipboard: Box<dyn Clipboard>,
+    frames: Vec<NodeId>,
+    relayout: Vec<NodeId>,
+    clock: Box<dyn Clock>,
+    transition: Transition,
+    highlights: HashMap<NodeId, Highlight>,
+}
+
+/// The hover and press highlights of a node, fading in and out.
+#[derive(Debug, Clone)]
+struct Highlight {
+    hover: Animated<f32>,
+    press: Animated<f32>,
+}
+
+impl Highlight {
+    fn new(transition: Transition) -> Self {
+        Highlight {
+            hover: Animated::new(0.0, transition),
+            press: Animated::new(0.0, transition),
+        }
+    }
+
+    fn is_animating(&self, now: Instant) -> bool {
+        self.hover.is_animating(now) || self.press.is_animating(now)
+    }
+
+    fn is_off(&self) -> bool {
+        *self.hover.target() == 0.0 && *self.press.target() == 0.0
+    }
+}
+
+impl<Message> UiTree<Message> {
+    /// Creates a new [`UiTree`] out of the given root [`Element`].
+    ///
+    /// [`UiTree`]: struct.UiTree.html
+    /// [`Element`]: ../widget/struct.Element.html
+    pub fn new(root: Element<Message>) -> Self {
+        let mut arena = Arena::new();
+        let root = insert(&mut arena, root);
+
+        UiTree {
+            arena,
+            root,
+            size: Size::ZERO,
+            cursor_position: None,
+            hovered: None,
+            captured: None,
+            focused: None,
+            focus_visible: false,
+            fingers: HashMap::new(),
+            clipboard: Box::new(clipboard::Local::default()),
+            frames: Vec::new(),
+            relayout: Vec::new(),
+            clock: Box::new(animation::System),
+            transition: Transition::ease(HIGHLIGHT_DURATION),
+            highlights: HashMap::new(),
+        }
+    }
+
+    /// Replaces the [`Clipboard`] used by the widgets of the [`UiTree`].
+    ///
+    /// [`Clipboard`]: ../clipboard/trait.Clipboard.html
+    /// [`UiTree`]: struct.UiTree.html
+    pub fn set_clipboard(&mut self, clipboard: Box<dyn Clipboard>) {
+        self.clipboard = clipboard;
+    }
+
+    /// Replaces the [`Clock`] the [`UiTree`] animates with.
+    ///
+    /// [`Clock`]: ../animation/trait.Clock.html
+    /// [`UiTree`]: struct.UiTree.html
+    pub fn set_clock(&mut self, clock: Box<dyn Clock>) {
+        self.clock = clock;
+    }
+
+    /// Sets the [`Transition`] followed by the hover and press highlights of
+    /// the widgets.
+    ///
+    /// [`Transition`]: ../animation/enum.Transition.html
+    pub fn set_transition(&mut self, transition: Transition) {
+        self.transition = transition;
+    }
+
+    /// Replaces the nodes of the [`UiTree`] with the given root [`Element`],
+    /// keeping the cursor position and the layout size.
+    ///
+    /// The state of the widgets is handed to the widgets found at the same
+    /// position in the new tree, identified by their keys or child indices.
+    ///
+    /// [`UiTree`]: struct.UiTree.html
+    /// [`Element`]: ../widget/struct.Element.html
+    pub fn rebuild(&mut self, root: Element<Message>) {
+        let references = self.references();
+        let mut states = self.take_states(self.root);
+
+        self.arena = Arena::new();
+        self.root = insert(&mut self.arena, root);
+        self.hovered = None;
+        self.captured = None;
+        self.focused = None;
+        self.fingers.clear();
+        self.frames.clear();
+        self.relayout.clear();
+        self.highlights.clear();
+
+        self.restore_states(self.root, &mut states);
+        self.arrange(self.root, self.viewport(), &mut states);
+        self.resolve(references);
+
+        self.hovered = self.cursor_position.and_then(|p| self.hit_test(p));
+        self.update_highlights();
+    }
+
+    /// Replaces the node with the given key and its descendants with the
+    /// element with the same key in the given root [`Element`], leaving the
+    /// rest of the [`UiTree`] untouched.
+    ///
+    /// The state of the widgets is handed to the widgets found at the same
+    /// position under the node. If several nodes share the key, the first one
+    /// in tree order is replaced.
+    ///
+    /// Returns false, without replacing anything, if either tree has no node
+    /// with the key.
+    ///
+    /// [`Element`]: ../widget/struct.Element.html
+    /// [`UiTree`]: struct.UiTree.html
+    pub fn rebuild_key(&mut self, key: &str, root: Element<Message>) -> bool {
+        let (id, element) = match (self.find_key(key), find_element(root, key)) {
+            (Some(id), Some(element)) => (id, element),
+            _ => return false,
+        };
+
+        let Element {
+            widget,
+            children,
+            tab_index,
+            ..
+        } = element;
+
+        let node = self.arena[id].get_mut();
+        let state = node.widget.take_state();
+
+        node.widget = widget;
+        node.tab_index = tab_index;
+
+        if let Some(state) = state {
+            node.widget.restore_state(state);
+        }
+
+        self.replace_children(id, children, &mut HashMap::new());
+        self.arrange(self.root, self.viewport(), &mut HashMap::new());
+
+        self.hovered = self.cursor_position.and_then(|p| self.hit_test(p));
+        self.update_highlights();
+
+        true
+    }
+
+    /// Takes the state of the given node and its descendants, by path.
+    fn take_states(&mut self, id: NodeId) -> States {
+        let ids: Vec<NodeId> = id.descendants(&self.arena).collect();
+        let mut states = HashMap::new();
+
+        for id in ids {
+            let path = self.path(id);
+
+            if let Some(state) = self.arena[id].get_mut().widget.take_state() {
+                let _ = states.insert(path, state);
+            }
+        }
+
+        states
+    }
+
+    /// Hands the states found at their paths to the given node and its
+    /// descendants.
+    fn restore_states(&mut self, id: NodeId, states: &mut States) {
+        if states.is_empty() {
+            return;
+        }
+
+        let ids: Vec<NodeId> = id.descendants(&self.arena).collect();
+
+        for id in ids {
+            if let Some(state) = states.remove(&self.path(id)) {
+                self.arena[id].get_mut().widget.restore_state(state);
+            }
+        }
+    }
+
+    /// Returns the paths of the nodes the [`UiTree`] refers to, so they can be
+    /// found again once nodes are replaced.
+    ///
+    /// [`UiTree`]: struct.UiTree.html
+    fn references(&self) -> References {
+        let paths = |ids: &[NodeId]| ids.iter().map(|&id| self.path(id)).collect();
+
+        References {
+            hovered: self.hovered.map(|id| self.path(id)),
+            captured: self.captured.map(|id| self.path(id)),
+            focused: self.focused.map(|id| self.path(id)),
+            fingers: self
+                .fingers
+                .iter()
+                .map(|(&finger, &id)| (finger, self.path(id)))
+                .collect(),
+            frames: paths(&self.frames),
+            relayout: paths(&self.relayout),
+            highlights: self
+                .highlights
+                .iter()
+                .map(|(&id, highlight)| (self.path(id), highlight.clone()))
+                .collect(),
+        }
+    }
+
+    /// Finds the nodes at the given [`References`] again.
+    ///
+    /// The pointer keeps targeting the closest remaining ancestor of a
+    /// removed node, so a drag goes on even if the row it started on is
+    /// replaced.
+    ///
+    /// [`References`]: struct.References.html
+    fn resolve(&mut self, references: References) {
+        let References {
+            hovered,
+            captured,
+            focused,
+            fingers,
+            frames,
+            relayout,
+            highlights,
+        } = references;
+
+        self.hovered = hovered.map(|path| self.find_closest(&path));
+        self.captured = captured.map(|path| self.find_closest(&path));
+        self.focused = focused
+            .and_then(|path| self.find(&path))
+            .filter(|&id| self.node(id).tab_index().is_some());
+        self.fingers = fingers
+            .into_iter()
+            .map(|(finger, path)| (finger, self.find_closest(&path)))
+            .collect();
+        self.frames = frames.iter().filter_map(|path| self.find(path)).collect();
+        self.relayout = relayout.iter().filter_map(|path| self.find(path)).collect();
+        self.highlights = highlights
+            .into_iter()
+            .filter_map(|(path, highlight)| self.find(&path).map(|id| (id, highlight)))
+            .collect();
+    }
+
+    /// Replaces the children of a node with the given elements.
+    ///
+    /// The state of the removed nodes is handed to the new nodes at the same
+    /// paths.
+    fn replace_children(
+        &mut self,
+        id: NodeId,
+        children: Vec<Element<Message>>,
+        states: &mut States,
+    ) {
+        let references = self.references();
+        let old: Vec<NodeId> = id.children(&self.arena).collect();
+
+        for child in old {
+            states.extend(self.take_states(child));
+
+            let nodes: Vec<NodeId> = child.descendants(&self.arena).collect();
+
+            // Descendants come after their ancestors, so leaves go first
+            for node in nodes.into_iter().rev() {
+                node.remove(&mut self.arena);
+            }
+        }
+
+        for element in children {
+            let child = insert(&mut self.arena, element);
+
+            id.append(child, &mut self.arena);
+            self.restore_states(child, states);
+        }
+
+        self.resolve(references);
+    }
+
+    /// Returns true if some widgets requested an [`Event::Frame`] or some
+    /// highlights are still fading.
+    ///
+    /// [`Event::Frame`]: ../event/enum.Event.html#variant.Frame
+    pub fn is_animating(&self) -> bool {
+        let now = self.clock.now();
+
+        !self.frames.is_empty()
+            || self.highlights.values().any(|highlight| highlight.is_animating(now))
+    }
+
+    /// Returns when the next frame should be drawn, or `None` if nothing is
+    /// animating and the [`UiTree`] can wait for the next event.
+    ///
+    /// [`UiTree`]: struct.UiTree.html
+    pub fn next_frame(&self) -> Option<Instant> {
+        if self.is_animating() {
+            Some(self.clock.now() + animation::FRAME)
+        } else {
+            None
+        }
+    }
+
+    /// Delivers an [`Event::Frame`] to the widgets that requested one and
+    /// forgets the highlights that finished fading out.
+    ///
+    /// [`Event::Frame`]: ../event/enum.Event.html#variant.Frame
+    pub fn frame(&mut self, messages: &mut Vec<Message>) {
+        let now = self.clock.now();
+        let frames = std::mem::take(&mut self.frames);
+
+        for id in frames {
+            let _ = self.deliver(id, id, Phase::Target, &Event::Frame(now), messages);
+        }
+
+        self.relayout(messages);
+        self.highlights
+            .retain(|_, highlight| !highlight.is_off() || highlight.is_animating(now));
+    }
+
+    /// Returns the root node of the [`UiTree`].
+    ///
+    /// [`UiTree`]: struct.UiTree.html
+    pub fn root(&self) -> NodeId {
+        self.root
+    }
+
+    /// Returns the [`Node`] with the given id.
+    ///
+    /// [`Node`]: struct.Node.html
+    pub fn node(&self, id: NodeId) -> &Node<Message> {
+        self.arena[id].get()
+    }
+
+    /// Returns the children of the given node, in order.
+    pub fn children(&self, id: NodeId) -> impl Iterator<Item = NodeId> + '_ {
+        id.children(&self.arena)
+    }
+
+    /// Returns the node currently under the cursor, if any.
+    pub fn hovered(&self) -> Option<NodeId> {
+        self.hovered
+    }
+
+    /// Returns the node that is currently capturing the mouse, if any.
+    pub fn captured(&self) -> Option<NodeId> {
+        self.captured
+    }
+
+    /// Returns the node that has the keyboard focus, if any.
+    pub fn focused(&self) -> Option<NodeId> {
+        self.focused
+    }
+
+    /// Returns true if the focus indicator should be drawn for the given node.
+    ///
+    /// Like `:focus-visible` on the web, the indicator is only shown when the
+    /// focus was moved with the keyboard or programmatically.
+    pub fn is_focus_visible(&self, id: NodeId) -> bool {
+        self.focus_visible && self.focused == Some(id)
+    }
+
+    /// Returns the first node with the given key, in tree order.
+    pub fn find_key(&self, key: &str) -> Option<NodeId> {
+        self.root
+            .descendants(&self.arena)
+            .find(|&id| self.arena[id].get().key() == Some(key))
+    }
+
+    /// Returns the first node whose widget has the given label, in tree
+    /// order.
+    pub fn find_label(&self, label: &str) -> Option<NodeId> {
+        self.root
+            .descendants(&self.arena)
+            .find(|&id| self.arena[id].get().widget.label() == Some(label))
+    }
+
+    /// Gives the keyboard focus to the given node.
+    ///
+    /// Returns false if the node cannot be focused.
+    pub fn focus(&mut self, id: NodeId, messages: &mut Vec<Message>) -> bool {
+        if self.node(id).tab_index().is_none() {
+            return false;
+        }
+
+        self.focus_visible = true;
+        self.set_focus(Some(id), messages);
+
+        true
+    }
+
+    /// Removes the keyboard focus from the focused node.
+    pub fn unfocus(&mut self, messages: &mut Vec<Message>) {
+        self.set_focus(None, messages);
+    }
+
+    /// Moves the focus to the next node in tab order, wrapping around.
+    pub fn focus_next(&mut self, messages: &mut Vec<Message>) {
+        let order = self.focus_order();
+
+        let next = match self.focus_position(&order) {
+            Some(i) => order.get((i + 1) % order.len()),
+            None => order.first(),
+        };
+
+        if let Some(&next) = next {
+            self.focus_visible = true;
+            self.set_focus(Some(next), messages);
+        }
+    }
+
+    /// Moves the focus to the previous node in tab order, wrapping around.
+    pub fn focus_previous(&mut self, messages: &mut Vec<Message>) {
+        let order = self.focus_order();
+
+        let previous = match self.focus_position(&order) {
+            Some(i) => order.get((i + order.len() - 1) % order.len()),
+            None => order.last(),
+        };
+
+        if let Some(&previous) = previous {
+            self.focus_visible = true;
+            self.set_focus(Some(previous), messages);
+        }
+    }
+
+    /// Performs a focus [`Command`].
+    ///
+    /// Window commands are left to the runtime.
+    ///
+    /// [`Command`]: ../command/enum.Command.html
+    pub fn perform(&mut self, command: Command, messages: &mut Vec<Message>) {
+        match command {
+            Command::None => {}
+            Command::Focus(key) => {
+                if let Some(id) = self.find_key(&key) {
+                    self.focus(id, messages);
+                }
+            }
+            Command::FocusNext => self.focus_next(messages),
+            Command::FocusPrevious => self.focus_previous(messages),
+            Command::Unfocus => self.unfocus(messages),
+            Command::OpenWindow(..) | Command::CloseWindow(_) => {}
+            Command::Batch(commands) => {
+                for command in commands {
+                    self.perform(command, messages);
+                }
+            }
+        }
+    }
+
+    /// Returns the accessibility tree of the [`UiTree`].
+    ///
+    /// Every node is described with the [`Properties`] of its widget and
+    /// named after its label. Nodes keep their [`Id`] across rebuilds as long
+    /// as they stay at the same position in the tree.
+    ///
+    /// [`UiTree`]: struct.UiTree.html
+    /// [`Properties`]: ../accessibility/struct.Properties.html
+    /// [`Id`]: ../accessibility/struct.Id.html
+    pub fn accessibility(&self) -> accessibility::Tree {
+        let mut nodes = HashMap::new();
+        let root = self.accessibility_node(self.root, &mut Vec::new(), &mut nodes);
+
+        accessibility::Tree {
+            root,
+            focus: self
+                .focused
+                .map(|focused| accessibility_id(&self.path(focused))),
+            nodes,
+        }
+    }
+
+    fn accessibility_node(
+        &self,
+        id: NodeId,
+        path: &mut Vec<PathSegment>,
+        nodes: &mut HashMap<accessibility::Id, accessibility::Node>,
+    ) -> accessibility::Id {
+        let node = self.node(id);
+        let properties = node.widget.accessibility();
+
+        let children = id
+            .children(&self.arena)
+            .enumerate()
+            .map(|(index, child)| {
+                path.push(match &self.arena[child].get().key {
+                    Some(key) => PathSegment::Key(key.clone()),
+                    None => PathSegment::Index(index),
+                });
+
+                let child = self.accessibility_node(child, path, nodes);
+
+                let _ = path.pop();
+                child
+            })
+            .collect();
+
+        let accessibility_id = accessibility_id(path);
+
+        let _ = nodes.insert(
+            accessibility_id,
+            accessibility::Node {
+                role: properties.role,
+                name: node.widget.label().map(String::from),
+                value: properties.value,
+                state: properties.state,
+                is_focusable: node.tab_index().is_some(),
+                bounds: node.bounds,
+                children,
+            },
+        );
+
+        accessibility_id
+    }
+
+    /// Performs an action requested by an assistive technology.
+    ///
+    /// [`Action::Focus`] moves the focus to the target node. Other actions
+    /// are routed to it as an [`Event::Accessibility`], like any other
+    /// event. Requests on nodes that are gone are ignored.
+    ///
+    /// [`Action::Focus`]: ../accessibility/enum.Action.html#variant.Focus
+    /// [`Event::Accessibility`]: ../event/enum.Event.html#variant.Accessibility
+    pub fn perform_action(
+        &mut self,
+        request: accessibility::Request,
+        messages: &mut Vec<Message>,
+    ) {
+        let target = match self
+            .root
+            .descendants(&self.arena)
+            .find(|&id| accessibility_id(&self.path(id)) == request.target)
+        {
+            Some(target) => target,
+            None => return,
+        };
+
+        match request.action {
+            accessibility::Action::Focus => {
+                self.focus(target, messages);
+            }
+            action => {
+                self.route(target, &Event::Accessibility(action), messages);
+
+                self.relayout(messages);
+                self.update_highlights();
+            }
+        }
+    }
+
+    /// Returns the nodes that take part in tab navigation, in order.
+    fn focus_order(&self) -> Vec<NodeId> {
+        let mut nodes: Vec<(i32, usize, NodeId)> = self
+            .root
+            .descendants(&self.arena)
+            .enumerate()
+            .filter_map(|(position, id)| {
+                self.node(id)
+                    .tab_index()
+                    .filter(|&tab_index| tab_index >= 0)
+                    .map(|tab_index| (tab_index, position, id))
+            })
+            .collect();
+
+        nodes.sort_by_key(|&(tab_index, position, _)| {
+            (tab_index == 0, tab_index, position)
+        });
+
+        nodes.into_iter().map(|(_, _, id)| id).collect()
+    }
+
+    fn focus_position(&self, order: &[NodeId]) -> Option<usize> {
+        self.focused
+            .and_then(|focused| order.iter().position(|&id| id == focused))
+    }
+
+    fn set_focus(
+        &mut self,
+        focused: Option<NodeId>,
+        messages: &mut Vec<Message>,
+    ) {
+        if focused == self.focused {
+            return;
+        }
+
+        if let Some(lost) = self.focused.take() {
+            self.deliver(
+                lost,
+                lost,
+                Phase::Target,
+                &Event::Focus(focus::Event::Lost),
+                messages,
+            );
+        }
+
+        self.focused = focused;
+
+        if let Some(gained) = focused {
+            self.deliver(
+                gained,
+                gained,
+                Phase::Target,
+                &Event::Focus(focus::Event::Gained),
+                messages,
+            );
+        }
+    }
+
+    /// Lays out the [`UiTree`] to fill the given size.
+    ///
+    /// [`UiTree`]: struct.UiTree.html
+    pub fn layout(&mut self, size: Size) {
+        self.size = size;
+
+        self.arrange(self.root, self.viewport(), &mut HashMap::new());
+    }
+
+    fn viewport(&self) -> Rectangle {
+        Rectangle {
+            x: 0.0,
+            y: 0.0,
+            width: self.size.width,
+            height: self.size.height,
+        }
+    }
+
+    /// Lays out a node, then lets the widgets that build their children on
+    /// demand build them for their new bounds.
+    fn arrange(&mut self, id: NodeId, bounds: Rectangle, states: &mut States) {
+        layout::arrange(&mut self.arena, id, bounds);
+
+        let mut pending = vec![id];
+
+        while let Some(id) = pending.pop() {
+            for _ in 0..MAX_BUILDS {
+                let children: Vec<Rectangle> = id
+                    .children(&self.arena)
+                    .map(|child| self.node(child).bounds)
+                    .collect();
+
+                let built = self.arena[id].get_mut().widget.build_children(&children);
+
+                match built {
+                    Some(elements) => {
+                        let bounds = self.node(id).bounds;
+
+                        self.replace_children(id, elements, states);
+                        layout::arrange(&mut self.arena, id, bounds);
+                    }
+                    None => break,
+                }
+            }
+
+            pending.extend(id.children(&self.arena));
+        }
+    }
+
+    /// Returns the deepest node whose bounds contain the given [`Point`].
+    ///
+    /// Later siblings are drawn on top of earlier ones, so they are tested
+    /// first.
+    ///
+    /// [`Point`]: ../../visper_graphics/core/point/struct.Point.html
+    pub fn hit_test(&self, point: Point) -> Option<NodeId> {
+        self.hit_test_node(self.root, point)
+    }
+
+    fn hit_test_node(&self, id: NodeId, point: Point) -> Option<NodeId> {
+        if !self.arena[id].get().bounds.contains(point) {
+            return None;
+        }
+
+        let last_child = self.arena[id].last_child();
+
+        std::iter::successors(last_child, |&child| self.arena[child].previous_sibling())
+            .find_map(|child| self.hit_test_node(child, point))
+            .or(Some(id))
+    }
+
+    /// Dispatches an [`Event`] through the [`UiTree`], collecting the messages
+    /// published by the widgets.
+    ///
+    /// Mouse events target the node under the cursor, or the node capturing
+    /// the mouse while a button is held down. Touch events target the node
+    /// under the finger when it was pressed. Keyboard events target the focused
+    /// node, or the root when nothing is focused; an unhandled Tab press moves
+    /// the focus.
+    ///
+    /// [`Event`]: ../event/enum.Event.html
+    /// [`UiTree`]: struct.UiTree.html
+    pub fn dispatch(&mut self, event: &Event, messages: &mut Vec<Message>) {
+        self.dispatch_event(event, messages);
+        self.relayout(messages);
+        self.update_highlights();
+    }
+
+    fn dispatch_event(&mut self, event: &Event, messages: &mut Vec<Message>) {
+        match event {
+            Event::Mouse(mouse::Event::CursorMoved { position }) => {
+                self.cursor_position = Some(*position);
+                self.update_hovered(messages);
+
+                if let Some(target) = self.captured.or(self.hovered) {
+                    self.route(target, event, messages);
+                }
+            }
+            Event::Mouse(mouse::Event::CursorEntered) => {}
+            Event::Mouse(mouse::Event::CursorLeft) => {
+                self.cursor_position = None;
+                self.update_hovered(messages);
+            }
+            Event::Mouse(mouse::Event::ButtonPressed(_)) => {
+                if let Some(target) = self.hovered {
+                    self.captured = Some(target);
+                    self.route(target, event, messages);
+
+                    let focusable = target
+                        .ancestors(&self.arena)
+                        .find(|&id| self.node(id).tab_index().is_some());
+
+                    self.focus_visible = false;
+                    self.set_focus(focusable, messages);
+                }
+            }
+            Event::Mouse(mouse::Event::ButtonReleased(_)) => {
+                if let Some(target) = self.captured.or(self.hovered) {
+                    self.route(target, event, messages);
+                }
+
+                self.captured = None;
+            }
+            Event::Mouse(mouse::Event::WheelScrolled { .. }) => {
+                if let Some(target) = self.hovered {
+                    self.route(target, event, messages);
+                }
+            }
+            Event::Touch(touch) => {
+                let finger = touch.finger();
+
+                let target = match touch {
+                    touch::Event::FingerPressed { position, .. } => {
+                        let target = self.hit_test(*position);
+
+                        if let Some(target) = target {
+                            let _ = self.fingers.insert(finger, target);
+                        }
+
+                        target
+                    }
+                    touch::Event::FingerMoved { .. } => {
+                        self.fingers.get(&finger).cloned()
+                    }
+                    touch::Event::FingerLifted { .. }
+                    | touch::Event::FingerLost { .. } => {
+                        self.fingers.remove(&finger)
+                    }
+                };
+
+                if let Some(target) = target {
+                    self.route(target, event, messages);
+                }
+            }
+            Event::Keyboard(key_event) => {
+                let target = self.focused.unwrap_or(self.root);
+                let stopped = self.route(target, event, messages);
+
+                if let keyboard::Event::KeyPressed {
+                    key_code: keyboard::KeyCode::Tab,
+                    modifiers,
+                } = key_event
+                {
+                    if !stopped {
+                        if modifiers.shift {
+                            self.focus_previous(messages);
+                        } else {
+                            self.focus_next(messages);
+                        }
+                    }
+                }
+            }
+            Event::Focus(_) | Event::Frame(_) | Event::Accessibility(_) => {}
+        }
+    }
+
+    /// Lays out again the nodes that scrolled or asked for it while handling
+    /// an event.
+    fn relayout(&mut self, messages: &mut Vec<Message>) {
+        if self.relayout.is_empty() {
+            return;
+        }
+
+        // Building children may replace the nodes left in the list
+        while let Some(id) = self.relayout.pop() {
+            let bounds = self.node(id).bounds;
+
+            self.arrange(id, bounds, &mut HashMap::new());
+        }
+
+        self.update_hovered(messages);
+    }
+
+    fn update_hovered(&mut self, messages: &mut Vec<Message>) {
+        let hovered = self.cursor_position.and_then(|p| self.hit_test(p));
+
+        if hovered == self.hovered {
+            return;
+        }
+
+        if let Some(left) = self.hovered {
+            self.deliver(
+                left,
+                left,
+                Phase::Target,
+                &Event::Mouse(mouse::Event::CursorLeft),
+                messages,
+            );
+        }
+
+        self.hovered = hovered;
+
+        if let Some(entered) = hovered {
+            self.deliver(
+                entered,
+                entered,
+                Phase::Target,
+                &Event::Mouse(mouse::Event::CursorEntered),
+                messages,
+            );
+        }
+    }
+
+    /// Fades the highlights in or out to follow the hovered and pressed
+    /// nodes.
+    fn update_highlights(&mut self) {
+        let now = self.clock.now();
+        let transition = self.transition;
+
+        let hovered: Vec<NodeId> = match self.hovered {
+            Some(hovered) => hovered.ancestors(&self.arena).collect(),
+            None => Vec::new(),
+        };
+
+        for &id in &hovered {
+            let _ = self
+                .highlights
+                .entry(id)
+                .or_insert_with(|| Highlight::new(transition));
+        }
+
+        for (id, highlight) in self.highlights.iter_mut() {
+            let is_hovered = hovered.contains(id);
+            let is_pressed = is_hovered && self.captured == Some(*id);
+
+            highlight.hover.set(if is_hovered { 1.0 } else { 0.0 }, now);
+            highlight.press.set(if is_pressed { 1.0 } else { 0.0 }, now);
+        }
+    }
+
+    /// Routes an event from the root to the target and back.
+    ///
+    /// Returns true if a widget stopped its propagation.
+    fn route(
+        &mut self,
+        target: NodeId,
+        event: &Event,
+        messages: &mut Vec<Message>,
+    ) -> bool {
+        let mut path: Vec<NodeId> = target.ancestors(&self.arena).collect();
+        path.reverse();
+
+        let (_, ancestors) = path.split_last().expect("Target is in the path");
+
+        let capture = ancestors.iter().map(|&id| (id, Phase::Capture));
+        let bubble = ancestors.iter().rev().map(|&id| (id, Phase::Bubble));
+
+        capture
+            .chain(std::iter::once((target, Phase::Target)))
+            .chain(bubble)
+            .any(|(id, phase)| self.deliver(id, target, phase, event, messages))
+    }
+
+    /// Delivers an event to a single node.
+    ///
+    /// Returns true if the widget stopped its propagation.
+    fn deliver(
+        &mut self,
+        id: NodeId,
+        target: NodeId,
+        phase: Phase,
+        event: &Event,
+        messages: &mut Vec<Message>,
+    ) -> bool {
+        let is_hovered = self.is_hovered(id);
+        let cursor_position = self.cursor_position;
+        let is_focused = self.focused == Some(id);
+        let is_captured = self.captured == Some(id);
+
+        let node = self.arena[id].get_mut();
+        let scroll = node.widget.scroll();
+        let mut ctx = EventCtx {
+            phase: if id == target { Phase::Target } else { phase },
+            bounds: node.bounds,
+            cursor_position,
+            is_hovered,
+            is_focused,
+            is_captured,
+            now: self.clock.now(),
+            messages,
+            clipboard: self.clipboard.as_mut(),
+            propagation_stopped: false,
+            frame_requested: false,
+            layout_requested: false,
+        };
+
+        node.widget.on_event(event, &mut ctx);
+
+        let EventCtx {
+            propagation_stopped,
+            frame_requested,
+            layout_requested,
+            ..
+        } = ctx;
+
+        if frame_requested && !self.frames.contains(&id) {
+            self.frames.push(id);
+        }
+
+        let needs_layout = layout_requested || node.widget.scroll() != scroll;
+
+        if needs_layout && !self.relayout.contains(&id) {
+            self.relayout.push(id);
+        }
+
+        propagation_stopped
+    }
+
+    /// Returns true if the cursor is over the given node or one of its
+    /// descendants.
+    fn is_hovered(&self, id: NodeId) -> bool {
+        self.hovered
+            .map(|hovered| hovered.ancestors(&self.arena).any(|a| a == id))
+            .unwrap_or(false)
+    }
+
+    /// Returns the primitives that render the [`UiTree`].
+    ///
+    /// Children are drawn on top of their parent, in order, and the focus
+    /// ring is drawn on top of the focused node when it is visible. The
+    /// children of scrolling nodes are clipped to their bounds, and nodes
+    /// outside of the visible area are skipped.
+    ///
+    /// [`UiTree`]: struct.UiTree.html
+    pub fn draw(&self, theme: &Theme) -> Primitive {
+        self.draw_node(self.root, theme, self.viewport(), self.clock.now())
+    }
+
+    fn draw_node(
+        &self,
+        id: NodeId,
+        theme: &Theme,
+        viewport: Rectangle,
+        now: Instant,
+    ) -> Primitive {
+        let node = self.node(id);
+        let is_hovered = self.is_hovered(id);
+        let highlight = self.highlights.get(&id);
+
+        let ctx = DrawCtx {
+            bounds: node.bounds,
+            theme,
+            is_hovered,
+            is_pressed: is_hovered && self.captured == Some(id),
+            is_focused: self.focused == Some(id),
+            hover: highlight.map_or(0.0, |highlight| highlight.hover.value(now)),
+            press: highlight.map_or(0.0, |highlight| highlight.press.value(now)),
+            now,
+        };
+
+        let mut primitives = vec![node.widget.draw(&ctx)];
+
+        let is_scrolling = node.widget.scroll().is_some();
+        let viewport = if is_scrolling {
+            viewport.intersection(&node.bounds)
+        } else {
+            Some(viewport)
+        };
+
+        if let Some(viewport) = viewport {
+            let children: Vec<Primitive> = id
+                .children(&self.arena)
+                .filter(|&child| {
+                    self.node(child).bounds.intersection(&viewport).is_some()
+                })
+                .map(|child| self.draw_node(child, theme, viewport, now))
+                .collect();
+
+            if is_scrolling {
+                primitives.push(Primitive::Clip {
+                    bounds: node.bounds,
+                    offset: Vector::default(),
+                    content: Box::new(Primitive::Group { primitives: children }),
+                });
+            } else {
+                primitives.extend(children);
+            }
+        }
+
+        primitives.push(node.widget.draw_overlay(&ctx));
+
+        if self.is_focus_visible(id) {
+            primitives.push(focus_ring(node.bounds, &theme.focus_ring));
+        }
+
+        Primitive::Group { primitives }
+    }
+
+    /// Returns the position of a node relative to the root, as a list of
+    /// child indices or keys.
+    fn path(&self, id: NodeId) -> Vec<PathSegment> {
+        let mut path: Vec<PathSegment> = id
+            .ancestors(&self.arena)
+            .take_while(|&ancestor| ancestor != self.root)
+            .map(|ancestor| match &self.arena[ancestor].get().key {
+                Some(key) => PathSegment::Key(key.clone()),
+                None => PathSegment::Index(
+                    ancestor.preceding_siblings(&self.arena).count() - 1,
+                ),
+            })
+            .collect();
+
+        path.reverse();
+        path
+    }
+
+    /// Finds the node at the given path.
+    fn find(&self, path: &[PathSegment]) -> Option<NodeId> {
+        path.iter().try_fold(self.root, |parent, segment| {
+            parent
+                .children(&self.arena)
+                .enumerate()
+                .find(|&(index, child)| {
+                    match (segment, &self.arena[child].get().key) {
+                        (PathSegment::Key(key), Some(child_key)) => key == child_key,
+                        (PathSegment::Index(i), None) => *i == index,
+                        _ => false,
+                    }
+                })
+                .map(|(_, child)| child)
+        })
+    }
+
+    /// Finds the node at the given path, or its closest remaining ancestor.
+    fn find_closest(&self, path: &[PathSegment]) -> NodeId {
+        (0..=path.len())
+            .rev()
+            .find_map(|length| self.find(&path[..length]))
+            .unwrap_or(self.root)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum PathSegment {
+    Key(String),
+    Index(usize),
+}
+
+fn accessibility_id(path: &[PathSegment]) -> accessibility::Id {
+    let mut hasher = DefaultHasher::new();
+
+    path.hash(&mut hasher);
+
+    accessibility::Id(hasher.finish())
+}
+
+/// The states of some widgets, by path.
+type States = HashMap<Vec<PathSegment>, Box<dyn Any>>;
+
+/// The paths of the nodes a [`UiTree`] refers to.
+///
+/// [`UiTree`]: struct.UiTree.html
+struct References {
+    hovered: Option<Vec<PathSegment>>,
+    captured: Option<Vec<PathSegment>>,
+    focused: Option<Vec<PathSegment>>,
+    fingers: Vec<(touch::Finger, Vec<PathSegment>)>,
+    frames: Vec<Vec<PathSegment>>,
+    relayout: Vec<Vec<PathSegment>>,
+    highlights: Vec<(Vec<PathSegment>, Highlight)>,
+}
+
+/// The time the hover and press highlights take to fade by default.
+const HIGHLIGHT_DURATION: Duration = Duration::from_millis(150);
+
+/// The number of times a widget may build its children during a single
+/// layout, as laying out the new children may change what it needs to build.
+const MAX_BUILDS: usize = 4;
+
+fn focus_ring(bounds: Rectangle, ring: &FocusRing) -> Primitive {
+    let outset = ring.offset + ring.width;
+
+    Primitive::Quad {
+        bounds: Rectangle {
+            x: bounds.x - outset,
+            y: bounds.y - outset,
+            width: bounds.width + outset * 2.0,
+            height: bounds.height + outset * 2.0,
+        },
+        background: Color::TRANSPARENT,
+        border_radius: ring.border_radius + outset,
+        border_width: ring.width,
+        border_color: ring.color,
+    }
+}
+
+/// Returns the first element with the given key, in tree order.
+fn find_element<Message>(element: Element<Message>, key: &str) -> Option<Element<Message>> {
+    if element.key.as_deref() == Some(key) {
+        return Some(element);
+    }
+
+    element
+        .children
+        .into_iter()
+        .find_map(|child| find_element(child, key))
+}
+
+fn insert<Message>(arena: &mut Arena<Node<Message>>, element: Element<Message>) -> NodeId {
+    let Element {
+        widget,
+        children,
+        key,
+        tab_index,
+    } = element;
+
+    let id = arena.new_node(Node {
+        widget,
+        key,
+        tab_index,
+        bounds: Rectangle::default(),
+    });
+
+    for child in children {
+        let child = insert(arena, child);
+
+        id.append(child, arena);
+    }
+
+    id
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::mouse;
+    use crate::style::{Length, Style};
+    use crate::widget::{Button, Column, Row, Scrollable, Text};
+
+    struct Probe {
+        name: &'static str,
+        stop_at: Option<Phase>,
+    }
+
+    impl Widget<(&'static str, Phase)> for Probe {
+        fn style(&self) -> Style {
+            Style::new().width(Length::Fill).height(Length::Fill)
+        }
+
+        fn on_event(
+            &mut self,
+            event: &Event,
+            ctx: &mut EventCtx<'_, (&'static str, Phase)>,
+        ) {
+            if let Event::Mouse(mouse::Event::ButtonPressed(_)) = event {
+                ctx.publish((self.name, ctx.phase()));
+
+                if Some(ctx.phase()) == self.stop_at {
+                    ctx.stop_propagation();
+                }
+            }
+        }
+    }
+
+    fn probe(name: &'static str) -> Element<(&'static str, Phase)> {
+        Element::new(Probe { name, stop_at: None })
+    }
+
+    fn press(tree: &mut UiTree<(&'static str, Phase)>, x: f32, y: f32) -> Vec<(&'static str, Phase)> {
+        let mut messages = Vec::new();
+
+        tree.dispatch(
+            &Event::Mouse(mouse::Event::CursorMoved { position: Point::new(x, y) }),
+            &mut messages,
+        );
+        tree.dispatch(
+            &Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Left)),
+            &mut messages,
+        );
+
+        messages
+    }
+
+    struct Focusable;
+
+    impl Widget<(&'static str, Phase)> for Focusable {
+        fn style(&self) -> Style {
+            Style::new().width(10.0).height(10.0)
+        }
+
+        fn focusable(&self) -> bool {
+            true
+        }
+    }
+
+    fn focusable(key: &'static str) -> Element<(&'static str, Phase)> {
+        Element::new(Focusable).key(key)
+    }
+
+    fn tab(tree: &mut UiTree<(&'static str, Phase)>, shift: bool) -> Option<String> {
+        let mut messages = Vec::new();
+
+        tree.dispatch(
+            &Event::Keyboard(keyboard::Event::KeyPressed {
+                key_code: keyboard::KeyCode::Tab,
+                modifiers: keyboard::ModifiersState {
+                    shift,
+                    ..Default::default()
+                },
+            }),
+            &mut messages,
+        );
+
+        tree.focused().and_then(|id| tree.node(id).key().map(String::from))
+    }
+
+    #[test]
+    fn row_splits_fill_children() {
+        let mut tree = UiTree::new(
+            Row::new().spacing(10.0).append(probe("a")).append(probe("b")),
+        );
+        tree.layout(Size::new(210.0, 50.0));
+
+        let bounds: Vec<Rectangle> = tree
+            .children(tree.root())
+            .map(|id| tree.node(id).bounds())
+            .collect();
+
+        assert_eq!(bounds[0], Rectangle { x: 0.0, y: 0.0, width: 100.0, height: 50.0 });
+        assert_eq!(bounds[1], Rectangle { x: 110.0, y: 0.0, width: 100.0, height: 50.0 });
+    }
+
+    #[test]
+    fn events_capture_then_bubble() {
+        let mut tree = UiTree::new(
+            Element::new(Probe { name: "root", stop_at: None }).append(
+                Column::new()
+                    .width(Length::Fill)
+                    .height(Length::Fill)
+                    .append(probe("leaf")),
+            ),
+        );
+        tree.layout(Size::new(100.0, 100.0));
+
+        assert_eq!(
+            press(&mut tree, 50.0, 50.0),
+            vec![
+                ("root", Phase::Capture),
+                ("leaf", Phase::Target),
+                ("root", Phase::Bubble),
+            ]
+        );
+    }
+
+    #[test]
+    fn stop_propagation_skips_remaining_nodes() {
+        let mut tree = UiTree::new(
+            Element::new(Probe { name: "root", stop_at: Some(Phase::Capture) })
+                .append(probe("leaf")),
+        );
+        tree.layout(Size::new(100.0, 100.0));
+
+        assert_eq!(press(&mut tree, 50.0, 50.0), vec![("root", Phase::Capture)]);
+    }
+
+    #[test]
+    fn tab_follows_tab_index_then_tree_order() {
+        let mut tree = UiTree::new(
+            Row::new()
+                .append(focusable("a"))
+                .append(focusable("b").tab_index(2))
+                .append(focusable("c").tab_index(-1))
+                .append(focusable("d"))
+                .append(focusable("e").tab_index(1)),
+        );
+        tree.layout(Size::new(100.0, 100.0));
+
+        assert_eq!(tab(&mut tree, false).as_deref(), Some("e"));
+        assert_eq!(tab(&mut tree, false).as_deref(), Some("b"));
+        assert_eq!(tab(&mut tree, false).as_deref(), Some("a"));
+        assert_eq!(tab(&mut tree, false).as_deref(), Some("d"));
+        assert_eq!(tab(&mut tree, false).as_deref(), Some("e"));
+        assert_eq!(tab(&mut tree, true).as_deref(), Some("d"));
+        assert!(tree.is_focus_visible(tree.focused().unwrap()));
+    }
+
+    #[test]
+    fn click_focuses_without_focus_ring() {
+        let mut tree = UiTree::new(
+            Row::new()
+                .append(focusable("a"))
+                .append(focusable("b").tab_index(-1)),
+        );
+        tree.layout(Size::new(100.0, 100.0));
+        let mut messages = Vec::new();
+
+        let _ = press(&mut tree, 15.0, 5.0);
+        let focused = tree.focused().unwrap();
+
+        assert_eq!(tree.node(focused).key(), Some("b"));
+        assert!(!tree.is_focus_visible(focused));
+
+        let _ = press(&mut tree, 50.0, 50.0);
+        assert_eq!(tree.focused(), None);
+
+        tree.perform(Command::focus("a"), &mut messages);
+        assert_eq!(tree.focused(), tree.find_key("a"));
+        assert!(tree.is_focus_visible(tree.focused().unwrap()));
+    }
+
+    #[test]
+    fn hit_test_misses_outside_of_bounds() {
+        let mut tree = UiTree::new(Row::new().append(probe("a")));
+        tree.layout(Size::new(100.0, 100.0));
+
+        assert_eq!(tree.hit_test(Point::new(150.0, 50.0)), None);
+        assert!(press(&mut tree, 150.0, 50.0).is_empty());
+    }
+    #[test]
+    fn rebuild_key_keeps_the_state_under_the_node() {
+        let view = |spacing: f32| {
+            let rows = (0..10).map(|_| Button::<()>::new("Row").height(50.0));
+            let list = rows.fold(Element::from(Scrollable::new()), Element::append);
+
+            Column::new()
+                .width(Length::Fill)
+                .height(Length::Fill)
+                .spacing(spacing)
+                .append(Element::from(Text::new("Header")).key("header"))
+                .append(list.key("list"))
+        };
+        let offset = |tree: &UiTree<()>| {
+            let list = tree.find_key("list").unwrap();
+
+            tree.node(list).widget().scroll().unwrap().offset.y
+        };
+
+        let mut tree = UiTree::new(view(0.0));
+        tree.layout(Size::new(200.0, 100.0));
+
+        let header = tree.find_key("header").unwrap();
+        let mut messages = Vec::new();
+
+        for event in &[
+            Event::Mouse(mouse::Event::CursorMoved { position: Point::new(100.0, 90.0) }),
+            Event::Mouse(mouse::Event::WheelScrolled {
+                delta: mouse::ScrollDelta::Pixels { x: 0.0, y: -30.0 },
+            }),
+        ] {
+            tree.dispatch(event, &mut messages);
+        }
+
+        assert_eq!(offset(&tree), 30.0);
+
+        // The spacing of the root does not come from the node with the key
+        assert!(tree.rebuild_key("list", view(10.0)));
+        assert_eq!(tree.find_key("header"), Some(header));
+        assert_eq!(offset(&tree), 30.0);
+
+        assert!(!tree.rebuild_key("missing", view(10.0)));
+    }
+}
@@ -0,0 +1,289 @@
+//! Describe widget trees with markup.
+
+/// Builds an [`Element`] out of a tree of widgets.
+///
+/// Each node names a widget type, or a path to one like `widget::Button`,
+/// followed by the arguments of its `new` constructor in parentheses, then
+/// its children in brackets. Both are optional. The arguments of the form `name = value` that come after the
+/// positional ones call the builder method of the same name, which covers
+/// style attributes like `width` and `padding` as well as event handlers
+/// like `on_press`; `key` and `tab_index` are set on the resulting
+/// [`Element`]. A block inserts any expression that converts into an
+/// [`Element`].
+///
+/// ```ignore
+/// view! {
+///     Column(width = Length::Fill, padding = 20.0, spacing = 10.0) [
+///         Text(format!("Clicked {} times", self.clicks)),
+///         Row(spacing = 8.0) [
+///             Button("Click", on_press = Message::Clicked),
+///             Checkbox(self.is_checked, "Check", Message::Checked, key = "check"),
+///         ],
+///         { self.footer() },
+///     ]
+/// }
+/// ```
+///
+/// is the same as
+///
+/// ```ignore
+/// Element::from(Column::new().width(Length::Fill).padding(20.0).spacing(10.0))
+///     .append(Text::new(format!("Clicked {} times", self.clicks)))
+///     .append(
+///         Element::from(Row::new().spacing(8.0))
+///             .append(Button::new("Click").on_press(Message::Clicked))
+///             .append(
+///                 Element::from(Checkbox::new(self.is_checked, "Check", Message::Checked))
+///                     .key("check"),
+///             ),
+///     )
+///     .append(self.footer())
+/// ```
+///
+/// Attributes keep the span of their name, so a misspelled attribute is
+/// reported on the attribute itself, and unexpected tokens are reported
+/// where they appear:
+///
+/// ```compile_fail
+/// # use visper_gui::view;
+/// # use visper_gui::widget::{Column, Element, Text};
+/// let element: Element<()> = view! {
+///     Column(spacing = 10.0) [
+///         Text("Title", widht = 100.0),
+///     ]
+/// };
+/// ```
+///
+/// ```compile_fail
+/// # use visper_gui::view;
+/// # use visper_gui::widget::{Column, Element, Text};
+/// let element: Element<()> = view! {
+///     Column [
+///         Text("Title")
+///         Text("Subtitle"),
+///     ]
+/// };
+/// ```
+///
+/// [`Element`]: widget/struct.Element.html
+#[macro_export]
+macro_rules! view {
+    ({ $element:expr }) => {
+        $crate::widget::Element::from($element)
+    };
+    ($($widget:ident)::+ $( ( $($arguments:tt)* ) )? $( [ $($children:tt)* ] )?) => {
+        $crate::view!(
+            @arguments [$($widget)::+] [] [] []
+            ($($($arguments)*)?)
+            [$($($children)*)?]
+        )
+    };
+
+    // Sort the arguments into constructor arguments, builder methods and
+    // element methods
+    (
+        @arguments $widget:tt [$($argument:expr;)*] [$($method:ident = $value:expr;)*]
+        [$($element_method:ident = $element_value:expr;)*]
+        (key = $key:expr $(, $($rest:tt)*)?)
+        $children:tt
+    ) => {
+        $crate::view!(
+            @arguments $widget [$($argument;)*] [$($method = $value;)*]
+            [$($element_method = $element_value;)* key = $key;]
+            ($($($rest)*)?)
+            $children
+        )
+    };
+    (
+        @arguments $widget:tt [$($argument:expr;)*] [$($method:ident = $value:expr;)*]
+        [$($element_method:ident = $element_value:expr;)*]
+        (tab_index = $tab_index:expr $(, $($rest:tt)*)?)
+        $children:tt
+    ) => {
+        $crate::view!(
+            @arguments $widget [$($argument;)*] [$($method = $value;)*]
+            [$($element_method = $element_value;)* tab_index = $tab_index;]
+            ($($($rest)*)?)
+            $children
+        )
+    };
+    (
+        @arguments $widget:tt [$($argument:expr;)*] [$($method:ident = $value:expr;)*]
+        $element_methods:tt
+        ($name:ident = $attribute:expr $(, $($rest:tt)*)?)
+        $children:tt
+    ) => {
+        $crate::view!(
+            @arguments $widget [$($argument;)*] [$($method = $value;)* $name = $attribute;]
+            $element_methods
+            ($($($rest)*)?)
+            $children
+        )
+    };
+    (
+        @arguments $widget:tt [$($argument:expr;)*] $methods:tt $element_methods:tt
+        ($next:expr $(, $($rest:tt)*)?)
+        $children:tt
+    ) => {
+        $crate::view!(
+            @arguments $widget [$($argument;)* $next;] $methods $element_methods
+            ($($($rest)*)?)
+            $children
+        )
+    };
+    (
+        @arguments [$($widget:ident)::+] [$($argument:expr;)*] [$($method:ident = $value:expr;)*]
+        [$($element_method:ident = $element_value:expr;)*]
+        ()
+        [$($children:tt)*]
+    ) => {
+        $crate::view!(
+            @children
+            $crate::widget::Element::from($($widget)::+::new($($argument),*) $(.$method($value))*)
+                $(.$element_method($element_value))*;
+            $($children)*
+        )
+    };
+
+    // Append the children one by one
+    (@children $parent:expr;) => {
+        $parent
+    };
+    (@children $parent:expr; { $element:expr } $(, $($rest:tt)*)?) => {
+        $crate::view!(
+            @children $parent.append($crate::view!({ $element }));
+            $($($rest)*)?
+        )
+    };
+    (
+        @children $parent:expr;
+        $($widget:ident)::+ $( ( $($arguments:tt)* ) )? $( [ $($children:tt)* ] )?
+        $(, $($rest:tt)*)?
+    ) => {
+        $crate::view!(
+            @children $parent.append(
+                $crate::view!($($widget)::+ $( ( $($arguments)* ) )? $( [ $($children)* ] )?)
+            );
+            $($($rest)*)?
+        )
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::event::{mouse, Event};
+    use crate::style::Length;
+    use crate::tree::UiTree;
+    use crate::widget::{self, Button, Column, Element, Row, Text};
+    use visper_graphics::core::point::Point;
+    use visper_graphics::core::size::Size;
+
+    #[derive(Debug, Clone, PartialEq)]
+    enum Message {
+        Pressed(usize),
+    }
+
+    fn footer() -> Element<Message> {
+        Text::new("Footer").into()
+    }
+
+    #[test]
+    fn view_builds_the_described_tree() {
+        let mut tree = UiTree::new(crate::view! {
+            Column(width = Length::Fill, height = Length::Fill) [
+                Text("Title", key = "title"),
+                Row(height = 40.0) [
+                    Button("One", on_press = Message::Pressed(1), width = 50.0),
+                    Button("Two", on_press = Message::Pressed(2), width = 50.0),
+                ],
+                { footer() },
+            ]
+        });
+        tree.layout(Size::new(200.0, 100.0));
+
+        let children: Vec<_> = tree.children(tree.root()).collect();
+        let row = children[1];
+
+        assert_eq!(children.len(), 3);
+        assert_eq!(tree.node(children[0]).key(), Some("title"));
+        assert_eq!(tree.children(row).count(), 2);
+        assert_eq!(tree.node(row).bounds().height, 40.0);
+
+        let button = tree.children(row).nth(1).unwrap();
+        let bounds = tree.node(button).bounds();
+        let mut messages = Vec::new();
+
+        for event in &[
+            Event::Mouse(mouse::Event::CursorMoved {
+                position: Point::new(bounds.x + 10.0, bounds.y + 10.0),
+            }),
+            Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Left)),
+            Event::Mouse(mouse::Event::ButtonReleased(mouse::Button::Left)),
+        ] {
+            tree.dispatch(event, &mut messages);
+        }
+
+        assert_eq!(messages, vec![Message::Pressed(2)]);
+    }
+
+    #[test]
+    fn view_accepts_paths_to_widgets() {
+        let tree: UiTree<Message> = UiTree::new(crate::view! {
+            crate::widget::Column [
+                widget::Text("Title", key = "title"),
+                Text("Subtitle"),
+            ]
+        });
+
+        assert_eq!(tree.children(tree.root()).count(), 2);
+        assert!(tree.find_key("title").is_some());
+        assert!(tree.find_label("Subtitle").is_some());
+    }
+
+    #[test]
+    fn view_nests_children_in_order() {
+        let tree: UiTree<Message> = UiTree::new(crate::view! {
+            Column [
+                Row [
+                    Column [
+                        Text("Deepest", key = "deepest"),
+                    ],
+                    Text("Second"),
+                ],
+                Row,
+                Text("Last"),
+            ]
+        });
+
+        let root = tree.root();
+        let outer: Vec<_> = tree.children(root).collect();
+        let inner: Vec<_> = tree.children(outer[0]).collect();
+        let deepest = tree.children(inner[0]).next().unwrap();
+
+        assert_eq!(outer.len(), 3);
+        assert_eq!(inner.len(), 2);
+        assert_eq!(tree.children(outer[1]).count(), 0);
+        assert_eq!(tree.node(deepest).key(), Some("deepest"));
+        assert_eq!(Some(deepest), tree.find_key("deepest"));
+    }
+
+    #[test]
+    fn view_sets_element_attributes_anywhere_in_the_arguments() {
+        let tree = UiTree::new(crate::view! {
+            Column [
+                Button(key = "first", "First", tab_index = 2, on_press = Message::Pressed(1)),
+                Button("Second", tab_index = 1, key = "second", width = 80.0),
+                Button("Third"),
+            ]
+        });
+
+        let first = tree.find_key("first").unwrap();
+        let second = tree.find_key("second").unwrap();
+        let third = tree.children(tree.root()).nth(2).unwrap();
+
+        assert_eq!(tree.node(first).tab_index(), Some(2));
+        assert_eq!(tree.node(second).tab_index(), Some(1));
+        assert_eq!(tree.node(third).key(), None);
+        assert_eq!(tree.node(third).tab_index(), None);
+    }
+}
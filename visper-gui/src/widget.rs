@@ -0,0 +1,469 @@
+//! Build user interfaces out of widgets.
+pub mod button;
+pub mod checkbox;
+pub mod column;
+pub mod container;
+pub mod radio;
+pub mod row;
+pub mod scrollable;
+pub mod slider;
+pub mod table;
+pub mod text;
+pub mod text_input;
+pub mod toggle;
+pub mod virtual_list;
+
+pub use button::Button;
+pub use checkbox::Checkbox;
+pub use column::Column;
+pub use container::Container;
+pub use radio::Radio;
+pub use row::Row;
+pub use scrollable::Scrollable;
+pub use slider::Slider;
+pub use table::Table;
+pub use text::Text;
+pub use text_input::TextInput;
+pub use toggle::Toggle;
+pub use virtual_list::VirtualList;
+
+use crate::accessibility::{self, Properties, Role};
+use crate::clipboard::Clipboard;
+use crate::event::{keyboard, mouse, Event, Phase};
+use crate::layout::{Axis, Scroll};
+use crate::style::Style;
+use crate::theme::Theme;
+use std::any::Any;
+use std::time::Instant;
+use visper_graphics::core::point::Point;
+use visper_graphics::core::rectangle::Rectangle;
+use visper_graphics::core::size::Size;
+use visper_graphics::primitive::Primitive;
+
+/// A component of a user interface.
+///
+/// Widgets live in the nodes of a [`UiTree`]. The tree owns the layout and the
+/// routing of events; a widget only describes its own size, reacts to the
+/// events dispatched to it and produces the primitives to render it.
+///
+/// [`UiTree`]: ../tree/struct.UiTree.html
+pub trait Widget<Message> {
+    /// Returns the layout [`Style`] of the widget.
+    ///
+    /// [`Style`]: ../style/struct.Style.html
+    fn style(&self) -> Style;
+
+    /// Returns the name of the widget, used to describe the tree for
+    /// debugging.
+    ///
+    /// It defaults to the name of the type of the widget.
+    fn name(&self) -> &'static str {
+        let name = std::any::type_name::<Self>();
+        let name = name.split('<').next().unwrap_or(name);
+
+        name.rsplit("::").next().unwrap_or(name)
+    }
+
+    /// Returns the text that identifies the widget to the user, like the
+    /// label of a button.
+    fn label(&self) -> Option<&str> {
+        None
+    }
+
+    /// Returns the accessibility [`Properties`] of the widget.
+    ///
+    /// Its name is its [`label`].
+    ///
+    /// [`Properties`]: ../accessibility/struct.Properties.html
+    /// [`label`]: #method.label
+    fn accessibility(&self) -> Properties {
+        Properties::new(Role::Group)
+    }
+
+    /// Returns the [`Axis`] the children of the widget are laid out along.
+    ///
+    /// [`Axis`]: ../layout/enum.Axis.html
+    fn axis(&self) -> Axis {
+        Axis::Vertical
+    }
+
+    /// Returns the size of the content of a leaf widget, given the available
+    /// space inside its padding.
+    fn measure(&self, _available: Size) -> Size {
+        Size::ZERO
+    }
+
+    /// Returns true if the widget can receive the keyboard focus.
+    ///
+    /// Focusable widgets take part in tab navigation in tree order, unless
+    /// their [`Element`] overrides the tab index.
+    ///
+    /// [`Element`]: struct.Element.html
+    fn focusable(&self) -> bool {
+        false
+    }
+
+    /// Returns the [`Scroll`] position of the children of the widget, if they
+    /// scroll inside its bounds.
+    ///
+    /// [`Scroll`]: ../layout/struct.Scroll.html
+    fn scroll(&self) -> Option<Scroll> {
+        None
+    }
+
+    /// Receives the size of the viewport and of the children of a scrolling
+    /// widget every time it is laid out, so it can keep its offset in range.
+    fn set_content_size(&mut self, _viewport: Size, _content: Size) {}
+
+    /// Builds the children of a widget that creates them on demand, like a
+    /// [`VirtualList`], once it has been laid out.
+    ///
+    /// The widget receives the laid out bounds of its current children and
+    /// returns the elements that replace them, or `None` to keep them. The
+    /// state of the replaced children is handed to the new children found at
+    /// the same position.
+    ///
+    /// [`VirtualList`]: virtual_list/struct.VirtualList.html
+    fn build_children(&mut self, _children: &[Rectangle]) -> Option<Vec<Element<Message>>> {
+        None
+    }
+
+    /// Processes an [`Event`] dispatched to the widget.
+    ///
+    /// The widget may publish messages and stop the propagation of the
+    /// [`Event`] through the [`EventCtx`].
+    ///
+    /// [`Event`]: ../event/enum.Event.html
+    /// [`EventCtx`]: struct.EventCtx.html
+    fn on_event(&mut self, _event: &Event, _ctx: &mut EventCtx<'_, Message>) {}
+
+    /// Returns the [`Primitive`] that renders the widget, without its
+    /// children.
+    ///
+    /// [`Primitive`]: ../../visper_graphics/primitive/enum.Primitive.html
+    fn draw(&self, _ctx: &DrawCtx<'_>) -> Primitive {
+        Primitive::None
+    }
+
+    /// Returns the [`Primitive`] drawn on top of the children of the widget,
+    /// like scrollbars.
+    ///
+    /// [`Primitive`]: ../../visper_graphics/primitive/enum.Primitive.html
+    fn draw_overlay(&self, _ctx: &DrawCtx<'_>) -> Primitive {
+        Primitive::None
+    }
+
+    /// Takes the internal state of the widget that must survive a rebuild of
+    /// the tree, like the cursor of a text input.
+    ///
+    /// When the [`UiTree`] is rebuilt, the state is handed to the widget that
+    /// takes the place of this one through [`restore_state`].
+    ///
+    /// [`UiTree`]: ../tree/struct.UiTree.html
+    /// [`restore_state`]: #method.restore_state
+    fn take_state(&mut self) -> Option<Box<dyn Any>> {
+        None
+    }
+
+    /// Restores the state taken from the widget this one replaces.
+    ///
+    /// The state may come from a widget of another type, so it should be
+    /// ignored when it cannot be downcast.
+    fn restore_state(&mut self, _state: Box<dyn Any>) {}
+}
+
+/// A widget together with its children, ready to be inserted in a
+/// [`UiTree`].
+///
+/// [`UiTree`]: ../tree/struct.UiTree.html
+pub struct Element<Message> {
+    pub(crate) widget: Box<dyn Widget<Message>>,
+    pub(crate) children: Vec<Element<Message>>,
+    pub(crate) key: Option<String>,
+    pub(crate) tab_index: Option<i32>,
+}
+
+impl<Message> Element<Message> {
+    /// Creates a new [`Element`] without children.
+    ///
+    /// [`Element`]: struct.Element.html
+    pub fn new(widget: impl Widget<Message> + 'static) -> Self {
+        Self {
+            widget: Box::new(widget),
+            children: Vec::new(),
+            key: None,
+            tab_index: None,
+        }
+    }
+
+    /// Appends a child to the [`Element`].
+    ///
+    /// [`Element`]: struct.Element.html
+    pub fn append(mut self, child: impl Into<Element<Message>>) -> Self {
+        self.children.push(child.into());
+        self
+    }
+
+    /// Sets the key of the [`Element`], used to identify its node across
+    /// rebuilds of the tree.
+    ///
+    /// [`Element`]: struct.Element.html
+    pub fn key(mut self, key: impl Into<String>) -> Self {
+        self.key = Some(key.into());
+        self
+    }
+
+    /// Overrides the tab index of the [`Element`].
+    ///
+    /// Nodes with a positive index are visited first, in increasing order,
+    /// followed by the nodes with an index of zero in tree order. Nodes with a
+    /// negative index can only be focused programmatically.
+    ///
+    /// [`Element`]: struct.Element.html
+    pub fn tab_index(mut self, tab_index: i32) -> Self {
+        self.tab_index = Some(tab_index);
+        self
+    }
+}
+
+/// The context of an [`Event`] being dispatched to a widget.
+///
+/// [`Event`]: ../event/enum.Event.html
+pub struct EventCtx<'a, Message> {
+    pub(crate) phase: Phase,
+    pub(crate) bounds: Rectangle,
+    pub(crate) cursor_position: Option<Point>,
+    pub(crate) is_hovered: bool,
+    pub(crate) is_focused: bool,
+    pub(crate) is_captured: bool,
+    pub(crate) now: Instant,
+    pub(crate) messages: &'a mut Vec<Message>,
+    pub(crate) clipboard: &'a mut dyn Clipboard,
+    pub(crate) propagation_stopped: bool,
+    pub(crate) frame_requested: bool,
+    pub(crate) layout_requested: bool,
+}
+
+impl<'a, Message> EventCtx<'a, Message> {
+    /// Returns the dispatch [`Phase`].
+    ///
+    /// [`Phase`]: ../event/enum.Phase.html
+    pub fn phase(&self) -> Phase {
+        self.phase
+    }
+
+    /// Returns the laid out bounds of the widget.
+    pub fn bounds(&self) -> Rectangle {
+        self.bounds
+    }
+
+    /// Returns the last known position of the cursor, if any.
+    pub fn cursor_position(&self) -> Option<Point> {
+        self.cursor_position
+    }
+
+    /// Returns true if the cursor is over the widget or one of its children.
+    pub fn is_hovered(&self) -> bool {
+        self.is_hovered
+    }
+
+    /// Returns true if the widget has the keyboard focus.
+    pub fn is_focused(&self) -> bool {
+        self.is_focused
+    }
+
+    /// Returns true if the widget is capturing the mouse, that is, a button
+    /// was pressed over it and has not been released yet.
+    pub fn is_captured(&self) -> bool {
+        self.is_captured
+    }
+
+    /// Returns the current time of the [`Clock`] of the tree, which widgets
+    /// should animate with.
+    ///
+    /// [`Clock`]: ../animation/trait.Clock.html
+    pub fn now(&self) -> Instant {
+        self.now
+    }
+
+    /// Returns the [`Clipboard`] of the application.
+    ///
+    /// [`Clipboard`]: ../clipboard/trait.Clipboard.html
+    pub fn clipboard(&mut self) -> &mut dyn Clipboard {
+        self.clipboard
+    }
+
+    /// Publishes a message for the application.
+    pub fn publish(&mut self, message: Message) {
+        self.messages.push(message);
+    }
+
+    /// Asks for an [`Event::Frame`] to be delivered to the widget before the
+    /// next frame is drawn, to animate it.
+    ///
+    /// [`Event::Frame`]: ../event/enum.Event.html#variant.Frame
+    pub fn request_frame(&mut self) {
+        self.frame_requested = true;
+    }
+
+    /// Asks for the widget and its children to be laid out again once the
+    /// event has been dispatched, because their size or their children
+    /// changed.
+    pub fn request_layout(&mut self) {
+        self.layout_requested = true;
+    }
+
+    /// Stops the propagation of the event to the remaining widgets.
+    pub fn stop_propagation(&mut self) {
+        self.propagation_stopped = true;
+    }
+
+    /// Returns true if the propagation of the event has been stopped.
+    pub fn is_propagation_stopped(&self) -> bool {
+        self.propagation_stopped
+    }
+}
+
+/// The context of a widget being drawn.
+#[derive(Debug, Clone, Copy)]
+pub struct DrawCtx<'a> {
+    pub(crate) bounds: Rectangle,
+    pub(crate) theme: &'a Theme,
+    pub(crate) is_hovered: bool,
+    pub(crate) is_pressed: bool,
+    pub(crate) is_focused: bool,
+    pub(crate) hover: f32,
+    pub(crate) press: f32,
+    pub(crate) now: Instant,
+}
+
+impl<'a> DrawCtx<'a> {
+    /// Returns the laid out bounds of the widget.
+    pub fn bounds(&self) -> Rectangle {
+        self.bounds
+    }
+
+    /// Returns the [`Theme`] of the application.
+    ///
+    /// [`Theme`]: ../theme/struct.Theme.html
+    pub fn theme(&self) -> &Theme {
+        self.theme
+    }
+
+    /// Returns true if the cursor is over the widget or one of its children.
+    pub fn is_hovered(&self) -> bool {
+        self.is_hovered
+    }
+
+    /// Returns true if the widget is being pressed, that is, it captured the
+    /// mouse and the cursor is still over it.
+    pub fn is_pressed(&self) -> bool {
+        self.is_pressed
+    }
+
+    /// Returns true if the widget has the keyboard focus.
+    pub fn is_focused(&self) -> bool {
+        self.is_focused
+    }
+
+    /// Returns how much the widget is highlighted by the cursor hovering it,
+    /// from 0 to 1.
+    ///
+    /// Unlike [`is_hovered`], the highlight fades in and out when the cursor
+    /// enters and leaves the widget.
+    ///
+    /// [`is_hovered`]: #method.is_hovered
+    pub fn hover(&self) -> f32 {
+        self.hover
+    }
+
+    /// Returns how much the widget is highlighted by being pressed, from 0
+    /// to 1.
+    ///
+    /// Unlike [`is_pressed`], the highlight fades in and out when the widget
+    /// is pressed and released.
+    ///
+    /// [`is_pressed`]: #method.is_pressed
+    pub fn press(&self) -> f32 {
+        self.press
+    }
+
+    /// Returns the time the frame is drawn at.
+    pub fn now(&self) -> Instant {
+        self.now
+    }
+}
+
+/// Returns true if the event activates a clickable widget: the left button
+/// was released over the widget that captured it, one of the given keys
+/// was pressed while it was focused, or an assistive technology clicked it.
+///
+/// Presses of the left button are stopped, so clicking a control does not
+/// also trigger its ancestors.
+pub(crate) fn is_activation<Message>(
+    event: &Event,
+    ctx: &mut EventCtx<'_, Message>,
+    keys: &[keyboard::KeyCode],
+) -> bool {
+    match event {
+        Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Left)) => {
+            ctx.stop_propagation();
+
+            false
+        }
+        Event::Mouse(mouse::Event::ButtonReleased(mouse::Button::Left)) => {
+            ctx.is_captured() && ctx.is_hovered()
+        }
+        Event::Keyboard(keyboard::Event::KeyPressed { key_code, .. }) => {
+            ctx.is_focused() && keys.contains(key_code)
+        }
+        Event::Accessibility(accessibility::Action::Click) => ctx.phase() == Phase::Target,
+        _ => false,
+    }
+}
+
+/// The space between the indicator of a checkbox, radio or toggle and its
+/// label.
+pub(crate) const LABEL_SPACING: f32 = 8.0;
+
+/// Returns the size of an indicator followed by a label.
+pub(crate) fn measure_labeled(indicator: Size, label: &str) -> Size {
+    let label = visper_graphics::text::measure(label, Text::DEFAULT_SIZE);
+
+    Size::new(
+        indicator.width + LABEL_SPACING + label.width,
+        indicator.height.max(label.height),
+    )
+}
+
+/// Splits the bounds of a labeled widget into the bounds of its indicator,
+/// vertically centered, and the bounds of its label.
+pub(crate) fn layout_labeled(bounds: Rectangle, indicator: Size) -> (Rectangle, Rectangle) {
+    let indicator_bounds = Rectangle {
+        x: bounds.x,
+        y: bounds.y + ((bounds.height - indicator.height) / 2.0).max(0.0),
+        width: indicator.width,
+        height: indicator.height,
+    };
+
+    let label_bounds = Rectangle {
+        x: bounds.x + indicator.width + LABEL_SPACING,
+        y: bounds.y,
+        width: (bounds.width - indicator.width - LABEL_SPACING).max(0.0),
+        height: bounds.height,
+    };
+
+    (indicator_bounds, label_bounds)
+}
+
+/// Returns the primitive of the label of a checkbox, radio or toggle.
+pub(crate) fn draw_label(label: &str, bounds: Rectangle, ctx: &DrawCtx<'_>) -> Primitive {
+    Primitive::Text {
+        content: label.to_owned(),
+        bounds,
+        color: ctx.theme().palette.text,
+        size: Text::DEFAULT_SIZE,
+        horizontal_alignment: visper_graphics::text::HorizontalAlignment::Left,
+        vertical_alignment: visper_graphics::text::VerticalAlignment::Center,
+    }
+}
@@ -0,0 +1,228 @@
+use crate::accessibility::{Properties, Role};
+use crate::event::{keyboard, Event};
+use crate::style::{Length, Style};
+use crate::theme::Palette;
+use crate::widget::{self, DrawCtx, Element, EventCtx, Text, Widget};
+use visper_graphics::core::size::Size;
+use visper_graphics::primitive::Primitive;
+use visper_graphics::text::{self, HorizontalAlignment, VerticalAlignment};
+
+/// A button that publishes a message when pressed.
+///
+/// A [`Button`] without a message is disabled: it is drawn muted and cannot
+/// be focused.
+///
+/// [`Button`]: struct.Button.html
+#[derive(Debug, Clone)]
+pub struct Button<Message> {
+    label: String,
+    on_press: Option<Message>,
+    style: Style,
+}
+
+impl<Message> Button<Message> {
+    /// Creates a new disabled [`Button`] with the given label.
+    ///
+    /// [`Button`]: struct.Button.html
+    pub fn new(label: impl Into<String>) -> Self {
+        Button {
+            label: label.into(),
+            on_press: None,
+            style: Style::new().padding(8.0),
+        }
+    }
+
+    /// Sets the message published when the [`Button`] is pressed.
+    ///
+    /// [`Button`]: struct.Button.html
+    pub fn on_press(mut self, message: Message) -> Self {
+        self.on_press = Some(message);
+        self
+    }
+
+    /// Sets the width of the [`Button`].
+    ///
+    /// [`Button`]: struct.Button.html
+    pub fn width(mut self, width: impl Into<Length>) -> Self {
+        self.style = self.style.width(width);
+        self
+    }
+
+    /// Sets the height of the [`Button`].
+    ///
+    /// [`Button`]: struct.Button.html
+    pub fn height(mut self, height: impl Into<Length>) -> Self {
+        self.style = self.style.height(height);
+        self
+    }
+
+    /// Sets the padding of the [`Button`].
+    ///
+    /// [`Button`]: struct.Button.html
+    pub fn padding(mut self, padding: f32) -> Self {
+        self.style = self.style.padding(padding);
+        self
+    }
+}
+
+impl<Message: Clone> Widget<Message> for Button<Message> {
+    fn style(&self) -> Style {
+        self.style
+    }
+
+    fn label(&self) -> Option<&str> {
+        Some(&self.label)
+    }
+
+    fn accessibility(&self) -> Properties {
+        Properties::new(Role::Button).disabled(self.on_press.is_none())
+    }
+
+    fn measure(&self, _available: Size) -> Size {
+        text::measure(&self.label, Text::DEFAULT_SIZE)
+    }
+
+    fn focusable(&self) -> bool {
+        self.on_press.is_some()
+    }
+
+    fn on_event(&mut self, event: &Event, ctx: &mut EventCtx<'_, Message>) {
+        let keys = [keyboard::KeyCode::Space, keyboard::KeyCode::Return];
+
+        if widget::is_activation(event, ctx, &keys) {
+            if let Some(message) = &self.on_press {
+                ctx.publish(message.clone());
+            }
+
+            ctx.stop_propagation();
+        }
+    }
+
+    fn draw(&self, ctx: &DrawCtx<'_>) -> Primitive {
+        let palette = &ctx.theme().palette;
+        let is_enabled = self.on_press.is_some();
+
+        let (background, foreground) = if is_enabled {
+            (
+                Palette::highlight(palette.primary, ctx.hover(), ctx.press()),
+                palette.on_primary,
+            )
+        } else {
+            (palette.surface, palette.border)
+        };
+
+        Primitive::Group {
+            primitives: vec![
+                Primitive::Quad {
+                    bounds: ctx.bounds(),
+                    background,
+                    border_radius: ctx.theme().border_radius,
+                    border_width: 0.0,
+                    border_color: background,
+                },
+                Primitive::Text {
+                    content: self.label.clone(),
+                    bounds: ctx.bounds(),
+                    color: foreground,
+                    size: Text::DEFAULT_SIZE,
+                    horizontal_alignment: HorizontalAlignment::Center,
+                    vertical_alignment: VerticalAlignment::Center,
+                },
+            ],
+        }
+    }
+}
+
+impl<Message: Clone + 'static> From<Button<Message>> for Element<Message> {
+    fn from(button: Button<Message>) -> Element<Message> {
+        Element::new(button)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::mouse;
+    use crate::testing::Harness;
+    use crate::theme::Theme;
+    use std::time::Duration;
+    use visper_graphics::core::point::Point;
+
+    #[derive(Debug, Clone, PartialEq)]
+    enum Message {
+        Pressed,
+    }
+
+    fn first_quad(primitive: &Primitive) -> Option<f32> {
+        match primitive {
+            Primitive::Quad { background, .. } => Some(background.r),
+            Primitive::Group { primitives } => primitives.iter().find_map(first_quad),
+            Primitive::Clip { content, .. } => first_quad(content),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn button_publishes_when_released_over_it() {
+        let mut harness = Harness::widget(|| Button::new("Press").on_press(Message::Pressed));
+
+        harness.click(Point::new(5.0, 5.0));
+        assert_eq!(harness.take_messages(), vec![Message::Pressed]);
+
+        harness.dispatch(Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Left)));
+        harness.move_to(Point::new(150.0, 90.0));
+        harness.dispatch(Event::Mouse(mouse::Event::ButtonReleased(mouse::Button::Left)));
+
+        assert!(harness.messages().is_empty());
+    }
+
+    #[test]
+    fn button_is_operable_with_the_keyboard() {
+        let mut harness = Harness::widget(|| Button::new("Press").on_press(Message::Pressed));
+
+        harness.key(keyboard::KeyCode::Tab);
+        assert!(harness.messages().is_empty());
+
+        harness.key(keyboard::KeyCode::Space);
+        assert_eq!(harness.take_messages(), vec![Message::Pressed]);
+
+        harness.key(keyboard::KeyCode::Return);
+        assert_eq!(harness.take_messages(), vec![Message::Pressed]);
+    }
+
+    #[test]
+    fn disabled_button_is_inert() {
+        let mut harness = Harness::widget(|| Button::<Message>::new("Press"));
+
+        harness.click(Point::new(5.0, 5.0));
+        assert!(harness.messages().is_empty());
+
+        harness.key(keyboard::KeyCode::Tab);
+        assert_eq!(harness.tree().focused(), None);
+    }
+    #[test]
+    fn button_fades_its_hover_highlight() {
+        let mut harness = Harness::widget(|| {
+            Button::new("Fade").on_press(Message::Pressed).height(50.0)
+        });
+        let primary = Theme::default().palette.primary;
+
+        harness.move_to(Point::new(10.0, 10.0));
+        assert!(harness.tree().next_frame().is_some());
+
+        harness.advance(Duration::from_millis(75));
+        let halfway = first_quad(&harness.draw()).unwrap();
+        assert!(halfway < primary.r && halfway > primary.r * 0.9);
+
+        harness.advance(Duration::from_millis(75));
+        assert_eq!(first_quad(&harness.draw()), Some(primary.r * 0.9));
+        assert_eq!(harness.tree().next_frame(), None);
+
+        harness.move_to(Point::new(10.0, 90.0));
+        assert!(harness.tree().is_animating());
+
+        harness.advance(Duration::from_millis(150));
+        assert_eq!(first_quad(&harness.draw()), Some(primary.r));
+        assert!(!harness.tree().is_animating());
+    }
+}
@@ -0,0 +1,153 @@
+use crate::accessibility::{Properties, Role};
+use crate::event::{keyboard, Event};
+use crate::style::{Length, Style};
+use crate::theme::Palette;
+use crate::widget::{self, DrawCtx, Element, EventCtx, Widget};
+use visper_graphics::core::size::Size;
+use visper_graphics::primitive::Primitive;
+use visper_graphics::text::{HorizontalAlignment, VerticalAlignment};
+
+/// A box that can be checked, followed by a label.
+///
+/// The [`Checkbox`] does not keep its own state: it publishes the new state
+/// when toggled and the application passes it back on the next view.
+///
+/// [`Checkbox`]: struct.Checkbox.html
+pub struct Checkbox<Message> {
+    is_checked: bool,
+    label: String,
+    on_toggle: Box<dyn Fn(bool) -> Message>,
+    style: Style,
+}
+
+impl<Message> Checkbox<Message> {
+    /// The size of the box of a [`Checkbox`].
+    ///
+    /// [`Checkbox`]: struct.Checkbox.html
+    pub const SIZE: f32 = 18.0;
+
+    /// Creates a new [`Checkbox`].
+    ///
+    /// `on_toggle` receives the new state of the [`Checkbox`] and produces the
+    /// message to publish.
+    ///
+    /// [`Checkbox`]: struct.Checkbox.html
+    pub fn new<F>(is_checked: bool, label: impl Into<String>, on_toggle: F) -> Self
+    where
+        F: 'static + Fn(bool) -> Message,
+    {
+        Checkbox {
+            is_checked,
+            label: label.into(),
+            on_toggle: Box::new(on_toggle),
+            style: Style::new(),
+        }
+    }
+
+    /// Sets the width of the [`Checkbox`].
+    ///
+    /// [`Checkbox`]: struct.Checkbox.html
+    pub fn width(mut self, width: impl Into<Length>) -> Self {
+        self.style = self.style.width(width);
+        self
+    }
+}
+
+impl<Message> Widget<Message> for Checkbox<Message> {
+    fn style(&self) -> Style {
+        self.style
+    }
+
+    fn label(&self) -> Option<&str> {
+        Some(&self.label)
+    }
+
+    fn accessibility(&self) -> Properties {
+        Properties::new(Role::CheckBox).checked(self.is_checked)
+    }
+
+    fn measure(&self, _available: Size) -> Size {
+        widget::measure_labeled(Size::new(Self::SIZE, Self::SIZE), &self.label)
+    }
+
+    fn focusable(&self) -> bool {
+        true
+    }
+
+    fn on_event(&mut self, event: &Event, ctx: &mut EventCtx<'_, Message>) {
+        if widget::is_activation(event, ctx, &[keyboard::KeyCode::Space]) {
+            ctx.publish((self.on_toggle)(!self.is_checked));
+            ctx.stop_propagation();
+        }
+    }
+
+    fn draw(&self, ctx: &DrawCtx<'_>) -> Primitive {
+        let palette = &ctx.theme().palette;
+        let (box_bounds, label_bounds) =
+            widget::layout_labeled(ctx.bounds(), Size::new(Self::SIZE, Self::SIZE));
+
+        let background = if self.is_checked {
+            Palette::highlight(palette.primary, ctx.hover(), ctx.press())
+        } else {
+            Palette::highlight(palette.on_primary, ctx.hover(), ctx.press())
+        };
+
+        let mut primitives = vec![Primitive::Quad {
+            bounds: box_bounds,
+            background,
+            border_radius: ctx.theme().border_radius,
+            border_width: if self.is_checked { 0.0 } else { 1.0 },
+            border_color: palette.border,
+        }];
+
+        if self.is_checked {
+            primitives.push(Primitive::Text {
+                content: String::from("\u{2713}"),
+                bounds: box_bounds,
+                color: palette.on_primary,
+                size: Self::SIZE,
+                horizontal_alignment: HorizontalAlignment::Center,
+                vertical_alignment: VerticalAlignment::Center,
+            });
+        }
+
+        primitives.push(widget::draw_label(&self.label, label_bounds, ctx));
+
+        Primitive::Group { primitives }
+    }
+}
+
+impl<Message: 'static> From<Checkbox<Message>> for Element<Message> {
+    fn from(checkbox: Checkbox<Message>) -> Element<Message> {
+        Element::new(checkbox)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::Harness;
+    use visper_graphics::core::point::Point;
+
+    #[derive(Debug, Clone, PartialEq)]
+    enum Message {
+        Checked(bool),
+    }
+
+    #[test]
+    fn checkbox_publishes_its_new_state() {
+        let mut unchecked = Harness::widget(|| Checkbox::new(false, "Check", Message::Checked));
+        let mut checked = Harness::widget(|| Checkbox::new(true, "Check", Message::Checked));
+
+        unchecked.click(Point::new(5.0, 5.0));
+        checked.click(Point::new(5.0, 5.0));
+
+        assert_eq!(unchecked.messages(), &[Message::Checked(true)]);
+        assert_eq!(checked.take_messages(), vec![Message::Checked(false)]);
+
+        checked.key(keyboard::KeyCode::Tab);
+        checked.key(keyboard::KeyCode::Space);
+
+        assert_eq!(checked.messages(), &[Message::Checked(false)]);
+    }
+}
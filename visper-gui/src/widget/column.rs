@@ -0,0 +1,94 @@
+use crate::layout::Axis;
+use crate::style::{Align, Length, Style};
+use crate::widget::{Element, Widget};
+
+/// A container that distributes its children vertically.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Column {
+    style: Style,
+}
+
+impl Column {
+    /// Creates an empty [`Column`].
+    ///
+    /// [`Column`]: struct.Column.html
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the [`Style`] of the [`Column`].
+    ///
+    /// [`Style`]: ../../style/struct.Style.html
+    /// [`Column`]: struct.Column.html
+    pub fn style(mut self, style: Style) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// Sets the width of the [`Column`].
+    ///
+    /// [`Column`]: struct.Column.html
+    pub fn width(mut self, width: impl Into<Length>) -> Self {
+        self.style = self.style.width(width);
+        self
+    }
+
+    /// Sets the height of the [`Column`].
+    ///
+    /// [`Column`]: struct.Column.html
+    pub fn height(mut self, height: impl Into<Length>) -> Self {
+        self.style = self.style.height(height);
+        self
+    }
+
+    /// Sets the padding of the [`Column`].
+    ///
+    /// [`Column`]: struct.Column.html
+    pub fn padding(mut self, padding: f32) -> Self {
+        self.style = self.style.padding(padding);
+        self
+    }
+
+    /// Sets the spacing between the children of the [`Column`].
+    ///
+    /// [`Column`]: struct.Column.html
+    pub fn spacing(mut self, spacing: f32) -> Self {
+        self.style = self.style.spacing(spacing);
+        self
+    }
+
+    /// Sets the cross axis alignment of the children of the [`Column`].
+    ///
+    /// [`Column`]: struct.Column.html
+    pub fn align_items(mut self, align: Align) -> Self {
+        self.style = self.style.align_items(align);
+        self
+    }
+
+    /// Turns the [`Column`] into an [`Element`] with the given child.
+    ///
+    /// [`Column`]: struct.Column.html
+    /// [`Element`]: ../struct.Element.html
+    pub fn append<Message: 'static>(
+        self,
+        child: impl Into<Element<Message>>,
+    ) -> Element<Message> {
+        Element::new(self).append(child)
+    }
+}
+
+impl<Message> Widget<Message> for Column {
+    fn style(&self) -> Style {
+        self.style
+    }
+
+    fn axis(&self) -> Axis {
+        Axis::Vertical
+    }
+}
+
+impl<Message: 'static> From<Column> for Element<Message> {
+    fn from(column: Column) -> Element<Message> {
+        Element::new(column)
+    }
+}
@@ -0,0 +1,89 @@
+use crate::style::{Align, Length, Style};
+use crate::widget::{Element, Widget};
+
+/// A container that aligns a single child inside its bounds.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Container {
+    style: Style,
+}
+
+impl Container {
+    /// Creates an empty [`Container`].
+    ///
+    /// [`Container`]: struct.Container.html
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the [`Style`] of the [`Container`].
+    ///
+    /// [`Style`]: ../../style/struct.Style.html
+    /// [`Container`]: struct.Container.html
+    pub fn style(mut self, style: Style) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// Sets the width of the [`Container`].
+    ///
+    /// [`Container`]: struct.Container.html
+    pub fn width(mut self, width: impl Into<Length>) -> Self {
+        self.style = self.style.width(width);
+        self
+    }
+
+    /// Sets the height of the [`Container`].
+    ///
+    /// [`Container`]: struct.Container.html
+    pub fn height(mut self, height: impl Into<Length>) -> Self {
+        self.style = self.style.height(height);
+        self
+    }
+
+    /// Sets the padding of the [`Container`].
+    ///
+    /// [`Container`]: struct.Container.html
+    pub fn padding(mut self, padding: f32) -> Self {
+        self.style = self.style.padding(padding);
+        self
+    }
+
+    /// Centers the child of the [`Container`] horizontally.
+    ///
+    /// [`Container`]: struct.Container.html
+    pub fn center_x(mut self) -> Self {
+        self.style = self.style.align_items(Align::Center);
+        self
+    }
+
+    /// Centers the child of the [`Container`] vertically.
+    ///
+    /// [`Container`]: struct.Container.html
+    pub fn center_y(mut self) -> Self {
+        self.style = self.style.justify_content(Align::Center);
+        self
+    }
+
+    /// Turns the [`Container`] into an [`Element`] with the given child.
+    ///
+    /// [`Container`]: struct.Container.html
+    /// [`Element`]: ../struct.Element.html
+    pub fn append<Message: 'static>(
+        self,
+        child: impl Into<Element<Message>>,
+    ) -> Element<Message> {
+        Element::new(self).append(child)
+    }
+}
+
+impl<Message> Widget<Message> for Container {
+    fn style(&self) -> Style {
+        self.style
+    }
+}
+
+impl<Message: 'static> From<Container> for Element<Message> {
+    fn from(container: Container) -> Element<Message> {
+        Element::new(container)
+    }
+}
@@ -0,0 +1,161 @@
+use crate::accessibility::{Properties, Role};
+use crate::event::{keyboard, Event};
+use crate::style::{Length, Style};
+use crate::theme::Palette;
+use crate::widget::{self, DrawCtx, Element, EventCtx, Widget};
+use visper_graphics::core::rectangle::Rectangle;
+use visper_graphics::core::size::Size;
+use visper_graphics::primitive::Primitive;
+
+/// A circular button representing one choice among several, followed by a
+/// label.
+///
+/// [`Radio`] buttons are grouped by the `selected` value they share.
+///
+/// [`Radio`]: struct.Radio.html
+pub struct Radio<Message> {
+    is_selected: bool,
+    label: String,
+    on_click: Message,
+    style: Style,
+}
+
+impl<Message> Radio<Message> {
+    /// The size of the circle of a [`Radio`].
+    ///
+    /// [`Radio`]: struct.Radio.html
+    pub const SIZE: f32 = 18.0;
+
+    /// Creates a new [`Radio`] for the given value.
+    ///
+    /// The [`Radio`] is selected when `selected` holds its `value`. When
+    /// clicked, it publishes the message produced by `f` for its `value`.
+    ///
+    /// [`Radio`]: struct.Radio.html
+    pub fn new<F, V>(value: V, label: impl Into<String>, selected: Option<V>, f: F) -> Self
+    where
+        V: Eq + Copy,
+        F: 'static + Fn(V) -> Message,
+    {
+        Radio {
+            is_selected: Some(value) == selected,
+            label: label.into(),
+            on_click: f(value),
+            style: Style::new(),
+        }
+    }
+
+    /// Sets the width of the [`Radio`].
+    ///
+    /// [`Radio`]: struct.Radio.html
+    pub fn width(mut self, width: impl Into<Length>) -> Self {
+        self.style = self.style.width(width);
+        self
+    }
+}
+
+impl<Message: Clone> Widget<Message> for Radio<Message> {
+    fn style(&self) -> Style {
+        self.style
+    }
+
+    fn label(&self) -> Option<&str> {
+        Some(&self.label)
+    }
+
+    fn accessibility(&self) -> Properties {
+        Properties::new(Role::RadioButton).checked(self.is_selected)
+    }
+
+    fn measure(&self, _available: Size) -> Size {
+        widget::measure_labeled(Size::new(Self::SIZE, Self::SIZE), &self.label)
+    }
+
+    fn focusable(&self) -> bool {
+        true
+    }
+
+    fn on_event(&mut self, event: &Event, ctx: &mut EventCtx<'_, Message>) {
+        if widget::is_activation(event, ctx, &[keyboard::KeyCode::Space]) {
+            if !self.is_selected {
+                ctx.publish(self.on_click.clone());
+            }
+
+            ctx.stop_propagation();
+        }
+    }
+
+    fn draw(&self, ctx: &DrawCtx<'_>) -> Primitive {
+        let palette = &ctx.theme().palette;
+        let (circle, label_bounds) =
+            widget::layout_labeled(ctx.bounds(), Size::new(Self::SIZE, Self::SIZE));
+
+        let mut primitives = vec![Primitive::Quad {
+            bounds: circle,
+            background: Palette::highlight(palette.on_primary, ctx.hover(), ctx.press()),
+            border_radius: Self::SIZE / 2.0,
+            border_width: 1.0,
+            border_color: if self.is_selected {
+                palette.primary
+            } else {
+                palette.border
+            },
+        }];
+
+        if self.is_selected {
+            let dot = Self::SIZE / 2.0;
+
+            primitives.push(Primitive::Quad {
+                bounds: Rectangle {
+                    x: circle.x + (circle.width - dot) / 2.0,
+                    y: circle.y + (circle.height - dot) / 2.0,
+                    width: dot,
+                    height: dot,
+                },
+                background: palette.primary,
+                border_radius: dot / 2.0,
+                border_width: 0.0,
+                border_color: palette.primary,
+            });
+        }
+
+        primitives.push(widget::draw_label(&self.label, label_bounds, ctx));
+
+        Primitive::Group { primitives }
+    }
+}
+
+impl<Message: Clone + 'static> From<Radio<Message>> for Element<Message> {
+    fn from(radio: Radio<Message>) -> Element<Message> {
+        Element::new(radio)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::Harness;
+    use crate::widget::Row;
+    use visper_graphics::core::point::Point;
+
+    #[derive(Debug, Clone, PartialEq)]
+    enum Message {
+        Selected(u8),
+    }
+
+    #[test]
+    fn radio_publishes_only_when_unselected() {
+        let mut harness = Harness::widget(|| {
+            Row::new()
+                .spacing(10.0)
+                .append(Radio::new(1, "One", Some(1), Message::Selected))
+                .append(Radio::new(2, "Two", Some(1), Message::Selected))
+        });
+
+        harness.click(Point::new(5.0, 5.0));
+        assert!(harness.messages().is_empty());
+
+        harness.click_label("Two");
+        assert_eq!(harness.messages(), &[Message::Selected(2)]);
+    }
+}
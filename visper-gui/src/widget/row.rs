@@ -0,0 +1,94 @@
+use crate::layout::Axis;
+use crate::style::{Align, Length, Style};
+use crate::widget::{Element, Widget};
+
+/// A container that distributes its children horizontally.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Row {
+    style: Style,
+}
+
+impl Row {
+    /// Creates an empty [`Row`].
+    ///
+    /// [`Row`]: struct.Row.html
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the [`Style`] of the [`Row`].
+    ///
+    /// [`Style`]: ../../style/struct.Style.html
+    /// [`Row`]: struct.Row.html
+    pub fn style(mut self, style: Style) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// Sets the width of the [`Row`].
+    ///
+    /// [`Row`]: struct.Row.html
+    pub fn width(mut self, width: impl Into<Length>) -> Self {
+        self.style = self.style.width(width);
+        self
+    }
+
+    /// Sets the height of the [`Row`].
+    ///
+    /// [`Row`]: struct.Row.html
+    pub fn height(mut self, height: impl Into<Length>) -> Self {
+        self.style = self.style.height(height);
+        self
+    }
+
+    /// Sets the padding of the [`Row`].
+    ///
+    /// [`Row`]: struct.Row.html
+    pub fn padding(mut self, padding: f32) -> Self {
+        self.style = self.style.padding(padding);
+        self
+    }
+
+    /// Sets the spacing between the children of the [`Row`].
+    ///
+    /// [`Row`]: struct.Row.html
+    pub fn spacing(mut self, spacing: f32) -> Self {
+        self.style = self.style.spacing(spacing);
+        self
+    }
+
+    /// Sets the cross axis alignment of the children of the [`Row`].
+    ///
+    /// [`Row`]: struct.Row.html
+    pub fn align_items(mut self, align: Align) -> Self {
+        self.style = self.style.align_items(align);
+        self
+    }
+
+    /// Turns the [`Row`] into an [`Element`] with the given child.
+    ///
+    /// [`Row`]: struct.Row.html
+    /// [`Element`]: ../struct.Element.html
+    pub fn append<Message: 'static>(
+        self,
+        child: impl Into<Element<Message>>,
+    ) -> Element<Message> {
+        Element::new(self).append(child)
+    }
+}
+
+impl<Message> Widget<Message> for Row {
+    fn style(&self) -> Style {
+        self.style
+    }
+
+    fn axis(&self) -> Axis {
+        Axis::Horizontal
+    }
+}
+
+impl<Message: 'static> From<Row> for Element<Message> {
+    fn from(row: Row) -> Element<Message> {
+        Element::new(row)
+    }
+}
@@ -0,0 +1,697 @@
+use crate::accessibility::{Properties, Role};
+use crate::event::{mouse, touch, Event, Phase};
+use crate::layout::{Axis, Scroll};
+use crate::style::{Align, Length, Style};
+use crate::theme::Palette;
+use crate::widget::{DrawCtx, Element, EventCtx, Widget};
+use std::any::Any;
+use std::time::Instant;
+use visper_graphics::core::point::Point;
+use visper_graphics::core::rectangle::Rectangle;
+use visper_graphics::core::size::Size;
+use visper_graphics::core::vector::Vector;
+use visper_graphics::primitive::Primitive;
+
+/// The distance scrolled by a line of a mouse wheel.
+const WHEEL_LINE: f32 = 40.0;
+
+/// The distance a finger has to travel before it scrolls instead of tapping.
+const TOUCH_SLOP: f32 = 8.0;
+
+/// The rate at which the velocity of a fling decays, per second.
+const FRICTION: f32 = 4.0;
+
+/// The speed under which a fling stops, in pixels per second.
+const MIN_VELOCITY: f32 = 10.0;
+
+/// The gap between a scrollbar and the edges of the [`Scrollable`].
+///
+/// [`Scrollable`]: struct.Scrollable.html
+const MARGIN: f32 = 2.0;
+
+/// The smallest length of the thumb of a scrollbar.
+const MIN_THUMB_LENGTH: f32 = 24.0;
+
+/// The directions a [`Scrollable`] scrolls in.
+///
+/// [`Scrollable`]: struct.Scrollable.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    /// Scroll vertically; children are placed from top to bottom.
+    Vertical,
+
+    /// Scroll horizontally; children are placed from left to right.
+    Horizontal,
+
+    /// Scroll in both directions; children are placed from top to bottom.
+    Both,
+}
+
+/// A container that scrolls its children inside its bounds.
+///
+/// A [`Scrollable`] scrolls with the mouse wheel, by dragging its scrollbars
+/// and by dragging a finger across it. Lifting a moving finger flings the
+/// content, which slows down until it stops.
+///
+/// [`Scrollable`]: struct.Scrollable.html
+#[derive(Debug, Clone, Copy)]
+pub struct Scrollable {
+    direction: Direction,
+    style: Style,
+    scrollbar_width: f32,
+    state: State,
+}
+
+/// The state of a [`Scrollable`] that survives the rebuilds of the tree.
+///
+/// [`Scrollable`]: struct.Scrollable.html
+#[derive(Debug, Clone, Copy, Default)]
+struct State {
+    offset: Vector,
+    viewport: Size,
+    content: Size,
+    velocity: Vector,
+    pending: Vector,
+    last_frame: Option<Instant>,
+    drag: Option<Drag>,
+    hovered_thumb: Option<Axis>,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Drag {
+    Thumb {
+        axis: Axis,
+        grab: f32,
+    },
+    Touch {
+        finger: touch::Finger,
+        start: Point,
+        last: Point,
+        is_scrolling: bool,
+    },
+}
+
+impl Default for Scrollable {
+    fn default() -> Self {
+        Scrollable {
+            direction: Direction::Vertical,
+            style: Style::new().width(Length::Fill).height(Length::Fill),
+            scrollbar_width: 8.0,
+            state: State::default(),
+        }
+    }
+}
+
+impl Scrollable {
+    /// Creates an empty vertical [`Scrollable`] that fills the available
+    /// space.
+    ///
+    /// [`Scrollable`]: struct.Scrollable.html
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the [`Direction`] of the [`Scrollable`].
+    ///
+    /// [`Direction`]: enum.Direction.html
+    /// [`Scrollable`]: struct.Scrollable.html
+    pub fn direction(mut self, direction: Direction) -> Self {
+        self.direction = direction;
+        self
+    }
+
+    /// Sets the width of the [`Scrollable`].
+    ///
+    /// [`Scrollable`]: struct.Scrollable.html
+    pub fn width(mut self, width: impl Into<Length>) -> Self {
+        self.style = self.style.width(width);
+        self
+    }
+
+    /// Sets the height of the [`Scrollable`].
+    ///
+    /// [`Scrollable`]: struct.Scrollable.html
+    pub fn height(mut self, height: impl Into<Length>) -> Self {
+        self.style = self.style.height(height);
+        self
+    }
+
+    /// Sets the padding of the [`Scrollable`].
+    ///
+    /// [`Scrollable`]: struct.Scrollable.html
+    pub fn padding(mut self, padding: f32) -> Self {
+        self.style = self.style.padding(padding);
+        self
+    }
+
+    /// Sets the spacing between the children of the [`Scrollable`].
+    ///
+    /// [`Scrollable`]: struct.Scrollable.html
+    pub fn spacing(mut self, spacing: f32) -> Self {
+        self.style = self.style.spacing(spacing);
+        self
+    }
+
+    /// Sets the cross axis alignment of the children of the [`Scrollable`].
+    ///
+    /// [`Scrollable`]: struct.Scrollable.html
+    pub fn align_items(mut self, align: Align) -> Self {
+        self.style = self.style.align_items(align);
+        self
+    }
+
+    /// Sets the thickness of the scrollbars of the [`Scrollable`].
+    ///
+    /// [`Scrollable`]: struct.Scrollable.html
+    pub fn scrollbar_width(mut self, width: f32) -> Self {
+        self.scrollbar_width = width;
+        self
+    }
+
+    /// Turns the [`Scrollable`] into an [`Element`] with the given child.
+    ///
+    /// [`Scrollable`]: struct.Scrollable.html
+    /// [`Element`]: ../struct.Element.html
+    pub fn append<Message: 'static>(
+        self,
+        child: impl Into<Element<Message>>,
+    ) -> Element<Message> {
+        Element::new(self).append(child)
+    }
+
+    fn scrolls(&self, axis: Axis) -> bool {
+        match (self.direction, axis) {
+            (Direction::Both, _) => true,
+            (Direction::Vertical, Axis::Vertical) => true,
+            (Direction::Horizontal, Axis::Horizontal) => true,
+            _ => false,
+        }
+    }
+
+    fn max_offset(&self) -> Vector {
+        let State {
+            viewport, content, ..
+        } = self.state;
+
+        Vector::new(
+            if self.scrolls(Axis::Horizontal) {
+                (content.width - viewport.width).max(0.0)
+            } else {
+                0.0
+            },
+            if self.scrolls(Axis::Vertical) {
+                (content.height - viewport.height).max(0.0)
+            } else {
+                0.0
+            },
+        )
+    }
+
+    /// Scrolls by the given amount, within range.
+    ///
+    /// Returns false if the offset did not change.
+    fn scroll_by(&mut self, delta: Vector) -> bool {
+        let max = self.max_offset();
+        let offset = self.state.offset;
+
+        self.state.offset = Vector::new(
+            (offset.x + delta.x).min(max.x).max(0.0),
+            (offset.y + delta.y).min(max.y).max(0.0),
+        );
+
+        self.state.offset != offset
+    }
+
+    /// Returns the track and the thumb of the scrollbar along the given axis,
+    /// if the content overflows along it.
+    fn scrollbar(&self, bounds: Rectangle, axis: Axis) -> Option<(Rectangle, Rectangle)> {
+        let max = self.max_offset();
+        let width = self.scrollbar_width;
+
+        let (overflow, other_overflow) = match axis {
+            Axis::Vertical => (max.y, max.x),
+            Axis::Horizontal => (max.x, max.y),
+        };
+
+        if overflow <= 0.0 {
+            return None;
+        }
+
+        let corner = if other_overflow > 0.0 { width + MARGIN } else { 0.0 };
+
+        let (track, viewport, content, offset) = match axis {
+            Axis::Vertical => (
+                Rectangle {
+                    x: bounds.x + bounds.width - width - MARGIN,
+                    y: bounds.y + MARGIN,
+                    width,
+                    height: (bounds.height - MARGIN * 2.0 - corner).max(0.0),
+                },
+                self.state.viewport.height,
+                self.state.content.height,
+                self.state.offset.y,
+            ),
+            Axis::Horizontal => (
+                Rectangle {
+                    x: bounds.x + MARGIN,
+                    y: bounds.y + bounds.height - width - MARGIN,
+                    width: (bounds.width - MARGIN * 2.0 - corner).max(0.0),
+                    height: width,
+                },
+                self.state.viewport.width,
+                self.state.content.width,
+                self.state.offset.x,
+            ),
+        };
+
+        let track_length = main(track, axis).1;
+        let thumb_length = (track_length * viewport / content)
+            .max(MIN_THUMB_LENGTH)
+            .min(track_length);
+        let thumb_start = (track_length - thumb_length) * offset / overflow;
+
+        let thumb = match axis {
+            Axis::Vertical => Rectangle {
+                y: track.y + thumb_start,
+                height: thumb_length,
+                ..track
+            },
+            Axis::Horizontal => Rectangle {
+                x: track.x + thumb_start,
+                width: thumb_length,
+                ..track
+            },
+        };
+
+        Some((track, thumb))
+    }
+
+    /// Moves the thumb of a scrollbar so the point it was grabbed at follows
+    /// the cursor.
+    fn drag_thumb(&mut self, bounds: Rectangle, axis: Axis, grab: f32, cursor: Point) {
+        if let Some((track, thumb)) = self.scrollbar(bounds, axis) {
+            let (track_start, track_length) = main(track, axis);
+            let (_, thumb_length) = main(thumb, axis);
+
+            let position = coordinate(cursor, axis);
+            let range = (track_length - thumb_length).max(1.0);
+            let ratio = ((position - grab - track_start) / range).max(0.0).min(1.0);
+            let max = self.max_offset();
+
+            let target = match axis {
+                Axis::Vertical => Vector::new(self.state.offset.x, ratio * max.y),
+                Axis::Horizontal => Vector::new(ratio * max.x, self.state.offset.y),
+            };
+
+            let offset = self.state.offset;
+            let _ = self.scroll_by(Vector::new(target.x - offset.x, target.y - offset.y));
+        }
+    }
+
+    fn thumb_at(&self, bounds: Rectangle, position: Point) -> Option<Axis> {
+        [Axis::Vertical, Axis::Horizontal]
+            .iter()
+            .cloned()
+            .find(|&axis| {
+                self.scrollbar(bounds, axis)
+                    .map_or(false, |(_, thumb)| thumb.contains(position))
+            })
+    }
+
+    fn on_frame<Message>(&mut self, now: Instant, ctx: &mut EventCtx<'_, Message>) {
+        let dt = self
+            .state
+            .last_frame
+            .map(|last| now.duration_since(last).as_secs_f32())
+            .filter(|&dt| dt > 0.0);
+
+        self.state.last_frame = Some(now);
+
+        match self.state.drag {
+            Some(Drag::Touch { is_scrolling, .. }) => {
+                if let Some(dt) = dt {
+                    let pending = self.state.pending;
+                    let velocity = self.state.velocity;
+
+                    self.state.velocity = Vector::new(
+                        pending.x / dt * 0.8 + velocity.x * 0.2,
+                        pending.y / dt * 0.8 + velocity.y * 0.2,
+                    );
+                }
+
+                self.state.pending = Vector::default();
+
+                if is_scrolling {
+                    ctx.request_frame();
+                } else {
+                    self.state.last_frame = None;
+                }
+            }
+            _ => {
+                if let Some(dt) = dt {
+                    let velocity = self.state.velocity;
+                    let before = self.state.offset;
+                    let _ = self.scroll_by(Vector::new(velocity.x * dt, velocity.y * dt));
+
+                    let decay = (-FRICTION * dt).exp();
+                    let after = self.state.offset;
+
+                    self.state.velocity = Vector::new(
+                        if after.x == before.x { 0.0 } else { velocity.x * decay },
+                        if after.y == before.y { 0.0 } else { velocity.y * decay },
+                    );
+                }
+
+                if speed(self.state.velocity) > MIN_VELOCITY {
+                    ctx.request_frame();
+                } else {
+                    self.state.velocity = Vector::default();
+                    self.state.last_frame = None;
+                }
+            }
+        }
+    }
+}
+
+impl<Message> Widget<Message> for Scrollable {
+    fn style(&self) -> Style {
+        self.style
+    }
+
+    fn accessibility(&self) -> Properties {
+        Properties::new(Role::ScrollView)
+    }
+
+    fn axis(&self) -> Axis {
+        match self.direction {
+            Direction::Horizontal => Axis::Horizontal,
+            Direction::Vertical | Direction::Both => Axis::Vertical,
+        }
+    }
+
+    fn scroll(&self) -> Option<Scroll> {
+        Some(Scroll {
+            offset: self.state.offset,
+            horizontal: self.scrolls(Axis::Horizontal),
+            vertical: self.scrolls(Axis::Vertical),
+        })
+    }
+
+    fn set_content_size(&mut self, viewport: Size, content: Size) {
+        self.state.viewport = viewport;
+        self.state.content = content;
+
+        let _ = self.scroll_by(Vector::default());
+    }
+
+    fn on_event(&mut self, event: &Event, ctx: &mut EventCtx<'_, Message>) {
+        let bounds = ctx.bounds();
+
+        match event {
+            Event::Mouse(mouse::Event::WheelScrolled { delta }) => {
+                // Nested scrollables get the first chance to scroll
+                if ctx.phase() == Phase::Capture {
+                    return;
+                }
+
+                let (x, y) = match *delta {
+                    mouse::ScrollDelta::Lines { x, y } => (x * WHEEL_LINE, y * WHEEL_LINE),
+                    mouse::ScrollDelta::Pixels { x, y } => (x, y),
+                };
+
+                let delta = if self.direction == Direction::Horizontal && x == 0.0 {
+                    Vector::new(-y, 0.0)
+                } else {
+                    Vector::new(-x, -y)
+                };
+
+                self.state.velocity = Vector::default();
+
+                if self.scroll_by(delta) {
+                    ctx.stop_propagation();
+                }
+            }
+            Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Left)) => {
+                let cursor = match ctx.cursor_position() {
+                    Some(cursor) => cursor,
+                    None => return,
+                };
+
+                for &axis in &[Axis::Vertical, Axis::Horizontal] {
+                    if let Some((track, thumb)) = self.scrollbar(bounds, axis) {
+                        if !track.contains(cursor) {
+                            continue;
+                        }
+
+                        let (thumb_start, thumb_length) = main(thumb, axis);
+                        let grab = if thumb.contains(cursor) {
+                            coordinate(cursor, axis) - thumb_start
+                        } else {
+                            thumb_length / 2.0
+                        };
+
+                        self.state.drag = Some(Drag::Thumb { axis, grab });
+                        self.state.velocity = Vector::default();
+                        self.drag_thumb(bounds, axis, grab, cursor);
+
+                        ctx.stop_propagation();
+                        return;
+                    }
+                }
+            }
+            Event::Mouse(mouse::Event::CursorMoved { position }) => {
+                self.state.hovered_thumb = self.thumb_at(bounds, *position);
+
+                if let Some(Drag::Thumb { axis, grab }) = self.state.drag {
+                    self.drag_thumb(bounds, axis, grab, *position);
+                    ctx.stop_propagation();
+                }
+            }
+            Event::Mouse(mouse::Event::ButtonReleased(mouse::Button::Left)) => {
+                if let Some(Drag::Thumb { .. }) = self.state.drag {
+                    self.state.drag = None;
+                    ctx.stop_propagation();
+                }
+            }
+            Event::Touch(touch::Event::FingerPressed { id, position }) => {
+                if self.state.drag.is_none() {
+                    self.state.drag = Some(Drag::Touch {
+                        finger: *id,
+                        start: *position,
+                        last: *position,
+                        is_scrolling: false,
+                    });
+                    self.state.velocity = Vector::default();
+                    self.state.pending = Vector::default();
+                }
+            }
+            Event::Touch(touch::Event::FingerMoved { id, position }) => {
+                if let Some(Drag::Touch {
+                    finger,
+                    start,
+                    last,
+                    is_scrolling,
+                }) = self.state.drag
+                {
+                    if finger != *id {
+                        return;
+                    }
+
+                    let travelled = Vector::new(position.x - start.x, position.y - start.y);
+                    let is_scrolling = is_scrolling || speed(travelled) > TOUCH_SLOP;
+
+                    if is_scrolling {
+                        let delta = Vector::new(last.x - position.x, last.y - position.y);
+
+                        let _ = self.scroll_by(delta);
+                        self.state.pending = self.state.pending + delta;
+
+                        ctx.request_frame();
+                        ctx.stop_propagation();
+                    }
+
+                    self.state.drag = Some(Drag::Touch {
+                        finger,
+                        start,
+                        last: *position,
+                        is_scrolling,
+                    });
+                }
+            }
+            Event::Touch(touch::Event::FingerLifted { id, .. })
+            | Event::Touch(touch::Event::FingerLost { id, .. }) => {
+                if let Some(Drag::Touch {
+                    finger,
+                    is_scrolling,
+                    ..
+                }) = self.state.drag
+                {
+                    if finger != *id {
+                        return;
+                    }
+
+                    self.state.drag = None;
+
+                    if is_scrolling {
+                        ctx.request_frame();
+                        ctx.stop_propagation();
+                    }
+                }
+            }
+            Event::Frame(now) => self.on_frame(*now, ctx),
+            _ => {}
+        }
+    }
+
+    fn draw_overlay(&self, ctx: &DrawCtx<'_>) -> Primitive {
+        let scrollbar = &ctx.theme().scrollbar;
+        let bounds = ctx.bounds();
+        let mut primitives = Vec::new();
+
+        for &axis in &[Axis::Vertical, Axis::Horizontal] {
+            if let Some((track, thumb)) = self.scrollbar(bounds, axis) {
+                let is_dragged = match self.state.drag {
+                    Some(Drag::Thumb { axis: dragged, .. }) => dragged == axis,
+                    _ => false,
+                };
+
+                let is_hovered = ctx.is_hovered() && self.state.hovered_thumb == Some(axis);
+
+                primitives.push(Primitive::Quad {
+                    bounds: track,
+                    background: scrollbar.track,
+                    border_radius: scrollbar.border_radius,
+                    border_width: 0.0,
+                    border_color: scrollbar.track,
+                });
+
+                primitives.push(Primitive::Quad {
+                    bounds: thumb,
+                    background: Palette::interactive(scrollbar.thumb, is_hovered, is_dragged),
+                    border_radius: scrollbar.border_radius,
+                    border_width: 0.0,
+                    border_color: scrollbar.thumb,
+                });
+            }
+        }
+
+        Primitive::Group { primitives }
+    }
+
+    fn take_state(&mut self) -> Option<Box<dyn Any>> {
+        Some(Box::new(self.state))
+    }
+
+    fn restore_state(&mut self, state: Box<dyn Any>) {
+        if let Ok(state) = state.downcast::<State>() {
+            self.state = *state;
+        }
+    }
+}
+
+impl<Message: 'static> From<Scrollable> for Element<Message> {
+    fn from(scrollable: Scrollable) -> Element<Message> {
+        Element::new(scrollable)
+    }
+}
+
+/// Returns the start and the length of a rectangle along the given axis.
+fn main(rectangle: Rectangle, axis: Axis) -> (f32, f32) {
+    match axis {
+        Axis::Vertical => (rectangle.y, rectangle.height),
+        Axis::Horizontal => (rectangle.x, rectangle.width),
+    }
+}
+
+fn coordinate(point: Point, axis: Axis) -> f32 {
+    match axis {
+        Axis::Vertical => point.y,
+        Axis::Horizontal => point.x,
+    }
+}
+
+fn speed(vector: Vector) -> f32 {
+    (vector.x * vector.x + vector.y * vector.y).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::animation;
+    use crate::testing::fixture::{texts, Fixture};
+    use crate::testing::Harness;
+    use crate::widget::Button;
+
+    #[derive(Debug, Clone, PartialEq)]
+    enum Message {
+        Pressed,
+    }
+
+    /// Returns ten rows in a scrollable, two of which are in view.
+    fn scrollable() -> Harness<Fixture<Message>> {
+        Harness::widget(|| {
+            let rows = (0..10).map(|_| Button::new("Row").on_press(Message::Pressed).height(50.0));
+
+            rows.fold(Element::from(Scrollable::new()), Element::append)
+        })
+    }
+
+    fn row_y(harness: &Harness<Fixture<Message>>, index: usize) -> f32 {
+        let tree = harness.tree();
+        let scrollable = tree.children(tree.root()).next().unwrap();
+        let row = tree.children(scrollable).nth(index).unwrap();
+
+        tree.node(row).bounds().y
+    }
+
+    fn touch(event: fn(touch::Finger, Point) -> touch::Event, y: f32) -> Event {
+        Event::Touch(event(touch::Finger(0), Point::new(100.0, y)))
+    }
+
+    #[test]
+    fn scrollable_scrolls_with_the_wheel_within_range() {
+        let mut harness = scrollable();
+        let wheel = |y| {
+            Event::Mouse(mouse::Event::WheelScrolled {
+                delta: mouse::ScrollDelta::Lines { x: 0.0, y },
+            })
+        };
+
+        harness.move_to(Point::new(10.0, 10.0));
+        harness.dispatch(wheel(-1.0));
+        assert_eq!(row_y(&harness, 0), -40.0);
+
+        harness.dispatch(wheel(-100.0));
+        assert_eq!(row_y(&harness, 9), 50.0);
+
+        harness.click(Point::new(10.0, 60.0));
+        assert_eq!(harness.messages(), &[Message::Pressed]);
+    }
+
+    #[test]
+    fn scrollable_skips_rows_out_of_view() {
+        let harness = scrollable();
+
+        assert_eq!(texts(&harness.draw()).len(), 2);
+    }
+
+    #[test]
+    fn scrollable_keeps_moving_after_a_fling() {
+        let mut harness = scrollable();
+
+        harness.dispatch(touch(|id, position| touch::Event::FingerPressed { id, position }, 90.0));
+        harness.dispatch(touch(|id, position| touch::Event::FingerMoved { id, position }, 60.0));
+        harness.advance(animation::FRAME);
+
+        harness.dispatch(touch(|id, position| touch::Event::FingerMoved { id, position }, 30.0));
+        harness.advance(animation::FRAME);
+
+        harness.dispatch(touch(|id, position| touch::Event::FingerLifted { id, position }, 30.0));
+        assert_eq!(row_y(&harness, 0), -60.0);
+
+        harness.advance(animation::FRAME);
+        assert!(row_y(&harness, 0) < -60.0);
+        assert!(harness.tree().is_animating());
+        assert!(harness.messages().is_empty());
+    }
+}
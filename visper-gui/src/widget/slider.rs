@@ -0,0 +1,324 @@
+use crate::accessibility::{self, Properties, Role, Value};
+use crate::event::{keyboard, mouse, Event, Phase};
+use crate::layout::Axis;
+use crate::style::{Length, Style};
+use crate::theme::Palette;
+use crate::widget::{DrawCtx, Element, EventCtx, Widget};
+use std::ops::RangeInclusive;
+use visper_graphics::core::point::Point;
+use visper_graphics::core::rectangle::Rectangle;
+use visper_graphics::core::size::Size;
+use visper_graphics::primitive::Primitive;
+
+/// A bar and a handle that selects a value from a range.
+///
+/// The [`Slider`] can be dragged with the mouse, or moved with the arrow,
+/// Home and End keys when focused.
+///
+/// [`Slider`]: struct.Slider.html
+pub struct Slider<Message> {
+    range: RangeInclusive<f32>,
+    value: f32,
+    step: Option<f32>,
+    axis: Axis,
+    on_change: Box<dyn Fn(f32) -> Message>,
+    style: Style,
+}
+
+impl<Message> Slider<Message> {
+    /// The thickness of the track of a [`Slider`].
+    ///
+    /// [`Slider`]: struct.Slider.html
+    pub const THICKNESS: f32 = 20.0;
+
+    /// The size of the handle of a [`Slider`].
+    ///
+    /// [`Slider`]: struct.Slider.html
+    pub const HANDLE_SIZE: f32 = 16.0;
+
+    /// Creates a new horizontal [`Slider`].
+    ///
+    /// `on_change` receives the new value of the [`Slider`] and produces the
+    /// message to publish.
+    ///
+    /// [`Slider`]: struct.Slider.html
+    pub fn new<F>(range: RangeInclusive<f32>, value: f32, on_change: F) -> Self
+    where
+        F: 'static + Fn(f32) -> Message,
+    {
+        let value = value.max(*range.start()).min(*range.end());
+
+        Slider {
+            range,
+            value,
+            step: None,
+            axis: Axis::Horizontal,
+            on_change: Box::new(on_change),
+            style: Style::new().width(Length::Fill).height(Self::THICKNESS),
+        }
+    }
+
+    /// Snaps the values of the [`Slider`] to multiples of the given step,
+    /// counted from the start of its range.
+    ///
+    /// [`Slider`]: struct.Slider.html
+    pub fn step(mut self, step: f32) -> Self {
+        if step > 0.0 {
+            self.step = Some(step);
+        }
+
+        self
+    }
+
+    /// Lays out the [`Slider`] vertically, with the start of its range at the
+    /// bottom.
+    ///
+    /// [`Slider`]: struct.Slider.html
+    pub fn vertical(mut self) -> Self {
+        self.axis = Axis::Vertical;
+        self.style = self.style.width(Self::THICKNESS).height(Length::Fill);
+        self
+    }
+
+    /// Sets the length of the [`Slider`] along its axis.
+    ///
+    /// [`Slider`]: struct.Slider.html
+    pub fn length(mut self, length: impl Into<Length>) -> Self {
+        self.style = match self.axis {
+            Axis::Horizontal => self.style.width(length),
+            Axis::Vertical => self.style.height(length),
+        };
+        self
+    }
+
+    /// Returns the value at the given position of the cursor.
+    fn value_at(&self, bounds: Rectangle, position: Point) -> f32 {
+        let ratio = match self.axis {
+            Axis::Horizontal => (position.x - bounds.x) / bounds.width,
+            Axis::Vertical => 1.0 - (position.y - bounds.y) / bounds.height,
+        };
+
+        let (start, end) = (*self.range.start(), *self.range.end());
+
+        self.snap(start + ratio.max(0.0).min(1.0) * (end - start))
+    }
+
+    fn snap(&self, value: f32) -> f32 {
+        let (start, end) = (*self.range.start(), *self.range.end());
+
+        let value = match self.step {
+            Some(step) => start + ((value - start) / step).round() * step,
+            None => value,
+        };
+
+        value.max(start).min(end)
+    }
+
+    /// The amount the value changes with a single key press.
+    fn key_step(&self) -> f32 {
+        self.step
+            .unwrap_or((self.range.end() - self.range.start()) / 100.0)
+    }
+
+    fn change(&mut self, value: f32, ctx: &mut EventCtx<'_, Message>) {
+        if (value - self.value).abs() > std::f32::EPSILON {
+            self.value = value;
+            ctx.publish((self.on_change)(value));
+        }
+
+        ctx.stop_propagation();
+    }
+}
+
+impl<Message> Widget<Message> for Slider<Message> {
+    fn style(&self) -> Style {
+        self.style
+    }
+
+    fn accessibility(&self) -> Properties {
+        Properties::new(Role::Slider).value(Value::Number {
+            value: self.value,
+            min: *self.range.start(),
+            max: *self.range.end(),
+            step: self.step,
+        })
+    }
+
+    fn measure(&self, _available: Size) -> Size {
+        Size::new(Self::THICKNESS, Self::THICKNESS)
+    }
+
+    fn focusable(&self) -> bool {
+        true
+    }
+
+    fn on_event(&mut self, event: &Event, ctx: &mut EventCtx<'_, Message>) {
+        let bounds = ctx.bounds();
+
+        match event {
+            Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Left)) => {
+                if let Some(position) = ctx.cursor_position() {
+                    let value = self.value_at(bounds, position);
+
+                    self.change(value, ctx);
+                }
+            }
+            Event::Mouse(mouse::Event::CursorMoved { position }) if ctx.is_captured() => {
+                let value = self.value_at(bounds, *position);
+
+                self.change(value, ctx);
+            }
+            Event::Keyboard(keyboard::Event::KeyPressed { key_code, .. }) if ctx.is_focused() => {
+                let value = match key_code {
+                    keyboard::KeyCode::Left | keyboard::KeyCode::Down => {
+                        Some(self.value - self.key_step())
+                    }
+                    keyboard::KeyCode::Right | keyboard::KeyCode::Up => {
+                        Some(self.value + self.key_step())
+                    }
+                    keyboard::KeyCode::Home => Some(*self.range.start()),
+                    keyboard::KeyCode::End => Some(*self.range.end()),
+                    _ => None,
+                };
+
+                if let Some(value) = value {
+                    let value = self.snap(value);
+
+                    self.change(value, ctx);
+                }
+            }
+            Event::Accessibility(accessibility::Action::SetValue(Value::Number { value, .. }))
+                if ctx.phase() == Phase::Target =>
+            {
+                let value = self.snap(*value);
+
+                self.change(value, ctx);
+            }
+            _ => {}
+        }
+    }
+
+    fn draw(&self, ctx: &DrawCtx<'_>) -> Primitive {
+        let palette = &ctx.theme().palette;
+        let bounds = ctx.bounds();
+
+        let (start, end) = (*self.range.start(), *self.range.end());
+        let ratio = if end > start {
+            (self.value - start) / (end - start)
+        } else {
+            0.0
+        };
+
+        let rail = 4.0;
+        let handle = Self::HANDLE_SIZE;
+
+        let (rail_bounds, handle_position) = match self.axis {
+            Axis::Horizontal => (
+                Rectangle {
+                    x: bounds.x,
+                    y: bounds.y + (bounds.height - rail) / 2.0,
+                    width: bounds.width,
+                    height: rail,
+                },
+                Point::new(
+                    bounds.x + ratio * (bounds.width - handle),
+                    bounds.y + (bounds.height - handle) / 2.0,
+                ),
+            ),
+            Axis::Vertical => (
+                Rectangle {
+                    x: bounds.x + (bounds.width - rail) / 2.0,
+                    y: bounds.y,
+                    width: rail,
+                    height: bounds.height,
+                },
+                Point::new(
+                    bounds.x + (bounds.width - handle) / 2.0,
+                    bounds.y + (1.0 - ratio) * (bounds.height - handle),
+                ),
+            ),
+        };
+
+        Primitive::Group {
+            primitives: vec![
+                Primitive::Quad {
+                    bounds: rail_bounds,
+                    background: palette.surface,
+                    border_radius: rail / 2.0,
+                    border_width: 0.0,
+                    border_color: palette.surface,
+                },
+                Primitive::Quad {
+                    bounds: Rectangle {
+                        x: handle_position.x,
+                        y: handle_position.y,
+                        width: handle,
+                        height: handle,
+                    },
+                    background: Palette::highlight(palette.primary, ctx.hover(), ctx.press()),
+                    border_radius: handle / 2.0,
+                    border_width: 0.0,
+                    border_color: palette.primary,
+                },
+            ],
+        }
+    }
+}
+
+impl<Message: 'static> From<Slider<Message>> for Element<Message> {
+    fn from(slider: Slider<Message>) -> Element<Message> {
+        Element::new(slider)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::Harness;
+
+    #[derive(Debug, Clone, PartialEq)]
+    enum Message {
+        Changed(f32),
+    }
+
+    #[test]
+    fn slider_follows_the_cursor_in_steps() {
+        let mut harness =
+            Harness::widget(|| Slider::new(0.0..=100.0, 0.0, Message::Changed).step(10.0));
+
+        harness.move_to(Point::new(96.0, 10.0));
+        harness.dispatch(Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Left)));
+        harness.move_to(Point::new(150.0, 10.0));
+        harness.move_to(Point::new(300.0, 10.0));
+        harness.dispatch(Event::Mouse(mouse::Event::ButtonReleased(mouse::Button::Left)));
+
+        assert_eq!(
+            harness.take_messages(),
+            vec![
+                Message::Changed(50.0),
+                Message::Changed(80.0),
+                Message::Changed(100.0),
+            ]
+        );
+
+        harness.move_to(Point::new(10.0, 10.0));
+        assert!(harness.messages().is_empty());
+    }
+
+    #[test]
+    fn slider_is_operable_with_the_keyboard() {
+        let mut harness =
+            Harness::widget(|| Slider::new(0.0..=1.0, 0.0, Message::Changed).step(0.25));
+
+        harness.key(keyboard::KeyCode::Tab);
+
+        harness.key(keyboard::KeyCode::Left);
+        assert!(harness.messages().is_empty());
+
+        harness.key(keyboard::KeyCode::Right);
+        assert_eq!(harness.take_messages(), vec![Message::Changed(0.25)]);
+
+        harness.key(keyboard::KeyCode::End);
+        assert_eq!(harness.take_messages(), vec![Message::Changed(1.0)]);
+    }
+}
@@ -0,0 +1,659 @@
+use crate::accessibility::{Properties, Role};
+use crate::event::{mouse, Event, Phase};
+use crate::layout::Axis;
+use crate::style::{Length, Style};
+use crate::widget::virtual_list::RowHeight;
+use crate::widget::{self, Container, DrawCtx, Element, EventCtx, Text, VirtualList, Widget};
+use std::any::Any;
+use std::rc::Rc;
+use visper_graphics::core::color::Color;
+use visper_graphics::core::point::Point;
+use visper_graphics::core::rectangle::Rectangle;
+use visper_graphics::core::vector::Vector;
+use visper_graphics::primitive::Primitive;
+use visper_graphics::text::{HorizontalAlignment, VerticalAlignment};
+
+/// The space between the borders of a cell and its content.
+const CELL_PADDING: f32 = 8.0;
+
+/// The distance from the border of a column at which it can be grabbed to
+/// resize it.
+const RESIZE_HANDLE: f32 = 4.0;
+
+/// A column of a [`Table`].
+///
+/// [`Table`]: struct.Table.html
+#[derive(Debug, Clone, PartialEq)]
+pub struct Column {
+    title: String,
+    width: f32,
+    min_width: f32,
+    is_resizable: bool,
+    is_sortable: bool,
+}
+
+impl Column {
+    /// Creates a new resizable [`Column`] with the given title and initial
+    /// width.
+    ///
+    /// [`Column`]: struct.Column.html
+    pub fn new(title: impl Into<String>, width: f32) -> Self {
+        Column {
+            title: title.into(),
+            width,
+            min_width: CELL_PADDING * 4.0,
+            is_resizable: true,
+            is_sortable: false,
+        }
+    }
+
+    /// Sets the width under which the [`Column`] cannot be resized.
+    ///
+    /// [`Column`]: struct.Column.html
+    pub fn min_width(mut self, min_width: f32) -> Self {
+        self.min_width = min_width;
+        self
+    }
+
+    /// Prevents the [`Column`] from being resized.
+    ///
+    /// [`Column`]: struct.Column.html
+    pub fn fixed(mut self) -> Self {
+        self.is_resizable = false;
+        self
+    }
+
+    /// Lets the [`Column`] be sorted by clicking its header.
+    ///
+    /// [`Column`]: struct.Column.html
+    pub fn sortable(mut self) -> Self {
+        self.is_sortable = true;
+        self
+    }
+}
+
+/// The order the rows of a [`Table`] are sorted in.
+///
+/// [`Table`]: struct.Table.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Order {
+    /// From the smallest to the largest value.
+    Ascending,
+
+    /// From the largest to the smallest value.
+    Descending,
+}
+
+/// A table with a sticky header that only builds the rows in view.
+///
+/// A [`Table`] takes its columns, the number of its rows and a closure that
+/// builds the cell at a given row and column. Its body is a [`VirtualList`],
+/// so a [`Table`] with hundreds of thousands of rows is as cheap as a table
+/// that fills the screen.
+///
+/// Columns are resized by dragging the borders of their header. A click on
+/// the header of a sortable column publishes the order the application should
+/// sort its rows in, and a click on a row publishes its index. The
+/// application owns the data, so it decides what a row index refers to.
+///
+/// [`Table`]: struct.Table.html
+/// [`VirtualList`]: ../virtual_list/struct.VirtualList.html
+pub struct Table<Message> {
+    columns: Vec<Column>,
+    count: usize,
+    cell: Rc<dyn Fn(usize, usize) -> Element<Message>>,
+    row_height: RowHeight,
+    header_height: f32,
+    sort: Option<(usize, Order)>,
+    on_sort: Option<Box<dyn Fn(usize, Order) -> Message>>,
+    selected: Option<usize>,
+    on_select: Option<Rc<dyn Fn(usize) -> Message>>,
+    style: Style,
+    state: State,
+    built: Option<Vec<f32>>,
+}
+
+/// The state of a [`Table`] that survives the rebuilds of the tree.
+///
+/// [`Table`]: struct.Table.html
+#[derive(Debug, Clone, Default)]
+struct State {
+    widths: Vec<f32>,
+    drag: Option<Drag>,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Drag {
+    Resize { column: usize, grab: f32, width: f32 },
+    Press { column: usize },
+}
+
+impl<Message: 'static> Table<Message> {
+    /// Creates a new [`Table`] with the given columns and number of rows,
+    /// whose cells are built by the given closure.
+    ///
+    /// The closure receives the index of the row and the index of the column
+    /// of the cell.
+    ///
+    /// [`Table`]: struct.Table.html
+    pub fn new<F>(columns: Vec<Column>, count: usize, cell: F) -> Self
+    where
+        F: 'static + Fn(usize, usize) -> Element<Message>,
+    {
+        let widths = columns.iter().map(|column| column.width).collect();
+
+        Table {
+            columns,
+            count,
+            cell: Rc::new(cell),
+            row_height: RowHeight::Fixed(32.0),
+            header_height: 32.0,
+            sort: None,
+            on_sort: None,
+            selected: None,
+            on_select: None,
+            style: Style::new().width(Length::Fill).height(Length::Fill),
+            state: State {
+                widths,
+                drag: None,
+            },
+            built: None,
+        }
+    }
+
+    /// Sets the [`RowHeight`] of the rows of the [`Table`].
+    ///
+    /// [`RowHeight`]: ../virtual_list/enum.RowHeight.html
+    /// [`Table`]: struct.Table.html
+    pub fn row_height(mut self, row_height: impl Into<RowHeight>) -> Self {
+        self.row_height = row_height.into();
+        self
+    }
+
+    /// Sets the height of the header of the [`Table`].
+    ///
+    /// [`Table`]: struct.Table.html
+    pub fn header_height(mut self, height: f32) -> Self {
+        self.header_height = height;
+        self
+    }
+
+    /// Shows that the rows are sorted by the given column in the given
+    /// [`Order`].
+    ///
+    /// [`Order`]: enum.Order.html
+    pub fn sort(mut self, column: usize, order: Order) -> Self {
+        self.sort = Some((column, order));
+        self
+    }
+
+    /// Sets the message published when the header of a sortable column is
+    /// clicked, given the column and the order to sort the rows in.
+    pub fn on_sort<F>(mut self, on_sort: F) -> Self
+    where
+        F: 'static + Fn(usize, Order) -> Message,
+    {
+        self.on_sort = Some(Box::new(on_sort));
+        self
+    }
+
+    /// Highlights the row at the given index as selected.
+    pub fn selected(mut self, row: usize) -> Self {
+        self.selected = Some(row);
+        self
+    }
+
+    /// Sets the message published when a row is clicked, given its index.
+    pub fn on_select<F>(mut self, on_select: F) -> Self
+    where
+        F: 'static + Fn(usize) -> Message,
+    {
+        self.on_select = Some(Rc::new(on_select));
+        self
+    }
+
+    /// Sets the width of the [`Table`].
+    ///
+    /// [`Table`]: struct.Table.html
+    pub fn width(mut self, width: impl Into<Length>) -> Self {
+        self.style = self.style.width(width);
+        self
+    }
+
+    /// Sets the height of the [`Table`].
+    ///
+    /// [`Table`]: struct.Table.html
+    pub fn height(mut self, height: impl Into<Length>) -> Self {
+        self.style = self.style.height(height);
+        self
+    }
+
+    fn header_bounds(&self, bounds: Rectangle) -> Rectangle {
+        Rectangle {
+            height: self.header_height.min(bounds.height),
+            ..bounds
+        }
+    }
+
+    /// Returns the horizontal ranges of the columns.
+    fn spans(&self, bounds: Rectangle) -> impl Iterator<Item = (f32, f32)> + '_ {
+        self.state.widths.iter().scan(bounds.x, |x, width| {
+            let start = *x;
+            *x += width;
+
+            Some((start, *x))
+        })
+    }
+
+    /// Returns the column whose right border is under the cursor, if it can be
+    /// resized.
+    fn border_at(&self, bounds: Rectangle, cursor: Point) -> Option<usize> {
+        self.spans(bounds)
+            .enumerate()
+            .filter(|&(column, _)| self.columns[column].is_resizable)
+            .find(|&(_, (_, end))| (cursor.x - end).abs() <= RESIZE_HANDLE)
+            .map(|(column, _)| column)
+    }
+
+    fn column_at(&self, bounds: Rectangle, cursor: Point) -> Option<usize> {
+        self.spans(bounds)
+            .position(|(start, end)| cursor.x >= start && cursor.x < end)
+    }
+
+    fn header(&self) -> Element<Message> {
+        let columns = self
+            .columns
+            .iter()
+            .zip(&self.state.widths)
+            .enumerate()
+            .map(|(index, (column, &width))| {
+                let order = self
+                    .sort
+                    .filter(|&(sorted, _)| sorted == index)
+                    .map(|(_, order)| order);
+
+                (column.title.clone(), width, order)
+            })
+            .collect();
+
+        Element::new(Header {
+            columns,
+            height: self.header_height,
+        })
+        .key("header")
+    }
+
+    fn body(&self) -> Element<Message> {
+        let cell = Rc::clone(&self.cell);
+        let widths = self.state.widths.clone();
+        let selected = self.selected;
+        let on_select = self.on_select.clone();
+
+        let body = VirtualList::new(self.count, move |index| {
+            let row = Element::new(Row {
+                index,
+                is_selected: selected == Some(index),
+                is_pressed: false,
+                on_select: on_select.clone(),
+            });
+
+            widths.iter().enumerate().fold(row, |row, (column, &width)| {
+                row.append(
+                    Container::new()
+                        .width(width)
+                        .height(Length::Fill)
+                        .padding(CELL_PADDING)
+                        .center_y()
+                        .append(cell(index, column)),
+                )
+            })
+        });
+
+        Element::from(body.row_height(self.row_height)).key("body")
+    }
+}
+
+impl<Message: 'static> Widget<Message> for Table<Message> {
+    fn style(&self) -> Style {
+        self.style
+    }
+
+    fn accessibility(&self) -> Properties {
+        Properties::new(Role::Table)
+    }
+
+    fn build_children(&mut self, _children: &[Rectangle]) -> Option<Vec<Element<Message>>> {
+        if self.built.as_ref() == Some(&self.state.widths) {
+            return None;
+        }
+
+        self.built = Some(self.state.widths.clone());
+
+        Some(vec![self.header(), self.body()])
+    }
+
+    fn on_event(&mut self, event: &Event, ctx: &mut EventCtx<'_, Message>) {
+        // Events for the header reach the table before its children
+        if ctx.phase() == Phase::Bubble {
+            return;
+        }
+
+        let header = self.header_bounds(ctx.bounds());
+
+        match event {
+            Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Left)) => {
+                let cursor = match ctx.cursor_position() {
+                    Some(cursor) if header.contains(cursor) => cursor,
+                    _ => return,
+                };
+
+                if let Some(column) = self.border_at(header, cursor) {
+                    self.state.drag = Some(Drag::Resize {
+                        column,
+                        grab: cursor.x,
+                        width: self.state.widths[column],
+                    });
+                } else if let Some(column) = self.column_at(header, cursor) {
+                    self.state.drag = Some(Drag::Press { column });
+                }
+
+                ctx.stop_propagation();
+            }
+            Event::Mouse(mouse::Event::CursorMoved { position }) => {
+                if let Some(Drag::Resize {
+                    column,
+                    grab,
+                    width,
+                }) = self.state.drag
+                {
+                    let min_width = self.columns[column].min_width;
+
+                    self.state.widths[column] = (width + position.x - grab).max(min_width);
+
+                    ctx.request_layout();
+                    ctx.stop_propagation();
+                }
+            }
+            Event::Mouse(mouse::Event::ButtonReleased(mouse::Button::Left)) => {
+                match self.state.drag.take() {
+                    Some(Drag::Resize { .. }) => ctx.stop_propagation(),
+                    Some(Drag::Press { column }) => {
+                        let is_released_over = ctx
+                            .cursor_position()
+                            .filter(|&cursor| header.contains(cursor))
+                            .and_then(|cursor| self.column_at(header, cursor))
+                            == Some(column);
+
+                        if is_released_over && self.columns[column].is_sortable {
+                            let order = match self.sort {
+                                Some((sorted, Order::Ascending)) if sorted == column => {
+                                    Order::Descending
+                                }
+                                _ => Order::Ascending,
+                            };
+
+                            if let Some(on_sort) = &self.on_sort {
+                                ctx.publish(on_sort(column, order));
+                            }
+                        }
+
+                        ctx.stop_propagation();
+                    }
+                    None => {}
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn take_state(&mut self) -> Option<Box<dyn Any>> {
+        Some(Box::new(self.state.clone()))
+    }
+
+    fn restore_state(&mut self, state: Box<dyn Any>) {
+        if let Ok(state) = state.downcast::<State>() {
+            // The columns may have changed since
+            if state.widths.len() == self.columns.len() {
+                self.state = *state;
+            }
+        }
+    }
+}
+
+impl<Message: 'static> From<Table<Message>> for Element<Message> {
+    fn from(table: Table<Message>) -> Element<Message> {
+        Element::new(table)
+    }
+}
+
+/// The header of a [`Table`], with the title and the sort order of each
+/// column.
+///
+/// [`Table`]: struct.Table.html
+struct Header {
+    columns: Vec<(String, f32, Option<Order>)>,
+    height: f32,
+}
+
+impl<Message> Widget<Message> for Header {
+    fn style(&self) -> Style {
+        Style::new().width(Length::Fill).height(self.height)
+    }
+
+    fn draw(&self, ctx: &DrawCtx<'_>) -> Primitive {
+        let bounds = ctx.bounds();
+        let theme = ctx.theme();
+        let mut primitives = vec![quad(bounds, theme.table.header)];
+        let mut x = bounds.x;
+
+        for (title, width, order) in &self.columns {
+            let cell = Rectangle {
+                x: x + CELL_PADDING,
+                width: (width - CELL_PADDING * 2.0).max(0.0),
+                ..bounds
+            };
+
+            primitives.push(widget::draw_label(title, cell, ctx));
+
+            if let Some(order) = order {
+                let arrow = match order {
+                    Order::Ascending => "▲",
+                    Order::Descending => "▼",
+                };
+
+                primitives.push(Primitive::Text {
+                    content: String::from(arrow),
+                    bounds: cell,
+                    color: theme.palette.text,
+                    size: Text::DEFAULT_SIZE * 0.75,
+                    horizontal_alignment: HorizontalAlignment::Right,
+                    vertical_alignment: VerticalAlignment::Center,
+                });
+            }
+
+            x += width;
+
+            primitives.push(quad(
+                Rectangle {
+                    x: x - 1.0,
+                    width: 1.0,
+                    ..bounds
+                },
+                theme.table.divider,
+            ));
+        }
+
+        primitives.push(quad(
+            Rectangle {
+                y: bounds.y + bounds.height - 1.0,
+                height: 1.0,
+                ..bounds
+            },
+            theme.table.divider,
+        ));
+
+        // Titles of wide columns must not spill out of the table
+        Primitive::Clip {
+            bounds,
+            offset: Vector::default(),
+            content: Box::new(Primitive::Group { primitives }),
+        }
+    }
+}
+
+/// A row of a [`Table`], laying out its cells from left to right.
+///
+/// [`Table`]: struct.Table.html
+struct Row<Message> {
+    index: usize,
+    is_selected: bool,
+    is_pressed: bool,
+    on_select: Option<Rc<dyn Fn(usize) -> Message>>,
+}
+
+impl<Message> Widget<Message> for Row<Message> {
+    fn style(&self) -> Style {
+        Style::new().width(Length::Fill).height(Length::Fill)
+    }
+
+    fn accessibility(&self) -> Properties {
+        Properties::new(Role::Row).selected(self.is_selected)
+    }
+
+    fn axis(&self) -> Axis {
+        Axis::Horizontal
+    }
+
+    fn on_event(&mut self, event: &Event, ctx: &mut EventCtx<'_, Message>) {
+        let on_select = match &self.on_select {
+            Some(on_select) => on_select,
+            None => return,
+        };
+
+        // Clicks usually land on a cell, so they are handled while bubbling
+        if ctx.phase() == Phase::Capture {
+            return;
+        }
+
+        match event {
+            Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Left)) => {
+                self.is_pressed = true;
+                ctx.stop_propagation();
+            }
+            Event::Mouse(mouse::Event::ButtonReleased(mouse::Button::Left)) => {
+                if self.is_pressed && ctx.is_hovered() {
+                    ctx.publish(on_select(self.index));
+                    ctx.stop_propagation();
+                }
+
+                self.is_pressed = false;
+            }
+            _ => {}
+        }
+    }
+
+    fn draw(&self, ctx: &DrawCtx<'_>) -> Primitive {
+        let table = &ctx.theme().table;
+
+        let background = if self.is_selected {
+            table.selection
+        } else if ctx.is_hovered() && self.on_select.is_some() {
+            table.hover
+        } else if self.index % 2 == 1 {
+            table.stripe
+        } else {
+            return Primitive::None;
+        };
+
+        quad(ctx.bounds(), background)
+    }
+}
+
+fn quad(bounds: Rectangle, color: Color) -> Primitive {
+    Primitive::Quad {
+        bounds,
+        background: color,
+        border_radius: 0.0,
+        border_width: 0.0,
+        border_color: color,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::fixture::{texts, Fixture};
+    use crate::testing::Harness;
+
+    #[derive(Debug, Clone, PartialEq)]
+    enum Message {
+        Sorted(usize, Order),
+        Row(usize),
+    }
+
+    /// Returns a table of a thousand rows, 32 units tall, under a sortable
+    /// column 100 units wide and a column 60 units wide.
+    fn table(sort: Option<(usize, Order)>) -> Harness<Fixture<Message>> {
+        Harness::widget(move || {
+            let columns = vec![
+                Column::new("Name", 100.0).sortable(),
+                Column::new("Size", 60.0),
+            ];
+            let table = Table::new(columns, 1_000, |row, column| {
+                Text::new(format!("{}:{}", row, column)).into()
+            });
+            let table = match sort {
+                Some((column, order)) => table.sort(column, order),
+                None => table,
+            };
+
+            table.on_sort(Message::Sorted).on_select(Message::Row)
+        })
+    }
+
+    fn count_clips(primitive: &Primitive) -> usize {
+        match primitive {
+            Primitive::Group { primitives } => primitives.iter().map(count_clips).sum(),
+            Primitive::Clip { content, .. } => 1 + count_clips(content),
+            _ => 0,
+        }
+    }
+
+    #[test]
+    fn table_sorts_selects_and_resizes() {
+        let mut unsorted = table(None);
+        let mut sorted = table(Some((0, Order::Ascending)));
+
+        unsorted.click(Point::new(10.0, 10.0));
+        sorted.click(Point::new(10.0, 10.0));
+
+        assert_eq!(unsorted.messages(), &[Message::Sorted(0, Order::Ascending)]);
+        assert_eq!(sorted.take_messages(), vec![Message::Sorted(0, Order::Descending)]);
+
+        sorted.click(Point::new(110.0, 10.0));
+        assert!(sorted.messages().is_empty());
+
+        sorted.click(Point::new(110.0, 70.0));
+        assert_eq!(sorted.take_messages(), vec![Message::Row(1)]);
+
+        sorted.move_to(Point::new(101.0, 10.0));
+        sorted.dispatch(Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Left)));
+        sorted.move_to(Point::new(151.0, 10.0));
+        sorted.dispatch(Event::Mouse(mouse::Event::ButtonReleased(mouse::Button::Left)));
+
+        sorted.click(Point::new(140.0, 10.0));
+        assert_eq!(sorted.take_messages(), vec![Message::Sorted(0, Order::Descending)]);
+
+        sorted.click(Point::new(160.0, 10.0));
+        assert!(sorted.messages().is_empty());
+    }
+
+    #[test]
+    fn table_draws_its_rows_in_a_single_clip() {
+        let primitive = table(None).draw();
+
+        // One for the header and one for the body
+        assert_eq!(count_clips(&primitive), 2);
+        assert_eq!(texts(&primitive).len(), 2 + 2 * 2);
+    }
+}
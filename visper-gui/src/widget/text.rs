@@ -0,0 +1,139 @@
+use crate::accessibility::{Properties, Role};
+use crate::style::{Length, Style};
+use crate::widget::{DrawCtx, Element, Widget};
+use visper_graphics::core::color::Color;
+use visper_graphics::core::size::Size;
+use visper_graphics::primitive::Primitive;
+use visper_graphics::text::{self, HorizontalAlignment, VerticalAlignment};
+
+/// A fragment of text.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Text {
+    content: String,
+    size: f32,
+    color: Option<Color>,
+    style: Style,
+    horizontal_alignment: HorizontalAlignment,
+    vertical_alignment: VerticalAlignment,
+}
+
+impl Text {
+    /// The default size of text, in logical pixels.
+    pub const DEFAULT_SIZE: f32 = 16.0;
+
+    /// Creates a new [`Text`] with the given content.
+    ///
+    /// [`Text`]: struct.Text.html
+    pub fn new(content: impl Into<String>) -> Self {
+        Text {
+            content: content.into(),
+            size: Self::DEFAULT_SIZE,
+            color: None,
+            style: Style::new(),
+            horizontal_alignment: HorizontalAlignment::Left,
+            vertical_alignment: VerticalAlignment::Top,
+        }
+    }
+
+    /// Sets the size of the [`Text`].
+    ///
+    /// [`Text`]: struct.Text.html
+    pub fn size(mut self, size: f32) -> Self {
+        self.size = size;
+        self
+    }
+
+    /// Sets the color of the [`Text`], overriding the color of the theme.
+    ///
+    /// [`Text`]: struct.Text.html
+    pub fn color(mut self, color: Color) -> Self {
+        self.color = Some(color);
+        self
+    }
+
+    /// Sets the width of the [`Text`].
+    ///
+    /// [`Text`]: struct.Text.html
+    pub fn width(mut self, width: impl Into<Length>) -> Self {
+        self.style = self.style.width(width);
+        self
+    }
+
+    /// Sets the height of the [`Text`].
+    ///
+    /// [`Text`]: struct.Text.html
+    pub fn height(mut self, height: impl Into<Length>) -> Self {
+        self.style = self.style.height(height);
+        self
+    }
+
+    /// Sets the horizontal alignment of the [`Text`] inside its bounds.
+    ///
+    /// [`Text`]: struct.Text.html
+    pub fn horizontal_alignment(mut self, alignment: HorizontalAlignment) -> Self {
+        self.horizontal_alignment = alignment;
+        self
+    }
+
+    /// Sets the vertical alignment of the [`Text`] inside its bounds.
+    ///
+    /// [`Text`]: struct.Text.html
+    pub fn vertical_alignment(mut self, alignment: VerticalAlignment) -> Self {
+        self.vertical_alignment = alignment;
+        self
+    }
+}
+
+impl<Message> Widget<Message> for Text {
+    fn style(&self) -> Style {
+        self.style
+    }
+
+    fn label(&self) -> Option<&str> {
+        Some(&self.content)
+    }
+
+    fn accessibility(&self) -> Properties {
+        Properties::new(Role::Text)
+    }
+
+    fn measure(&self, _available: Size) -> Size {
+        text::measure(&self.content, self.size)
+    }
+
+    fn draw(&self, ctx: &DrawCtx<'_>) -> Primitive {
+        Primitive::Text {
+            content: self.content.clone(),
+            bounds: ctx.bounds(),
+            color: self.color.unwrap_or(ctx.theme().palette.text),
+            size: self.size,
+            horizontal_alignment: self.horizontal_alignment,
+            vertical_alignment: self.vertical_alignment,
+        }
+    }
+}
+
+impl<Message: 'static> From<Text> for Element<Message> {
+    fn from(text: Text) -> Element<Message> {
+        Element::new(text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::fixture::Fixture;
+    use crate::testing::Harness;
+
+    #[test]
+    fn text_shrinks_to_its_content() {
+        let harness: Harness<Fixture<()>> = Harness::widget(|| Text::new("Hello"));
+        let tree = harness.tree();
+        let text = tree.children(tree.root()).next().unwrap();
+
+        let size = text::measure("Hello", Text::DEFAULT_SIZE);
+
+        assert_eq!(tree.node(text).bounds().width, size.width);
+        assert_eq!(tree.node(text).bounds().height, size.height);
+    }
+}
@@ -0,0 +1,780 @@
+use crate::accessibility::{self, Properties, Role, Value};
+use crate::editor::{Editor, Motion};
+use crate::event::{keyboard, mouse, Event, Phase};
+use crate::style::{Length, Style};
+use crate::widget::{DrawCtx, Element, EventCtx, Text, Widget};
+use std::any::Any;
+use std::borrow::Cow;
+use unicode_segmentation::UnicodeSegmentation;
+use visper_graphics::core::color::Color;
+use visper_graphics::core::point::Point;
+use visper_graphics::core::rectangle::Rectangle;
+use visper_graphics::core::size::Size;
+use visper_graphics::core::vector::Vector;
+use visper_graphics::primitive::Primitive;
+use visper_graphics::text::{self, HorizontalAlignment, VerticalAlignment};
+
+/// A field where text can be typed.
+///
+/// A [`TextInput`] edits a single line of text by default and scrolls
+/// horizontally to keep the cursor in view. A multi-line [`TextInput`] allows
+/// line breaks and scrolls vertically.
+///
+/// The cursor moves over graphemes, or over words while Control is held, and
+/// Shift or a drag of the mouse extends the selection. Control with A, C, X,
+/// V, Z and Y selects all, copies, cuts, pastes, undoes and redoes.
+///
+/// [`TextInput`]: struct.TextInput.html
+pub struct TextInput<Message> {
+    placeholder: String,
+    state: State,
+    on_change: Box<dyn Fn(String) -> Message>,
+    on_submit: Option<Message>,
+    is_secure: bool,
+    lines: usize,
+    size: f32,
+    style: Style,
+}
+
+/// The state of a [`TextInput`] that survives the rebuilds of the tree.
+///
+/// [`TextInput`]: struct.TextInput.html
+struct State {
+    editor: Editor,
+    offset: Vector,
+}
+
+impl<Message> TextInput<Message> {
+    /// The character shown in place of every grapheme of a password.
+    pub const MASK: char = '•';
+
+    /// The width of the caret.
+    const CARET_WIDTH: f32 = 1.0;
+
+    /// Creates a new single-line [`TextInput`] with the given placeholder and
+    /// value.
+    ///
+    /// `on_change` receives the new value of the [`TextInput`] after every
+    /// edit and produces the message to publish.
+    ///
+    /// [`TextInput`]: struct.TextInput.html
+    pub fn new<F>(placeholder: impl Into<String>, value: &str, on_change: F) -> Self
+    where
+        F: 'static + Fn(String) -> Message,
+    {
+        TextInput {
+            placeholder: placeholder.into(),
+            state: State {
+                editor: Editor::new(value),
+                offset: Vector::default(),
+            },
+            on_change: Box::new(on_change),
+            on_submit: None,
+            is_secure: false,
+            lines: 1,
+            size: Text::DEFAULT_SIZE,
+            style: Style::new().width(Length::Fill).padding(6.0),
+        }
+    }
+
+    /// Sets the message published when Enter is pressed in a single-line
+    /// [`TextInput`].
+    ///
+    /// [`TextInput`]: struct.TextInput.html
+    pub fn on_submit(mut self, message: Message) -> Self {
+        self.on_submit = Some(message);
+        self
+    }
+
+    /// Masks the value of the [`TextInput`], which cannot be copied or cut
+    /// anymore.
+    ///
+    /// [`TextInput`]: struct.TextInput.html
+    pub fn password(mut self) -> Self {
+        self.is_secure = true;
+        self
+    }
+
+    /// Allows line breaks in the [`TextInput`] and makes it tall enough to
+    /// show the given number of lines.
+    ///
+    /// [`TextInput`]: struct.TextInput.html
+    pub fn multiline(mut self, lines: usize) -> Self {
+        let value = self.state.editor.value().to_owned();
+
+        self.state.editor = Editor::new(value).multiline();
+        self.lines = lines.max(1);
+        self
+    }
+
+    /// Sets the text size of the [`TextInput`].
+    ///
+    /// [`TextInput`]: struct.TextInput.html
+    pub fn size(mut self, size: f32) -> Self {
+        self.size = size;
+        self
+    }
+
+    /// Sets the width of the [`TextInput`].
+    ///
+    /// [`TextInput`]: struct.TextInput.html
+    pub fn width(mut self, width: impl Into<Length>) -> Self {
+        self.style = self.style.width(width);
+        self
+    }
+
+    /// Sets the padding of the [`TextInput`].
+    ///
+    /// [`TextInput`]: struct.TextInput.html
+    pub fn padding(mut self, padding: f32) -> Self {
+        self.style = self.style.padding(padding);
+        self
+    }
+
+    fn line_height(&self) -> f32 {
+        text::line_height(self.size)
+    }
+
+    fn content(&self, bounds: Rectangle) -> Rectangle {
+        let padding = self.style.padding;
+
+        Rectangle {
+            x: bounds.x + padding,
+            y: bounds.y + padding,
+            width: (bounds.width - padding * 2.0).max(0.0),
+            height: (bounds.height - padding * 2.0).max(0.0),
+        }
+    }
+
+    /// Returns the text shown for the value, masked for passwords.
+    fn display(&self) -> Cow<'_, str> {
+        let value = self.state.editor.value();
+
+        if self.is_secure {
+            Cow::Owned(value.graphemes(true).map(|_| Self::MASK).collect())
+        } else {
+            Cow::Borrowed(value)
+        }
+    }
+
+    /// Converts an index of the value into an index of the displayed text.
+    fn to_display(&self, index: usize) -> usize {
+        if self.is_secure {
+            let value = self.state.editor.value();
+
+            value[..index].graphemes(true).count() * Self::MASK.len_utf8()
+        } else {
+            index
+        }
+    }
+
+    /// Converts an index of the displayed text into an index of the value.
+    fn to_value(&self, index: usize) -> usize {
+        if self.is_secure {
+            let value = self.state.editor.value();
+
+            value
+                .grapheme_indices(true)
+                .nth(index / Self::MASK.len_utf8())
+                .map_or(value.len(), |(start, _)| start)
+        } else {
+            index
+        }
+    }
+
+    /// Returns the position of the given index of the value, relative to the
+    /// unscrolled content.
+    fn position_of(&self, display: &str, index: usize) -> Point {
+        let index = self.to_display(index);
+        let line_start = display[..index].rfind('\n').map_or(0, |i| i + 1);
+        let line = display[..index].matches('\n').count();
+
+        Point::new(
+            text::advance(&display[line_start..index], self.size),
+            line as f32 * self.line_height(),
+        )
+    }
+
+    /// Returns the index of the value under the given position, relative to
+    /// the unscrolled content.
+    fn index_at(&self, display: &str, position: Point) -> usize {
+        let lines: Vec<&str> = display.split('\n').collect();
+        let line = ((position.y / self.line_height()).floor().max(0.0) as usize)
+            .min(lines.len() - 1);
+
+        let line_start: usize = lines[..line].iter().map(|line| line.len() + 1).sum();
+        let index = line_start + text::hit_test(lines[line], self.size, position.x);
+
+        self.to_value(index)
+    }
+
+    /// Returns the index of the value under the cursor.
+    fn index_under(&self, content: Rectangle, cursor: Point) -> usize {
+        let offset = self.state.offset;
+        let position = Point::new(
+            cursor.x - content.x + offset.x,
+            cursor.y - content.y + offset.y,
+        );
+
+        self.index_at(&self.display(), position)
+    }
+
+    /// Returns the largest offset the content can be scrolled by.
+    fn max_offset(&self, content: Rectangle) -> Vector {
+        let size = text::measure(&self.display(), self.size);
+
+        Vector::new(
+            (size.width + Self::CARET_WIDTH - content.width).max(0.0),
+            (size.height - content.height).max(0.0),
+        )
+    }
+
+    /// Scrolls the content the least possible to show the caret.
+    fn scroll_to_cursor(&mut self, content: Rectangle) {
+        let caret = self.position_of(&self.display(), self.state.editor.cursor());
+        let line_height = self.line_height();
+        let max = self.max_offset(content);
+        let mut offset = self.state.offset;
+
+        if caret.x + Self::CARET_WIDTH > offset.x + content.width {
+            offset.x = caret.x + Self::CARET_WIDTH - content.width;
+        }
+
+        if caret.x < offset.x {
+            offset.x = caret.x;
+        }
+
+        if caret.y + line_height > offset.y + content.height {
+            offset.y = caret.y + line_height - content.height;
+        }
+
+        if caret.y < offset.y {
+            offset.y = caret.y;
+        }
+
+        self.state.offset = Vector::new(
+            offset.x.min(max.x).max(0.0),
+            offset.y.min(max.y).max(0.0),
+        );
+    }
+
+    /// Handles a key press while focused.
+    ///
+    /// Returns `None` if the key is not handled, or whether the value changed
+    /// otherwise.
+    fn key_pressed(
+        &mut self,
+        key_code: keyboard::KeyCode,
+        modifiers: keyboard::ModifiersState,
+        ctx: &mut EventCtx<'_, Message>,
+    ) -> Option<bool>
+    where
+        Message: Clone,
+    {
+        use keyboard::KeyCode;
+
+        let command = modifiers.control || modifiers.logo;
+        let extend = modifiers.shift;
+        let editor = &mut self.state.editor;
+
+        let (word_left, word_right) = if command {
+            (Motion::WordLeft, Motion::WordRight)
+        } else {
+            (Motion::Left, Motion::Right)
+        };
+
+        let (home, end) = if command {
+            (Motion::Start, Motion::End)
+        } else {
+            (Motion::LineStart, Motion::LineEnd)
+        };
+
+        let changed = match key_code {
+            KeyCode::Left => {
+                editor.move_cursor(word_left, extend);
+                false
+            }
+            KeyCode::Right => {
+                editor.move_cursor(word_right, extend);
+                false
+            }
+            KeyCode::Up => {
+                editor.move_cursor(Motion::Up, extend);
+                false
+            }
+            KeyCode::Down => {
+                editor.move_cursor(Motion::Down, extend);
+                false
+            }
+            KeyCode::Home => {
+                editor.move_cursor(home, extend);
+                false
+            }
+            KeyCode::End => {
+                editor.move_cursor(end, extend);
+                false
+            }
+            KeyCode::Back => editor.delete(word_left),
+            KeyCode::Delete => editor.delete(word_right),
+            KeyCode::Return => {
+                if editor.is_multiline() {
+                    editor.insert("\n")
+                } else {
+                    if let Some(on_submit) = self.on_submit.clone() {
+                        ctx.publish(on_submit);
+                    }
+
+                    false
+                }
+            }
+            KeyCode::A if command => {
+                editor.select_all();
+                false
+            }
+            KeyCode::C if command => {
+                if !self.is_secure && !editor.selection().is_collapsed() {
+                    ctx.clipboard().write(editor.selected_text().to_owned());
+                }
+
+                false
+            }
+            KeyCode::X if command => {
+                if self.is_secure {
+                    false
+                } else {
+                    match editor.cut() {
+                        Some(text) => {
+                            ctx.clipboard().write(text);
+                            true
+                        }
+                        None => false,
+                    }
+                }
+            }
+            KeyCode::V if command => match ctx.clipboard().read() {
+                Some(text) => editor.insert(&text),
+                None => false,
+            },
+            KeyCode::Z if command => {
+                if modifiers.shift {
+                    editor.redo()
+                } else {
+                    editor.undo()
+                }
+            }
+            KeyCode::Y if command => editor.redo(),
+            _ => return None,
+        };
+
+        Some(changed)
+    }
+}
+
+impl<Message: Clone> Widget<Message> for TextInput<Message> {
+    fn style(&self) -> Style {
+        self.style
+    }
+
+    fn label(&self) -> Option<&str> {
+        Some(&self.placeholder)
+    }
+
+    fn accessibility(&self) -> Properties {
+        if self.is_secure {
+            Properties::new(Role::PasswordInput)
+        } else {
+            Properties::new(Role::TextInput)
+                .value(Value::Text(self.state.editor.value().to_owned()))
+        }
+    }
+
+    fn measure(&self, _available: Size) -> Size {
+        Size::new(
+            text::advance(&self.placeholder, self.size).ceil(),
+            self.line_height() * self.lines as f32,
+        )
+    }
+
+    fn focusable(&self) -> bool {
+        true
+    }
+
+    fn on_event(&mut self, event: &Event, ctx: &mut EventCtx<'_, Message>) {
+        let content = self.content(ctx.bounds());
+
+        let changed = match event {
+            Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Left)) => {
+                if let Some(cursor) = ctx.cursor_position() {
+                    let index = self.index_under(content, cursor);
+
+                    self.state.editor.move_to(index, false);
+                }
+
+                ctx.stop_propagation();
+                false
+            }
+            Event::Mouse(mouse::Event::CursorMoved { position }) if ctx.is_captured() => {
+                let index = self.index_under(content, *position);
+
+                self.state.editor.move_to(index, true);
+                ctx.stop_propagation();
+                false
+            }
+            Event::Mouse(mouse::Event::WheelScrolled { delta }) if self.lines > 1 => {
+                let delta = match delta {
+                    mouse::ScrollDelta::Lines { y, .. } => y * self.line_height(),
+                    mouse::ScrollDelta::Pixels { y, .. } => *y,
+                };
+
+                let max = self.max_offset(content);
+                let offset = (self.state.offset.y - delta).min(max.y).max(0.0);
+
+                if (offset - self.state.offset.y).abs() > std::f32::EPSILON {
+                    self.state.offset.y = offset;
+                    ctx.stop_propagation();
+                }
+
+                return;
+            }
+            Event::Keyboard(keyboard::Event::CharacterReceived(c)) if ctx.is_focused() => {
+                ctx.stop_propagation();
+
+                self.state.editor.insert(c.encode_utf8(&mut [0; 4]))
+            }
+            Event::Keyboard(keyboard::Event::KeyPressed { key_code, modifiers })
+                if ctx.is_focused() =>
+            {
+                match self.key_pressed(*key_code, *modifiers, ctx) {
+                    Some(changed) => {
+                        ctx.stop_propagation();
+                        changed
+                    }
+                    None => return,
+                }
+            }
+            Event::Accessibility(accessibility::Action::SetValue(Value::Text(value)))
+                if ctx.phase() == Phase::Target =>
+            {
+                ctx.stop_propagation();
+
+                if value == self.state.editor.value() {
+                    false
+                } else {
+                    self.state.editor.select_all();
+                    self.state.editor.insert(value)
+                }
+            }
+            _ => return,
+        };
+
+        if changed {
+            ctx.publish((self.on_change)(self.state.editor.value().to_owned()));
+        }
+
+        self.scroll_to_cursor(content);
+    }
+
+    fn draw(&self, ctx: &DrawCtx<'_>) -> Primitive {
+        let theme = ctx.theme();
+        let palette = &theme.palette;
+        let bounds = ctx.bounds();
+        let content = self.content(bounds);
+        let line_height = self.line_height();
+        let offset = self.state.offset;
+
+        let display = self.display();
+        let editor = &self.state.editor;
+        let mut primitives = Vec::new();
+
+        if ctx.is_focused() && !editor.selection().is_collapsed() {
+            let selection = editor.selection();
+            let (start, end) = (
+                self.to_display(selection.start()),
+                self.to_display(selection.end()),
+            );
+
+            let mut line_start = 0;
+
+            for (i, line) in display.split('\n').enumerate() {
+                let line_end = line_start + line.len();
+
+                if start <= line_end && line_start <= end {
+                    let from = start.max(line_start) - line_start;
+                    let to = end.min(line_end) - line_start;
+
+                    let x = text::advance(&line[..from], self.size);
+                    let mut width = text::advance(&line[..to], self.size) - x;
+
+                    if end > line_end {
+                        width += text::advance(" ", self.size);
+                    }
+
+                    if width > 0.0 {
+                        primitives.push(Primitive::Quad {
+                            bounds: Rectangle {
+                                x: content.x + x,
+                                y: content.y + i as f32 * line_height,
+                                width,
+                                height: line_height,
+                            },
+                            background: Color {
+                                a: 0.3,
+                                ..palette.primary
+                            },
+                            border_radius: 0.0,
+                            border_width: 0.0,
+                            border_color: palette.primary,
+                        });
+                    }
+                }
+
+                line_start = line_end + 1;
+            }
+        }
+
+        if display.is_empty() {
+            primitives.push(Primitive::Text {
+                content: self.placeholder.clone(),
+                bounds: Rectangle {
+                    width: text::advance(&self.placeholder, self.size).ceil() + 1.0,
+                    height: line_height,
+                    ..content
+                },
+                color: palette.border,
+                size: self.size,
+                horizontal_alignment: HorizontalAlignment::Left,
+                vertical_alignment: VerticalAlignment::Top,
+            });
+        }
+
+        for (i, line) in display.split('\n').enumerate() {
+            let y = i as f32 * line_height;
+
+            if line.is_empty() || y + line_height < offset.y || y > offset.y + content.height {
+                continue;
+            }
+
+            primitives.push(Primitive::Text {
+                content: line.to_owned(),
+                bounds: Rectangle {
+                    x: content.x,
+                    y: content.y + y,
+                    width: text::advance(line, self.size).ceil() + 1.0,
+                    height: line_height,
+                },
+                color: palette.text,
+                size: self.size,
+                horizontal_alignment: HorizontalAlignment::Left,
+                vertical_alignment: VerticalAlignment::Top,
+            });
+        }
+
+        if ctx.is_focused() {
+            let caret = self.position_of(&display, editor.cursor());
+
+            primitives.push(Primitive::Quad {
+                bounds: Rectangle {
+                    x: content.x + caret.x,
+                    y: content.y + caret.y,
+                    width: Self::CARET_WIDTH,
+                    height: line_height,
+                },
+                background: palette.text,
+                border_radius: 0.0,
+                border_width: 0.0,
+                border_color: palette.text,
+            });
+        }
+
+        Primitive::Group {
+            primitives: vec![
+                Primitive::Quad {
+                    bounds,
+                    background: palette.background,
+                    border_radius: theme.border_radius,
+                    border_width: 1.0,
+                    border_color: if ctx.is_focused() {
+                        palette.primary
+                    } else {
+                        palette.border
+                    },
+                },
+                Primitive::Clip {
+                    bounds: content,
+                    offset,
+                    content: Box::new(Primitive::Group { primitives }),
+                },
+            ],
+        }
+    }
+
+    fn take_state(&mut self) -> Option<Box<dyn Any>> {
+        let state = std::mem::replace(
+            &mut self.state,
+            State {
+                editor: Editor::new(""),
+                offset: Vector::default(),
+            },
+        );
+
+        Some(Box::new(state))
+    }
+
+    fn restore_state(&mut self, state: Box<dyn Any>) {
+        if let Ok(state) = state.downcast::<State>() {
+            let mut state = *state;
+
+            if state.editor.is_multiline() == self.state.editor.is_multiline() {
+                state.editor.set_value(self.state.editor.value());
+                self.state = state;
+            }
+        }
+    }
+}
+
+impl<Message: Clone + 'static> From<TextInput<Message>> for Element<Message> {
+    fn from(text_input: TextInput<Message>) -> Element<Message> {
+        Element::new(text_input)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::application::Application;
+    use crate::command::Command;
+    use crate::testing::Harness;
+
+    /// An application with a single field.
+    struct Form {
+        value: String,
+        is_password: bool,
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    enum Message {
+        Edited(String),
+    }
+
+    impl Application for Form {
+        type Message = Message;
+
+        fn new() -> Self {
+            Form {
+                value: String::new(),
+                is_password: false,
+            }
+        }
+
+        fn title(&self) -> String {
+            String::from("Form")
+        }
+
+        fn update(&mut self, message: Message) -> Command {
+            match message {
+                Message::Edited(value) => self.value = value,
+            }
+
+            Command::None
+        }
+
+        fn view(&mut self) -> Element<Message> {
+            let input = TextInput::new("Name", &self.value, Message::Edited);
+
+            if self.is_password {
+                input.password().into()
+            } else {
+                input.into()
+            }
+        }
+    }
+
+    fn form(value: &str, is_password: bool) -> Harness<Form> {
+        let form = Form {
+            value: String::from(value),
+            is_password,
+        };
+
+        Harness::with(form, Size::new(200.0, 100.0))
+    }
+
+    fn edited(value: &str) -> Message {
+        Message::Edited(String::from(value))
+    }
+
+    fn shortcut(harness: &mut Harness<Form>, key_code: keyboard::KeyCode) {
+        let modifiers = keyboard::ModifiersState {
+            control: true,
+            ..Default::default()
+        };
+
+        harness.key_with(key_code, modifiers);
+    }
+
+    #[test]
+    fn text_input_publishes_edits_when_focused() {
+        let mut harness = form("", false);
+
+        harness.type_text("a");
+        assert!(harness.messages().is_empty());
+
+        harness.click(Point::new(5.0, 5.0));
+        harness.type_text("ab");
+        assert_eq!(harness.take_messages(), vec![edited("a"), edited("ab")]);
+
+        harness.key(keyboard::KeyCode::Back);
+        assert_eq!(harness.take_messages(), vec![edited("a")]);
+
+        harness.key(keyboard::KeyCode::Left);
+        assert!(harness.messages().is_empty());
+    }
+
+    #[test]
+    fn text_input_keeps_its_cursor_across_rebuilds() {
+        let mut harness = form("hello", false);
+
+        harness.click(Point::new(5.0, 5.0));
+        harness.publish(edited("hello"));
+        harness.type_text("!");
+
+        assert_eq!(harness.messages(), &[edited("hello"), edited("!hello")]);
+    }
+
+    #[test]
+    fn text_input_copies_and_pastes() {
+        let mut harness = form("hello", false);
+
+        harness.click(Point::new(5.0, 5.0));
+
+        for key_code in [
+            keyboard::KeyCode::A,
+            keyboard::KeyCode::C,
+            keyboard::KeyCode::End,
+            keyboard::KeyCode::V,
+            keyboard::KeyCode::Z,
+        ] {
+            shortcut(&mut harness, key_code);
+        }
+
+        assert_eq!(harness.messages(), &[edited("hellohello"), edited("hello")]);
+        assert_eq!(harness.application().value, "hello");
+    }
+
+    #[test]
+    fn password_cannot_be_copied() {
+        let mut harness = form("secret", true);
+
+        harness.click(Point::new(5.0, 5.0));
+
+        for key_code in [
+            keyboard::KeyCode::A,
+            keyboard::KeyCode::C,
+            keyboard::KeyCode::X,
+            keyboard::KeyCode::V,
+        ] {
+            shortcut(&mut harness, key_code);
+        }
+
+        assert!(harness.messages().is_empty());
+    }
+}
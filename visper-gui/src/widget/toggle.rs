@@ -0,0 +1,146 @@
+use crate::accessibility::{Properties, Role};
+use crate::event::{keyboard, Event};
+use crate::style::{Length, Style};
+use crate::theme::Palette;
+use crate::widget::{self, DrawCtx, Element, EventCtx, Widget};
+use visper_graphics::core::rectangle::Rectangle;
+use visper_graphics::core::size::Size;
+use visper_graphics::primitive::Primitive;
+
+/// A switch that can be turned on or off, followed by a label.
+pub struct Toggle<Message> {
+    is_on: bool,
+    label: String,
+    on_toggle: Box<dyn Fn(bool) -> Message>,
+    style: Style,
+}
+
+impl<Message> Toggle<Message> {
+    /// The size of the track of a [`Toggle`].
+    ///
+    /// [`Toggle`]: struct.Toggle.html
+    pub const TRACK: Size = Size::new(36.0, 20.0);
+
+    /// Creates a new [`Toggle`].
+    ///
+    /// `on_toggle` receives the new state of the [`Toggle`] and produces the
+    /// message to publish.
+    ///
+    /// [`Toggle`]: struct.Toggle.html
+    pub fn new<F>(is_on: bool, label: impl Into<String>, on_toggle: F) -> Self
+    where
+        F: 'static + Fn(bool) -> Message,
+    {
+        Toggle {
+            is_on,
+            label: label.into(),
+            on_toggle: Box::new(on_toggle),
+            style: Style::new(),
+        }
+    }
+
+    /// Sets the width of the [`Toggle`].
+    ///
+    /// [`Toggle`]: struct.Toggle.html
+    pub fn width(mut self, width: impl Into<Length>) -> Self {
+        self.style = self.style.width(width);
+        self
+    }
+}
+
+impl<Message> Widget<Message> for Toggle<Message> {
+    fn style(&self) -> Style {
+        self.style
+    }
+
+    fn label(&self) -> Option<&str> {
+        Some(&self.label)
+    }
+
+    fn accessibility(&self) -> Properties {
+        Properties::new(Role::Switch).checked(self.is_on)
+    }
+
+    fn measure(&self, _available: Size) -> Size {
+        widget::measure_labeled(Self::TRACK, &self.label)
+    }
+
+    fn focusable(&self) -> bool {
+        true
+    }
+
+    fn on_event(&mut self, event: &Event, ctx: &mut EventCtx<'_, Message>) {
+        if widget::is_activation(event, ctx, &[keyboard::KeyCode::Space]) {
+            ctx.publish((self.on_toggle)(!self.is_on));
+            ctx.stop_propagation();
+        }
+    }
+
+    fn draw(&self, ctx: &DrawCtx<'_>) -> Primitive {
+        let palette = &ctx.theme().palette;
+        let (track, label_bounds) = widget::layout_labeled(ctx.bounds(), Self::TRACK);
+
+        let knob_padding = 2.0;
+        let knob_size = track.height - knob_padding * 2.0;
+
+        let knob_x = if self.is_on {
+            track.x + track.width - knob_padding - knob_size
+        } else {
+            track.x + knob_padding
+        };
+
+        let track_color = if self.is_on { palette.primary } else { palette.border };
+
+        Primitive::Group {
+            primitives: vec![
+                Primitive::Quad {
+                    bounds: track,
+                    background: Palette::highlight(track_color, ctx.hover(), ctx.press()),
+                    border_radius: track.height / 2.0,
+                    border_width: 0.0,
+                    border_color: track_color,
+                },
+                Primitive::Quad {
+                    bounds: Rectangle {
+                        x: knob_x,
+                        y: track.y + knob_padding,
+                        width: knob_size,
+                        height: knob_size,
+                    },
+                    background: palette.on_primary,
+                    border_radius: knob_size / 2.0,
+                    border_width: 0.0,
+                    border_color: palette.on_primary,
+                },
+                widget::draw_label(&self.label, label_bounds, ctx),
+            ],
+        }
+    }
+}
+
+impl<Message: 'static> From<Toggle<Message>> for Element<Message> {
+    fn from(toggle: Toggle<Message>) -> Element<Message> {
+        Element::new(toggle)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::Harness;
+    use visper_graphics::core::point::Point;
+
+    #[derive(Debug, Clone, PartialEq)]
+    enum Message {
+        Toggled(bool),
+    }
+
+    #[test]
+    fn toggle_flips_its_state() {
+        let mut harness = Harness::widget(|| Toggle::new(false, "Toggle", Message::Toggled));
+
+        harness.click(Point::new(5.0, 5.0));
+
+        assert_eq!(harness.messages(), &[Message::Toggled(true)]);
+    }
+}
@@ -0,0 +1,402 @@
+use crate::accessibility::{Properties, Role};
+use crate::event::Event;
+use crate::layout::{Axis, Scroll};
+use crate::style::{Length, Style};
+use crate::widget::{Container, DrawCtx, Element, EventCtx, Scrollable, Widget};
+use std::any::Any;
+use std::collections::BTreeMap;
+use std::ops::Range;
+use visper_graphics::core::rectangle::Rectangle;
+use visper_graphics::core::size::Size;
+use visper_graphics::primitive::Primitive;
+
+/// The height of the rows of a [`VirtualList`].
+///
+/// [`VirtualList`]: struct.VirtualList.html
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RowHeight {
+    /// Every row has the same height.
+    Fixed(f32),
+
+    /// Rows are as tall as their content.
+    ///
+    /// Rows that have not been built yet are assumed to have the estimated
+    /// height. The height of a row is remembered once it has been laid out.
+    Measured {
+        /// The height assumed for the rows that have not been measured.
+        estimate: f32,
+    },
+}
+
+impl From<f32> for RowHeight {
+    fn from(height: f32) -> Self {
+        RowHeight::Fixed(height)
+    }
+}
+
+/// A vertical list that only builds the rows in view.
+///
+/// A [`VirtualList`] takes the number of its rows and a closure that builds
+/// the row at a given index. Only the rows that intersect its bounds, plus a
+/// few rows before and after them, are built and laid out; the space taken by
+/// the other rows is reserved by two empty spacers. This keeps the cost of a
+/// [`VirtualList`] independent of its length.
+///
+/// Rows are keyed by their index, so the state of a row, like the contents of
+/// a text input, follows it while it stays built.
+///
+/// A [`VirtualList`] scrolls like a vertical [`Scrollable`].
+///
+/// [`VirtualList`]: struct.VirtualList.html
+/// [`Scrollable`]: ../scrollable/struct.Scrollable.html
+pub struct VirtualList<Message> {
+    count: usize,
+    builder: Box<dyn Fn(usize) -> Element<Message>>,
+    row_height: RowHeight,
+    overscan: usize,
+    scrollable: Scrollable,
+    measured: BTreeMap<usize, f32>,
+    viewport: Size,
+    built: Option<Built>,
+}
+
+/// The state of a [`VirtualList`] that survives the rebuilds of the tree.
+///
+/// [`VirtualList`]: struct.VirtualList.html
+struct State {
+    scrollable: Option<Box<dyn Any>>,
+    measured: BTreeMap<usize, f32>,
+}
+
+/// The rows currently built by a [`VirtualList`] and the space reserved
+/// around them.
+///
+/// [`VirtualList`]: struct.VirtualList.html
+#[derive(Debug, Clone, PartialEq)]
+struct Built {
+    rows: Range<usize>,
+    above: f32,
+    below: f32,
+}
+
+impl<Message: 'static> VirtualList<Message> {
+    /// Creates a new [`VirtualList`] with the given number of rows, built by
+    /// the given closure.
+    ///
+    /// Rows are 32 units tall by default.
+    ///
+    /// [`VirtualList`]: struct.VirtualList.html
+    pub fn new<F>(count: usize, builder: F) -> Self
+    where
+        F: 'static + Fn(usize) -> Element<Message>,
+    {
+        VirtualList {
+            count,
+            builder: Box::new(builder),
+            row_height: RowHeight::Fixed(32.0),
+            overscan: 3,
+            scrollable: Scrollable::new(),
+            measured: BTreeMap::new(),
+            viewport: Size::ZERO,
+            built: None,
+        }
+    }
+
+    /// Sets the [`RowHeight`] of the [`VirtualList`].
+    ///
+    /// [`RowHeight`]: enum.RowHeight.html
+    /// [`VirtualList`]: struct.VirtualList.html
+    pub fn row_height(mut self, row_height: impl Into<RowHeight>) -> Self {
+        self.row_height = row_height.into();
+        self
+    }
+
+    /// Sets the number of rows built before and after the rows in view, so
+    /// they are ready when scrolling starts.
+    pub fn overscan(mut self, overscan: usize) -> Self {
+        self.overscan = overscan;
+        self
+    }
+
+    /// Sets the width of the [`VirtualList`].
+    ///
+    /// [`VirtualList`]: struct.VirtualList.html
+    pub fn width(mut self, width: impl Into<Length>) -> Self {
+        self.scrollable = self.scrollable.width(width);
+        self
+    }
+
+    /// Sets the height of the [`VirtualList`].
+    ///
+    /// [`VirtualList`]: struct.VirtualList.html
+    pub fn height(mut self, height: impl Into<Length>) -> Self {
+        self.scrollable = self.scrollable.height(height);
+        self
+    }
+
+    /// Sets the padding of the [`VirtualList`].
+    ///
+    /// [`VirtualList`]: struct.VirtualList.html
+    pub fn padding(mut self, padding: f32) -> Self {
+        self.scrollable = self.scrollable.padding(padding);
+        self
+    }
+
+    /// Sets the thickness of the scrollbar of the [`VirtualList`].
+    ///
+    /// [`VirtualList`]: struct.VirtualList.html
+    pub fn scrollbar_width(mut self, width: f32) -> Self {
+        self.scrollable = self.scrollable.scrollbar_width(width);
+        self
+    }
+
+    fn estimate(&self) -> f32 {
+        match self.row_height {
+            RowHeight::Fixed(height) => height,
+            RowHeight::Measured { estimate } => estimate,
+        }
+    }
+
+    /// Returns the distance between the top of the list and the top of the
+    /// row at the given index.
+    fn top(&self, index: usize) -> f32 {
+        let estimate = self.estimate();
+
+        self.measured
+            .range(..index)
+            .fold(index as f32 * estimate, |top, (_, height)| {
+                top + height - estimate
+            })
+    }
+
+    /// Returns the index of the row at the given distance from the top of the
+    /// list.
+    fn row_at(&self, y: f32) -> usize {
+        let estimate = self.estimate().max(1.0);
+        let skip = |from: usize, top: f32| from + ((y - top) / estimate).max(0.0) as usize;
+
+        let mut index = 0;
+        let mut top = 0.0;
+
+        for (&row, &height) in &self.measured {
+            let row_top = top + (row - index) as f32 * estimate;
+
+            if y < row_top {
+                return skip(index, top);
+            }
+
+            if y < row_top + height {
+                return row;
+            }
+
+            index = row + 1;
+            top = row_top + height;
+        }
+
+        skip(index, top)
+    }
+
+    /// Remembers the heights of the rows built for the given layout.
+    fn measure(&mut self, built: &Built, children: &[Rectangle]) {
+        // The rows sit between the two spacers
+        if children.len() != built.rows.len() + 2 {
+            return;
+        }
+
+        for (index, bounds) in built.rows.clone().zip(&children[1..]) {
+            let _ = self.measured.insert(index, bounds.height);
+        }
+    }
+
+    fn row(&self, index: usize) -> Element<Message> {
+        let container = match self.row_height {
+            RowHeight::Fixed(height) => Container::new().width(Length::Fill).height(height),
+            RowHeight::Measured { .. } => Container::new().width(Length::Fill),
+        };
+
+        container.append((self.builder)(index)).key(index.to_string())
+    }
+}
+
+impl<Message: 'static> Widget<Message> for VirtualList<Message> {
+    fn style(&self) -> Style {
+        Widget::<Message>::style(&self.scrollable)
+    }
+
+    fn accessibility(&self) -> Properties {
+        Properties::new(Role::List)
+    }
+
+    fn axis(&self) -> Axis {
+        Axis::Vertical
+    }
+
+    fn scroll(&self) -> Option<Scroll> {
+        Widget::<Message>::scroll(&self.scrollable)
+    }
+
+    fn set_content_size(&mut self, viewport: Size, content: Size) {
+        // The rows that are not built still take space
+        let height = self.top(self.count);
+
+        self.viewport = viewport;
+        Widget::<Message>::set_content_size(
+            &mut self.scrollable,
+            viewport,
+            Size::new(content.width, height),
+        );
+    }
+
+    fn build_children(&mut self, children: &[Rectangle]) -> Option<Vec<Element<Message>>> {
+        if let (RowHeight::Measured { .. }, Some(built)) = (self.row_height, self.built.clone()) {
+            self.measure(&built, children);
+        }
+
+        let offset = Widget::<Message>::scroll(&self.scrollable).map_or(0.0, |scroll| scroll.offset.y);
+
+        let last = (self.row_at(offset + self.viewport.height) + 1 + self.overscan).min(self.count);
+        let first = self.row_at(offset).saturating_sub(self.overscan).min(last);
+
+        let built = Built {
+            rows: first..last,
+            above: self.top(first),
+            below: self.top(self.count) - self.top(last),
+        };
+
+        if self.built.as_ref() == Some(&built) {
+            return None;
+        }
+
+        let spacer = |key: &str, height: f32| Element::from(Container::new().height(height)).key(key);
+
+        let mut elements = Vec::with_capacity(built.rows.len() + 2);
+        elements.push(spacer("above", built.above));
+        elements.extend(built.rows.clone().map(|index| self.row(index)));
+        elements.push(spacer("below", built.below));
+
+        self.built = Some(built);
+
+        Some(elements)
+    }
+
+    fn on_event(&mut self, event: &Event, ctx: &mut EventCtx<'_, Message>) {
+        self.scrollable.on_event(event, ctx);
+    }
+
+    fn draw_overlay(&self, ctx: &DrawCtx<'_>) -> Primitive {
+        Widget::<Message>::draw_overlay(&self.scrollable, ctx)
+    }
+
+    fn take_state(&mut self) -> Option<Box<dyn Any>> {
+        Some(Box::new(State {
+            scrollable: Widget::<Message>::take_state(&mut self.scrollable),
+            measured: std::mem::take(&mut self.measured),
+        }))
+    }
+
+    fn restore_state(&mut self, state: Box<dyn Any>) {
+        if let Ok(state) = state.downcast::<State>() {
+            let State {
+                scrollable,
+                mut measured,
+            } = *state;
+
+            if let Some(scrollable) = scrollable {
+                Widget::<Message>::restore_state(&mut self.scrollable, scrollable);
+            }
+
+            if let RowHeight::Measured { .. } = self.row_height {
+                let _ = measured.split_off(&self.count);
+                self.measured = measured;
+            }
+        }
+    }
+}
+
+impl<Message: 'static> From<VirtualList<Message>> for Element<Message> {
+    fn from(virtual_list: VirtualList<Message>) -> Element<Message> {
+        Element::new(virtual_list)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::fixture::Fixture;
+    use crate::testing::Harness;
+    use crate::tree::{NodeId, UiTree};
+    use crate::widget::Button;
+    use visper_graphics::core::point::Point;
+    use visper_graphics::core::vector::Vector;
+
+    #[derive(Debug, Clone, PartialEq)]
+    enum Message {
+        Row(usize),
+    }
+
+    /// Returns a list of a hundred thousand rows that alternate between 30
+    /// and 10 units.
+    fn list(row_height: RowHeight) -> Harness<Fixture<Message>> {
+        Harness::widget(move || {
+            VirtualList::new(100_000, |index| {
+                Button::new(index.to_string())
+                    .on_press(Message::Row(index))
+                    .height(if index % 2 == 0 { 30.0 } else { 10.0 })
+                    .into()
+            })
+            .row_height(row_height)
+            .overscan(0)
+        })
+    }
+
+    /// Returns the indices of the rows built by the list, and their nodes.
+    fn built_rows(tree: &UiTree<Message>) -> Vec<(usize, NodeId)> {
+        let list = tree.children(tree.root()).next().unwrap();
+
+        tree.children(list)
+            .filter_map(|row| {
+                let index = tree.node(row).key()?.parse().ok()?;
+
+                Some((index, row))
+            })
+            .collect()
+    }
+
+    fn indices(harness: &Harness<Fixture<Message>>) -> Vec<usize> {
+        built_rows(harness.tree())
+            .into_iter()
+            .map(|(index, _)| index)
+            .collect()
+    }
+
+    #[test]
+    fn virtual_list_builds_only_the_rows_in_view() {
+        let mut harness = list(RowHeight::Fixed(20.0));
+
+        assert_eq!(indices(&harness), (0..6).collect::<Vec<_>>());
+
+        harness.wheel(Point::new(10.0, 10.0), Vector::new(0.0, -50.0));
+        assert_eq!(indices(&harness), (2..8).collect::<Vec<_>>());
+
+        harness.wheel(Point::new(10.0, 10.0), Vector::new(0.0, -1e7));
+        assert_eq!(indices(&harness), (99_995..100_000).collect::<Vec<_>>());
+
+        harness.click(Point::new(10.0, 85.0));
+        assert_eq!(harness.messages(), &[Message::Row(99_999)]);
+    }
+
+    #[test]
+    fn virtual_list_remembers_measured_heights() {
+        let mut harness = list(RowHeight::Measured { estimate: 20.0 });
+
+        harness.wheel(Point::new(10.0, 10.0), Vector::new(0.0, -45.0));
+
+        let (first, row) = built_rows(harness.tree())[0];
+
+        assert_eq!(first, 2);
+        assert_eq!(harness.tree().node(row).bounds().y, -5.0);
+
+        harness.click(Point::new(10.0, 30.0));
+        assert_eq!(harness.messages(), &[Message::Row(3)]);
+    }
+}
@@ -0,0 +1,72 @@
+//! Open more windows than the main one.
+//!
+//! Every window of an [`Application`] has its own widget tree, built from
+//! [`Application::view_window`], its own focus and its own scale factor.
+//! Windows are opened and closed with [`Command`]s:
+//!
+//! ```ignore
+//! let (id, command) = Command::open_window(window::Settings {
+//!     title: String::from("Layers"),
+//!     size: (300, 500),
+//!     ..window::Settings::default()
+//! });
+//!
+//! self.panel = Some(id);
+//! command
+//! ```
+//!
+//! [`Application`]: ../application/trait.Application.html
+//! [`Application::view_window`]: ../application/trait.Application.html#method.view_window
+//! [`Command`]: ../command/enum.Command.html
+use std::sync::atomic::{AtomicU64, Ordering};
+
+static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+
+/// The identifier of a window of an [`Application`].
+///
+/// [`Application`]: ../application/trait.Application.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Id(u64);
+
+impl Id {
+    /// The window opened when the [`Application`] starts.
+    ///
+    /// Closing it closes the [`Application`].
+    ///
+    /// [`Application`]: ../application/trait.Application.html
+    pub const MAIN: Id = Id(0);
+
+    /// Creates a new [`Id`], different from every other one.
+    ///
+    /// [`Id`]: struct.Id.html
+    pub fn unique() -> Id {
+        Id(NEXT_ID.fetch_add(1, Ordering::Relaxed))
+    }
+}
+
+/// The settings of a new window.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Settings {
+    /// The title of the window.
+    pub title: String,
+
+    /// The initial width and height of the window, in logical pixels.
+    pub size: (u32, u32),
+
+    /// Whether the user can resize the window.
+    pub is_resizable: bool,
+
+    /// Whether the window has a title bar and borders.
+    pub has_decorations: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            title: String::new(),
+            size: (400, 300),
+            is_resizable: true,
+            has_decorations: true,
+        }
+    }
+}